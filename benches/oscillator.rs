@@ -0,0 +1,32 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use twelve_et::{Harmony, Oscillator, Waveform, SATB};
+
+fn c_major() -> SATB {
+    SATB::new(
+        0,
+        (0, 5).into(),
+        (7, 4).into(),
+        (4, 4).into(),
+        (0, 3).into(),
+    )
+}
+
+fn phasor_and_sin(c: &mut Criterion) {
+    let chord = c_major();
+    let direct_sin = Oscillator {
+        waveform: Waveform::Sine,
+        naive: true,
+    };
+    let mut group = c.benchmark_group("sixty_second_sine");
+    group.sample_size(10);
+    group.bench_function("phasor", |b| {
+        b.iter(|| chord.sound_wave_with(60, 44100, Waveform::Sine))
+    });
+    group.bench_function("sin", |b| {
+        b.iter(|| chord.sound_wave_with(60, 44100, direct_sin))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, phasor_and_sin);
+criterion_main!(benches);
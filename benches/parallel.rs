@@ -0,0 +1,26 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use twelve_et::{Harmony, SATB};
+
+fn c_major() -> SATB {
+    SATB::new(
+        0,
+        (0, 5).into(),
+        (7, 4).into(),
+        (4, 4).into(),
+        (0, 3).into(),
+    )
+}
+
+fn serial_and_parallel(c: &mut Criterion) {
+    let chord = c_major();
+    let mut group = c.benchmark_group("sixty_second_render");
+    group.sample_size(10);
+    group.bench_function("serial", |b| b.iter(|| chord.sound_wave(60, 44100)));
+    group.bench_function("parallel", |b| {
+        b.iter(|| chord.sound_wave_parallel(60, 44100))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, serial_and_parallel);
+criterion_main!(benches);
@@ -0,0 +1,31 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use twelve_et::{Harmony, Progression, RenderOptions, SATB};
+
+fn c_major() -> SATB {
+    SATB::new(
+        0,
+        (0, 5).into(),
+        (7, 4).into(),
+        (4, 4).into(),
+        (0, 3).into(),
+    )
+}
+
+fn sixty_second_render(c: &mut Criterion) {
+    let chord = c_major();
+    let progression: Progression = std::iter::repeat_n(chord.clone(), 60).collect();
+    let crossfaded = RenderOptions::new().crossfade(std::time::Duration::from_millis(50));
+    let mut group = c.benchmark_group("sixty_second_render");
+    group.sample_size(10);
+    group.bench_function("sound_wave", |b| b.iter(|| chord.sound_wave(60, 44100)));
+    group.bench_function("progression", |b| {
+        b.iter(|| progression.render(44100, &RenderOptions::new()))
+    });
+    group.bench_function("progression_crossfaded", |b| {
+        b.iter(|| progression.render(44100, &crossfaded))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, sixty_second_render);
+criterion_main!(benches);
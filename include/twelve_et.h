@@ -0,0 +1,79 @@
+#ifndef TWELVE_ET_H
+#define TWELVE_ET_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Returned when the call succeeded and, for `twelve_et_validate_satb`, the harmony is valid.
+#define TWELVE_ET_OK 0
+
+// Returned when a voice of the harmony is outside of its range.
+#define TWELVE_ET_ERROR_OUT_OF_RANGE 1
+
+// Returned when a lower voice of the harmony sounds above an upper voice.
+#define TWELVE_ET_ERROR_CROSSING 2
+
+// Returned when two adjacent upper voices of the harmony are more than an octave apart.
+#define TWELVE_ET_ERROR_SPACING 3
+
+// Returned when the pitch classes of the harmony do not form a complete harmony on its root.
+#define TWELVE_ET_ERROR_INVALID_HARMONY 4
+
+// Returned when an argument is invalid, i.e. a pitch class or root of 12 or more, a null buffer or a sample rate of zero.
+#define TWELVE_ET_ERROR_INVALID_ARGUMENT -1
+
+// Returned when the call panicked.
+#define TWELVE_ET_ERROR_PANIC -2
+
+// A pitch given by its pitch class, from 0 for C to 11 for B, and its octave, where middle C is in octave 4.
+typedef struct TwelveEtPitch {
+  uint8_t pitch_class;
+  uint8_t octave;
+} TwelveEtPitch;
+
+// A four part harmony given by the pitch class of its root and the pitch of each voice.
+typedef struct TwelveEtSatb {
+  uint8_t root;
+  struct TwelveEtPitch soprano;
+  struct TwelveEtPitch alto;
+  struct TwelveEtPitch tenor;
+  struct TwelveEtPitch bass;
+} TwelveEtSatb;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Returns the equally tempered frequency in Hz of the pitch with `pitch_class`, from 0 for C to 11 for B, in `octave`, where
+// A4 is 440 Hz. Returns NaN if `pitch_class` is 12 or more.
+double twelve_et_frequency(uint8_t pitch_class, uint8_t octave);
+
+// Returns the equally tempered frequency in Hz of the MIDI note `note`, where note 69 is A4 at 440 Hz and note 60 is middle
+// C, agreeing with `twelve_et_frequency` from note 24, C1, upwards. Returns NaN if `note` is above 127.
+double twelve_et_midi_to_freq(uint8_t note);
+
+// Validates `satb` as a four part harmony, returning `TWELVE_ET_OK` if it is valid, otherwise the error code of the first
+// rule it breaks, or `TWELVE_ET_ERROR_INVALID_ARGUMENT` if its root or a pitch class is 12 or more.
+int32_t twelve_et_validate_satb(struct TwelveEtSatb satb);
+
+// Fills the `length` floats of `buffer` with the sound wave of `satb` sampled at `sample_rate`, each voice a sine wave
+// starting at phase zero and the sum normalized by the number of voices. Returns `TWELVE_ET_OK`, or the error code from
+// `twelve_et_validate_satb` if the harmony is invalid, or `TWELVE_ET_ERROR_INVALID_ARGUMENT` if `buffer` is null or
+// `sample_rate` is zero, in which cases the buffer is left untouched.
+//
+// # Safety
+//
+// Unless it is null, `buffer` must point to `length` floats that are valid for writes and not accessed by anything else for
+// the duration of the call.
+int32_t twelve_et_render_satb(struct TwelveEtSatb satb,
+                              uint32_t sample_rate,
+                              float *buffer,
+                              size_t length);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TWELVE_ET_H */
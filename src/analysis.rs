@@ -0,0 +1,587 @@
+//! Roman numeral analysis, labelling harmonies by the scale degree of their root and their quality in a key.
+use crate::{Chord, ChordQuality, Key, KeyMode, MinorVariant, Progression, ToneRole, SATB};
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+
+/// A struct that represents the roman numeral label of a harmony in a key, e.g. ii, V7, V7/V or ♭VI.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RomanNumeral {
+    /// The 1-based scale degree of the root, relative to the tonicized degree for secondary harmonies
+    pub degree: u8,
+    /// The quality of the harmony
+    pub quality: ChordQuality,
+    /// The semitones the root is raised, or lowered when negative, from the scale degree of the key, e.g. -1 for ♭VI in a major key
+    pub alteration: i8,
+    /// The 1-based scale degree and triad quality of the degree tonicized by a secondary harmony, e.g. `Some((5, Major))` for V/V
+    pub target: Option<(u8, ChordQuality)>,
+}
+
+impl RomanNumeral {
+    /// Method that returns true if the label is a secondary harmony, one tonicizing a degree other than the tonic.
+    pub fn is_secondary(&self) -> bool {
+        self.target.is_some()
+    }
+
+    /// Method that returns true if the root of the label is chromatically altered from the scale degree of the key.
+    pub fn is_altered(&self) -> bool {
+        self.alteration != 0
+    }
+}
+
+impl Display for RomanNumeral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let accidental = match self.alteration.cmp(&0) {
+            Ordering::Less => "♭",
+            Ordering::Greater => "♯",
+            Ordering::Equal => "",
+        };
+        write!(f, "{}{}", accidental, numeral(self.degree, self.quality))?;
+        if let Some((degree, quality)) = self.target {
+            write!(f, "/{}", numeral(degree, quality))?;
+        }
+        Ok(())
+    }
+}
+
+/// Helper function that formats the numeral of `degree`, lower case for harmonies with a minor third and upper case otherwise,
+/// followed by the figure for `quality`, e.g. "vii°" or "V7".
+fn numeral(degree: u8, quality: ChordQuality) -> String {
+    let numeral = ["I", "II", "III", "IV", "V", "VI", "VII"][((degree.max(1) - 1) % 7) as usize];
+    let numeral = if quality.intervals()[1] == 3 {
+        numeral.to_lowercase()
+    } else {
+        numeral.to_string()
+    };
+    let figure = match quality {
+        ChordQuality::Major | ChordQuality::Minor => "",
+        ChordQuality::Diminished => "°",
+        ChordQuality::Augmented => "+",
+        ChordQuality::Dominant7 | ChordQuality::Minor7 => "7",
+        ChordQuality::Major7 | ChordQuality::MinorMajor7 => "M7",
+        ChordQuality::HalfDiminished7 => "ø7",
+        ChordQuality::Diminished7 => "°7",
+        ChordQuality::AugmentedMajor7 => "+M7",
+        ChordQuality::Sus2 => "sus2",
+        ChordQuality::Sus4 => "sus4",
+        ChordQuality::Major6 | ChordQuality::Minor6 => "add6",
+        ChordQuality::Add9 => "add9",
+    };
+    numeral + figure
+}
+
+/// Enumerates the parts of a harmony that can lie in the bass, the root, one of the other chord tones giving an inversion, or
+/// a pitch foreign to the harmony such as a pedal.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BassNote {
+    /// The harmony is in root position
+    Root,
+    /// The harmony is inverted, 1 for the third in the bass, 2 for the fifth and 3 for the seventh
+    Inversion(u8),
+    /// The bass is the pitch class foreign to the harmony, e.g. 2 for the D of C/D
+    Foreign(u8),
+}
+
+/// A struct that represents a pivot chord, a triad diatonic in two keys that can join a progression in one key to the other.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PivotChord {
+    /// The pitch class of the root
+    pub root: u8,
+    /// The quality of the triad
+    pub quality: ChordQuality,
+    /// The label of the triad in the key being left
+    pub from: RomanNumeral,
+    /// The label of the triad in the key being entered
+    pub to: RomanNumeral,
+}
+
+impl Display for PivotChord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {}", self.from, self.to)
+    }
+}
+
+/// A struct that represents one step of a modulation plan, moving from one key to a closely related or parallel key.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ModulationStep {
+    /// The key being left
+    pub from: Key,
+    /// The key being entered
+    pub to: Key,
+    /// The suggested pivot chord, or `None` if the keys share no diatonic triad
+    pub pivot: Option<PivotChord>,
+}
+
+/// A function that lists the triads diatonic in both `from` and `to`, in the order of their degrees in `from`, each labelled
+/// in both keys, e.g. vi in C major is ii in G major. Keys without a common diatonic triad, such as C and F# major, give an
+/// empty list.
+pub fn find_pivots(from: &Key, to: &Key) -> Vec<PivotChord> {
+    (1..=7)
+        .filter_map(|degree| {
+            let (root, quality) = from.triad(degree);
+            let to_degree = (1..=7).find(|d| to.triad(*d) == (root, quality))?;
+            Some(PivotChord {
+                root,
+                quality,
+                from: RomanNumeral {
+                    degree,
+                    quality,
+                    alteration: 0,
+                    target: None,
+                },
+                to: RomanNumeral {
+                    degree: to_degree,
+                    quality,
+                    alteration: 0,
+                    target: None,
+                },
+            })
+        })
+        .collect()
+}
+
+impl Key {
+    /// Method that labels the harmony with root pitch class `root` and quality `quality` in the key, taking harmonies of minor
+    /// keys as diatonic if they belong to any form of the minor scale, see `roman_numeral_with`.
+    pub fn roman_numeral(&self, root: u8, quality: ChordQuality) -> Option<RomanNumeral> {
+        self.roman_numeral_with(root, quality, MinorVariant::Auto)
+    }
+
+    /// Method that labels the harmony with root pitch class `root` and quality `quality` in the key. Diatonic triads and seventh
+    /// chords are labelled by their degree, where the diatonic harmonies of minor keys are built from the form of the minor
+    /// scale given by `variant`. For `MinorVariant::Auto` they are those of `triad` and `seventh`, then of the natural,
+    /// harmonic and melodic minor scales in turn, so in A minor E-G#-B is V, G-B-D is VII and C-E-G# is III+. Suspended and added
+    /// tone harmonies are labelled by the degree of their root when all of their members belong to the scale, e.g. Iadd6.
+    /// Chromatic harmonies that are the secondary dominant, dominant seventh or leading tone triad of a degree are labelled as
+    /// such, e.g. V7/V. Chromatic triads borrowed from the parallel key are then labelled by their degree there, with an
+    /// accidental if the root is altered, e.g. iv or ♭VI in a major key. Returns `None` for any other chromatic harmony.
+    pub fn roman_numeral_with(
+        &self,
+        root: u8,
+        quality: ChordQuality,
+        variant: MinorVariant,
+    ) -> Option<RomanNumeral> {
+        let root = root % 12;
+        let variants: &[MinorVariant] = match variant {
+            MinorVariant::Auto => &[
+                MinorVariant::Auto,
+                MinorVariant::Natural,
+                MinorVariant::Harmonic,
+                MinorVariant::Melodic,
+            ],
+            _ => std::slice::from_ref(&variant),
+        };
+        let diatonic = variants.iter().find_map(|variant| {
+            if !quality.is_tertian() {
+                // Suspended and added tone harmonies are diatonic if all of their members are
+                let scale = self.variant_scale(*variant).unwrap_or_else(|| self.scale());
+                let members = quality.pitch_classes(root);
+                return (1..=7).find(|d| {
+                    scale.degree(*d) == root && members.iter().all(|pc| scale.contains(pc))
+                });
+            }
+            (1..=7).find(|d| {
+                [
+                    self.triad_with(*d, *variant),
+                    self.seventh_with(*d, *variant),
+                ]
+                .contains(&(root, quality))
+            })
+        });
+        if let Some(degree) = diatonic {
+            return Some(RomanNumeral {
+                degree,
+                quality,
+                alteration: 0,
+                target: None,
+            });
+        }
+        let secondary = (2..=7).find_map(|target| {
+            let (degree, secondary) = match quality {
+                ChordQuality::Major => (5, self.secondary_dominant(target)),
+                ChordQuality::Dominant7 => (5, self.secondary_dominant_seventh(target)),
+                ChordQuality::Diminished => (7, self.secondary_leading_tone(target)),
+                _ => return None,
+            };
+            (secondary.ok()? == (root, quality)).then(|| RomanNumeral {
+                degree,
+                quality,
+                alteration: 0,
+                target: Some((target, self.triad(target).1)),
+            })
+        });
+        secondary.or_else(|| {
+            let degree = (1..=7).find(|d| self.borrowed(*d) == (root, quality))?;
+            // The altered root is at most a semitone from the degree, so wrap the difference into -6..6
+            let alteration = (root as i8 - self.degree(degree) as i8 + 18) % 12 - 6;
+            Some(RomanNumeral {
+                degree,
+                quality,
+                alteration,
+                target: None,
+            })
+        })
+    }
+
+    /// Method that labels `chord` in the key, see `roman_numeral`. Returns `None` if the chord is not a complete triad or seventh
+    /// chord on its root, or is a chromatic harmony without a label.
+    pub fn analyze(&self, chord: &SATB) -> Option<RomanNumeral> {
+        self.analyze_with(chord, MinorVariant::Auto)
+    }
+
+    /// Method that labels `chord` in the key, building the diatonic harmonies of minor keys from the form of the minor scale
+    /// given by `variant`, see `roman_numeral_with`.
+    pub fn analyze_with(&self, chord: &SATB, variant: MinorVariant) -> Option<RomanNumeral> {
+        let root = chord.root();
+        let mut pitch_classes: Vec<u8> = chord.voices().iter().map(|p| p.pitch_class()).collect();
+        pitch_classes.sort_by_key(|pc| (pc + 12 - root) % 12);
+        pitch_classes.dedup();
+        self.roman_numeral_with(
+            root,
+            ChordQuality::from_pitch_classes(root, &pitch_classes)?,
+            variant,
+        )
+    }
+
+    /// Method that plans a modulation from the key to `target` as a chain of steps, each to a closely related key, one whose
+    /// signature differs by at most one accidental such as the dominant, subdominant, relative or their relatives, or to the
+    /// parallel key. The chain is one of the shortest, so a closely related `target` is reached in a single step and C major
+    /// reaches B major in three, by G major and B minor. Each step suggests a pivot chord from `find_pivots`, preferring one
+    /// that acts as a predominant in the key being entered, ii and then IV, then vi. Planning a modulation to the key itself gives no steps.
+    pub fn modulation_plan(&self, target: &Key) -> Vec<ModulationStep> {
+        let keys: Vec<Key> = [KeyMode::Major, KeyMode::Minor]
+            .into_iter()
+            .flat_map(|mode| (0..12).map(move |tonic| Key::new(tonic, mode)))
+            .collect();
+        let adjacent =
+            |a: &Key, b: &Key| a != b && (a.fifths_distance(b) <= 1 || a.parallel() == *b);
+
+        // Breadth first search over the keys, remembering the key each was first reached from
+        let mut previous: HashMap<Key, Key> = HashMap::new();
+        let mut queue = VecDeque::from([*self]);
+        while let Some(key) = queue.pop_front() {
+            if key == *target {
+                break;
+            }
+            for next in keys.iter().filter(|next| adjacent(&key, next)) {
+                if *next != *self && !previous.contains_key(next) {
+                    previous.insert(*next, key);
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        let mut steps = Vec::new();
+        let mut to = *target;
+        while let Some(from) = previous.get(&to).copied() {
+            let pivot =
+                find_pivots(&from, &to)
+                    .into_iter()
+                    .min_by_key(|pivot| match pivot.to.degree {
+                        2 => 0,
+                        4 => 1,
+                        6 => 2,
+                        _ => 3,
+                    });
+            steps.push(ModulationStep { from, to, pivot });
+            to = from;
+        }
+        steps.reverse();
+        steps
+    }
+
+    /// Method that labels `chord`, a chord of any number of pitches such as one parsed from a chord symbol, in the key, along
+    /// with the part of the harmony in its bass. The harmony is made up of the chord tones of the chord, see
+    /// `Chord::chord_tones`, so tensions are ignored and C9 is labelled as C7, and a foreign bass is left out of it, so C/D is
+    /// labelled as I over a foreign D while C/E is I in first inversion. Returns `None` if the chord has no root or does not
+    /// sound it, its chord tones do not have one of the qualities of `ChordQuality`, or the harmony has no label, see
+    /// `roman_numeral`.
+    pub fn analyze_chord(&self, chord: &Chord) -> Option<(RomanNumeral, BassNote)> {
+        let tones = chord.classify_tones();
+        let root = tones
+            .iter()
+            .find(|(_, role)| *role == ToneRole::Root)?
+            .0
+            .pitch_class();
+        let (bass, role) = tones[0];
+        let bass = bass.pitch_class();
+        let mut chord_tones = chord.chord_tones();
+        let bass_note = match role {
+            ToneRole::Root => BassNote::Root,
+            ToneRole::Third => BassNote::Inversion(1),
+            ToneRole::Fifth => BassNote::Inversion(2),
+            ToneRole::Seventh => BassNote::Inversion(3),
+            _ => {
+                chord_tones.remove(bass);
+                BassNote::Foreign(bass)
+            }
+        };
+        let mut pitch_classes: Vec<u8> = chord_tones.iter().collect();
+        pitch_classes.sort_by_key(|pc| (pc + 12 - root) % 12);
+        let quality = ChordQuality::from_pitch_classes(root, &pitch_classes)?;
+        Some((self.roman_numeral(root, quality)?, bass_note))
+    }
+
+    /// Method that labels each chord of `progression` in the key, see `analyze`.
+    pub fn analyze_progression(&self, progression: &Progression) -> Vec<Option<RomanNumeral>> {
+        progression
+            .chords()
+            .iter()
+            .map(|chord| self.analyze(chord))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Pitch;
+
+    #[test]
+    fn test_analyze_chord() {
+        let c_major = Key::new(0, KeyMode::Major);
+        let label = |symbol: &str| {
+            let (numeral, bass) = c_major.analyze_chord(&Chord::from_symbol(symbol).unwrap())?;
+            Some((numeral.to_string(), bass))
+        };
+        assert_eq!(label("C"), Some(("I".to_string(), BassNote::Root)));
+        assert_eq!(
+            label("C/E"),
+            Some(("I".to_string(), BassNote::Inversion(1)))
+        );
+        assert_eq!(
+            label("G7/F"),
+            Some(("V7".to_string(), BassNote::Inversion(3)))
+        );
+        assert_eq!(label("C/D"), Some(("I".to_string(), BassNote::Foreign(2))));
+        assert_eq!(label("D9"), Some(("V7/V".to_string(), BassNote::Root)));
+        assert_eq!(label("Csus4"), Some(("Isus4".to_string(), BassNote::Root)));
+        assert_eq!(label("C7sus4"), None);
+
+        // C6 with the declared root C is an added sixth chord on the tonic rather than vi7
+        let c6 = Chord::from_symbol("C6").unwrap();
+        let satb = c6.to_satb(&Default::default()).unwrap();
+        let numeral = c_major.analyze(&satb).unwrap();
+        assert_eq!((numeral.degree, numeral.quality), (1, ChordQuality::Major6));
+        assert_eq!(numeral.to_string(), "Iadd6");
+        assert_eq!(
+            c_major
+                .roman_numeral(7, ChordQuality::Sus4)
+                .unwrap()
+                .to_string(),
+            "Vsus4"
+        );
+    }
+
+    #[test]
+    fn test_secondary_dominants() {
+        let c_major = Key::new(0, KeyMode::Major);
+        // V/V in C major is a D major triad, spelled with F#
+        assert_eq!(c_major.secondary_dominant(5), Ok((2, ChordQuality::Major)));
+        assert_eq!(
+            c_major.secondary_dominant_seventh(5),
+            Ok((2, ChordQuality::Dominant7))
+        );
+        assert_eq!(
+            c_major.secondary_leading_tone(5),
+            Ok((6, ChordQuality::Diminished))
+        );
+        assert!(c_major.secondary_dominant(7).is_err());
+        assert!(Key::new(9, KeyMode::Minor).secondary_dominant(2).is_err());
+
+        // D-F#-A-C resolving to G major
+        let mut progression = Progression::new();
+        progression.push(SATB::new(
+            2,
+            Pitch::from((0, 5)),
+            Pitch::from((6, 4)),
+            Pitch::from((9, 3)),
+            Pitch::from((2, 3)),
+        ));
+        progression.push(SATB::new(
+            7,
+            Pitch::from((11, 4)),
+            Pitch::from((7, 4)),
+            Pitch::from((2, 4)),
+            Pitch::from((7, 2)),
+        ));
+        let labels: Vec<String> = c_major
+            .analyze_progression(&progression)
+            .iter()
+            .map(|label| label.unwrap().to_string())
+            .collect();
+        assert_eq!(labels, vec!["V7/V", "V"]);
+
+        assert_eq!(
+            c_major
+                .roman_numeral(11, ChordQuality::Diminished)
+                .unwrap()
+                .to_string(),
+            "vii°"
+        );
+        assert_eq!(
+            c_major
+                .roman_numeral(1, ChordQuality::Diminished)
+                .unwrap()
+                .to_string(),
+            "vii°/ii"
+        );
+        assert_eq!(c_major.roman_numeral(8, ChordQuality::Augmented), None);
+    }
+
+    #[test]
+    fn test_find_pivots() {
+        let c_major = Key::new(0, KeyMode::Major);
+        let pivots = find_pivots(&c_major, &Key::new(7, KeyMode::Major));
+        let labels: Vec<String> = pivots.iter().map(|pivot| pivot.to_string()).collect();
+        assert_eq!(labels, ["I = IV", "iii = vi", "V = I", "vi = ii"]);
+        assert_eq!(
+            (pivots[3].root, pivots[3].quality),
+            (9, ChordQuality::Minor)
+        );
+
+        assert!(find_pivots(&c_major, &Key::new(6, KeyMode::Major)).is_empty());
+        assert_eq!(find_pivots(&c_major, &Key::new(9, KeyMode::Minor)).len(), 5);
+    }
+
+    #[test]
+    fn test_modulation_plan() {
+        let c_major = Key::new(0, KeyMode::Major);
+        let g_major = Key::new(7, KeyMode::Major);
+        let plan = c_major.modulation_plan(&g_major);
+        assert_eq!(plan.len(), 1);
+        assert_eq!((plan[0].from, plan[0].to), (c_major, g_major));
+        assert_eq!(plan[0].pivot.unwrap().to_string(), "vi = ii");
+
+        let b_major = Key::new(11, KeyMode::Major);
+        let plan = c_major.modulation_plan(&b_major);
+        assert_eq!(plan.len(), 3);
+        assert_eq!(plan[0].from, c_major);
+        assert_eq!(plan.last().unwrap().to, b_major);
+        for (step, next) in plan.iter().zip(plan.iter().skip(1)) {
+            assert_eq!(step.to, next.from);
+        }
+        for step in &plan {
+            assert!(step.from.fifths_distance(&step.to) <= 1 || step.from.parallel() == step.to);
+            assert!(step.pivot.is_some());
+        }
+        assert!(c_major.modulation_plan(&c_major).is_empty());
+    }
+
+    #[test]
+    fn test_minor_variants() {
+        let a_minor = Key::new(9, KeyMode::Minor);
+        let triad = |root: u8, voices: [(u8, u8); 4]| {
+            SATB::new(
+                root,
+                Pitch::from(voices[0]),
+                Pitch::from(voices[1]),
+                Pitch::from(voices[2]),
+                Pitch::from(voices[3]),
+            )
+        };
+        let label = |chord: &SATB| a_minor.analyze(chord).unwrap().to_string();
+        assert_eq!(label(&triad(4, [(11, 4), (8, 4), (4, 4), (4, 2)])), "V");
+        assert_eq!(label(&triad(7, [(7, 4), (2, 4), (11, 3), (7, 2)])), "VII");
+        // Augmented triads are not accepted by the SATB validation
+        let augmented = SATB::new_unchecked(
+            0,
+            Pitch::from((8, 4)),
+            Pitch::from((4, 4)),
+            Pitch::from((0, 4)),
+            Pitch::from((0, 3)),
+        );
+        assert_eq!(label(&augmented), "III+");
+        assert_eq!(label(&triad(4, [(11, 4), (7, 4), (4, 4), (4, 2)])), "v");
+
+        assert_eq!(
+            a_minor.triad_with(7, MinorVariant::Natural),
+            (7, ChordQuality::Major)
+        );
+        assert_eq!(
+            a_minor.triad_with(7, MinorVariant::Harmonic),
+            (8, ChordQuality::Diminished)
+        );
+        assert_eq!(
+            a_minor.triad_with(3, MinorVariant::Harmonic),
+            (0, ChordQuality::Augmented)
+        );
+        assert_eq!(
+            a_minor.triad_with(4, MinorVariant::Melodic),
+            (2, ChordQuality::Major)
+        );
+        assert_eq!(a_minor.triad_with(3, MinorVariant::Auto), a_minor.triad(3));
+        assert_eq!(
+            a_minor.seventh_with(7, MinorVariant::Harmonic),
+            (8, ChordQuality::Diminished7)
+        );
+
+        // A single variant only accepts its own harmonies
+        let g_major = triad(7, [(7, 4), (2, 4), (11, 3), (7, 2)]);
+        assert_eq!(
+            a_minor
+                .analyze_with(&g_major, MinorVariant::Natural)
+                .unwrap()
+                .to_string(),
+            "VII"
+        );
+        assert_ne!(
+            a_minor
+                .analyze_with(&g_major, MinorVariant::Harmonic)
+                .map(|label| label.to_string()),
+            Some("VII".to_string())
+        );
+    }
+
+    #[test]
+    fn test_borrowed_chords() {
+        let c_major = Key::new(0, KeyMode::Major);
+        assert_eq!(c_major.borrowed(4), (5, ChordQuality::Minor));
+        assert_eq!(c_major.borrowed(6), (8, ChordQuality::Major));
+        assert_eq!(c_major.borrowed(7), (10, ChordQuality::Major));
+        assert_eq!(c_major.borrowed(2), (2, ChordQuality::Diminished));
+
+        // Ab-C-Eb and F-Ab-C
+        let flat_six = SATB::new(
+            8,
+            Pitch::from((8, 4)),
+            Pitch::from((3, 4)),
+            Pitch::from((0, 4)),
+            Pitch::from((8, 2)),
+        );
+        let minor_four = SATB::new(
+            5,
+            Pitch::from((5, 4)),
+            Pitch::from((0, 4)),
+            Pitch::from((8, 3)),
+            Pitch::from((5, 2)),
+        );
+        assert_eq!(c_major.analyze(&flat_six).unwrap().to_string(), "♭VI");
+        assert_eq!(c_major.analyze(&minor_four).unwrap().to_string(), "iv");
+        assert!(c_major.analyze(&flat_six).unwrap().is_altered());
+        let labels: Vec<String> = (2..=7)
+            .map(|d| {
+                let (root, quality) = c_major.borrowed(d);
+                c_major.roman_numeral(root, quality).unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(labels, vec!["ii°", "♭III", "iv", "v", "♭VI", "♭VII"]);
+
+        // In a minor key the borrowed chords come from the parallel major
+        let a_minor = Key::new(9, KeyMode::Minor);
+        assert_eq!(
+            a_minor
+                .roman_numeral(2, ChordQuality::Major)
+                .unwrap()
+                .to_string(),
+            "IV"
+        );
+        assert_eq!(
+            a_minor
+                .roman_numeral(6, ChordQuality::Minor)
+                .unwrap()
+                .to_string(),
+            "♯vi"
+        );
+    }
+}
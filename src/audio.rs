@@ -0,0 +1,3160 @@
+//! Types and helper functions for rendering harmonies as sampled sound waves.
+use crate::{Harmony, Melody, OrnamentedProgression, Progression, Voice, SATB};
+use std::borrow::Cow;
+use std::f64::consts::PI;
+use std::fmt::Display;
+use std::time::Duration;
+
+/// Enumerates the strategies for scaling the sum of a harmony's voices into a sound wave.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Normalization {
+    /// The voices are summed without scaling, so samples lie within [-n, n] for n voices.
+    None,
+    /// The sum is divided by the number of voices, so samples lie within [-1, 1] and the level of a harmony does not depend
+    /// on the pitches it contains.
+    #[default]
+    VoiceCount,
+    /// The sum is divided by its measured peak, so the loudest sample has an absolute value of exactly 1.
+    Peak,
+}
+
+impl Normalization {
+    /// Method that scales `wave`, the sum of `voice_count` unit amplitude voices, in place according to the strategy.
+    /// Every strategy scales all samples by the same factor, so the relative balance of the voices is preserved.
+    pub fn apply(&self, wave: &mut [f64], voice_count: usize) {
+        let divisor = match self {
+            Normalization::None => return,
+            Normalization::VoiceCount => voice_count as f64,
+            Normalization::Peak => wave.iter().fold(0.0, |peak: f64, s| peak.max(s.abs())),
+        };
+        if divisor > 0.0 {
+            for sample in wave.iter_mut() {
+                *sample /= divisor;
+            }
+        }
+    }
+}
+
+/// An ADSR amplitude envelope, shaping the level of a harmony over the time it sounds. All times are in seconds.
+///
+/// The release is carved out of the nominal duration of the harmony rather than extending past it, so a rendered harmony keeps
+/// the length requested and ends at silence. If the attack, decay and release together are longer than the harmony, they are
+/// shortened in proportion to fit. Negative times are treated as zero and the sustain level is clamped to [0, 1].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Envelope {
+    /// The time taken to rise from silence to full amplitude
+    pub attack: f64,
+    /// The time taken to fall from full amplitude to the sustain level
+    pub decay: f64,
+    /// The amplitude held between the decay and the release
+    pub sustain_level: f64,
+    /// The time taken to fall from the sustain level to silence
+    pub release: f64,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Envelope {
+            attack: 0.01,
+            decay: 0.1,
+            sustain_level: 0.8,
+            release: 0.1,
+        }
+    }
+}
+
+impl Envelope {
+    /// Method that computes the amplitude of the envelope `time` seconds into a harmony lasting `duration` seconds.
+    pub fn amplitude(&self, time: f64, duration: f64) -> f64 {
+        let (mut attack, mut decay, mut release) = (
+            self.attack.max(0.0),
+            self.decay.max(0.0),
+            self.release.max(0.0),
+        );
+        let sustain = self.sustain_level.clamp(0.0, 1.0);
+        let total = attack + decay + release;
+        if total > duration && total > 0.0 {
+            let scale = duration.max(0.0) / total;
+            attack *= scale;
+            decay *= scale;
+            release *= scale;
+        }
+        if time < 0.0 || time >= duration {
+            return 0.0;
+        }
+
+        // The level before the release, at time `t`
+        let level = |t: f64| {
+            if t < attack {
+                t / attack
+            } else if t < attack + decay {
+                1.0 - (1.0 - sustain) * (t - attack) / decay
+            } else {
+                sustain
+            }
+        };
+        let release_start = duration - release;
+        if time < release_start {
+            level(time)
+        } else {
+            level(release_start) * (duration - time) / release
+        }
+    }
+}
+
+/// Enumerates the shapes of wave each voice of a harmony can be rendered with. Every waveform has unit amplitude.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Waveform {
+    #[default]
+    Sine,
+    Square,
+    Sawtooth,
+    Triangle,
+}
+
+impl Waveform {
+    /// Method that computes the value of the waveform at `phase`, the fraction of a cycle completed, in [0, 1).
+    /// Each waveform starts its cycle at 0 or, for the square wave, at its positive half.
+    pub fn value(&self, phase: f64) -> f64 {
+        match self {
+            Waveform::Sine => f64::sin(2.0 * PI * phase),
+            Waveform::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Sawtooth => 2.0 * (phase + 0.5).fract() - 1.0,
+            Waveform::Triangle => {
+                if phase < 0.25 {
+                    4.0 * phase
+                } else if phase < 0.75 {
+                    2.0 - 4.0 * phase
+                } else {
+                    4.0 * phase - 4.0
+                }
+            }
+        }
+    }
+}
+
+/// The limit of the overshoot of the partial Fourier sums of the sawtooth wave at its discontinuity, the Wilbraham-Gibbs constant.
+const GIBBS_OVERSHOOT: f64 = 1.178_979_744_472_167;
+
+impl Waveform {
+    /// Method that computes the value of the band limited waveform at `phase`, built by additive synthesis from the harmonics
+    /// of the waveform numbered up to `harmonics`. Band limited waves contain no energy above the highest harmonic, so they do
+    /// not alias. The square wave has unit amplitude like `value`, so away from its edges it lies near 1 and -1, while the Gibbs
+    /// overshoot at its edges rises to about `GIBBS_OVERSHOOT`. The sawtooth wave is scaled to keep that overshoot within unit
+    /// amplitude.
+    pub fn band_limited_value(&self, phase: f64, harmonics: usize) -> f64 {
+        if *self == Waveform::Sine {
+            return self.value(phase);
+        }
+        // sin(k x) for successive k by the recurrence sin((k + 1) x) = 2 cos(x) sin(k x) - sin((k - 1) x)
+        let x = 2.0 * PI * phase;
+        let coeff = 2.0 * x.cos();
+        let (mut previous, mut current) = (0.0, x.sin());
+        let mut sum = 0.0;
+        for k in 1..=harmonics {
+            let k_f = k as f64;
+            sum += match self {
+                Waveform::Square if k % 2 == 1 => current / k_f,
+                Waveform::Sawtooth if k % 2 == 1 => current / k_f,
+                Waveform::Sawtooth => -current / k_f,
+                Waveform::Triangle if k % 4 == 1 => current / (k_f * k_f),
+                Waveform::Triangle if k % 4 == 3 => -current / (k_f * k_f),
+                _ => 0.0,
+            };
+            let next = coeff * current - previous;
+            previous = current;
+            current = next;
+        }
+        match self {
+            // The Fourier series of the unit square wave is 4 / π times the sum of its odd harmonics
+            Waveform::Square => 4.0 / PI * sum,
+            Waveform::Sawtooth => 2.0 / PI * sum / GIBBS_OVERSHOOT,
+            _ => 8.0 / (PI * PI) * sum,
+        }
+    }
+}
+
+/// Selects how each voice is generated, the shape of its wave and whether the wave is band limited.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Oscillator {
+    /// The shape of the wave
+    pub waveform: Waveform,
+    /// When true the wave is generated directly from its phase, which is fast but aliases for the square, sawtooth and
+    /// triangle waves. Otherwise these waves are band limited to the harmonics below the Nyquist frequency. Sine waves are
+    /// generated by a `Phasor` unless this is set, in which case `sin` is called for every sample.
+    pub naive: bool,
+}
+
+impl From<Waveform> for Oscillator {
+    /// Creates a band limited oscillator of the waveform.
+    fn from(waveform: Waveform) -> Self {
+        Oscillator {
+            waveform,
+            naive: false,
+        }
+    }
+}
+
+/// Helper function that sums a unit amplitude wave generated by `oscillator` at each of `frequencies`, producing `num_samples`
+/// samples at `sample_freq`. Each voice keeps a phase accumulator, measured in cycles, so the waves are continuous over the
+/// whole duration.
+pub(crate) fn sum_waves(
+    frequencies: &[f64],
+    num_samples: usize,
+    sample_freq: u32,
+    oscillator: Oscillator,
+) -> Vec<f64> {
+    let mut wave = vec![0.0; num_samples];
+    fill_waves(&mut wave, frequencies, 0, sample_freq, oscillator);
+    wave
+}
+
+/// The number of samples rendered by each task of `Harmony::sound_wave_parallel`.
+pub const PARALLEL_CHUNK_SAMPLES: usize = 1 << 16;
+
+/// Helper function that writes the sum of the waves generated by `oscillator` at each of `frequencies` into `wave`, starting
+/// from sample `start` of the sum. The phase of each voice at `start` is computed directly from its frequency, so the sum
+/// may be rendered in separate chunks that agree with `sum_waves` up to rounding.
+pub(crate) fn fill_waves(
+    wave: &mut [f64],
+    frequencies: &[f64],
+    start: usize,
+    sample_freq: u32,
+    oscillator: Oscillator,
+) {
+    let nyquist = sample_freq as f64 / 2.0;
+    let increments: Vec<f64> = frequencies.iter().map(|f| f / sample_freq as f64).collect();
+    // The number of harmonics of each voice below the Nyquist frequency
+    let harmonics: Vec<usize> = frequencies
+        .iter()
+        .map(|f| {
+            if *f > 0.0 {
+                (nyquist / f).ceil() as usize - 1
+            } else {
+                0
+            }
+        })
+        .collect();
+    if oscillator.waveform == Waveform::Sine && !oscillator.naive {
+        let mut phasors: Vec<Phasor> = increments
+            .iter()
+            .map(|increment| Phasor::new((start as f64 * increment).fract(), *increment))
+            .collect();
+        for sample in wave.iter_mut() {
+            *sample = phasors.iter_mut().map(Phasor::next_value).sum();
+        }
+        return;
+    }
+    let band_limited = !oscillator.naive && oscillator.waveform != Waveform::Sine;
+    let mut phases: Vec<f64> = increments
+        .iter()
+        .map(|increment| (start as f64 * increment).fract())
+        .collect();
+    for sample in wave.iter_mut() {
+        *sample = 0.0;
+        for ((phase, increment), harmonics) in phases.iter_mut().zip(&increments).zip(&harmonics) {
+            *sample += if band_limited {
+                oscillator.waveform.band_limited_value(*phase, *harmonics)
+            } else {
+                oscillator.waveform.value(*phase)
+            };
+            *phase = (*phase + increment).fract();
+        }
+    }
+}
+
+/// The number of samples a `Phasor` produces between renormalizations.
+const PHASOR_RENORMALIZE_SAMPLES: u32 = 1024;
+
+/// A sine oscillator that avoids calling `sin` for every sample. The oscillator keeps a point on the unit circle, a complex
+/// phasor, and rotates it by the angle of one sample's increment with a complex multiplication, returning its imaginary part.
+/// Rounding slowly changes the magnitude of the phasor, so it is rescaled to unit magnitude every `PHASOR_RENORMALIZE_SAMPLES` samples.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Phasor {
+    /// The real part of the phasor, the cosine of the phase
+    re: f64,
+    /// The imaginary part of the phasor, the sine of the phase
+    im: f64,
+    /// The cosine of the angle the phasor rotates by per sample
+    rotation_re: f64,
+    /// The sine of the angle the phasor rotates by per sample
+    rotation_im: f64,
+    /// The number of samples left until the phasor is renormalized
+    until_renormalize: u32,
+}
+
+impl Phasor {
+    /// Associated method for creating a new `Phasor` at `phase`, advancing by `increment` per sample, both measured in cycles.
+    pub(crate) fn new(phase: f64, increment: f64) -> Self {
+        let angle = 2.0 * PI * phase;
+        let mut phasor = Phasor {
+            re: angle.cos(),
+            im: angle.sin(),
+            rotation_re: 1.0,
+            rotation_im: 0.0,
+            until_renormalize: PHASOR_RENORMALIZE_SAMPLES,
+        };
+        phasor.set_increment(increment);
+        phasor
+    }
+
+    /// Method that changes the cycles the phasor advances by per sample to `increment`, keeping its current phase.
+    pub(crate) fn set_increment(&mut self, increment: f64) {
+        let angle = 2.0 * PI * increment;
+        self.rotation_re = angle.cos();
+        self.rotation_im = angle.sin();
+    }
+
+    /// Method that returns the current phase of the phasor, in cycles.
+    pub(crate) fn phase(&self) -> f64 {
+        (self.im.atan2(self.re) / (2.0 * PI)).rem_euclid(1.0)
+    }
+
+    /// Method that returns the sine of the current phase, then advances the phase by one sample.
+    pub(crate) fn next_value(&mut self) -> f64 {
+        let value = self.im;
+        let re = self.re * self.rotation_re - self.im * self.rotation_im;
+        self.im = self.re * self.rotation_im + self.im * self.rotation_re;
+        self.re = re;
+        self.until_renormalize -= 1;
+        if self.until_renormalize == 0 {
+            let magnitude = self.re.hypot(self.im);
+            self.re /= magnitude;
+            self.im /= magnitude;
+            self.until_renormalize = PHASOR_RENORMALIZE_SAMPLES;
+        }
+        value
+    }
+}
+
+/// The timbre of a voice given by the relative amplitudes of its first harmonics, where the first entry is the amplitude of the
+/// fundamental, the second of the harmonic at twice its frequency, and so on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timbre {
+    /// The relative amplitude of each harmonic, starting from the fundamental
+    harmonics: Vec<f64>,
+}
+
+impl Default for Timbre {
+    fn default() -> Self {
+        Timbre::sine()
+    }
+}
+
+impl Timbre {
+    /// Associated method for creating a new `Timbre` from the relative amplitudes of its harmonics, starting from the fundamental.
+    pub fn new(harmonics: Vec<f64>) -> Self {
+        Timbre { harmonics }
+    }
+
+    /// Associated method for creating a pure sine timbre, i.e. the fundamental alone.
+    pub fn sine() -> Self {
+        Timbre::new(vec![1.0])
+    }
+
+    /// Associated method for creating a bright, organ like timbre with strong upper harmonics.
+    pub fn organ() -> Self {
+        Timbre::new(vec![1.0, 0.8, 0.6, 0.4, 0.3, 0.2, 0.1, 0.05])
+    }
+
+    /// Associated method for creating a soft timbre, with quickly fading upper harmonics.
+    pub fn soft() -> Self {
+        Timbre::new(vec![1.0, 0.25, 0.06])
+    }
+
+    /// Method for accessing the relative amplitudes of the harmonics, starting from the fundamental.
+    pub fn harmonics(&self) -> &[f64] {
+        &self.harmonics
+    }
+}
+
+/// Helper function that sums a voice at each of `frequencies` with the harmonics of `timbre`, producing `num_samples` samples
+/// at `sample_freq`. Harmonics at or above the Nyquist frequency are skipped, rather than aliasing. Each voice is scaled by the
+/// total amplitude of its harmonics, so a voice peaks at no more than 1 whatever its timbre.
+pub(crate) fn sum_timbres(
+    frequencies: &[f64],
+    num_samples: usize,
+    sample_freq: u32,
+    timbre: &Timbre,
+) -> Vec<f64> {
+    let nyquist = sample_freq as f64 / 2.0;
+    let total: f64 = timbre.harmonics.iter().map(|a| a.abs()).sum();
+    let scale = if total > 0.0 { 1.0 / total } else { 0.0 };
+    // The harmonic numbers and amplitudes sounded by each voice
+    let partials: Vec<Vec<(f64, f64)>> = frequencies
+        .iter()
+        .map(|f| {
+            timbre
+                .harmonics
+                .iter()
+                .enumerate()
+                .map(|(k, a)| ((k + 1) as f64, a * scale))
+                .filter(|(k, _)| k * f < nyquist)
+                .collect()
+        })
+        .collect();
+    let increments: Vec<f64> = frequencies.iter().map(|f| f / sample_freq as f64).collect();
+    let mut phases = vec![0.0; frequencies.len()];
+    let mut wave = Vec::with_capacity(num_samples);
+    for _ in 0..num_samples {
+        let mut sample = 0.0;
+        for ((phase, increment), partials) in phases.iter_mut().zip(&increments).zip(&partials) {
+            for (k, amplitude) in partials {
+                sample += amplitude * f64::sin(2.0 * PI * k * *phase);
+            }
+            *phase = (*phase + increment).fract();
+        }
+        wave.push(sample);
+    }
+    wave
+}
+
+/// A trait for the sample types a sound wave can be converted to, from an `f64` sample that satisfies the amplitude contract
+/// of `Harmony`.
+pub trait Sample: Copy {
+    /// Required method that converts `sample`, nominally within [-1, 1], to the sample type.
+    fn from_f64(sample: f64) -> Self;
+}
+
+impl Sample for f64 {
+    fn from_f64(sample: f64) -> Self {
+        sample
+    }
+}
+
+impl Sample for f32 {
+    fn from_f64(sample: f64) -> Self {
+        sample as f32
+    }
+}
+
+impl Sample for i16 {
+    /// Samples are clamped to [-1, 1], so values outside of the range clip rather than wrap, then scaled so 1.0 maps to
+    /// `i16::MAX` and -1.0 maps to `i16::MIN`, and rounded to the nearest integer.
+    fn from_f64(sample: f64) -> Self {
+        let sample = sample.clamp(-1.0, 1.0);
+        if sample >= 0.0 {
+            (sample * i16::MAX as f64).round() as i16
+        } else {
+            (sample * -(i16::MIN as f64)).round() as i16
+        }
+    }
+}
+
+/// The seed of the noise `to_i16_pcm` dithers with, so conversions are reproducible.
+pub const DITHER_SEED: u64 = 0x5EED_D17E;
+
+/// A function that converts `samples` to 16 bit PCM, scaling and clamping as `Sample for i16` does. When `dither` is true,
+/// triangular probability density dither of one least significant bit is added before quantizing, trading the quantization
+/// distortion of quiet material for a low, constant noise floor. The dither is drawn from `DITHER_SEED`, see `to_i16_pcm_seeded`.
+pub fn to_i16_pcm(samples: &[f64], dither: bool) -> Vec<i16> {
+    if dither {
+        to_i16_pcm_seeded(samples, DITHER_SEED)
+    } else {
+        samples.iter().map(|s| i16::from_f64(*s)).collect()
+    }
+}
+
+/// A function that converts `samples` to 16 bit PCM with triangular probability density dither, as `to_i16_pcm` does,
+/// drawing the dither from `seed`.
+pub fn to_i16_pcm_seeded(samples: &[f64], seed: u64) -> Vec<i16> {
+    let mut state = seed;
+    let mut uniform = || (splitmix64(&mut state) >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+    samples
+        .iter()
+        .map(|s| {
+            let s = s.clamp(-1.0, 1.0);
+            let scaled = if s >= 0.0 {
+                s * i16::MAX as f64
+            } else {
+                s * -(i16::MIN as f64)
+            };
+            // The sum of two uniform values spans [-1, 1] least significant bits with a triangular density
+            let dithered = scaled + uniform() + uniform();
+            dithered.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16
+        })
+        .collect()
+}
+
+/// An iterator over the samples of an `SATB` harmony's sound wave, normalized by the number of voices. Samples are computed
+/// lazily from a `Phasor` per voice, so the iterator never allocates. Created by `SATB::samples`, it is effectively
+/// infinite, use `take` to bound it to a number of samples.
+#[derive(Debug, Clone)]
+pub struct SatbSamples {
+    /// The oscillator of each voice
+    phasors: [Phasor; 4],
+    /// The number of samples left to produce
+    remaining: usize,
+}
+
+impl Iterator for SatbSamples {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<f64> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(advance_phasors(&mut self.phasors))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for SatbSamples {}
+
+/// An iterator over the samples of a `Progression`'s sound wave, each harmony lasting for its duration. Each voice keeps its
+/// phase from one harmony to the next, so the wave is continuous across harmony changes, and starts afresh after a rest, which
+/// is silent. Created by `Progression::samples`.
+#[derive(Debug, Clone)]
+pub struct ProgressionSamples<'a> {
+    /// The progression being rendered
+    progression: Cow<'a, Progression>,
+    /// The sample rate in samples per second
+    sample_freq: u32,
+    /// The index of the harmony currently sounding
+    chord: usize,
+    /// The number of samples left in the current harmony
+    chord_remaining: usize,
+    /// The number of samples left in the rest before the current harmony
+    rest_remaining: usize,
+    /// The oscillator of each voice
+    phasors: [Phasor; 4],
+    /// The number of samples left to produce
+    remaining: usize,
+}
+
+impl<'a> ProgressionSamples<'a> {
+    /// Associated method for creating a new iterator over the samples of `progression` at `sample_freq`.
+    fn new(progression: Cow<'a, Progression>, sample_freq: u32) -> Self {
+        let remaining = progression
+            .durations()
+            .iter()
+            .chain(progression.rests())
+            .chain([progression.trailing_rest()].iter())
+            .map(|d| sample_count(*d, sample_freq))
+            .sum();
+        let mut samples = ProgressionSamples {
+            progression,
+            sample_freq,
+            chord: 0,
+            chord_remaining: 0,
+            rest_remaining: 0,
+            phasors: [Phasor::new(0.0, 0.0); 4],
+            remaining,
+        };
+        samples.start_chord(0);
+        samples
+    }
+
+    /// Helper method that moves on to the harmony at index `chord`.
+    fn start_chord(&mut self, chord: usize) {
+        self.chord = chord;
+        if let Some(satb) = self.progression.chords().get(chord) {
+            self.rest_remaining = sample_count(self.progression.rests()[chord], self.sample_freq);
+            for (phasor, increment) in self
+                .phasors
+                .iter_mut()
+                .zip(increments(satb, self.sample_freq))
+            {
+                // The voices start afresh after a rest, as when rendered
+                if self.rest_remaining > 0 {
+                    *phasor = Phasor::new(0.0, increment);
+                } else {
+                    phasor.set_increment(increment);
+                }
+            }
+            self.chord_remaining =
+                sample_count(self.progression.durations()[chord], self.sample_freq);
+        }
+    }
+}
+
+impl<'a> Iterator for ProgressionSamples<'a> {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<f64> {
+        while self.rest_remaining == 0 && self.chord_remaining == 0 {
+            if self.chord + 1 >= self.progression.chords().len() {
+                // Any samples left belong to the rest after the last harmony
+                self.remaining = self.remaining.checked_sub(1)?;
+                return Some(0.0);
+            }
+            self.start_chord(self.chord + 1);
+        }
+        self.remaining -= 1;
+        if self.rest_remaining > 0 {
+            self.rest_remaining -= 1;
+            return Some(0.0);
+        }
+        self.chord_remaining -= 1;
+        Some(advance_phasors(&mut self.phasors))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for ProgressionSamples<'a> {}
+
+/// Helper function that computes the sample of four unit amplitude sine waves at `phases`, normalized by the number of voices,
+/// then advances each phase by its increment.
+fn advance(phases: &mut [f64; 4], increments: &[f64; 4]) -> f64 {
+    let mut sample = 0.0;
+    for (phase, increment) in phases.iter_mut().zip(increments) {
+        sample += Waveform::Sine.value(*phase);
+        *phase = (*phase + increment).fract();
+    }
+    sample / 4.0
+}
+
+/// Helper function that computes the sample of the four sine oscillators `phasors`, normalized by the number of voices, then
+/// advances each of them.
+fn advance_phasors(phasors: &mut [Phasor; 4]) -> f64 {
+    phasors.iter_mut().map(Phasor::next_value).sum::<f64>() / 4.0
+}
+
+/// Helper function that computes the cycles each voice of `chord` advances by per sample at `sample_freq`.
+fn increments(chord: &SATB, sample_freq: u32) -> [f64; 4] {
+    chord.voices().map(|p| p.frequency() / sample_freq as f64)
+}
+
+/// A function that joins the sound waves `a` and `b`, overlapping the last `overlap_samples` samples of `a` with the first
+/// `overlap_samples` samples of `b` using an equal power crossfade. The overlap is clamped to the length of the shorter wave.
+/// The result has `a.len() + b.len() - overlap` samples.
+pub fn crossfade(a: &[f64], b: &[f64], overlap_samples: usize) -> Vec<f64> {
+    let overlap = overlap_samples.min(a.len()).min(b.len());
+    let mut wave = Vec::with_capacity(a.len() + b.len() - overlap);
+    wave.extend_from_slice(a);
+    crossfade_onto(&mut wave, b, overlap);
+    wave
+}
+
+/// Helper function that crossfades `b` onto the end of `wave` in place, overlapping `overlap` samples as `crossfade` does.
+/// `overlap` must not exceed the length of either wave.
+fn crossfade_onto(wave: &mut Vec<f64>, b: &[f64], overlap: usize) {
+    let start = wave.len() - overlap;
+    for (i, sample) in wave[start..].iter_mut().enumerate() {
+        let t = (i as f64 + 0.5) / overlap as f64 * PI / 2.0;
+        *sample = *sample * t.cos() + b[i] * t.sin();
+    }
+    wave.extend_from_slice(&b[overlap..]);
+}
+
+/// A periodic modulation of the frequency of each voice. The instantaneous frequency of a voice of frequency `f` is
+/// `f * 2^(depth_cents * sin(2π * rate_hz * t) / 1200)`, and the phase of the voice is the integral of this frequency.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Vibrato {
+    /// The number of cycles of the modulation per second
+    pub rate_hz: f64,
+    /// The largest deviation from the frequency of the voice, in cents
+    pub depth_cents: f64,
+    /// The time in seconds before the vibrato begins
+    pub onset: f64,
+}
+
+impl Default for Vibrato {
+    /// A natural choral vibrato, of 5.5 Hz and 30 cents starting straight away.
+    fn default() -> Self {
+        Vibrato {
+            rate_hz: 5.5,
+            depth_cents: 30.0,
+            onset: 0.0,
+        }
+    }
+}
+
+impl Vibrato {
+    /// Method that computes the factor the frequency of a voice is multiplied by `time` seconds after the render starts.
+    pub fn frequency_ratio(&self, time: f64) -> f64 {
+        if time < self.onset {
+            return 1.0;
+        }
+        let cents = self.depth_cents * f64::sin(2.0 * PI * self.rate_hz * (time - self.onset));
+        2f64.powf(cents / 1200.0)
+    }
+}
+
+/// Options for rendering harmonies and progressions, built up with the builder methods.
+///
+/// The signal chain of a render runs in this order: each voice, along with its detuned copies, enters at its strummed onset and is
+/// rendered with vibrato, shaped by its envelope and scaled by its gain, the voices are summed and normalized by their number,
+/// each harmony is declicked and crossfaded into the next, then the low-pass filter, the delay, the master gain and finally the
+/// limiter are applied.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RenderOptions {
+    /// The time each harmony overlaps with the next
+    crossfade: Option<Duration>,
+    /// The vibrato applied to every voice
+    vibrato: Option<Vibrato>,
+    /// The detuning in cents of the outer copies of each voice
+    detune_cents: f64,
+    /// The seed the initial phases of detuned copies are drawn from
+    seed: u64,
+    /// The length of the fade applied to the start and end of each rendered harmony
+    declick: Option<Duration>,
+    /// The order the voices are played in one at a time, and the time in seconds each is held for
+    arpeggio: Option<(ArpDirection, f64)>,
+    /// The factor the normalized wave is scaled by
+    gain: f64,
+    /// When true samples beyond `LIMITER_KNEE` are soft clipped to keep the wave within unit amplitude
+    limiter: bool,
+    /// Whether each voice, indexed by `Voice as usize`, is muted
+    muted: [bool; 4],
+    /// Whether each voice, indexed by `Voice as usize`, is soloed
+    soloed: [bool; 4],
+    /// The cutoff frequency of the low-pass filter applied to the mixed wave
+    lowpass_hz: Option<f64>,
+    /// The delay time, feedback and wet mix of the echo applied to the mixed wave
+    delay: Option<(Duration, f64, f64)>,
+    /// The envelope shaping every voice without an envelope of its own
+    envelope: Option<Envelope>,
+    /// The envelope of each voice, indexed by `Voice as usize`
+    voice_envelopes: [Option<Envelope>; 4],
+    /// The time between the entries of consecutive voices, from the lowest voice to the highest
+    strum: Option<Duration>,
+    /// The time each voice takes to sweep from its pitch in one harmony to its pitch in the next
+    glide: Option<Duration>,
+    /// When true each voice is weighted by `equal_loudness_gain` of its frequency
+    equal_loudness: bool,
+    /// The stereo position of each voice in interleaved stereo renders
+    pans: Option<VoicePans>,
+    /// The gain of each voice before the voices are summed
+    gains: Option<VoiceGains>,
+    /// The stereo channel being rendered, 0 for left and 1 for right, or `None` for a mono render
+    channel: Option<usize>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            crossfade: None,
+            vibrato: None,
+            detune_cents: 0.0,
+            seed: 0,
+            declick: Some(DEFAULT_DECLICK),
+            arpeggio: None,
+            gain: 1.0,
+            limiter: false,
+            muted: [false; 4],
+            soloed: [false; 4],
+            lowpass_hz: None,
+            delay: None,
+            envelope: None,
+            voice_envelopes: [None; 4],
+            strum: None,
+            glide: None,
+            equal_loudness: false,
+            pans: None,
+            gains: None,
+            channel: None,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Associated method for creating new `RenderOptions`, with harmonies joined end to end.
+    pub fn new() -> Self {
+        RenderOptions::default()
+    }
+
+    /// Builder method that overlaps the tail of each harmony with the head of the next for `overlap` using an equal power crossfade.
+    pub fn crossfade(mut self, overlap: Duration) -> Self {
+        self.crossfade = Some(overlap);
+        self
+    }
+
+    /// Builder method that applies `vibrato` to every voice.
+    pub fn vibrato(mut self, vibrato: Vibrato) -> Self {
+        self.vibrato = Some(vibrato);
+        self
+    }
+
+    /// Builder method that thickens every voice into three copies, tuned `cents` below, at and `cents` above its frequency,
+    /// for a chorused ensemble sound. The copies start at random phases drawn from the seed set by `seed`, so renders are
+    /// reproducible. A detuning of 0 renders a single copy.
+    pub fn detune(mut self, cents: f64) -> Self {
+        self.detune_cents = cents;
+        self
+    }
+
+    /// Builder method that sets the seed the initial phases of detuned copies are drawn from.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Builder method that fades each rendered harmony in and out over `fade` with a raised cosine ramp, removing the clicks
+    /// of a wave starting or stopping abruptly. By default the fade lasts `DEFAULT_DECLICK`.
+    pub fn declick(mut self, fade: Duration) -> Self {
+        self.declick = Some(fade);
+        self
+    }
+
+    /// Builder method that leaves each rendered harmony unfaded, so it starts and stops abruptly as the raw sound wave does.
+    pub fn no_declick(mut self) -> Self {
+        self.declick = None;
+        self
+    }
+
+    /// Builder method that breaks each harmony into an arpeggio, playing its voices one at a time in the order given by
+    /// `direction`, each for `note_duration` seconds, and cycling through them for the full duration of the harmony. Voices are
+    /// ordered by frequency, lowest first, with voices in unison kept in their order in the harmony, soprano first. A note
+    /// duration shorter than one sample leaves the harmony blocked.
+    pub fn arpeggio(mut self, direction: ArpDirection, note_duration: f64) -> Self {
+        self.arpeggio = Some((direction, note_duration));
+        self
+    }
+
+    /// Builder method that scales the normalized wave by `gain`, the master gain. A gain above 1 can break the amplitude contract
+    /// unless the limiter is enabled.
+    pub fn gain(mut self, gain: f64) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Builder method that enables or disables the limiter. When enabled, each sample whose magnitude after the master gain exceeds
+    /// `LIMITER_KNEE` is soft clipped by `soft_clip`, which keeps the wave within [-1, 1] without the flat tops of hard clipping.
+    /// Samples within the knee pass through unchanged, so the quiet passages of a loud render are left alone.
+    pub fn limiter(mut self, enabled: bool) -> Self {
+        self.limiter = enabled;
+        self
+    }
+
+    /// Builder method that silences `voice`. Voices are identified by their position in the harmony's frequencies, soprano first,
+    /// as for `SATB`. Mutes are ignored while any voice is soloed.
+    pub fn mute(mut self, voice: Voice) -> Self {
+        self.muted[voice as usize] = true;
+        self
+    }
+
+    /// Builder method that solos `voice`, silencing every voice that is not soloed. Solos accumulate, so soloing several voices
+    /// renders all of them, and a soloed voice sounds even if it is also muted. Normalization counts only the voices that sound,
+    /// so a soloed voice is as loud as a lone voice.
+    pub fn solo(mut self, voice: Voice) -> Self {
+        self.soloed[voice as usize] = true;
+        self
+    }
+
+    /// Builder method that shapes every voice with `envelope` over the duration of each harmony, unless the voice has an
+    /// envelope of its own set by `voice_envelope`.
+    pub fn envelope(mut self, envelope: Envelope) -> Self {
+        self.envelope = Some(envelope);
+        self
+    }
+
+    /// Builder method that shapes `voice` with `envelope` over the duration of each harmony in place of the envelope set by
+    /// `envelope`, e.g. to let the soprano swell while the bass sustains. Voices are identified as for `mute`.
+    pub fn voice_envelope(mut self, voice: Voice, envelope: Envelope) -> Self {
+        self.voice_envelopes[voice as usize] = Some(envelope);
+        self
+    }
+
+    /// Builder method that staggers the entries of the voices of each harmony, guitar or harp style. The lowest voice enters with
+    /// the harmony and each voice above it enters `strum` after the voice below, then sustains to the end of the harmony.
+    /// Entries later than the end of the harmony are clamped to it, so those voices do not sound.
+    pub fn strum(mut self, strum: Duration) -> Self {
+        self.strum = Some(strum);
+        self
+    }
+
+    /// Builder method that makes each voice of a progression glide into each new harmony, sweeping its frequency linearly in
+    /// log frequency from its pitch in the previous harmony to its new pitch over `glide`, portamento style, rather than jumping.
+    /// Voices holding a common tone are unaffected. Gliding applies only to harmonies joined end to end, not crossfaded, and the
+    /// glide is clamped to the duration of each harmony.
+    pub fn glide(mut self, glide: Duration) -> Self {
+        self.glide = Some(glide);
+        self
+    }
+
+    /// Builder method that enables or disables equal loudness weighting. When enabled each voice of a harmony is weighted by
+    /// `equal_loudness_gain` of its frequency, relative to the most heavily weighted voice so no voice is amplified, which
+    /// lifts low voices that sound quieter than high voices at the same amplitude.
+    pub fn equal_loudness(mut self, enabled: bool) -> Self {
+        self.equal_loudness = enabled;
+        self
+    }
+
+    /// Builder method that places each voice in the stereo field according to `pans` when rendering in stereo, e.g. with
+    /// `render_interleaved_f32`, using constant power panning. Without pans a stereo render carries the mono wave in both
+    /// channels. Mono renders ignore the pans.
+    pub fn pan(mut self, pans: VoicePans) -> Self {
+        self.pans = Some(pans);
+        self
+    }
+
+    /// Builder method that scales each voice by its gain in `gains` before the voices are summed. The sum is divided by the total
+    /// of the gains of the sounding voices or their number, whichever is greater, so boosting a voice never clips while lowering
+    /// or muting a voice leaves the level of the others unchanged. Voices beyond the fourth keep unit gain.
+    /// Returns `RenderError::InvalidGain` if any gain is negative or not finite.
+    pub fn voice_gains(mut self, gains: VoiceGains) -> Result<Self, RenderError> {
+        gains.validate()?;
+        self.gains = Some(gains);
+        Ok(self)
+    }
+
+    /// Helper method that returns the gain set for the voice at `index`, 1 for voices beyond the fourth or without gains.
+    fn voice_gain(&self, index: usize) -> f64 {
+        match (self.gains, Voice::ALL.get(index)) {
+            (Some(gains), Some(voice)) => gains.gain(*voice),
+            _ => 1.0,
+        }
+    }
+
+    /// Helper method that returns the gain of the voice at `index` in the stereo channel being rendered, 1 for mono renders.
+    /// Voices beyond the fourth are placed in the center.
+    fn pan_gain(&self, index: usize) -> f64 {
+        match (self.pans, self.channel) {
+            (Some(pans), Some(channel)) => {
+                let pan = Voice::ALL.get(index).map_or(0.0, |v| pans.pan(*v));
+                constant_power_pan(pan)[channel]
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// Helper method that returns true if the options place the voices in the stereo field.
+    pub(crate) fn is_panned(&self) -> bool {
+        self.pans.is_some()
+    }
+
+    /// Helper method that returns the options rendering the stereo `channel`, 0 for left and 1 for right.
+    pub(crate) fn for_channel(&self, channel: usize) -> Self {
+        RenderOptions {
+            channel: Some(channel),
+            ..*self
+        }
+    }
+
+    /// Helper method that returns the options rendering the voice at `index` on its own as the only voice, with its envelope,
+    /// pan and gain given to every voice and no mutes, solos or arpeggio.
+    pub(crate) fn lone_voice(&self, index: usize) -> Self {
+        RenderOptions {
+            arpeggio: None,
+            muted: [false; 4],
+            soloed: [false; 4],
+            envelope: self.envelope_of(index),
+            voice_envelopes: [None; 4],
+            pans: self.pans.map(|pans| {
+                let pan = Voice::ALL.get(index).map_or(0.0, |v| pans.pan(*v));
+                VoicePans {
+                    soprano: pan,
+                    alto: pan,
+                    tenor: pan,
+                    bass: pan,
+                }
+            }),
+            gains: self.gains.map(|_| {
+                let gain = self.voice_gain(index);
+                VoiceGains {
+                    soprano: gain,
+                    alto: gain,
+                    tenor: gain,
+                    bass: gain,
+                }
+            }),
+            ..*self
+        }
+    }
+
+    /// Helper method that returns the envelope of the voice at `index`, falling back to the envelope of every voice.
+    fn envelope_of(&self, index: usize) -> Option<Envelope> {
+        self.voice_envelopes
+            .get(index)
+            .copied()
+            .flatten()
+            .or(self.envelope)
+    }
+
+    /// Helper method that returns the index and frequency of each voice left sounding by the mutes and solos. Voices beyond the
+    /// fourth are never silenced.
+    fn audible(&self, frequencies: &[f64]) -> Vec<(usize, f64)> {
+        let any_solo = self.soloed.contains(&true);
+        frequencies
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(i, _)| match i {
+                0..=3 if any_solo => self.soloed[*i],
+                0..=3 => !self.muted[*i],
+                _ => !any_solo,
+            })
+            .collect()
+    }
+
+    /// Builder method that filters the mixed wave with a one-pole low-pass filter of cutoff `cutoff_hz`, taming the high harmonics
+    /// of bright timbres. A cutoff at or above the Nyquist frequency leaves the wave unfiltered.
+    /// Returns `RenderError::InvalidCutoff` if the cutoff is not finite or not positive.
+    pub fn lowpass(mut self, cutoff_hz: f64) -> Result<Self, RenderError> {
+        if !(cutoff_hz.is_finite() && cutoff_hz > 0.0) {
+            return Err(RenderError::InvalidCutoff);
+        }
+        self.lowpass_hz = Some(cutoff_hz);
+        Ok(self)
+    }
+
+    /// Builder method that adds an echo to the mixed wave, repeating it every `delay` with each echo `feedback` times as loud
+    /// as the last, blended with the dry wave by `mix`. The rendered wave is extended by the tail of the echoes, see `apply_delay`.
+    /// Returns `RenderError::InvalidFeedback` unless `feedback` lies strictly between -1 and 1, and `RenderError::InvalidMix`
+    /// unless `mix` lies within [0, 1].
+    pub fn delay(mut self, delay: Duration, feedback: f64, mix: f64) -> Result<Self, RenderError> {
+        validate_delay(feedback, mix)?;
+        self.delay = Some((delay, feedback, mix));
+        Ok(self)
+    }
+
+    /// Helper method that applies the low-pass filter, the delay, the master gain and then the limiter to the rendered wave
+    /// `samples` at `sample_freq`, the last stages of the signal chain, with the filter continuing from `state`.
+    pub(crate) fn apply_output(
+        &self,
+        samples: &mut Vec<f64>,
+        sample_freq: u32,
+        state: &mut RenderState,
+    ) {
+        if let Some(cutoff_hz) = self.lowpass_hz {
+            apply_lowpass(samples, cutoff_hz, sample_freq, state);
+        }
+        if let Some((delay, feedback, mix)) = self.delay {
+            // The parameters were validated by the builder
+            let _ = apply_delay(samples, delay, feedback, mix, sample_freq);
+        }
+        if self.gain != 1.0 {
+            for sample in samples.iter_mut() {
+                *sample *= self.gain;
+            }
+        }
+        if self.limiter {
+            for sample in samples.iter_mut() {
+                *sample = soft_clip(*sample);
+            }
+        }
+    }
+
+    /// Helper method that returns the frequency ratio of each copy a voice is rendered as.
+    fn layers(&self) -> Vec<f64> {
+        if self.detune_cents == 0.0 || !self.detune_cents.is_finite() {
+            return vec![1.0];
+        }
+        let ratio = 2f64.powf(self.detune_cents / 1200.0);
+        vec![1.0 / ratio, 1.0, ratio]
+    }
+}
+
+/// A function that computes the gain that evens out the perceived loudness of a tone of `frequency` Hz, the inverse of the
+/// A-weighting curve of IEC 61672. The curve is given analytically by
+/// `R(f) = 12194² f⁴ / ((f² + 20.6²) √((f² + 107.7²)(f² + 737.9²)) (f² + 12194²))`, normalized so the gain at 1 kHz is 1.
+/// Low frequencies, which the ear hears as quieter, are given larger gains, e.g. about 9 at 100 Hz.
+pub fn equal_loudness_gain(frequency: f64) -> f64 {
+    let response = |f: f64| {
+        let f2 = f * f;
+        12194f64.powi(2) * f2 * f2
+            / ((f2 + 20.6f64.powi(2))
+                * ((f2 + 107.7f64.powi(2)) * (f2 + 737.9f64.powi(2))).sqrt()
+                * (f2 + 12194f64.powi(2)))
+    };
+    response(1000.0) / response(frequency)
+}
+
+/// The number of zero crossings on each side of the windowed sinc kernel `resample` interpolates with.
+const RESAMPLE_ZERO_CROSSINGS: f64 = 32.0;
+
+/// A function that resamples `samples`, a sound wave sampled at `from_rate`, to `to_rate`, returning `round(n * to / from)`
+/// samples for `n` input samples. Each output sample is interpolated with a Blackman windowed sinc kernel, a linear phase
+/// low-pass filter at the lower of the two Nyquist frequencies, so downsampling removes the content that would otherwise alias.
+/// Returns the samples unchanged if the rates are equal, and no samples if either rate is zero.
+pub fn resample(samples: &[f64], from_rate: u32, to_rate: u32) -> Vec<f64> {
+    if from_rate == 0 || to_rate == 0 {
+        return Vec::new();
+    }
+    if from_rate == to_rate {
+        return samples.to_vec();
+    }
+    let step = from_rate as f64 / to_rate as f64;
+    let num_samples = (samples.len() as f64 / step).round() as usize;
+    // The cutoff relative to the input Nyquist frequency, and the half width of the kernel in input samples
+    let cutoff = (to_rate as f64 / from_rate as f64).min(1.0);
+    let half_width = RESAMPLE_ZERO_CROSSINGS / cutoff;
+    let kernel = |x: f64| {
+        let u = x / half_width;
+        if u.abs() >= 1.0 {
+            return 0.0;
+        }
+        let sinc = if x == 0.0 {
+            1.0
+        } else {
+            (PI * cutoff * x).sin() / (PI * cutoff * x)
+        };
+        let window = 0.42 + 0.5 * (PI * u).cos() + 0.08 * (2.0 * PI * u).cos();
+        cutoff * sinc * window
+    };
+    (0..num_samples)
+        .map(|j| {
+            let t = j as f64 * step;
+            let first = (t - half_width).ceil().max(0.0) as usize;
+            let last = ((t + half_width).floor() as usize).min(samples.len().saturating_sub(1));
+            (first..=last)
+                .filter_map(|k| samples.get(k).map(|s| s * kernel(t - k as f64)))
+                .sum()
+        })
+        .collect()
+}
+
+/// Enumerates the orders the voices of an arpeggio can be played in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ArpDirection {
+    /// From the lowest voice to the highest
+    #[default]
+    Up,
+    /// From the highest voice to the lowest
+    Down,
+    /// From the lowest voice to the highest and back down, without repeating the highest and lowest voices
+    UpDown,
+}
+
+impl ArpDirection {
+    /// Method that returns one cycle of the arpeggio of `voice_count` voices, as indices of the voices ordered from lowest to highest.
+    pub fn order(&self, voice_count: usize) -> Vec<usize> {
+        match self {
+            ArpDirection::Up => (0..voice_count).collect(),
+            ArpDirection::Down => (0..voice_count).rev().collect(),
+            ArpDirection::UpDown => (0..voice_count)
+                .chain((1..voice_count.saturating_sub(1)).rev())
+                .collect(),
+        }
+    }
+}
+
+/// The magnitude above which the limiter of `RenderOptions` begins to soft clip samples.
+pub const LIMITER_KNEE: f64 = 0.9;
+
+/// A function that soft clips `sample` for the limiter. Samples within `LIMITER_KNEE` are returned unchanged, while the excess
+/// of louder samples is compressed by `tanh` into the space between the knee and 1. The curve is continuous with unit slope at
+/// the knee, and never exceeds unit amplitude.
+pub fn soft_clip(sample: f64) -> f64 {
+    let magnitude = sample.abs();
+    if magnitude <= LIMITER_KNEE {
+        return sample;
+    }
+    let headroom = 1.0 - LIMITER_KNEE;
+    let clipped = LIMITER_KNEE + headroom * ((magnitude - LIMITER_KNEE) / headroom).tanh();
+    clipped.copysign(sample)
+}
+
+/// The default length of the fades applied by `RenderOptions::declick`, long enough to remove clicks but too short to be heard.
+pub const DEFAULT_DECLICK: Duration = Duration::from_millis(5);
+
+/// A function that fades `samples` in over its first `fade_samples` samples and out over its last `fade_samples` samples with
+/// a raised cosine ramp, so the first and last samples are silent. Fades longer than half of `samples` are clamped to half.
+pub fn fade_edges(samples: &mut [f64], fade_samples: usize) {
+    let fade = fade_samples.min(samples.len() / 2);
+    fade_in(samples, fade);
+    fade_out(samples, fade);
+}
+
+/// Helper function that fades `samples` in over its first `fade_samples` samples with a raised cosine ramp, clamped to its length.
+fn fade_in(samples: &mut [f64], fade_samples: usize) {
+    let fade = fade_samples.min(samples.len());
+    for (i, sample) in samples.iter_mut().take(fade).enumerate() {
+        *sample *= 0.5 * (1.0 - f64::cos(PI * i as f64 / fade as f64));
+    }
+}
+
+/// Helper function that fades `samples` out over its last `fade_samples` samples with a raised cosine ramp, clamped to its length.
+fn fade_out(samples: &mut [f64], fade_samples: usize) {
+    let fade = fade_samples.min(samples.len());
+    for (i, sample) in samples.iter_mut().rev().take(fade).enumerate() {
+        *sample *= 0.5 * (1.0 - f64::cos(PI * i as f64 / fade as f64));
+    }
+}
+
+/// Helper function that advances the splitmix64 generator `state`, returning the next pseudo random number.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Helper function that renders a unit amplitude sine voice at each of `frequencies`, according to `options`, producing
+/// `num_samples` samples at `sample_freq` normalized by the number of voices and copies of each voice. The phase of each copy
+/// continues from `phases`, which is left holding the phases after the last sample, and `start` is the index of the first
+/// sample within the whole render, so modulations stay continuous from one harmony to the next. When `glide_from` holds the
+/// frequencies of the previous harmony and `options` sets a glide, each voice sweeps from its previous frequency.
+pub(crate) fn render_voices(
+    frequencies: &[f64],
+    num_samples: usize,
+    sample_freq: u32,
+    options: &RenderOptions,
+    phases: &mut Vec<f64>,
+    start: usize,
+    glide_from: Option<&[f64]>,
+) -> Vec<f64> {
+    let voices = options.audible(frequencies);
+    if let Some((direction, note_duration)) = options.arpeggio {
+        let note_samples = sample_count(note_duration, sample_freq);
+        if note_samples > 0 && !voices.is_empty() {
+            return render_arpeggio(
+                &voices,
+                num_samples,
+                sample_freq,
+                options,
+                direction,
+                note_samples,
+                start,
+            );
+        }
+    }
+    let layers = options.layers();
+    if phases.len() != voices.len() * layers.len() {
+        // Copies beyond the first of each voice start at random phases, so the copies do not simply double in amplitude
+        let mut state = options.seed;
+        *phases = (0..voices.len() * layers.len())
+            .map(|i| match i % layers.len() {
+                0 => 0.0,
+                _ => (splitmix64(&mut state) >> 11) as f64 / (1u64 << 53) as f64,
+            })
+            .collect();
+    }
+    let increments: Vec<f64> = voices
+        .iter()
+        .flat_map(|(_, f)| layers.iter().map(move |l| f * l))
+        .map(|f| f / sample_freq as f64)
+        .collect();
+    // The envelope of each copy of each voice
+    let envelopes: Vec<Option<Envelope>> = voices
+        .iter()
+        .flat_map(|(index, _)| layers.iter().map(|_| options.envelope_of(*index)))
+        .collect();
+    // The sample each copy of each voice enters at, later for higher voices when strummed
+    let strum_samples = options
+        .strum
+        .map_or(0, |strum| sample_count(strum.as_secs_f64(), sample_freq));
+    let onsets: Vec<usize> = voices
+        .iter()
+        .flat_map(|(_, f)| {
+            let rank = voices.iter().filter(|(_, other)| other < f).count();
+            let onset = rank.saturating_mul(strum_samples).min(num_samples);
+            layers.iter().map(move |_| onset)
+        })
+        .collect();
+    // The equal loudness weight of each copy of each voice, relative to the most heavily weighted voice
+    let weights: Vec<f64> = if options.equal_loudness {
+        let gains: Vec<f64> = voices
+            .iter()
+            .map(|(_, f)| equal_loudness_gain(*f))
+            .collect();
+        let loudest = gains.iter().fold(0.0, |max: f64, g| max.max(*g));
+        gains
+            .iter()
+            .flat_map(|g| layers.iter().map(move |_| g / loudest))
+            .collect()
+    } else {
+        vec![1.0; increments.len()]
+    };
+    // The gain of each copy of each voice in the stereo channel being rendered
+    let weights: Vec<f64> = voices
+        .iter()
+        .flat_map(|(index, _)| {
+            layers
+                .iter()
+                .map(|_| options.voice_gain(*index) * options.pan_gain(*index))
+        })
+        .zip(weights)
+        .map(|(gain, weight)| gain * weight)
+        .collect();
+    let duration = num_samples as f64 / sample_freq as f64;
+    let level = |copy: usize, i: usize| {
+        weights[copy]
+            * envelopes[copy].map_or(1.0, |e| {
+                e.amplitude(i as f64 / sample_freq as f64, duration)
+            })
+    };
+    // The ratio of the previous frequency to the new frequency of each copy of each voice, swept out over the glide
+    let glide_samples = options
+        .glide
+        .map_or(0, |glide| sample_count(glide.as_secs_f64(), sample_freq))
+        .min(num_samples);
+    let glide_ratios: Vec<f64> = voices
+        .iter()
+        .flat_map(|(index, f)| {
+            let ratio = match glide_from.and_then(|from| from.get(*index)) {
+                Some(from) if glide_samples > 0 && *from > 0.0 && *f > 0.0 => from / f,
+                _ => 1.0,
+            };
+            layers.iter().map(move |_| ratio)
+        })
+        .collect();
+    let gliding = glide_ratios.iter().any(|r| *r != 1.0);
+    // The voices are normalized by their number, or by the total of their gains if greater
+    let total_gain: f64 = voices
+        .iter()
+        .map(|(index, _)| options.voice_gain(*index))
+        .sum();
+    let divisor = (total_gain.max(voices.len() as f64) * layers.len() as f64).max(1.0);
+    let mut wave = Vec::with_capacity(num_samples);
+    match options.vibrato.filter(|v| v.depth_cents != 0.0) {
+        vibrato if vibrato.is_some() || gliding => {
+            for i in 0..num_samples {
+                let ratio = vibrato.map_or(1.0, |v| {
+                    v.frequency_ratio((start + i) as f64 / sample_freq as f64)
+                });
+                let mut sample = 0.0;
+                for (copy, (phase, increment)) in phases.iter_mut().zip(&increments).enumerate() {
+                    if i < onsets[copy] {
+                        continue;
+                    }
+                    sample += Waveform::Sine.value(*phase) * level(copy, i);
+                    // The frequency sweeps linearly in log frequency, integrated into the phase a sample at a time
+                    let glide = if i < glide_samples {
+                        glide_ratios[copy].powf(1.0 - i as f64 / glide_samples as f64)
+                    } else {
+                        1.0
+                    };
+                    *phase = (*phase + increment * ratio * glide).fract();
+                }
+                wave.push(sample / divisor);
+            }
+        }
+        _ => {
+            // Without vibrato the increments are fixed, so each voice is rendered by a phasor
+            let mut phasors: Vec<Phasor> = phases
+                .iter()
+                .zip(&increments)
+                .map(|(phase, increment)| Phasor::new(*phase, *increment))
+                .collect();
+            for i in 0..num_samples {
+                let mut sample = 0.0;
+                for (copy, phasor) in phasors.iter_mut().enumerate() {
+                    if i < onsets[copy] {
+                        continue;
+                    }
+                    sample += phasor.next_value() * level(copy, i);
+                }
+                wave.push(sample / divisor);
+            }
+            for (phase, phasor) in phases.iter_mut().zip(&phasors) {
+                *phase = phasor.phase();
+            }
+        }
+    }
+    if let Some(fade) = options.declick {
+        fade_edges(&mut wave, sample_count(fade.as_secs_f64(), sample_freq));
+    }
+    wave
+}
+
+/// Helper function that renders `num_samples` samples of the arpeggio of `voices`, given by their index and frequency, at
+/// `sample_freq`, playing them one at a time in the order given by `direction` for `note_samples` samples each. Each note is
+/// rendered on its own with `options` and the envelope of its voice, so it starts from zero phase and is declicked separately.
+fn render_arpeggio(
+    voices: &[(usize, f64)],
+    num_samples: usize,
+    sample_freq: u32,
+    options: &RenderOptions,
+    direction: ArpDirection,
+    note_samples: usize,
+    start: usize,
+) -> Vec<f64> {
+    // The sort is stable, so voices in unison keep their order in the harmony
+    let mut sorted = voices.to_vec();
+    sorted.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let order = direction.order(sorted.len());
+    let mut wave = Vec::with_capacity(num_samples);
+    for position in order.iter().cycle() {
+        if wave.len() >= num_samples {
+            break;
+        }
+        let (index, frequency) = sorted[*position];
+        // The note is rendered as a lone voice, already chosen by the mutes and solos
+        let note_options = options.lone_voice(index);
+        let note = render_voices(
+            &[frequency],
+            note_samples.min(num_samples - wave.len()),
+            sample_freq,
+            &note_options,
+            &mut Vec::new(),
+            start + wave.len(),
+            None,
+        );
+        wave.extend(note);
+    }
+    wave
+}
+
+/// The longest crossfade `render_loop` joins the end of a loop to its start with, in seconds.
+const LOOP_CROSSFADE: f64 = 0.01;
+
+/// Helper function that finds the loop length within an eighth of `target` samples at which the voices of `frequencies`
+/// come closest to completing a whole number of cycles, measured by the total distance of their phases from a whole cycle.
+pub(crate) fn seamless_loop_length(frequencies: &[f64], target: usize, sample_freq: u32) -> usize {
+    let increments: Vec<f64> = frequencies.iter().map(|f| f / sample_freq as f64).collect();
+    let mismatch = |length: usize| -> f64 {
+        increments
+            .iter()
+            .map(|increment| {
+                let cycles = length as f64 * increment;
+                (cycles - cycles.round()).abs()
+            })
+            .sum()
+    };
+    (target.saturating_sub(target / 8).max(1)..=target + target / 8)
+        .min_by(|a, b| mismatch(*a).total_cmp(&mismatch(*b)))
+        .unwrap_or(target)
+}
+
+/// Helper function that sums unit amplitude sine waves at each of `frequencies` into `num_samples` samples at `sample_freq`,
+/// repeating a loop of about `loop_samples` samples chosen by `seamless_loop_length`. The samples following the loop are
+/// crossfaded into its start, so the end of each repeat leads into the next as the continuous wave would.
+pub(crate) fn render_loop(
+    frequencies: &[f64],
+    num_samples: usize,
+    loop_samples: usize,
+    sample_freq: u32,
+) -> Vec<f64> {
+    if loop_samples == 0 || loop_samples >= num_samples {
+        return sum_waves(frequencies, num_samples, sample_freq, Waveform::Sine.into());
+    }
+    let length = seamless_loop_length(frequencies, loop_samples, sample_freq);
+    let fade = sample_count(LOOP_CROSSFADE, sample_freq).min(length / 2);
+    let mut region = sum_waves(
+        frequencies,
+        length + fade,
+        sample_freq,
+        Waveform::Sine.into(),
+    );
+    let (head, tail) = region.split_at_mut(length);
+    for (i, (sample, next)) in head.iter_mut().zip(tail.iter()).enumerate() {
+        let t = (i as f64 + 0.5) / fade as f64 * PI / 2.0;
+        *sample = *next * t.cos() + *sample * t.sin();
+    }
+    region.truncate(length);
+    region.iter().copied().cycle().take(num_samples).collect()
+}
+
+/// A function that filters `samples` in place with a one-pole low-pass filter of cutoff `cutoff_hz` at `sample_freq`, continuing
+/// from the filter memory held in `state` so a stream may be filtered in chunks. The filter rolls off at 6 dB per octave above
+/// the cutoff. Cutoffs at or above the Nyquist frequency, and cutoffs that are not finite and positive, leave `samples` unchanged.
+pub fn apply_lowpass(
+    samples: &mut [f64],
+    cutoff_hz: f64,
+    sample_freq: u32,
+    state: &mut RenderState,
+) {
+    if !(cutoff_hz.is_finite() && cutoff_hz > 0.0) || cutoff_hz >= sample_freq as f64 / 2.0 {
+        return;
+    }
+    let coefficient = 1.0 - (-2.0 * PI * cutoff_hz / sample_freq as f64).exp();
+    for sample in samples.iter_mut() {
+        state.lowpass += coefficient * (*sample - state.lowpass);
+        *sample = state.lowpass;
+    }
+}
+
+/// The longest tail `apply_delay` extends a wave by, in seconds.
+pub const DELAY_TAIL_CAP: f64 = 10.0;
+
+/// The level relative to the loudest echo below which `apply_delay` lets the tail of the echoes end, -60 dB.
+const DELAY_TAIL_LEVEL: f64 = 0.001;
+
+/// Helper function that checks the `feedback` and `mix` of a delay are valid.
+fn validate_delay(feedback: f64, mix: f64) -> Result<(), RenderError> {
+    if !(feedback.is_finite() && feedback.abs() < 1.0) {
+        return Err(RenderError::InvalidFeedback);
+    }
+    if !(0.0..=1.0).contains(&mix) {
+        return Err(RenderError::InvalidMix);
+    }
+    Ok(())
+}
+
+/// A function that adds an echo to `samples` at `sample_rate` with a feedback comb filter. The wet signal repeats the input
+/// every `delay`, each echo `feedback` times as loud as the last, and the output is the dry input scaled by `1 - mix` plus the
+/// wet signal scaled by `mix`, so the first echo arrives after `delay` at `mix` times the level of the input. `samples` is
+/// extended until the echoes decay below -60 dB of the first, by at most `DELAY_TAIL_CAP` seconds. A delay shorter than one
+/// sample leaves `samples` unchanged.
+/// Returns `RenderError::InvalidFeedback` unless `feedback` lies strictly between -1 and 1, as the echoes would otherwise never
+/// decay, and `RenderError::InvalidMix` unless `mix` lies within [0, 1].
+pub fn apply_delay(
+    samples: &mut Vec<f64>,
+    delay: Duration,
+    feedback: f64,
+    mix: f64,
+    sample_rate: u32,
+) -> Result<(), RenderError> {
+    validate_delay(feedback, mix)?;
+    let delay_samples = sample_count(delay.as_secs_f64(), sample_rate);
+    if delay_samples == 0 || mix == 0.0 {
+        return Ok(());
+    }
+    // The number of echoes before the last falls below the tail level
+    let echoes = if feedback == 0.0 {
+        1.0
+    } else {
+        1.0 + (DELAY_TAIL_LEVEL.ln() / feedback.abs().ln()).ceil()
+    };
+    let tail = (echoes * delay_samples as f64).min(DELAY_TAIL_CAP * sample_rate as f64) as usize;
+    samples.resize(samples.len() + tail, 0.0);
+
+    let mut wet = vec![0.0; samples.len()];
+    for n in delay_samples..samples.len() {
+        wet[n] = samples[n - delay_samples] + feedback * wet[n - delay_samples];
+    }
+    for (sample, wet) in samples.iter_mut().zip(wet) {
+        *sample = (1.0 - mix) * *sample + mix * wet;
+    }
+    Ok(())
+}
+
+/// Enumerates the problems noticed while rendering, that did not prevent the rendering from completing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RenderWarning {
+    /// The crossfade into harmony `index` was longer than one of the harmonies it joins, and was shortened to `applied` samples.
+    OverlapClamped {
+        index: usize,
+        requested: usize,
+        applied: usize,
+    },
+}
+
+/// The result of rendering a `Progression`, the samples of its sound wave along with any warnings raised.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Rendered {
+    /// The samples of the sound wave
+    pub samples: Vec<f64>,
+    /// The warnings raised while rendering
+    pub warnings: Vec<RenderWarning>,
+}
+
+/// A function that computes the number of samples spanned by `duration` seconds at `sample_freq`, i.e. `duration * sample_freq`
+/// rounded to the nearest integer. Negative, zero and non-finite durations span no samples.
+pub fn sample_count(duration: f64, sample_freq: u32) -> usize {
+    if !(duration.is_finite() && duration > 0.0) {
+        return 0;
+    }
+    (duration * sample_freq as f64).round() as usize
+}
+
+/// The stereo position of each voice of an `SATB` harmony, from -1 for hard left through 0 for center to 1 for hard right.
+/// Positions outside of [-1, 1] are clamped.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VoicePans {
+    pub soprano: f64,
+    pub alto: f64,
+    pub tenor: f64,
+    pub bass: f64,
+}
+
+impl Default for VoicePans {
+    /// The lower voices are placed slightly left and the upper voices slightly right, as a choir is often seated.
+    fn default() -> Self {
+        VoicePans {
+            soprano: 0.3,
+            alto: 0.15,
+            tenor: -0.15,
+            bass: -0.3,
+        }
+    }
+}
+
+impl VoicePans {
+    /// Associated method that places every voice in the center.
+    pub fn centered() -> Self {
+        VoicePans {
+            soprano: 0.0,
+            alto: 0.0,
+            tenor: 0.0,
+            bass: 0.0,
+        }
+    }
+
+    /// Method for accessing the pan position of `voice`.
+    pub fn pan(&self, voice: Voice) -> f64 {
+        match voice {
+            Voice::Soprano => self.soprano,
+            Voice::Alto => self.alto,
+            Voice::Tenor => self.tenor,
+            Voice::Bass => self.bass,
+        }
+    }
+}
+
+/// A function that computes the left and right channel gains of a signal at the pan position `pan` using constant power
+/// panning, so the sum of the squared gains is 1 wherever the signal is placed.
+pub fn constant_power_pan(pan: f64) -> [f64; 2] {
+    // The angles are computed symmetrically so a centered signal has exactly equal gains
+    let pan = pan.clamp(-1.0, 1.0);
+    [
+        ((1.0 + pan) * PI / 4.0).cos(),
+        ((1.0 - pan) * PI / 4.0).cos(),
+    ]
+}
+
+/// The linear gain applied to each voice of an `SATB` harmony when it is rendered. A gain of 0 removes the voice entirely.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VoiceGains {
+    pub soprano: f64,
+    pub alto: f64,
+    pub tenor: f64,
+    pub bass: f64,
+}
+
+impl Default for VoiceGains {
+    fn default() -> Self {
+        VoiceGains {
+            soprano: 1.0,
+            alto: 1.0,
+            tenor: 1.0,
+            bass: 1.0,
+        }
+    }
+}
+
+impl VoiceGains {
+    /// Method for accessing the gain of `voice`.
+    pub fn gain(&self, voice: Voice) -> f64 {
+        match voice {
+            Voice::Soprano => self.soprano,
+            Voice::Alto => self.alto,
+            Voice::Tenor => self.tenor,
+            Voice::Bass => self.bass,
+        }
+    }
+
+    /// Method that checks every gain is a finite, non negative number, returning the first voice whose gain is not.
+    pub fn validate(&self) -> Result<(), RenderError> {
+        match Voice::ALL
+            .iter()
+            .find(|v| !(self.gain(**v).is_finite() && self.gain(**v) >= 0.0))
+        {
+            Some(voice) => Err(RenderError::InvalidGain(*voice)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Enumerates the errors that can occur when rendering a harmony.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RenderError {
+    /// The gain of the voice is negative or not finite.
+    InvalidGain(Voice),
+    /// The cutoff frequency of a filter is not positive or not finite.
+    InvalidCutoff,
+    /// The feedback of a delay does not lie strictly between -1 and 1, so its echoes would never decay.
+    InvalidFeedback,
+    /// The wet mix of a delay does not lie within [0, 1].
+    InvalidMix,
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderError::InvalidGain(voice) => {
+                write!(
+                    f,
+                    "the gain of the {} must be finite and non negative",
+                    voice
+                )
+            }
+            RenderError::InvalidCutoff => {
+                write!(f, "the cutoff frequency must be finite and positive")
+            }
+            RenderError::InvalidFeedback => {
+                write!(f, "the delay feedback must lie strictly between -1 and 1")
+            }
+            RenderError::InvalidMix => write!(f, "the delay mix must lie between 0 and 1"),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+/// The state carried between calls to `SATB::render_into`, holding the phase of each voice so consecutive buffers, including
+/// buffers rendered from different harmonies, join without discontinuities, along with the memory of the low-pass filter.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct RenderState {
+    /// The phase of each voice, in cycles, ordered soprano, alto, tenor, bass
+    phases: [f64; 4],
+    /// The last output of the low-pass filter
+    lowpass: f64,
+}
+
+impl RenderState {
+    /// Method for accessing the phase of each voice in cycles, ordered soprano, alto, tenor, bass.
+    pub fn phases(&self) -> [f64; 4] {
+        self.phases
+    }
+}
+
+impl SATB {
+    /// Method that returns an iterator over the samples of the harmony's sound wave at `sample_freq`, normalized by the number
+    /// of voices. The iterator is effectively infinite, use `take` to bound it.
+    pub fn samples(&self, sample_freq: u32) -> SatbSamples {
+        SatbSamples {
+            phasors: increments(self, sample_freq).map(|increment| Phasor::new(0.0, increment)),
+            remaining: usize::MAX,
+        }
+    }
+
+    /// Method that returns `duration` seconds of the harmony's sound wave sampled at `sample_freq` in stereo, as pairs of left and
+    /// right samples, with each voice placed according to `pans` as by `RenderOptions::pan`. Each channel is normalized by the
+    /// number of voices. With every voice centered each channel equals the mono `sound_wave` scaled by `1 / sqrt(2)`.
+    pub fn sound_wave_stereo(
+        &self,
+        duration: u32,
+        sample_freq: u32,
+        pans: &VoicePans,
+    ) -> Vec<[f64; 2]> {
+        self.render_stereo(
+            duration as f64,
+            sample_freq,
+            &RenderOptions::new().no_declick().pan(*pans),
+        )
+    }
+
+    /// Method that returns `duration` seconds of the harmony's sound wave sampled at `sample_freq`, with each voice scaled by its
+    /// gain in `gains` as by `RenderOptions::voice_gains`, so boosting a voice never clips while lowering or muting a voice
+    /// leaves the level of the others unchanged. Returns an error if any gain is negative or not finite.
+    pub fn sound_wave_with_gains(
+        &self,
+        duration: u32,
+        sample_freq: u32,
+        gains: &VoiceGains,
+    ) -> Result<Vec<f64>, RenderError> {
+        let options = RenderOptions::new().no_declick().voice_gains(*gains)?;
+        Ok(self.render(duration as f64, sample_freq, &options))
+    }
+
+    /// Method that fills `buf` with the next samples of the harmony's sound wave at `sample_freq`, normalized by the number of
+    /// voices, continuing from the phases held in `state`. Does not allocate, so it is suitable for real time audio callbacks.
+    /// Swapping to a different harmony between calls keeps the phase of each voice continuous.
+    pub fn render_into(&self, buf: &mut [f32], sample_freq: u32, state: &mut RenderState) {
+        let increments = increments(self, sample_freq);
+        for sample in buf.iter_mut() {
+            *sample = advance(&mut state.phases, &increments) as f32;
+        }
+    }
+
+    /// Method that fills `buf` with the next samples of the harmony's sound wave as `render_into` does, then applies the stages of
+    /// `options` that work on a stream: the low-pass filter, continuing from the filter memory in `state`, and the master gain.
+    pub fn render_into_with(
+        &self,
+        buf: &mut [f32],
+        sample_freq: u32,
+        options: &RenderOptions,
+        state: &mut RenderState,
+    ) {
+        let increments = increments(self, sample_freq);
+        let cutoff_hz = options.lowpass_hz;
+        for sample in buf.iter_mut() {
+            let mut value = advance(&mut state.phases, &increments);
+            if let Some(cutoff_hz) = cutoff_hz {
+                let mut filtered = [value];
+                apply_lowpass(&mut filtered, cutoff_hz, sample_freq, state);
+                value = filtered[0];
+            }
+            *sample = (value * options.gain) as f32;
+        }
+    }
+}
+
+impl Progression {
+    /// Method that renders the progression's sound wave at `sample_freq`, normalized by the number of voices, according to `options`.
+    /// Without a crossfade the harmonies are joined end to end with the phase of each voice continuous, otherwise each harmony
+    /// is rendered in full and crossfaded into the next, so the rendered wave is shorter than the total duration by the overlaps.
+    /// Each rest is rendered as exactly its duration of silence, with the harmonies either side of it faded out and back in over
+    /// `DEFAULT_DECLICK` unless `options` declicks every harmony, and harmonies are neither crossfaded nor glided across a rest.
+    pub fn render(&self, sample_freq: u32, options: &RenderOptions) -> Rendered {
+        let mut rendered = Rendered::default();
+        let mut phases = Vec::new();
+        let requested = options
+            .crossfade
+            .map(|overlap| sample_count(overlap.as_secs_f64(), sample_freq));
+        // The fade either side of a rest, unless each harmony is already declicked
+        let rest_fade = match options.declick {
+            Some(_) => 0,
+            None => sample_count(DEFAULT_DECLICK.as_secs_f64(), sample_freq),
+        };
+        // The wave is at most the total duration long, shorter by any overlaps
+        rendered.samples.reserve(
+            self.durations()
+                .iter()
+                .chain(self.rests())
+                .chain([self.trailing_rest()].iter())
+                .map(|d| sample_count(*d, sample_freq))
+                .sum(),
+        );
+        for (index, ((chord, duration), rest)) in self
+            .chords()
+            .iter()
+            .zip(self.durations())
+            .zip(self.rests())
+            .enumerate()
+        {
+            let rest = sample_count(*rest, sample_freq);
+            if rest > 0 {
+                fade_out(&mut rendered.samples, rest_fade);
+                rendered.samples.resize(rendered.samples.len() + rest, 0.0);
+                phases.clear();
+            }
+            let head = rendered.samples.len();
+            match requested {
+                None => {
+                    let previous = index
+                        .checked_sub(1)
+                        .filter(|_| rest == 0)
+                        .map(|i| self.chords()[i].frequencies());
+                    rendered.samples.extend(render_voices(
+                        &chord.frequencies(),
+                        sample_count(*duration, sample_freq),
+                        sample_freq,
+                        options,
+                        &mut phases,
+                        head,
+                        previous.as_deref(),
+                    ));
+                }
+                Some(requested) => {
+                    let start = match rest {
+                        0 => head.saturating_sub(requested),
+                        _ => head,
+                    };
+                    let wave = render_voices(
+                        &chord.frequencies(),
+                        sample_count(*duration, sample_freq),
+                        sample_freq,
+                        options,
+                        &mut Vec::new(),
+                        start,
+                        None,
+                    );
+                    if index == 0 || rest > 0 {
+                        rendered.samples.extend(wave);
+                    } else {
+                        let applied = requested.min(rendered.samples.len()).min(wave.len());
+                        if applied < requested {
+                            rendered.warnings.push(RenderWarning::OverlapClamped {
+                                index,
+                                requested,
+                                applied,
+                            });
+                        }
+                        crossfade_onto(&mut rendered.samples, &wave, applied);
+                    }
+                }
+            }
+            if rest > 0 {
+                fade_in(&mut rendered.samples[head..], rest_fade);
+            }
+        }
+        let trailing = sample_count(self.trailing_rest(), sample_freq);
+        if trailing > 0 {
+            fade_out(&mut rendered.samples, rest_fade);
+            rendered
+                .samples
+                .resize(rendered.samples.len() + trailing, 0.0);
+        }
+        options.apply_output(
+            &mut rendered.samples,
+            sample_freq,
+            &mut RenderState::default(),
+        );
+        rendered
+    }
+
+    /// Method that returns an iterator over the samples of the progression's sound wave at `sample_freq`, normalized by the
+    /// number of voices, with each harmony sounding for its duration.
+    pub fn samples(&self, sample_freq: u32) -> ProgressionSamples<'_> {
+        ProgressionSamples::new(Cow::Borrowed(self), sample_freq)
+    }
+
+    /// Method that consumes the progression, returning an iterator over the samples of its sound wave as `samples` does.
+    /// The iterator owns the progression, so it may outlive the scope it was created in.
+    pub fn into_samples(self, sample_freq: u32) -> ProgressionSamples<'static> {
+        ProgressionSamples::new(Cow::Owned(self), sample_freq)
+    }
+}
+
+impl Melody {
+    /// Method that renders the melody as a sound wave sampled at `sample_freq` according to `options`, each note a single sine
+    /// voice, treated as the soprano, lasting for its note value and shaped by the envelope over its own duration, and each rest silent.
+    /// Each note starts from zero phase and is declicked on its own, unless the melody is legato, in which case each note
+    /// continues from the last and glides into its pitch when `options` sets a glide. Without a declick, the notes either side
+    /// of a rest are faded out and back in over `DEFAULT_DECLICK`. The wave has the number of samples of each event's duration summed.
+    pub fn render(&self, sample_freq: u32, options: &RenderOptions) -> Vec<f64> {
+        let legato = self.is_legato();
+        let note_options = RenderOptions {
+            declick: options.declick.filter(|_| !legato),
+            ..*options
+        };
+        let fade = options
+            .declick
+            .filter(|_| legato)
+            .map(|fade| sample_count(fade.as_secs_f64(), sample_freq));
+        let rest_fade = match options.declick {
+            Some(_) => 0,
+            None => sample_count(DEFAULT_DECLICK.as_secs_f64(), sample_freq),
+        };
+        let mut wave = Vec::new();
+        let mut phases = Vec::new();
+        let mut previous: Option<f64> = None;
+        let mut after_rest = false;
+        // The index of the first sample of the current legato line
+        let mut line_start = 0;
+        for (pitch, value) in self {
+            let num_samples = sample_count(value.seconds(self.tempo()), sample_freq);
+            let Some(pitch) = pitch else {
+                if let Some(fade) = fade {
+                    fade_edges(&mut wave[line_start..], fade);
+                }
+                fade_out(&mut wave, rest_fade);
+                wave.resize(wave.len() + num_samples, 0.0);
+                line_start = wave.len();
+                phases.clear();
+                previous = None;
+                after_rest = true;
+                continue;
+            };
+            if !legato {
+                phases.clear();
+            }
+            let start = wave.len();
+            let glide_from = previous.filter(|_| legato).map(|f| [f]);
+            wave.extend(render_voices(
+                &[pitch.frequency()],
+                num_samples,
+                sample_freq,
+                &note_options,
+                &mut phases,
+                start,
+                glide_from.as_ref().map(|f| &f[..]),
+            ));
+            if after_rest {
+                fade_in(&mut wave[start..], rest_fade);
+                after_rest = false;
+            }
+            previous = Some(pitch.frequency());
+        }
+        if let Some(fade) = fade {
+            fade_edges(&mut wave[line_start..], fade);
+        }
+        options.apply_output(&mut wave, sample_freq, &mut RenderState::default());
+        wave
+    }
+}
+
+impl OrnamentedProgression {
+    /// Method that renders the ornamented progression as a sound wave sampled at `sample_freq` according to `options`. Each voice
+    /// is rendered on its own as an unbroken line, continuing its phase and gliding from one note event to the next, with its
+    /// notes shaped by the envelope over their own duration and each line declicked as a whole. The voices are weighted by their
+    /// gains and normalized as the voices of a harmony are. The wave has the number of samples of the progression's duration.
+    pub fn render(&self, sample_freq: u32, options: &RenderOptions) -> Vec<f64> {
+        let total = sample_count(self.duration(), sample_freq);
+        let fade = options
+            .declick
+            .map(|fade| sample_count(fade.as_secs_f64(), sample_freq));
+        let voices = options.audible(&[0.0; 4]);
+        let mut wave = vec![0.0; total];
+        for (index, _) in &voices {
+            let voice_options = RenderOptions {
+                declick: None,
+                gains: None,
+                ..options.lone_voice(*index)
+            };
+            let mut line = Vec::with_capacity(total);
+            let mut phases = Vec::new();
+            let mut previous: Option<f64> = None;
+            // The index of the first sample of the current line
+            let mut line_start = 0;
+            for event in self.voice(Voice::ALL[*index]) {
+                // The bounds are clamped to the wave, so an event can never reach past its end
+                let start = sample_count(event.start, sample_freq).min(total);
+                let end =
+                    sample_count(event.start + event.duration, sample_freq).clamp(start, total);
+                if start > line.len() {
+                    // A rest breaks the line, so the next note starts a new one
+                    if let Some(fade) = fade {
+                        fade_edges(&mut line[line_start..], fade);
+                    }
+                    line.resize(start, 0.0);
+                    line_start = start;
+                    phases.clear();
+                    previous = None;
+                }
+                line.truncate(start);
+                let glide_from = previous.map(|f| [f]);
+                line.extend(render_voices(
+                    &[event.pitch.frequency()],
+                    end - start,
+                    sample_freq,
+                    &voice_options,
+                    &mut phases,
+                    start,
+                    glide_from.as_ref().map(|f| &f[..]),
+                ));
+                previous = Some(event.pitch.frequency());
+            }
+            if let Some(fade) = fade {
+                fade_edges(&mut line[line_start..], fade);
+            }
+            let gain = options.voice_gain(*index);
+            for (sample, value) in wave.iter_mut().zip(&line) {
+                *sample += gain * value;
+            }
+        }
+        // The voices are normalized by their number, or by the total of their gains if greater
+        let total_gain: f64 = voices
+            .iter()
+            .map(|(index, _)| options.voice_gain(*index))
+            .sum();
+        let divisor = total_gain.max(voices.len() as f64).max(1.0);
+        for sample in &mut wave {
+            *sample /= divisor;
+        }
+        options.apply_output(&mut wave, sample_freq, &mut RenderState::default());
+        wave
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{NoteValue, Pitch, Rest, Tempo};
+
+    /// Helper function that estimates the amplitude of the `freq` component of `samples` with the Goertzel algorithm.
+    fn goertzel(samples: &[f64], freq: f64, sample_freq: u32) -> f64 {
+        let coeff = 2.0 * (2.0 * PI * freq / sample_freq as f64).cos();
+        let (mut s1, mut s2) = (0.0, 0.0);
+        for sample in samples {
+            let s0 = sample + coeff * s1 - s2;
+            s2 = s1;
+            s1 = s0;
+        }
+        (s1 * s1 + s2 * s2 - coeff * s1 * s2).sqrt() * 2.0 / samples.len() as f64
+    }
+
+    fn naive(waveform: Waveform) -> Oscillator {
+        Oscillator {
+            waveform,
+            naive: true,
+        }
+    }
+
+    fn c_major() -> SATB {
+        SATB::new(
+            0,
+            (0, 5).into(),
+            (7, 4).into(),
+            (4, 4).into(),
+            (0, 3).into(),
+        )
+    }
+
+    #[test]
+    fn test_sound_wave_within_unit_range() {
+        let wave = c_major().sound_wave(1, 44100);
+        let peak = wave.iter().fold(0.0, |peak: f64, s| peak.max(s.abs()));
+        assert!(peak <= 1.0);
+
+        let wave = c_major().sound_wave_normalized(1, 44100, Normalization::Peak);
+        let peak = wave.iter().fold(0.0, |peak: f64, s| peak.max(s.abs()));
+        assert!((peak - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_normalization_preserves_balance() {
+        let chord = c_major();
+        let raw = chord.sound_wave_normalized(1, 8000, Normalization::None);
+        let normalized = chord.sound_wave(1, 8000);
+        assert!(raw.iter().any(|s| s.abs() > 1.0));
+        for (r, n) in raw.iter().zip(&normalized) {
+            assert!((r / 4.0 - n).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_envelope_shapes_wave() {
+        let envelope = Envelope {
+            attack: 0.1,
+            decay: 0.1,
+            sustain_level: 0.5,
+            release: 0.2,
+        };
+        assert_eq!(envelope.amplitude(0.0, 1.0), 0.0);
+        assert!((envelope.amplitude(0.5, 1.0) - 0.5).abs() < 1e-12);
+
+        let wave = c_major().sound_wave_with_envelope(1, 8000, &envelope);
+        let plain = c_major().sound_wave(1, 8000);
+        assert_eq!(wave.len(), 8000);
+        assert!(wave[0].abs() < 1e-9);
+        assert!((wave[4000] - 0.5 * plain[4000]).abs() < 1e-12);
+        assert!(wave[7999].abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_degenerate_envelope() {
+        let envelope = Envelope {
+            attack: 0.0,
+            decay: 0.0,
+            sustain_level: 0.0,
+            release: 0.0,
+        };
+        let wave = c_major().sound_wave_with_envelope(1, 100, &envelope);
+        assert!(wave.iter().all(|s| s.is_finite()));
+        assert_eq!(envelope.amplitude(0.0, 1.0), 0.0);
+        let held = Envelope {
+            sustain_level: 1.0,
+            ..envelope
+        };
+        assert_eq!(held.amplitude(0.0, 1.0), 1.0);
+        assert_eq!(held.amplitude(0.99, 1.0), 1.0);
+
+        // Segments longer than the harmony are shortened to fit
+        let long = Envelope {
+            attack: 2.0,
+            decay: 0.0,
+            sustain_level: 1.0,
+            release: 2.0,
+        };
+        assert!((long.amplitude(0.5, 1.0) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_square_wave_period_and_duty_cycle() {
+        // A 10 Hz square wave sampled at 1 kHz has a period of 100 samples, half of them positive
+        let wave = sum_waves(&[10.0], 1000, 1000, naive(Waveform::Square));
+        assert_eq!(wave.len(), 1000);
+        for (i, sample) in wave.iter().enumerate() {
+            let expected = if i % 100 < 50 { 1.0 } else { -1.0 };
+            assert_eq!(*sample, expected, "sample {}", i);
+        }
+    }
+
+    #[test]
+    fn test_triangle_wave_peaks() {
+        let wave = sum_waves(&[10.0], 1000, 1000, naive(Waveform::Triangle));
+        let max = wave.iter().cloned().fold(f64::MIN, f64::max);
+        let min = wave.iter().cloned().fold(f64::MAX, f64::min);
+        assert!((max - 1.0).abs() < 1e-9);
+        assert!((min + 1.0).abs() < 1e-9);
+
+        let wave = c_major().sound_wave_with(1, 8000, Waveform::Sawtooth);
+        assert!(wave.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_sample_conversion() {
+        assert_eq!(i16::from_f64(1.0), 32767);
+        assert_eq!(i16::from_f64(-1.0), -32768);
+        assert_eq!(i16::from_f64(0.0), 0);
+        assert_eq!(i16::from_f64(2.5), 32767);
+        assert_eq!(i16::from_f64(f64::NAN), 0);
+
+        let chord = c_major();
+        let wide: Vec<f64> = chord.sound_wave_as(1, 8000);
+        let narrow: Vec<f32> = chord.sound_wave_as(1, 8000);
+        assert_eq!(wide, chord.sound_wave(1, 8000));
+        for (w, n) in wide.iter().zip(&narrow) {
+            assert!((*w - *n as f64).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_samples_match_sound_wave() {
+        let chord = c_major();
+        let buffered = chord.sound_wave(2, 8000);
+        let streamed: Vec<f64> = chord.samples(8000).take(16000).collect();
+        assert_eq!(buffered, streamed);
+        assert_eq!(
+            chord.sound_wave_normalized(2, 8000, Normalization::VoiceCount),
+            buffered
+        );
+
+        // The iterator holds no buffer, so taking millions of samples needs no allocation
+        let samples = chord.samples(44100).take(5_000_000);
+        assert_eq!(samples.len(), 5_000_000);
+        assert_eq!(samples.filter(|s| s.abs() > 1.0).count(), 0);
+    }
+
+    #[test]
+    fn test_progression_samples() {
+        let mut progression = Progression::new();
+        progression.push_with_duration(c_major(), 0.5);
+        progression.push_with_duration(
+            SATB::new(
+                7,
+                (11, 4).into(),
+                (7, 4).into(),
+                (2, 4).into(),
+                (7, 2).into(),
+            ),
+            0.25,
+        );
+        let samples = progression.samples(8000);
+        assert_eq!(samples.len(), 6000);
+        let wave: Vec<f64> = samples.collect();
+        assert_eq!(wave.len(), 6000);
+        // The first harmony matches rendering it on its own
+        let first: Vec<f64> = c_major().samples(8000).take(4000).collect();
+        assert_eq!(&wave[..4000], &first[..]);
+    }
+
+    #[test]
+    fn test_fractional_durations() {
+        let chord = c_major();
+        assert_eq!(chord.sound_wave_secs(0.25, 48000).len(), 12000);
+        assert_eq!(chord.sound_wave_secs(1.0, 8000), chord.sound_wave(1, 8000));
+        assert!(chord.sound_wave_secs(0.0, 8000).is_empty());
+        assert!(chord.sound_wave_secs(-1.0, 8000).is_empty());
+        assert!(chord.sound_wave_secs(f64::NAN, 8000).is_empty());
+    }
+
+    #[test]
+    fn test_render_into_blocks() {
+        let chord = c_major();
+        let mut state = RenderState::default();
+        let mut blocks = vec![0.0f32; 768];
+        for block in blocks.chunks_mut(256) {
+            chord.render_into(block, 44100, &mut state);
+        }
+        let mut whole = vec![0.0f32; 768];
+        chord.render_into(&mut whole, 44100, &mut RenderState::default());
+        assert_eq!(blocks, whole);
+        let streamed: Vec<f32> = chord.samples(44100).take(768).map(|s| s as f32).collect();
+        assert_eq!(whole, streamed);
+    }
+
+    #[test]
+    fn test_render_into_chord_swap() {
+        let dominant = SATB::new(
+            7,
+            (11, 4).into(),
+            (7, 4).into(),
+            (2, 4).into(),
+            (7, 2).into(),
+        );
+        let mut state = RenderState::default();
+        let mut buf = vec![0.0f32; 256];
+        c_major().render_into(&mut buf, 44100, &mut state);
+        let phases = state.phases();
+        assert!(phases.iter().any(|p| *p != 0.0));
+
+        // The new harmony picks up each voice at the phase the previous harmony left it
+        dominant.render_into(&mut buf, 44100, &mut state);
+        let expected: f64 = phases.iter().map(|p| Waveform::Sine.value(*p)).sum::<f64>() / 4.0;
+        assert_eq!(buf[0], expected as f32);
+    }
+
+    #[test]
+    fn test_constant_power_pan() {
+        for i in 0..=20 {
+            let [left, right] = constant_power_pan(-1.0 + i as f64 / 10.0);
+            assert!((left * left + right * right - 1.0).abs() < 1e-12);
+        }
+        let [left, right] = constant_power_pan(-1.0);
+        assert!((left - 1.0).abs() < 1e-12 && right.abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_sound_wave_stereo() {
+        let chord = c_major();
+        let mono = chord.sound_wave(1, 8000);
+        let centered = chord.sound_wave_stereo(1, 8000, &VoicePans::centered());
+        assert_eq!(centered.len(), mono.len());
+        for (frame, sample) in centered.iter().zip(&mono) {
+            assert!((frame[0] + frame[1] - std::f64::consts::SQRT_2 * sample).abs() < 1e-12);
+        }
+
+        // Every voice hard right leaves the left channel, the first of each pair, silent
+        let right = VoicePans {
+            soprano: 1.0,
+            alto: 1.0,
+            tenor: 1.0,
+            bass: 1.0,
+        };
+        let wave = chord.sound_wave_stereo(1, 8000, &right);
+        assert!(wave.iter().all(|frame| frame[0].abs() < 1e-12));
+        assert!(wave.iter().any(|frame| frame[1].abs() > 0.1));
+
+        // Panning runs through the rest of the signal chain, so the hard right channel is the mono render with the same options
+        let options = RenderOptions::new().envelope(Envelope::default()).gain(0.5);
+        let mono = chord.render(1.0, 8000, &options);
+        let stereo = chord.render_stereo(1.0, 8000, &options.pan(right));
+        assert_eq!(stereo.len(), mono.len());
+        for (frame, sample) in stereo.iter().zip(&mono) {
+            assert!(frame[0].abs() < 1e-12);
+            assert!((frame[1] - sample).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_sound_wave_with_gains() {
+        let chord = c_major();
+        let muted = VoiceGains {
+            soprano: 0.0,
+            ..VoiceGains::default()
+        };
+        let wave = chord.sound_wave_with_gains(1, 8000, &muted).unwrap();
+        let full = chord.sound_wave(1, 8000);
+        let [soprano, alto, tenor, bass] = chord.voices().map(|p| p.frequency());
+        assert!(goertzel(&full, soprano, 8000) > 0.2);
+        assert!(goertzel(&wave, soprano, 8000) < 0.01);
+        for freq in [alto, tenor, bass] {
+            assert!((goertzel(&wave, freq, 8000) - goertzel(&full, freq, 8000)).abs() < 0.01);
+        }
+
+        let boosted = VoiceGains {
+            soprano: 4.0,
+            ..VoiceGains::default()
+        };
+        let wave = chord.sound_wave_with_gains(1, 8000, &boosted).unwrap();
+        assert!(wave.iter().all(|s| s.abs() <= 1.0));
+
+        let negative = VoiceGains {
+            tenor: -1.0,
+            ..VoiceGains::default()
+        };
+        assert_eq!(
+            chord.sound_wave_with_gains(1, 8000, &negative),
+            Err(RenderError::InvalidGain(Voice::Tenor))
+        );
+    }
+
+    #[test]
+    fn test_voice_gains_in_signal_chain() {
+        let chord = c_major();
+        let enveloped = RenderOptions::new().envelope(Envelope::default());
+        let halved = VoiceGains {
+            soprano: 0.5,
+            alto: 0.5,
+            tenor: 0.5,
+            bass: 0.5,
+        };
+        // Gains below unit amplitude scale the voices while the divisor stays the number of voices
+        let quiet = chord.render(1.0, 8000, &enveloped.voice_gains(halved).unwrap());
+        let full = chord.render(1.0, 8000, &enveloped);
+        for (q, f) in quiet.iter().zip(&full) {
+            assert!((q - 0.5 * f).abs() < 1e-12);
+        }
+
+        // A boosted voice is caught by the limiter after the master gain
+        let boosted = VoiceGains {
+            bass: 4.0,
+            ..VoiceGains::default()
+        };
+        let options = RenderOptions::new()
+            .voice_gains(boosted)
+            .unwrap()
+            .gain(3.0)
+            .limiter(true);
+        let wave = chord.render(1.0, 8000, &options);
+        assert!(wave.iter().all(|s| s.abs() <= 1.0));
+        let bass = chord.voices()[3].frequency();
+        let soprano = chord.voices()[0].frequency();
+        assert!(goertzel(&wave, bass, 8000) > goertzel(&wave, soprano, 8000));
+
+        let invalid = VoiceGains {
+            alto: f64::NAN,
+            ..VoiceGains::default()
+        };
+        assert_eq!(
+            RenderOptions::new().voice_gains(invalid),
+            Err(RenderError::InvalidGain(Voice::Alto))
+        );
+    }
+
+    #[test]
+    fn test_organ_timbre_harmonics() {
+        // A2
+        let wave = sum_timbres(&[110.0], 44100, 44100, &Timbre::organ());
+        let total: f64 = Timbre::organ().harmonics().iter().sum();
+        for (k, amplitude) in Timbre::organ().harmonics()[..3].iter().enumerate() {
+            let measured = goertzel(&wave, 110.0 * (k + 1) as f64, 44100);
+            assert!((measured - amplitude / total).abs() < 1e-3);
+        }
+        assert!(wave.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_timbre_skips_harmonics_above_nyquist() {
+        // Every harmonic of a 15 kHz fundamental lies above the Nyquist frequency, so only the fundamental sounds
+        let wave = sum_timbres(&[15000.0], 44100, 44100, &Timbre::organ());
+        assert!(goertzel(&wave, 15000.0, 44100) > 0.1);
+        // The second harmonic would alias to 14.1 kHz
+        assert!(goertzel(&wave, 14100.0, 44100) < 1e-3);
+
+        let chord = c_major().sound_wave_with_timbre(1, 8000, &Timbre::soft());
+        assert!(chord.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_no_phase_jump_at_second_boundary() {
+        // 261.63 Hz is not a whole number of cycles per second, so resetting the phase every second would cause a jump
+        let wave = sum_waves(&[261.63], 2 * 44100, 44100, Waveform::Sine.into());
+        // The largest slope of a wave away from the boundary between its first and second seconds
+        let max_elsewhere = |slopes: &[f64]| {
+            slopes
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| (*i as i64 - 44099).abs() > 1)
+                .map(|(_, s)| *s)
+                .fold(0.0, f64::max)
+        };
+        let slopes: Vec<f64> = wave.windows(2).map(|w| (w[1] - w[0]).abs()).collect();
+        assert!(slopes[44099] <= max_elsewhere(&slopes));
+
+        let chord = c_major().sound_wave(2, 44100);
+        let slopes: Vec<f64> = chord.windows(2).map(|w| (w[1] - w[0]).abs()).collect();
+        assert!(slopes[44099] <= max_elsewhere(&slopes));
+    }
+
+    #[test]
+    fn test_crossfade() {
+        let a = c_major().sound_wave_secs(0.5, 8000);
+        let b: Vec<f64> = SATB::new(
+            7,
+            (11, 4).into(),
+            (7, 4).into(),
+            (2, 4).into(),
+            (7, 2).into(),
+        )
+        .samples(8000)
+        .skip(123)
+        .take(4000)
+        .collect();
+        let joined = crossfade(&a, &b, 400);
+        assert_eq!(joined.len(), a.len() + b.len() - 400);
+
+        // The junction is no steeper than the waves on either side of it
+        let slope = |w: &[f64]| {
+            w.windows(2)
+                .map(|p| (p[1] - p[0]).abs())
+                .fold(0.0, f64::max)
+        };
+        assert_eq!(crossfade(&a, &b, 0).len(), a.len() + b.len());
+        let junction = slope(&joined[3500..4100]);
+        assert!(junction <= slope(&joined[..3500]).max(slope(&joined[4100..])));
+    }
+
+    #[test]
+    fn test_render_with_crossfade() {
+        let mut progression = Progression::new();
+        progression.push_with_duration(c_major(), 0.5);
+        progression.push_with_duration(c_major(), 0.5);
+        progression.push_with_duration(c_major(), 0.01);
+
+        let plain = progression.render(8000, &RenderOptions::new());
+        assert_eq!(plain.samples.len(), 8080);
+        assert!(plain.warnings.is_empty());
+
+        let options = RenderOptions::new().crossfade(Duration::from_millis(50));
+        let rendered = progression.render(8000, &options);
+        assert_eq!(rendered.samples.len(), 8080 - 400 - 80);
+        assert_eq!(
+            rendered.warnings,
+            vec![RenderWarning::OverlapClamped {
+                index: 2,
+                requested: 400,
+                applied: 80
+            }]
+        );
+    }
+
+    /// Helper function that estimates the instantaneous frequency of `wave` from the time between consecutive upward zero
+    /// crossings, returning pairs of the time in seconds and the frequency.
+    fn instantaneous_frequencies(wave: &[f64], sample_freq: u32) -> Vec<(f64, f64)> {
+        let crossings: Vec<f64> = wave
+            .windows(2)
+            .enumerate()
+            .filter(|(_, w)| w[0] < 0.0 && w[1] >= 0.0)
+            .map(|(i, w)| (i as f64 + w[0] / (w[0] - w[1])) / sample_freq as f64)
+            .collect();
+        crossings
+            .windows(2)
+            .map(|c| ((c[0] + c[1]) / 2.0, 1.0 / (c[1] - c[0])))
+            .collect()
+    }
+
+    #[test]
+    fn test_vibrato() {
+        let a = Pitch::from((9, 4));
+        let flat = RenderOptions::new().no_declick().vibrato(Vibrato {
+            depth_cents: 0.0,
+            ..Vibrato::default()
+        });
+        assert_eq!(a.render(1.0, 44100, &flat), a.sound_wave_secs(1.0, 44100));
+
+        // A 5 Hz, 100 cent vibrato rises to its highest at 0.05 s and falls to its lowest at 0.15 s of each 0.2 s cycle
+        let options = RenderOptions::new().vibrato(Vibrato {
+            rate_hz: 5.0,
+            depth_cents: 100.0,
+            onset: 0.0,
+        });
+        let wave = a.render(1.0, 44100, &options);
+        let frequencies = instantaneous_frequencies(&wave, 44100);
+        let average = |from: f64, to: f64| {
+            let window: Vec<f64> = frequencies
+                .iter()
+                .filter(|(t, _)| *t >= from && *t < to)
+                .map(|(_, f)| *f)
+                .collect();
+            window.iter().sum::<f64>() / window.len() as f64
+        };
+        let frequency = a.frequency();
+        for cycle in 0..5 {
+            let start = 0.2 * cycle as f64;
+            assert!(average(start + 0.03, start + 0.07) > frequency * 1.04);
+            assert!(average(start + 0.13, start + 0.17) < frequency / 1.04);
+        }
+        let mean = average(0.0, 1.0);
+        assert!((mean - frequency).abs() < 5.0);
+    }
+
+    #[test]
+    fn test_detune() {
+        let a = Pitch::from((9, 4));
+        let plain = a.render(1.0, 8000, &RenderOptions::new());
+        assert_eq!(
+            a.render(1.0, 8000, &RenderOptions::new().detune(0.0)),
+            plain
+        );
+
+        // The copies 20 cents apart beat at their difference frequency, so the loudness of the render rises and falls with
+        // that period
+        let options = RenderOptions::new().detune(20.0).seed(7);
+        let wave = a.render(2.0, 8000, &options);
+        assert_eq!(wave, a.render(2.0, 8000, &options));
+        assert!(wave.iter().all(|s| s.abs() <= 1.0));
+
+        let envelope: Vec<f64> = wave
+            .chunks(40)
+            .map(|c| (c.iter().map(|s| s * s).sum::<f64>() / c.len() as f64).sqrt())
+            .collect();
+        let mean = envelope.iter().sum::<f64>() / envelope.len() as f64;
+        let centered: Vec<f64> = envelope.iter().map(|e| e - mean).collect();
+        let autocorrelation = |lag: usize| {
+            centered
+                .iter()
+                .zip(&centered[lag..])
+                .map(|(a, b)| a * b)
+                .sum::<f64>()
+        };
+        // Envelope samples are 5 ms apart, search lags from 0.1 s to 0.3 s
+        let peak = (20..60).max_by(|x, y| autocorrelation(*x).total_cmp(&autocorrelation(*y)));
+        let beat = a.frequency() * (2f64.powf(20.0 / 1200.0) - 1.0);
+        let period = peak.unwrap() as f64 * 0.005;
+        assert!(
+            (period - 1.0 / beat).abs() < 0.02,
+            "{} {}",
+            period,
+            1.0 / beat
+        );
+    }
+
+    #[test]
+    fn test_sound_wave_samples() {
+        let chord = c_major();
+        let window = chord.sound_wave_samples(4097, 44100);
+        assert_eq!(window.len(), 4097);
+        assert!(chord.sound_wave_samples(0, 44100).is_empty());
+
+        // The iterator carries each voice's phase past the first request, continuing the same wave
+        let next: Vec<f64> = chord.samples(44100).skip(4097).take(1000).collect();
+        assert_eq!(&chord.sound_wave_samples(5097, 44100)[4097..], &next[..]);
+        assert_eq!(&chord.sound_wave_samples(5097, 44100)[..4097], &window[..]);
+
+        let a = Pitch::from((9, 4));
+        assert_eq!(a.sound_wave_samples(44100, 44100), a.sound_wave(1, 44100));
+    }
+
+    #[test]
+    fn test_fade_edges() {
+        let mut samples = vec![1.0; 1000];
+        fade_edges(&mut samples, 100);
+        assert_eq!(samples[0], 0.0);
+        assert_eq!(samples[999], 0.0);
+        assert!(samples[50] > 0.0 && samples[50] < 1.0);
+        assert!(samples[100..900].iter().all(|s| *s == 1.0));
+
+        // A fade longer than half the buffer is clamped
+        let mut short = vec![1.0; 10];
+        fade_edges(&mut short, 100);
+        assert_eq!(short[0], 0.0);
+        assert_eq!(short[9], 0.0);
+
+        let chord = c_major();
+        let options = RenderOptions::new().declick(DEFAULT_DECLICK);
+        let declicked = chord.render(1.0, 8000, &options);
+        let plain = chord.render(1.0, 8000, &RenderOptions::new().no_declick());
+        assert_eq!(chord.render(1.0, 8000, &RenderOptions::new()), declicked);
+        assert_eq!(plain, chord.sound_wave(1, 8000));
+        assert_eq!(declicked[0], 0.0);
+        assert!(declicked[7999].abs() < 1e-12);
+        assert_eq!(&declicked[40..7960], &plain[40..7960]);
+    }
+
+    #[test]
+    fn test_band_limited_sawtooth() {
+        // A 1 kHz sawtooth at 44.1 kHz, the 23rd, 24th and 25th harmonics lie above the Nyquist frequency and alias
+        let band_limited = sum_waves(&[1000.0], 44100, 44100, Waveform::Sawtooth.into());
+        let aliased = sum_waves(&[1000.0], 44100, 44100, naive(Waveform::Sawtooth));
+        let decibels = |wave: &[f64], freq: f64| {
+            20.0 * (goertzel(wave, freq, 44100) / goertzel(wave, 1000.0, 44100)).log10()
+        };
+        for alias in [21100.0, 20100.0, 19100.0] {
+            assert!(decibels(&band_limited, alias) < -40.0);
+            assert!(decibels(&aliased, alias) > -40.0);
+        }
+        assert!(band_limited.iter().all(|s| s.abs() <= 1.0));
+
+        // Below the Nyquist frequency the 15 kHz square keeps only its fundamental, a sine of amplitude 4 / π
+        let square = sum_waves(&[15000.0, 110.0], 44100, 44100, Waveform::Square.into());
+        assert!(square.iter().all(|s| s.abs() <= 4.0 / PI + GIBBS_OVERSHOOT));
+    }
+
+    #[test]
+    fn test_band_limited_square_amplitude() {
+        // Away from its edges the band limited square wave lies at unit amplitude, like the naive wave
+        let square = Waveform::Square;
+        assert!((square.band_limited_value(0.25, 1001) - 1.0).abs() < 1e-3);
+        assert!((square.band_limited_value(0.75, 1001) + 1.0).abs() < 1e-3);
+        // With only the fundamental it is a sine of amplitude 4 / π, and with many harmonics it peaks at the Gibbs overshoot
+        assert!((square.band_limited_value(0.25, 1) - 4.0 / PI).abs() < 1e-12);
+        let peak = (0..10000)
+            .map(|i| square.band_limited_value(i as f64 / 10000.0, 1001))
+            .fold(0.0, f64::max);
+        assert!((peak - GIBBS_OVERSHOOT).abs() < 1e-3, "{}", peak);
+    }
+
+    #[test]
+    fn test_phasor_matches_sin() {
+        // Sixty seconds of a four voice harmony, against sines of the exact phase of each sample
+        let chord = c_major();
+        let increments = increments(&chord, 44100);
+        let num_samples = 60 * 44100;
+        let direct: Vec<f64> = (0..num_samples)
+            .map(|n| {
+                increments
+                    .iter()
+                    .map(|increment| (2.0 * PI * (n as f64 * increment).fract()).sin())
+                    .sum::<f64>()
+                    / 4.0
+            })
+            .collect();
+        for wave in [
+            chord.sound_wave(60, 44100),
+            chord.voices()[..].sound_wave(60, 44100),
+        ] {
+            assert_eq!(wave.len(), num_samples);
+            let error = wave
+                .iter()
+                .zip(&direct)
+                .map(|(w, d)| (w - d).abs())
+                .fold(0.0, f64::max);
+            assert!(20.0 * error.log10() < -90.0, "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_arpeggio() {
+        let chord = c_major();
+        let mut voices = chord.voices();
+        voices.sort();
+        assert_eq!(voices, [chord.bass, chord.tenor, chord.alto, chord.soprano]);
+
+        let options = RenderOptions::new().arpeggio(ArpDirection::Up, 0.25);
+        let wave = chord.render(2.0, 44100, &options);
+        assert_eq!(wave.len(), 88200);
+        let segments: Vec<&[f64]> = wave.chunks(sample_count(0.25, 44100)).collect();
+        assert_eq!(segments.len(), 8);
+        for (i, segment) in segments.iter().enumerate() {
+            let dominant = voices
+                .iter()
+                .max_by(|a, b| {
+                    goertzel(segment, a.frequency(), 44100).total_cmp(&goertzel(
+                        segment,
+                        b.frequency(),
+                        44100,
+                    ))
+                })
+                .unwrap();
+            assert_eq!(*dominant, voices[i % 4]);
+        }
+
+        assert_eq!(ArpDirection::Down.order(4), [3, 2, 1, 0]);
+        assert_eq!(ArpDirection::UpDown.order(4), [0, 1, 2, 3, 2, 1]);
+        // A note shorter than a sample leaves the harmony blocked
+        let blocked = RenderOptions::new().arpeggio(ArpDirection::Up, 0.0);
+        assert_eq!(
+            chord.render(0.1, 44100, &blocked),
+            chord.render(0.1, 44100, &RenderOptions::new())
+        );
+    }
+
+    #[test]
+    fn test_master_gain_and_limiter() {
+        let chord = c_major();
+        let unity = chord.render(1.0, 8000, &RenderOptions::new());
+        let halved = chord.render(1.0, 8000, &RenderOptions::new().gain(0.5));
+        for (h, u) in halved.iter().zip(&unity) {
+            assert_eq!(*h, u * 0.5);
+        }
+
+        let a = Pitch::from((9, 4));
+        let loud = a.render(1.0, 44100, &RenderOptions::new().gain(2.0));
+        assert!(loud.iter().any(|s| s.abs() > 1.0));
+        let limited = a.render(1.0, 44100, &RenderOptions::new().gain(2.0).limiter(true));
+        let peak = limited.iter().fold(0.0, |peak: f64, s| peak.max(s.abs()));
+        assert!(peak <= 1.0);
+        assert!(!limited
+            .windows(2)
+            .any(|w| w[0] == w[1] && w[0].abs() == peak));
+        // The limiter leaves a wave within its knee untouched
+        let quiet = a.render(1.0, 44100, &RenderOptions::new().gain(0.5).limiter(true));
+        assert_eq!(quiet, a.render(1.0, 44100, &RenderOptions::new().gain(0.5)));
+    }
+
+    #[test]
+    fn test_limiter_is_per_sample() {
+        // A quiet passage followed by one far beyond unit amplitude
+        let mut wave: Vec<f64> = (0..8000)
+            .map(|i| {
+                let level = if i < 4000 { 0.5 } else { 4.0 };
+                level * (2.0 * PI * 440.0 * i as f64 / 8000.0).sin()
+            })
+            .collect();
+        let original = wave.clone();
+        RenderOptions::new().limiter(true).apply_output(
+            &mut wave,
+            8000,
+            &mut RenderState::default(),
+        );
+        assert_eq!(&wave[..4000], &original[..4000]);
+        assert!(wave[4000..].iter().all(|s| s.abs() <= 1.0));
+        assert!(wave[4000..].iter().any(|s| s.abs() > LIMITER_KNEE));
+
+        // The soft clip is continuous at the knee, odd, and increasing
+        assert!((soft_clip(LIMITER_KNEE + 1e-9) - LIMITER_KNEE).abs() < 1e-8);
+        assert_eq!(soft_clip(-2.0), -soft_clip(2.0));
+        assert!(soft_clip(1.0) < soft_clip(2.0) && soft_clip(2.0) < 1.0);
+    }
+
+    #[test]
+    fn test_exact_lengths() {
+        let chord = c_major();
+        for (duration, sample_freq) in [(1, 44100), (3, 8000), (0, 44100), (2, 22050)] {
+            let expected = duration as usize * sample_freq as usize;
+            assert_eq!(chord.sound_wave(duration, sample_freq).len(), expected);
+            assert_eq!(
+                chord.voices()[..].sound_wave(duration, sample_freq).len(),
+                expected
+            );
+            assert_eq!(
+                chord
+                    .sound_wave_with(duration, sample_freq, Waveform::Square)
+                    .len(),
+                expected
+            );
+            assert_eq!(
+                chord.sound_wave_as::<i16>(duration, sample_freq).len(),
+                expected
+            );
+        }
+        // Fractional durations are rounded to the nearest sample
+        assert_eq!(chord.sound_wave_secs(0.5, 44100).len(), 22050);
+        assert_eq!(chord.render(1.25, 8000, &RenderOptions::new()).len(), 10000);
+    }
+
+    #[test]
+    fn test_to_i16_pcm() {
+        let extremes = [1.0, -1.0, 1.5, -1.5, 0.0];
+        assert_eq!(
+            to_i16_pcm(&extremes, false),
+            [i16::MAX, i16::MIN, i16::MAX, i16::MIN, 0]
+        );
+        // Dither may pull full scale samples in by a bit, but never wraps them around
+        let pcm = to_i16_pcm(&extremes, true);
+        for (p, e) in pcm.iter().zip([i16::MAX, i16::MIN, i16::MAX, i16::MIN, 0]) {
+            assert!((*p as i32 - e as i32).abs() <= 1, "{} {}", p, e);
+        }
+
+        // Dithered silence is noise of zero mean, at most one least significant bit
+        let silence = vec![0.0; 44100];
+        let pcm = to_i16_pcm(&silence, true);
+        assert!(pcm.iter().all(|s| s.abs() <= 1));
+        assert!(pcm.iter().any(|s| *s != 0));
+        let mean = pcm.iter().map(|s| *s as f64).sum::<f64>() / pcm.len() as f64;
+        assert!(mean.abs() < 0.01, "{}", mean);
+
+        assert_eq!(pcm, to_i16_pcm(&silence, true));
+        assert_eq!(pcm, to_i16_pcm_seeded(&silence, DITHER_SEED));
+        assert_ne!(pcm, to_i16_pcm_seeded(&silence, 1));
+    }
+
+    #[test]
+    fn test_mute_and_solo() {
+        let chord = c_major();
+        let voices = chord.voices();
+        let full = chord.render(1.0, 44100, &RenderOptions::new());
+        let solo = chord.render(1.0, 44100, &RenderOptions::new().solo(Voice::Tenor));
+        let tenor = goertzel(&solo, voices[2].frequency(), 44100);
+        assert!(tenor > 0.9, "{}", tenor);
+        assert!(tenor >= goertzel(&full, voices[2].frequency(), 44100));
+        for voice in [0, 1, 3] {
+            assert!(goertzel(&solo, voices[voice].frequency(), 44100) < 0.01);
+        }
+
+        // A solo overrides a mute of the same voice, and solos accumulate
+        let options = RenderOptions::new()
+            .mute(Voice::Tenor)
+            .solo(Voice::Tenor)
+            .solo(Voice::Bass);
+        let wave = chord.render(1.0, 44100, &options);
+        assert!(goertzel(&wave, voices[2].frequency(), 44100) > 0.4);
+        assert!(goertzel(&wave, voices[3].frequency(), 44100) > 0.4);
+        assert!(goertzel(&wave, voices[0].frequency(), 44100) < 0.01);
+
+        let muted = chord.render(1.0, 44100, &RenderOptions::new().mute(Voice::Soprano));
+        assert!(goertzel(&muted, voices[0].frequency(), 44100) < 0.01);
+        assert!(goertzel(&muted, voices[1].frequency(), 44100) > 0.3);
+    }
+
+    #[test]
+    fn test_lowpass() {
+        assert_eq!(
+            RenderOptions::new().lowpass(f64::NAN),
+            Err(RenderError::InvalidCutoff)
+        );
+        assert_eq!(
+            RenderOptions::new().lowpass(f64::INFINITY),
+            Err(RenderError::InvalidCutoff)
+        );
+
+        let saw = sum_waves(&[500.0], 44100, 44100, Waveform::Sawtooth.into());
+        let mut filtered = saw.clone();
+        apply_lowpass(&mut filtered, 1000.0, 44100, &mut RenderState::default());
+        // The 10th harmonic lies more than two octaves above the cutoff, so falls by at least 6 dB per octave
+        let attenuation = |freq: f64| {
+            20.0 * (goertzel(&filtered, freq, 44100) / goertzel(&saw, freq, 44100)).log10()
+        };
+        assert!(
+            attenuation(5000.0) < -6.0 * 5f64.log2(),
+            "{}",
+            attenuation(5000.0)
+        );
+        assert!(attenuation(500.0) > -1.5);
+
+        // Filtering in chunks matches filtering in one pass
+        let mut chunked = saw.clone();
+        let mut state = RenderState::default();
+        for chunk in chunked.chunks_mut(1000) {
+            apply_lowpass(chunk, 1000.0, 44100, &mut state);
+        }
+        assert_eq!(chunked, filtered);
+
+        // Cutoffs above the Nyquist frequency leave the wave unfiltered
+        let chord = c_major();
+        let options = RenderOptions::new().lowpass(30000.0).unwrap();
+        assert_eq!(
+            chord.render(0.5, 44100, &options),
+            chord.render(0.5, 44100, &RenderOptions::new())
+        );
+
+        let options = RenderOptions::new().lowpass(1000.0).unwrap();
+        let mut streamed = vec![0.0f32; 4410];
+        let mut state = RenderState::default();
+        for chunk in streamed.chunks_mut(441) {
+            chord.render_into_with(chunk, 44100, &options, &mut state);
+        }
+        let mut expected = vec![0.0f32; 4410];
+        chord.render_into(&mut expected, 44100, &mut RenderState::default());
+        let mut expected: Vec<f64> = expected.iter().map(|s| *s as f64).collect();
+        apply_lowpass(&mut expected, 1000.0, 44100, &mut RenderState::default());
+        assert!(streamed
+            .iter()
+            .zip(&expected)
+            .all(|(s, e)| (*s as f64 - e).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_delay() {
+        let mut impulse = vec![0.0; 100];
+        impulse[0] = 1.0;
+        apply_delay(&mut impulse, Duration::from_millis(10), 0.5, 0.5, 1000).unwrap();
+        // Echoes of half the level of the last every 10 samples, until they fall below -60 dB
+        assert_eq!(impulse[0], 0.5);
+        for k in 1..=10 {
+            let expected = 0.5 * 0.5f64.powi(k as i32 - 1);
+            assert_eq!(impulse[10 * k], expected);
+        }
+        let echoes: Vec<usize> = (1..impulse.len()).filter(|i| impulse[*i] != 0.0).collect();
+        assert!(echoes.iter().all(|i| i % 10 == 0));
+        assert_eq!(impulse.len(), 100 + 11 * 10);
+        assert!(impulse[impulse.len() - 10] < 0.001 * 0.5);
+
+        let mut wave = vec![0.0; 10];
+        assert_eq!(
+            apply_delay(&mut wave, Duration::from_millis(10), 1.0, 0.5, 1000),
+            Err(RenderError::InvalidFeedback)
+        );
+        assert_eq!(
+            RenderOptions::new().delay(Duration::from_millis(10), -1.5, 0.5),
+            Err(RenderError::InvalidFeedback)
+        );
+        assert_eq!(
+            RenderOptions::new().delay(Duration::from_millis(10), 0.5, 2.0),
+            Err(RenderError::InvalidMix)
+        );
+        assert_eq!(wave, vec![0.0; 10]);
+
+        let options = RenderOptions::new()
+            .delay(Duration::from_millis(100), 0.5, 0.3)
+            .unwrap();
+        let wave = c_major().render(0.5, 8000, &options);
+        assert!(wave.len() > 4000);
+        assert!(wave.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_voice_envelope() {
+        let chord = c_major();
+        let voices = chord.voices();
+        let swell = Envelope {
+            attack: 1.0,
+            decay: 0.0,
+            sustain_level: 1.0,
+            release: 0.0,
+        };
+        let options = RenderOptions::new().voice_envelope(Voice::Soprano, swell);
+        let wave = chord.render(2.0, 44100, &options);
+        let early = &wave[..11025];
+        let late = &wave[66150..77175];
+        let soprano = voices[0].frequency();
+        assert!(goertzel(early, soprano, 44100) < 0.5 * goertzel(late, soprano, 44100));
+        for voice in &voices[1..] {
+            let ratio = goertzel(early, voice.frequency(), 44100)
+                / goertzel(late, voice.frequency(), 44100);
+            assert!((ratio - 1.0).abs() < 0.05, "{}", ratio);
+        }
+
+        // Voices without an envelope of their own fall back to the envelope of every voice
+        let silent = Envelope {
+            sustain_level: 0.0,
+            ..swell
+        };
+        let options = options.envelope(silent);
+        let wave = chord.render(2.0, 44100, &options);
+        assert!(goertzel(&wave[66150..77175], soprano, 44100) > 0.2);
+        assert!(goertzel(&wave[66150..77175], voices[3].frequency(), 44100) < 0.01);
+
+        // Arpeggiated notes keep the mutes, solos and envelopes of their voices
+        let options = RenderOptions::new()
+            .solo(Voice::Tenor)
+            .arpeggio(ArpDirection::Up, 0.25);
+        let wave = chord.render(0.5, 44100, &options);
+        assert!(goertzel(&wave[..11025], voices[2].frequency(), 44100) > 0.9);
+    }
+
+    #[test]
+    fn test_strum() {
+        let chord = c_major();
+        let voices = chord.voices();
+        let options = RenderOptions::new().strum(Duration::from_millis(50));
+        let wave = chord.render(1.0, 44100, &options);
+        assert_eq!(wave.len(), 44100);
+        let before = &wave[..2205];
+        assert!(goertzel(before, voices[3].frequency(), 44100) > 0.2);
+        for voice in &voices[..3] {
+            assert!(goertzel(before, voice.frequency(), 44100) < 0.05);
+        }
+        let after = &wave[6615..];
+        for voice in &voices {
+            assert!(goertzel(after, voice.frequency(), 44100) > 0.2);
+        }
+
+        // Entries past the end of the harmony are clamped, so only the lowest voices sound
+        let options = RenderOptions::new().strum(Duration::from_millis(400));
+        let wave = chord.render(0.5, 44100, &options);
+        assert_eq!(wave.len(), 22050);
+        assert!(goertzel(&wave, voices[0].frequency(), 44100) < 0.01);
+        assert!(goertzel(&wave, voices[1].frequency(), 44100) < 0.01);
+    }
+
+    #[test]
+    fn test_glide() {
+        let mut progression = Progression::new();
+        progression.push(c_major());
+        progression.push(SATB::new(
+            7,
+            (2, 5).into(),
+            (7, 4).into(),
+            (11, 3).into(),
+            (7, 3).into(),
+        ));
+        let options = RenderOptions::new()
+            .glide(Duration::from_millis(200))
+            .solo(Voice::Soprano);
+        let wave = progression.render(44100, &options).samples;
+        assert_eq!(wave.len(), 88200);
+        let (from, to) = (
+            progression.chords()[0].soprano.frequency(),
+            progression.chords()[1].soprano.frequency(),
+        );
+        let glide: Vec<f64> = instantaneous_frequencies(&wave, 44100)
+            .into_iter()
+            .filter(|(t, _)| *t > 1.005 && *t < 1.195)
+            .map(|(_, f)| f)
+            .collect();
+        assert!(!glide.is_empty());
+        assert!(glide.iter().all(|f| *f > from && *f < to));
+        assert!(glide.windows(2).all(|w| w[1] > w[0]));
+        // After the glide the soprano holds its new pitch
+        let held = goertzel(&wave[55000..], to, 44100);
+        assert!(held > 0.9, "{}", held);
+
+        // Common tones do not glide
+        let options = RenderOptions::new()
+            .glide(Duration::from_millis(200))
+            .solo(Voice::Alto);
+        let wave = progression.render(44100, &options).samples;
+        let steady = progression.render(44100, &RenderOptions::new().solo(Voice::Alto));
+        assert!(wave
+            .iter()
+            .zip(&steady.samples)
+            .all(|(w, s)| (w - s).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_equal_loudness() {
+        assert!((equal_loudness_gain(1000.0) - 1.0).abs() < 1e-12);
+        // A-weighting is about -19.1 dB at 100 Hz
+        let decibels = 20.0 * equal_loudness_gain(100.0).log10();
+        assert!((decibels - 19.1).abs() < 0.2, "{}", decibels);
+        assert!(equal_loudness_gain(98.0) > equal_loudness_gain(988.0));
+
+        let chord = c_major();
+        let voices = chord.voices();
+        let plain = chord.render(1.0, 44100, &RenderOptions::new());
+        assert_eq!(
+            chord.render(1.0, 44100, &RenderOptions::new().equal_loudness(false)),
+            plain
+        );
+        let weighted = chord.render(1.0, 44100, &RenderOptions::new().equal_loudness(true));
+        assert!(weighted.iter().all(|s| s.abs() <= 1.0));
+        let bass = goertzel(&weighted, voices[3].frequency(), 44100);
+        let soprano = goertzel(&weighted, voices[0].frequency(), 44100);
+        assert!(bass > soprano);
+        assert!((bass - goertzel(&plain, voices[3].frequency(), 44100)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_resample() {
+        let sine = |freq: f64, rate: u32, n: usize| -> Vec<f64> {
+            (0..n)
+                .map(|i| (2.0 * PI * freq * i as f64 / rate as f64).sin())
+                .collect()
+        };
+        let crossings = |wave: &[f64]| {
+            wave.windows(2)
+                .filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0))
+                .count()
+        };
+
+        let wave = sine(1000.0, 48000, 48000);
+        let resampled = resample(&wave, 48000, 44100);
+        assert_eq!(resampled.len(), 44100);
+        assert!((1998..=2002).contains(&crossings(&resampled)));
+
+        // Halving and doubling the sample rate are near transparent
+        let correlation = |a: &[f64], b: &[f64]| {
+            let dot = |x: &[f64], y: &[f64]| x.iter().zip(y).map(|(x, y)| x * y).sum::<f64>();
+            dot(a, b) / (dot(a, a) * dot(b, b)).sqrt()
+        };
+        let halved = resample(&wave, 48000, 24000);
+        assert_eq!(halved.len(), 24000);
+        assert!(correlation(&halved, &sine(1000.0, 24000, 24000)) > 0.999);
+        let doubled = resample(&sine(1000.0, 24000, 24000), 24000, 48000);
+        assert_eq!(doubled.len(), 48000);
+        assert!(correlation(&doubled, &wave) > 0.999);
+
+        // Content above the new Nyquist frequency is filtered rather than aliased
+        let high = resample(&sine(15000.0, 48000, 48000), 48000, 24000);
+        assert!(goertzel(&high, 9000.0, 24000) < 0.01);
+        assert!(high[1000..23000].iter().all(|s| s.abs() < 0.01));
+
+        assert_eq!(resample(&wave, 48000, 48000), wave);
+        assert!(resample(&wave, 0, 48000).is_empty());
+    }
+
+    #[test]
+    fn test_melody_render() {
+        let mut melody = Melody::new(Tempo::new(120.0).unwrap());
+        melody.push(Pitch::from((0, 4)), NoteValue::Quarter);
+        melody.push_rest(Rest(NoteValue::Eighth));
+        melody.push(Pitch::from((4, 4)), NoteValue::Eighth);
+        melody.push(Pitch::from((7, 4)), NoteValue::Half);
+        let pitches = melody.pitches();
+        for legato in [false, true] {
+            let melody = melody.clone().legato(legato);
+            let wave = melody.render(8000, &RenderOptions::new().declick(DEFAULT_DECLICK));
+            assert_eq!(wave.len(), 16000);
+            let segments = [&wave[..4000], &wave[6000..8000], &wave[8000..]];
+            for (segment, pitch) in segments.iter().zip(&pitches) {
+                let energy = goertzel(segment, pitch.frequency(), 8000);
+                assert!(energy > 0.9, "{} {}", pitch, energy);
+                for other in pitches.iter().filter(|p| *p != pitch) {
+                    assert!(goertzel(segment, other.frequency(), 8000) < 0.1);
+                }
+            }
+            assert!(wave[4000..6000].iter().all(|s| *s == 0.0));
+        }
+    }
+
+    #[test]
+    fn test_progression_rests() {
+        let mut progression = Progression::new();
+        progression.push(SATB::new(
+            0,
+            (0, 5).into(),
+            (7, 4).into(),
+            (4, 4).into(),
+            (0, 3).into(),
+        ));
+        progression.push_rest(Rest(NoteValue::Quarter), Tempo::new(120.0).unwrap());
+        progression.push(SATB::new(
+            7,
+            (11, 4).into(),
+            (7, 4).into(),
+            (2, 4).into(),
+            (7, 2).into(),
+        ));
+        progression.push_rest_with_duration(0.25);
+        for options in [
+            RenderOptions::new(),
+            RenderOptions::new().crossfade(Duration::from_millis(50)),
+            RenderOptions::new().declick(DEFAULT_DECLICK),
+        ] {
+            let wave = progression.render(8000, &options).samples;
+            assert_eq!(wave.len(), 22000);
+            assert!(wave[8000..12000].iter().all(|s| *s == 0.0));
+            assert!(wave[20000..].iter().all(|s| *s == 0.0));
+            // The harmonies either side of each rest fade out and back in
+            assert!(wave[7999].abs() < 1e-3 && wave[12000].abs() < 1e-3);
+            assert!(wave[19999].abs() < 1e-3);
+            assert!(wave[8000 - 400..8000].iter().any(|s| s.abs() > 0.1));
+        }
+        let streamed: Vec<f64> = progression.samples(8000).collect();
+        assert_eq!(streamed.len(), 22000);
+        assert!(streamed[8000..12000].iter().all(|s| *s == 0.0));
+        assert!(streamed[20000..].iter().all(|s| *s == 0.0));
+    }
+}
@@ -0,0 +1,1771 @@
+//! Types for arbitrary harmonies of any number of pitches, and for naming the quality of a harmony, given by the intervals of its
+//! members above its root.
+use crate::progression::motion;
+use crate::voicing::{
+    enumerate_quality_voicings, enumerate_voicings, standalone_cost, VoicingPreferences,
+};
+use crate::{
+    Harmony, Interval, Key, Pitch, PitchClassSet, SatbError, SpelledPitchClass, Spelling, Voice,
+    SATB,
+};
+use std::fmt::Display;
+
+/// Enumerates the qualities of triads, seventh chords, and the suspended and added tone chords.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ChordQuality {
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+    Dominant7,
+    Major7,
+    Minor7,
+    HalfDiminished7,
+    Diminished7,
+    MinorMajor7,
+    AugmentedMajor7,
+    /// A triad with the third replaced by a major second
+    Sus2,
+    /// A triad with the third replaced by a perfect fourth
+    Sus4,
+    /// A major triad with an added major sixth
+    Major6,
+    /// A minor triad with an added major sixth
+    Minor6,
+    /// A major triad with an added major ninth
+    Add9,
+}
+
+impl ChordQuality {
+    /// All chord qualities, the triads followed by the seventh chords, the suspended triads and then the added tone chords.
+    pub const ALL: [ChordQuality; 16] = [
+        ChordQuality::Major,
+        ChordQuality::Minor,
+        ChordQuality::Diminished,
+        ChordQuality::Augmented,
+        ChordQuality::Dominant7,
+        ChordQuality::Major7,
+        ChordQuality::Minor7,
+        ChordQuality::HalfDiminished7,
+        ChordQuality::Diminished7,
+        ChordQuality::MinorMajor7,
+        ChordQuality::AugmentedMajor7,
+        ChordQuality::Sus2,
+        ChordQuality::Sus4,
+        ChordQuality::Major6,
+        ChordQuality::Minor6,
+        ChordQuality::Add9,
+    ];
+
+    /// Method that returns the semitones above the root of each member of a chord of this quality, starting with 0 for the root,
+    /// within an octave, so the ninth of an added ninth chord is given as a second.
+    pub fn intervals(&self) -> &'static [u8] {
+        match self {
+            ChordQuality::Major => &[0, 4, 7],
+            ChordQuality::Minor => &[0, 3, 7],
+            ChordQuality::Diminished => &[0, 3, 6],
+            ChordQuality::Augmented => &[0, 4, 8],
+            ChordQuality::Dominant7 => &[0, 4, 7, 10],
+            ChordQuality::Major7 => &[0, 4, 7, 11],
+            ChordQuality::Minor7 => &[0, 3, 7, 10],
+            ChordQuality::HalfDiminished7 => &[0, 3, 6, 10],
+            ChordQuality::Diminished7 => &[0, 3, 6, 9],
+            ChordQuality::MinorMajor7 => &[0, 3, 7, 11],
+            ChordQuality::AugmentedMajor7 => &[0, 4, 8, 11],
+            ChordQuality::Sus2 => &[0, 2, 7],
+            ChordQuality::Sus4 => &[0, 5, 7],
+            ChordQuality::Major6 => &[0, 4, 7, 9],
+            ChordQuality::Minor6 => &[0, 3, 7, 9],
+            ChordQuality::Add9 => &[0, 2, 4, 7],
+        }
+    }
+
+    /// Method that returns the pitch classes of the chord of this quality on `root`, taken modulo 12, e.g. {2, 6, 9} for a
+    /// major triad on D.
+    pub fn pitch_classes(&self, root: u8) -> PitchClassSet {
+        self.intervals().iter().map(|i| root % 12 + i).collect()
+    }
+
+    /// Method that returns true if the chord has a seventh.
+    pub fn is_seventh(&self) -> bool {
+        self.is_tertian() && self.intervals().len() == 4
+    }
+
+    /// Method that returns true if the chord is a stack of thirds, a triad or seventh chord rather than a suspended or added
+    /// tone chord.
+    pub fn is_tertian(&self) -> bool {
+        !matches!(
+            self,
+            ChordQuality::Sus2
+                | ChordQuality::Sus4
+                | ChordQuality::Major6
+                | ChordQuality::Minor6
+                | ChordQuality::Add9
+        )
+    }
+
+    /// Associated method that returns every root and quality whose chord is consistent with the pitch classes `classes`, from
+    /// the most to the least plausible: chords with exactly those pitch classes that are stacks of thirds, then suspended and
+    /// added tone chords with exactly those pitch classes, then seventh chords missing only their fifth. Interpretations of the same
+    /// plausibility are ordered as `ALL` and then by root. Symmetric and ambiguous collections give several, e.g. {0, 3, 6, 9}
+    /// is a diminished seventh on each of its members and {0, 4, 7, 9} is both Am7 and C6, while a collection no chord matches,
+    /// such as a tone cluster, gives none.
+    pub fn detect(classes: &PitchClassSet) -> Vec<(u8, ChordQuality)> {
+        let mut detected: Vec<(u8, (usize, usize), ChordQuality)> = Vec::new();
+        for root in classes.iter() {
+            for (index, quality) in ChordQuality::ALL.into_iter().enumerate() {
+                let mut pitch_classes = quality.pitch_classes(root);
+                let group = if pitch_classes == *classes {
+                    if quality.is_tertian() {
+                        0
+                    } else {
+                        1
+                    }
+                } else {
+                    pitch_classes.remove(root + quality.intervals()[2]);
+                    if quality.is_seventh() && pitch_classes == *classes {
+                        2
+                    } else {
+                        continue;
+                    }
+                };
+                detected.push((root, (group, index), quality));
+            }
+        }
+        detected.sort_by_key(|(root, rank, _)| (*rank, *root));
+        detected
+            .into_iter()
+            .map(|(root, _, quality)| (root, quality))
+            .collect()
+    }
+
+    /// Associated method that returns the quality of the chord on `root` whose members are `pitch_classes`, ordered root, third,
+    /// fifth and optionally seventh, or `None` if they do not form a triad or seventh chord.
+    pub fn from_pitch_classes(root: u8, pitch_classes: &[u8]) -> Option<Self> {
+        let intervals: Vec<u8> = pitch_classes
+            .iter()
+            .map(|pc| (pc % 12 + 12 - root % 12) % 12)
+            .collect();
+        ChordQuality::ALL
+            .into_iter()
+            .find(|quality| quality.intervals() == intervals)
+    }
+}
+
+impl Display for ChordQuality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ChordQuality::Major => "major",
+            ChordQuality::Minor => "minor",
+            ChordQuality::Diminished => "diminished",
+            ChordQuality::Augmented => "augmented",
+            ChordQuality::Dominant7 => "dominant seventh",
+            ChordQuality::Major7 => "major seventh",
+            ChordQuality::Minor7 => "minor seventh",
+            ChordQuality::HalfDiminished7 => "half diminished seventh",
+            ChordQuality::Diminished7 => "diminished seventh",
+            ChordQuality::MinorMajor7 => "minor major seventh",
+            ChordQuality::AugmentedMajor7 => "augmented major seventh",
+            ChordQuality::Sus2 => "suspended second",
+            ChordQuality::Sus4 => "suspended fourth",
+            ChordQuality::Major6 => "major sixth",
+            ChordQuality::Minor6 => "minor sixth",
+            ChordQuality::Add9 => "added ninth",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The chord symbol suffixes understood by `Chord::from_symbol`, along with the semitones above the root of each member of the
+/// chord they name, with extensions lying above the octave.
+const SYMBOL_SUFFIXES: [(&str, &[u8]); 46] = [
+    ("", &[0, 4, 7]),
+    ("M", &[0, 4, 7]),
+    ("maj", &[0, 4, 7]),
+    ("m", &[0, 3, 7]),
+    ("min", &[0, 3, 7]),
+    ("-", &[0, 3, 7]),
+    ("dim", &[0, 3, 6]),
+    ("°", &[0, 3, 6]),
+    ("aug", &[0, 4, 8]),
+    ("+", &[0, 4, 8]),
+    ("5", &[0, 7]),
+    ("sus2", &[0, 2, 7]),
+    ("sus4", &[0, 5, 7]),
+    ("sus", &[0, 5, 7]),
+    ("6", &[0, 4, 7, 9]),
+    ("m6", &[0, 3, 7, 9]),
+    ("7", &[0, 4, 7, 10]),
+    ("maj7", &[0, 4, 7, 11]),
+    ("M7", &[0, 4, 7, 11]),
+    ("Δ", &[0, 4, 7, 11]),
+    ("Δ7", &[0, 4, 7, 11]),
+    ("m7", &[0, 3, 7, 10]),
+    ("min7", &[0, 3, 7, 10]),
+    ("-7", &[0, 3, 7, 10]),
+    ("mMaj7", &[0, 3, 7, 11]),
+    ("mM7", &[0, 3, 7, 11]),
+    ("m7b5", &[0, 3, 6, 10]),
+    ("ø", &[0, 3, 6, 10]),
+    ("ø7", &[0, 3, 6, 10]),
+    ("dim7", &[0, 3, 6, 9]),
+    ("°7", &[0, 3, 6, 9]),
+    ("aug7", &[0, 4, 8, 10]),
+    ("+7", &[0, 4, 8, 10]),
+    ("7sus4", &[0, 5, 7, 10]),
+    ("add9", &[0, 4, 7, 14]),
+    ("madd9", &[0, 3, 7, 14]),
+    ("9", &[0, 4, 7, 10, 14]),
+    ("maj9", &[0, 4, 7, 11, 14]),
+    ("m9", &[0, 3, 7, 10, 14]),
+    ("7b9", &[0, 4, 7, 10, 13]),
+    ("7#9", &[0, 4, 7, 10, 15]),
+    ("11", &[0, 4, 7, 10, 14, 17]),
+    ("13", &[0, 4, 7, 10, 14, 21]),
+    ("m11", &[0, 3, 7, 10, 14, 17]),
+    ("maj13", &[0, 4, 7, 11, 14, 21]),
+    ("m13", &[0, 3, 7, 10, 14, 21]),
+];
+
+/// The alterations that may follow a chord symbol suffix, along with the semitones above the root of the altered tension and
+/// of the tone it replaces.
+const SYMBOL_ALTERATIONS: [(&str, u8, u8); 4] = [
+    ("b9", 13, 14),
+    ("#9", 15, 14),
+    ("#11", 18, 17),
+    ("b13", 20, 21),
+];
+
+/// Helper function that returns the semitones above the root of each member of the chord named by the chord symbol suffix
+/// `suffix`, one of `SYMBOL_SUFFIXES` followed by any number of `SYMBOL_ALTERATIONS`, each optionally in parentheses.
+/// An alteration replaces the tension it alters, and a sharpened eleventh also replaces the perfect fifth.
+fn suffix_intervals(suffix: &str) -> Option<Vec<u8>> {
+    SYMBOL_SUFFIXES
+        .iter()
+        .filter(|(name, _)| suffix.starts_with(name))
+        .find_map(|(name, intervals)| {
+            let mut intervals = intervals.to_vec();
+            let mut rest = &suffix[name.len()..];
+            while !rest.is_empty() {
+                let (inner, parenthesized) = match rest.strip_prefix('(') {
+                    Some(inner) => (inner, true),
+                    None => (rest, false),
+                };
+                let (alteration, altered, replaced) = SYMBOL_ALTERATIONS
+                    .iter()
+                    .find(|(alteration, _, _)| inner.starts_with(alteration))?;
+                rest = &inner[alteration.len()..];
+                if parenthesized {
+                    rest = rest.strip_prefix(')')?;
+                }
+                intervals.retain(|i| *i != *replaced && (*altered != 18 || *i != 7));
+                intervals.push(*altered);
+            }
+            intervals.sort();
+            Some(intervals)
+        })
+}
+
+/// Enumerates the reasons a chord symbol cannot be parsed, each holding the offending part of the symbol.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ChordSymbolError {
+    /// The symbol does not start with a note name, a letter from A to G followed by any sharps or flats.
+    InvalidRoot(String),
+    /// The quality suffix following the root is not recognized.
+    UnknownSuffix(String),
+    /// The note following the slash is not a note name.
+    InvalidBass(String),
+}
+
+impl Display for ChordSymbolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChordSymbolError::InvalidRoot(root) => {
+                write!(f, "\"{}\" does not start with a note name", root)
+            }
+            ChordSymbolError::UnknownSuffix(suffix) => {
+                write!(f, "unknown chord suffix \"{}\"", suffix)
+            }
+            ChordSymbolError::InvalidBass(bass) => {
+                write!(f, "the slash bass \"{}\" is not a note name", bass)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChordSymbolError {}
+
+/// Enumerates the reasons a chord cannot be built.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ChordError {
+    /// The note placed by the interval at `index` would lie above octave 255.
+    OutOfRange { index: usize },
+}
+
+impl Display for ChordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChordError::OutOfRange { index } => {
+                write!(f, "the note placed by interval {} is out of range", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChordError {}
+
+/// Helper function that parses the note name at the start of `s`, a letter from A to G followed by any number of sharps, `#` or
+/// `♯`, flats, `b` or `♭`, double sharps, `x` or `𝄪`, or double flats, `𝄫`, see `SpelledPitchClass::parse`, returning its
+/// pitch class and the rest of `s`, or `None` if `s` does not start with a note name.
+pub(crate) fn parse_note(s: &str) -> Option<(u8, &str)> {
+    let (spelled, rest) = SpelledPitchClass::parse_prefix(s)?;
+    Some((spelled.pitch_class(), rest))
+}
+
+/// Helper function that parses a pitch written as a note name followed by the octave of its letter, e.g. "C4", "F#3" or "Bb2",
+/// as for `parse_note`, so "B#3" is the C sounding in octave 4 and "Cb4" the B in octave 3. Returns `None` if `s` is not a
+/// note name and octave, or the pitch would lie outside octaves 0 to 255.
+pub(crate) fn parse_pitch(s: &str) -> Option<Pitch> {
+    let (spelled, rest) = SpelledPitchClass::parse_prefix(s)?;
+    let octave: i32 = rest.parse().ok()?;
+    let natural = SpelledPitchClass {
+        accidental: 0,
+        ..spelled
+    }
+    .pitch_class();
+    Pitch::from_pitch_class(0, 0)
+        .transpose(12 * octave + natural as i32 + spelled.accidental as i32)
+}
+
+/// Helper function that names the pitch class `pc` with a sharp or a flat, as given by `spelling`, if it is not a natural note.
+pub(crate) fn note_name(pc: u8, spelling: Spelling) -> &'static str {
+    let sharps = [
+        "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+    ];
+    let flats = [
+        "C", "Db", "D", "Eb", "E", "F", "Gb", "G", "Ab", "A", "Bb", "B",
+    ];
+    match spelling {
+        Spelling::Sharps => sharps[pc as usize % 12],
+        Spelling::Flats => flats[pc as usize % 12],
+    }
+}
+
+/// Helper function that returns the suffix naming the chord on `root` with exactly the pitch classes `pitch_classes`: the
+/// first listed in `SYMBOL_SUFFIXES` if one does, or otherwise a suffix of a seventh chord followed by the fewest
+/// `SYMBOL_ALTERATIONS`, in their listed order, that name it, e.g. "13#11" or "7b9b13", so `from_symbol` reads it back.
+fn symbol_suffix(root: u8, pitch_classes: PitchClassSet) -> Option<String> {
+    let names = |intervals: &[u8]| {
+        intervals
+            .iter()
+            .map(|i| root + i)
+            .collect::<PitchClassSet>()
+    };
+    if let Some((suffix, _)) = SYMBOL_SUFFIXES
+        .iter()
+        .find(|(_, intervals)| names(intervals) == pitch_classes)
+    {
+        return Some(suffix.to_string());
+    }
+    // Each subset of the alterations, as a bit mask over their listed order, from the fewest alterations to the most
+    let mut subsets: Vec<u32> = (1..1 << SYMBOL_ALTERATIONS.len()).collect();
+    subsets.sort_by_key(|subset| subset.count_ones());
+    subsets.into_iter().find_map(|subset| {
+        SYMBOL_SUFFIXES
+            .iter()
+            // Alterations are only written after a seventh, so the suffix cannot be read as part of the root, as in "Cb9"
+            .filter(|(_, intervals)| intervals.iter().any(|i| *i == 10 || *i == 11))
+            .map(|(name, _)| {
+                SYMBOL_ALTERATIONS
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| subset & 1 << index != 0)
+                    .fold(name.to_string(), |suffix, (_, (alteration, _, _))| {
+                        suffix + alteration
+                    })
+            })
+            .find(|suffix| suffix_intervals(suffix).is_some_and(|i| names(&i) == pitch_classes))
+    })
+}
+
+/// Enumerates the roles a pitch can play in a chord, given by its interval above the root.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ToneRole {
+    Root,
+    /// The third, or the second or fourth that takes its place in a suspended chord
+    Third,
+    Fifth,
+    /// The seventh, or the diminished seventh of a diminished seventh chord
+    Seventh,
+    Ninth,
+    Eleventh,
+    /// The thirteenth, or the added sixth of a sixth chord
+    Thirteenth,
+    /// A pitch that plays no role, e.g. a second seventh, or every pitch of a chord without a root
+    Foreign,
+}
+
+/// Enumerates the orders the pitches of a chord can be broken into for an arpeggio, see `Chord::arpeggio`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ArpPattern {
+    /// From the lowest pitch to the highest
+    Up,
+    /// From the highest pitch to the lowest
+    Down,
+    /// From the lowest pitch to the highest and back down, without repeating the highest
+    UpDown,
+    /// The pitches at the given indices, counted from the lowest pitch of the chord, e.g. `[0, 2, 1, 2]` for an Alberti bass.
+    /// Indices past the highest pitch continue in the octaves above.
+    Indices(Vec<usize>),
+}
+
+/// A struct that represents a chord of any number of simultaneous pitches, e.g. a piano voicing, held from the lowest pitch to the
+/// highest, optionally along with the pitch class of its root. Unlike `SATB` no voice ranges or doubling rules are enforced.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Chord {
+    /// The pitches of the chord, ordered by height without repeats
+    pitches: Vec<Pitch>,
+    /// The pitch class of the root, if known
+    root: Option<u8>,
+}
+
+impl Chord {
+    /// Associated method for creating a new, empty `Chord` without a root.
+    pub fn new() -> Self {
+        Chord::default()
+    }
+
+    /// Associated method for creating a `Chord` holding `pitches`, see `push`.
+    pub fn from_pitches(pitches: impl IntoIterator<Item = Pitch>) -> Self {
+        let mut chord = Chord::new();
+        for pitch in pitches {
+            chord.push(pitch);
+        }
+        chord
+    }
+
+    /// Associated method for creating a `Chord` by stacking `intervals` above `root`, each placing a note the interval above the
+    /// note before it, e.g. `[MajorThird, MinorThird, MinorThird]` above C4 gives C4-E4-G4-Bb4 and `[PerfectFourth;3]` the
+    /// quartal C4-F4-Bb4-Eb5. The root of the chord is set to that of `root`. Returns `ChordError::OutOfRange` if a note would
+    /// lie above octave 255.
+    pub fn stack(root: Pitch, intervals: &[Interval]) -> Result<Self, ChordError> {
+        let mut chord = Chord::from_pitches([root]).with_root(root.pitch_class());
+        let mut previous = root;
+        for (index, interval) in intervals.iter().enumerate() {
+            previous = previous
+                .transpose(interval.semitones() as i32)
+                .ok_or(ChordError::OutOfRange { index })?;
+            chord.push(previous);
+        }
+        Ok(chord)
+    }
+
+    /// Associated method for creating a `Chord` with a note each of `intervals` above `root`, e.g. `[MajorThird, PerfectFifth,
+    /// MinorSeventh]` above C4 gives C4-E4-G4-Bb4, see `stack`.
+    pub fn stack_from_root(root: Pitch, intervals: &[Interval]) -> Result<Self, ChordError> {
+        let mut chord = Chord::from_pitches([root]).with_root(root.pitch_class());
+        for (index, interval) in intervals.iter().enumerate() {
+            chord.push(
+                root.transpose(interval.semitones() as i32)
+                    .ok_or(ChordError::OutOfRange { index })?,
+            );
+        }
+        Ok(chord)
+    }
+
+    /// Associated method for creating a `Chord` from a chord symbol, e.g. "Cmaj7", "F#m7b5" or "G7/B": a root note name, a
+    /// quality suffix and an optional slash bass note. Sharps are written `#` or `♯`, flats `b` or `♭`, double sharps `x` or
+    /// `𝄪` and double flats `𝄫`, in note names and suffixes alike, so "F♯m7♭5" is "F#m7b5", and enharmonic roots such as "Db"
+    /// and "C#" give the same chord. The suffixes understood are those of triads, "m", "dim", "aug", "sus2",
+    /// "sus4" and "5", sixths, "6" and "m6", sevenths, "7", "maj7", "m7", "mMaj7", "m7b5", "dim7", "aug7" and "7sus4", and the
+    /// extended chords "add9", "madd9", "9", "maj9", "m9", "7b9", "7#9", "11", "m11", "13", "maj13" and "m13", along with the
+    /// alternative spellings "M", "maj", "min", "-", "°", "+", "sus", "M7", "Δ", "Δ7", "min7", "-7", "mM7", "ø", "ø7", "°7" and
+    /// "+7". A suffix may be followed by the alterations "b9", "#9", "#11" and "b13", each optionally in parentheses, e.g.
+    /// "C13#11" or "G7(b9)(b13)". An alteration replaces the tension it alters, and "#11" also takes the place of the perfect
+    /// fifth, so "C13#11" is C-E-Bb-D-F#-A. Any avoid notes are otherwise kept.
+    ///
+    /// The chord is given close position pitches with the root in octave 4 and extensions above the octave, and its bass, the
+    /// slash note or otherwise the root, added in octave 3 as the lowest pitch. The root of the chord is set to the root of
+    /// the symbol.
+    pub fn from_symbol(symbol: &str) -> Result<Self, ChordSymbolError> {
+        let symbol = symbol.trim();
+        let (root, rest) =
+            parse_note(symbol).ok_or_else(|| ChordSymbolError::InvalidRoot(symbol.to_string()))?;
+        let (suffix, bass) = match rest.split_once('/') {
+            Some((suffix, bass)) => match parse_note(bass) {
+                Some((pc, "")) => (suffix, pc),
+                _ => return Err(ChordSymbolError::InvalidBass(bass.to_string())),
+            },
+            None => (rest, root),
+        };
+        // Suffixes are matched with ASCII accidentals, so "m7♭5" reads as "m7b5"
+        let intervals = suffix_intervals(&suffix.replace('♭', "b").replace('♯', "#"))
+            .ok_or_else(|| ChordSymbolError::UnknownSuffix(suffix.to_string()))?;
+
+        let mut chord = Chord::from_pitches(
+            intervals
+                .iter()
+                .map(|i| Pitch::from_pitch_class((root + i) % 12, 4 + (root + i) / 12)),
+        );
+        chord.push(Pitch::from_pitch_class(bass, 3));
+        Ok(chord.with_root(root))
+    }
+
+    /// Method that returns the chord symbol of the chord, e.g. "Am7", "Bbmaj7", "D7/F#" or "C/D" when the lowest pitch is not the root,
+    /// naming notes off the white keys with sharps or flats as given by `spelling`. The quality is found from the pitch classes
+    /// of the chord alone, named by the first suffix understood by `from_symbol` whose chord has exactly those pitch classes, or
+    /// failing that by the suffix of a seventh chord with the fewest alterations that name it, e.g. "C13#11".
+    /// When the chord has a root it is used, and a bass outside of the chord on that root is written as a slash bass, as is a
+    /// bass that is a tension of the chord when it sounds only in the bass, so C3-C4-E4-G4 with D below is C/D. Otherwise
+    /// each pitch class is tried as the root and the chord is named if exactly one names it, or if several do and one is the
+    /// lowest pitch, so C-E-G-A is C6 over C and Am7 over A. Returns `None` for empty chords, collections no suffix names, such
+    /// as tone clusters, and ambiguous chords.
+    pub fn symbol(&self, spelling: Spelling) -> Option<String> {
+        let bass = self.bass()?.pitch_class();
+        let (root, suffix) = self.named_root()?;
+        let mut symbol = format!("{}{}", note_name(root, spelling), suffix);
+        if bass != root {
+            symbol = format!("{}/{}", symbol, note_name(bass, spelling));
+        }
+        Some(symbol)
+    }
+
+    /// Helper method that returns the root of the chord and the suffix naming it, found as described for `symbol`.
+    fn named_root(&self) -> Option<(u8, String)> {
+        let bass = self.bass()?.pitch_class();
+        let pitch_classes = self.pitch_classes();
+        match self.root {
+            Some(root) => {
+                let mut without_bass = pitch_classes;
+                without_bass.remove(bass);
+                // A bass sounding only below the chord that is not one of its chord tones is a slash bass, as for C/D
+                let slash = bass != root
+                    && self
+                        .pitches
+                        .iter()
+                        .filter(|p| p.pitch_class() == bass)
+                        .count()
+                        == 1
+                    && !self.chord_tones().contains(bass);
+                let suffix = if slash {
+                    symbol_suffix(root, without_bass)
+                        .or_else(|| symbol_suffix(root, pitch_classes))?
+                } else {
+                    symbol_suffix(root, pitch_classes)
+                        .or_else(|| symbol_suffix(root, without_bass).filter(|_| bass != root))?
+                };
+                Some((root, suffix))
+            }
+            None => {
+                let mut named: Vec<(u8, String)> = pitch_classes
+                    .iter()
+                    .filter_map(|root| Some((root, symbol_suffix(root, pitch_classes)?)))
+                    .collect();
+                match named.len() {
+                    1 => named.pop(),
+                    _ => named.into_iter().find(|(root, _)| *root == bass),
+                }
+            }
+        }
+    }
+
+    /// Method that realizes the chord as a four part `SATB` harmony voiced by the auto voicer under `prefs`, e.g. to write out
+    /// a chorale style realization of a lead sheet. The root is that of the chord, or found from its pitch content as for
+    /// `symbol`. At most four members are kept, by priority the root, the third, the seventh and then the fifth, all taken from
+    /// its `chord_tones`, so tensions such as ninths, thirteenths and the sharpened eleventh of "C13#11" are dropped first, as
+    /// by `reduce`, and the fifth is dropped next if the chord cannot be voiced with it,
+    /// which requires `prefs.validation` to allow omitted fifths. Major thirds are preferred to minor, perfect fifths to
+    /// diminished, and minor and major sevenths to diminished, which are kept as the sixth of sixth chords. When the lowest pitch
+    /// of the chord is not its root it is kept in the bass, so "G7/B" is voiced in first inversion. A foreign bass, see
+    /// `has_foreign_bass`, is kept below the upper voices, which are given the root, the third and the seventh or otherwise the
+    /// fifth, so "C/D" is voiced as C-E-G over D. Such a harmony is not a valid `SATB` harmony on its root, so it is created
+    /// without the checks for validity.
+    ///
+    /// Suspended and added tone chords, those whose pitch classes are a suspended, sixth or added ninth chord on the root, are
+    /// instead voiced with exactly their members, validated as by `SATB::try_with_quality`, so the suspension takes the place
+    /// of the third, e.g. "Gsus4" is voiced with C rather than B, and all four members of "C6" or "Cadd9" are kept, with the
+    /// fifth dropped to double the root only if they cannot all be voiced.
+    ///
+    /// Returns `SatbError::InvalidHarmony` if the chord has no root, no third outside of a suspended chord, an augmented fifth
+    /// without a perfect or diminished one, a bass the voicing cannot keep, or no valid voicing.
+    pub fn to_satb(&self, prefs: &VoicingPreferences) -> Result<SATB, SatbError> {
+        let root = self
+            .root
+            .or_else(|| Some(self.named_root()?.0))
+            .ok_or(SatbError::InvalidHarmony)?;
+        let bass = self.bass().ok_or(SatbError::InvalidHarmony)?.pitch_class();
+        let foreign_bass = self.has_foreign_bass();
+        let mut pitch_classes = self.pitch_classes();
+        if foreign_bass {
+            pitch_classes.remove(bass);
+        }
+        let mut members: Vec<u8> = pitch_classes.iter().collect();
+        members.sort_by_key(|pc| (pc + 12 - root) % 12);
+        let added = ChordQuality::from_pitch_classes(root, &members).filter(|q| !q.is_tertian());
+        if let (Some(quality), false) = (added, foreign_bass) {
+            // Complete voicings come first, the fifth is only dropped if none can be found
+            return enumerate_quality_voicings(root, quality, None, prefs)
+                .into_iter()
+                .filter(|voicing| voicing.bass.pitch_class() == bass)
+                .min_by_key(|voicing| {
+                    let sounding: PitchClassSet =
+                        voicing.voices().iter().map(|p| p.pitch_class()).collect();
+                    (sounding.len() < members.len(), standalone_cost(voicing))
+                })
+                .ok_or(SatbError::InvalidHarmony);
+        }
+        // Only chord tones are voiced, so a tension such as the sharpened eleventh never stands in for the fifth
+        let chord_tones = self.chord_tones();
+        let member = |intervals: &[u8]| {
+            intervals
+                .iter()
+                .map(|i| (root + i) % 12)
+                .find(|pc| pitch_classes.contains(*pc) && chord_tones.contains(*pc))
+        };
+        let third = member(&[4, 3]).ok_or(SatbError::InvalidHarmony)?;
+        let fifth = member(&[7, 6]);
+        let seventh = member(&[10, 11, 9]);
+        if fifth.is_none() && chord_tones.contains(root + 8) {
+            return Err(SatbError::InvalidHarmony);
+        }
+
+        let core: Vec<u8> = [Some(root), Some(third), seventh]
+            .into_iter()
+            .flatten()
+            .collect();
+        if foreign_bass {
+            let upper: Vec<u8> = [Some(root), Some(third), seventh.or(fifth)]
+                .into_iter()
+                .flatten()
+                .collect();
+            let mut voicings = enumerate_voicings(root, &upper, None, prefs);
+            voicings.sort_by_key(standalone_cost);
+            return voicings
+                .into_iter()
+                .find_map(|voicing| {
+                    let voices = [voicing.soprano, voicing.alto, voicing.tenor];
+                    if !upper
+                        .iter()
+                        .all(|pc| voices.iter().any(|p| p.pitch_class() == *pc))
+                    {
+                        return None;
+                    }
+                    // The highest pitch of the bass below the tenor
+                    let below = (1..=12)
+                        .filter_map(|i| voicing.tenor.transpose(-i))
+                        .find(|p| p.pitch_class() == bass)?;
+                    Voice::Bass.contains(&below).then(|| {
+                        SATB::new_unchecked(
+                            root,
+                            voicing.soprano,
+                            voicing.alto,
+                            voicing.tenor,
+                            below,
+                        )
+                    })
+                })
+                .ok_or(SatbError::InvalidHarmony);
+        }
+
+        // Voicings with the fifth come first, it is only dropped if none can be found
+        let members: Vec<u8> = core.iter().copied().chain(fifth).collect();
+        enumerate_voicings(root, &members, None, prefs)
+            .into_iter()
+            .filter(|voicing| voicing.bass.pitch_class() == bass)
+            .min_by_key(|voicing| {
+                let missing_fifth = fifth.is_some_and(|pc| !voicing.contains_pitch_class(pc));
+                (missing_fifth, standalone_cost(voicing))
+            })
+            .ok_or(SatbError::InvalidHarmony)
+    }
+
+    /// Method that returns the chord moved `semitones` half steps up (or down when negative), each pitch transposed as by
+    /// `Pitch::transpose` and the root, if known, moved with it. Returns `None` if any pitch would leave octaves 0 to 255.
+    pub fn transpose(&self, semitones: i32) -> Option<Chord> {
+        let pitches = self
+            .pitches
+            .iter()
+            .map(|p| p.transpose(semitones))
+            .collect::<Option<Vec<Pitch>>>()?;
+        Some(Chord {
+            pitches,
+            root: self
+                .root
+                .map(|root| (root as i32 + semitones).rem_euclid(12) as u8),
+        })
+    }
+
+    /// Method that returns the chord moved from `from_key` into `to_key`, by the interval between their tonics, so it keeps its
+    /// function, e.g. ii7 in C major becomes ii7 in Eb major. The chord moves by the nearest such interval, up to a tritone up
+    /// or a perfect fourth down, to stay in about the same register. Returns `None` if any pitch would leave octaves 0 to 255.
+    pub fn transpose_to_degree(&self, from_key: &Key, to_key: &Key) -> Option<Chord> {
+        let semitones = (to_key.tonic as i32 - from_key.tonic as i32).rem_euclid(12);
+        self.transpose(if semitones > 6 {
+            semitones - 12
+        } else {
+            semitones
+        })
+    }
+
+    /// Method that returns an iterator over the pitches of the chord broken into an arpeggio in the order given by `pattern`,
+    /// spread over `octaves` repeats of the chord, each transposed up an octave from the last, e.g. C4-E4-G4 arpeggiated up
+    /// and down over two octaves is C4-E4-G4-C5-E5-G5-E5-C5-G4-E4-C4. For the up and down patterns, pitches of a repeat that
+    /// do not lie above the previous repeat are skipped, so a chord spanning an octave, such as C4-E4-G4-C5, is not doubled.
+    /// For `ArpPattern::Indices` the whole pattern is played once for each repeat, ending at the first pitch that would rise
+    /// above octave 255, while the other patterns skip such pitches.
+    pub fn arpeggio(&self, pattern: ArpPattern, octaves: u8) -> impl Iterator<Item = Pitch> {
+        let len = self.pitches.len();
+        let pitches: Vec<Pitch> = match pattern {
+            ArpPattern::Indices(indices) if len > 0 => (0..octaves as u64)
+                .flat_map(|octave| indices.iter().map(move |i| (octave, i)))
+                .map_while(|(octave, i)| {
+                    // The octaves are counted in u64, so a large index cannot wrap around to a low pitch
+                    let shift = octave + (i / len) as u64;
+                    if shift > u8::MAX as u64 {
+                        return None;
+                    }
+                    self.pitches[i % len].transpose(12 * shift as i32)
+                })
+                .collect(),
+            ArpPattern::Indices(_) => Vec::new(),
+            direction => {
+                let mut span: Vec<Pitch> = Vec::new();
+                for octave in 0..octaves as i32 {
+                    for pitch in self.pitches.iter().filter_map(|p| p.transpose(12 * octave)) {
+                        if span.last().is_none_or(|highest| pitch > *highest) {
+                            span.push(pitch);
+                        }
+                    }
+                }
+                let descending = span.iter().rev().copied();
+                match direction {
+                    ArpPattern::Up => span,
+                    ArpPattern::Down => descending.collect(),
+                    _ => span.iter().copied().chain(descending.skip(1)).collect(),
+                }
+            }
+        };
+        pitches.into_iter()
+    }
+
+    /// Method that returns the number of pitch classes the chord shares with `other`, e.g. three for Cmaj7 and Am7.
+    pub fn common_tone_count(&self, other: &Chord) -> usize {
+        let other_classes = other.pitch_classes();
+        self.pitch_classes()
+            .iter()
+            .filter(|pc| other_classes.contains(*pc))
+            .count()
+    }
+
+    /// Method that returns the pitches sounding in both the chord and `other` at the same height, from the lowest to the highest.
+    pub fn shared_pitches(&self, other: &Chord) -> Vec<Pitch> {
+        self.pitches
+            .iter()
+            .filter(|p| other.pitches.binary_search(p).is_ok())
+            .copied()
+            .collect()
+    }
+
+    /// Method that returns how alike the chord and `other` are, from 0 to 1 for identical pitch classes, e.g. to rank
+    /// substitute chords. It is the mean of the share of common tones, the common tone count over the larger number of pitch
+    /// classes, and the closeness of the voice leading between them, where each pitch class of either chord moves to the
+    /// nearest pitch class of the other, as for `voice_leading_distance` but by pitch class, scaled so that every pitch class
+    /// moving by a tritone is 0. Chords without pitches are alike only to each other.
+    pub fn similarity(&self, other: &Chord) -> f64 {
+        let (from, to) = (self.pitch_classes(), other.pitch_classes());
+        if from.is_empty() || to.is_empty() {
+            return if from.is_empty() && to.is_empty() {
+                1.0
+            } else {
+                0.0
+            };
+        }
+        let nearest = |pc: u8, classes: &PitchClassSet| {
+            classes
+                .iter()
+                .map(|other| {
+                    let distance = (pc + 12 - other) % 12;
+                    distance.min(12 - distance) as usize
+                })
+                .min()
+                .unwrap_or(0)
+        };
+        let distance: usize = from.iter().map(|pc| nearest(pc, &to)).sum::<usize>()
+            + to.iter().map(|pc| nearest(pc, &from)).sum::<usize>();
+        let common = self.common_tone_count(other) as f64 / from.len().max(to.len()) as f64;
+        let closeness = 1.0 - distance as f64 / (6 * (from.len() + to.len())) as f64;
+        (common + closeness) / 2.0
+    }
+
+    /// Method that returns the chord inverted `n` times. Each inversion moves the lowest pitch up by the fewest octaves that
+    /// place it above the highest pitch, keeping the other pitches where they are, so a close position chord stays in close
+    /// position, e.g. the first inversion of C4-E4-G4 is E4-G4-C5. Inverting a chord of `len` pitches `len` times transposes
+    /// it up an octave. Pitches that would rise above octave 255 are left in place.
+    pub fn invert(&self, n: usize) -> Chord {
+        let mut chord = self.clone();
+        for _ in 0..n {
+            let (Some(lowest), Some(highest)) = (chord.pitches.first(), chord.pitches.last())
+            else {
+                break;
+            };
+            let octaves = (motion(lowest, highest) / 12 + 1) * 12;
+            if let Some(raised) = lowest.transpose(octaves) {
+                chord.pitches.remove(0);
+                chord.push(raised);
+            }
+        }
+        chord
+    }
+
+    /// Method that returns each inversion of the chord, in the order given by `invert`, one for each other pitch class that
+    /// can lie in the bass, e.g. the first and second inversions of a triad whatever its doublings.
+    pub fn inversions(&self) -> Vec<Chord> {
+        let mut basses: Vec<u8> = self.bass().map(|p| p.pitch_class()).into_iter().collect();
+        (1..self.len())
+            .map(|n| self.invert(n))
+            .filter(|chord| {
+                let bass = chord.bass().map(|p| p.pitch_class());
+                match bass {
+                    Some(pc) if !basses.contains(&pc) => {
+                        basses.push(pc);
+                        true
+                    }
+                    _ => false,
+                }
+            })
+            .collect()
+    }
+
+    /// Method that returns the pitch classes of the chord tones, the root, third, fifth and seventh, relative to the root of
+    /// the chord, or that found from its pitch content as for `symbol`. Any other pitch class is a tension, see `tensions`.
+    /// Intervals that could be either are decided by the rest of the chord: a minor third is a sharpened ninth if there is a
+    /// major third, a diminished fifth is a sharpened eleventh unless the third is minor, a minor sixth is a flattened
+    /// thirteenth if there is a perfect fifth, a major sixth is a thirteenth if there is a seventh, and a second or fourth is
+    /// a tension unless it replaces the third of a suspended chord. Chords without a root have no chord tones.
+    pub fn chord_tones(&self) -> PitchClassSet {
+        let Some(root) = self.root.or_else(|| Some(self.named_root()?.0)) else {
+            return PitchClassSet::new();
+        };
+        let pitch_classes = self.pitch_classes();
+        let has = |interval: u8| pitch_classes.contains(root + interval);
+        pitch_classes
+            .iter()
+            .filter(|pc| match (pc + 12 - root) % 12 {
+                0 | 4 | 7 | 10 | 11 => true,
+                3 => !has(4),
+                6 => has(3) && !has(4),
+                8 => !has(7),
+                9 => !has(10) && !has(11),
+                _ => !has(3) && !has(4),
+            })
+            .collect()
+    }
+
+    /// Method that returns each pitch of the chord, from the lowest to the highest, along with its role relative to the root of
+    /// the chord, or that found from its pitch content as for `symbol`. Whether a pitch is a chord tone or a tension is decided
+    /// as for `chord_tones`, so a diminished fifth is the fifth of a half diminished chord but the sharpened eleventh of a
+    /// dominant one. Tensions a second or third above the root are ninths, a fourth or diminished fifth elevenths, and a sixth
+    /// thirteenths. A major seventh alongside a minor seventh, or an augmented fifth alongside a diminished one, is foreign, as
+    /// is every pitch of a chord without a root.
+    pub fn classify_tones(&self) -> Vec<(Pitch, ToneRole)> {
+        let root = self.root.or_else(|| Some(self.named_root()?.0));
+        let chord_tones = self.chord_tones();
+        let pitch_classes = self.pitch_classes();
+        let role = |pc: u8| {
+            let Some(root) = root else {
+                return ToneRole::Foreign;
+            };
+            let has = |interval: u8| pitch_classes.contains(root + interval);
+            match (chord_tones.contains(pc), (pc + 12 - root) % 12) {
+                (true, 0) => ToneRole::Root,
+                (true, 1..=5) => ToneRole::Third,
+                (true, 8) if has(6) => ToneRole::Foreign,
+                (true, 6..=8) => ToneRole::Fifth,
+                (true, 9) if !(has(3) && has(6)) => ToneRole::Thirteenth,
+                (true, 11) if has(10) => ToneRole::Foreign,
+                (true, _) => ToneRole::Seventh,
+                (false, 1..=3) => ToneRole::Ninth,
+                (false, 5 | 6) => ToneRole::Eleventh,
+                (false, 8 | 9) => ToneRole::Thirteenth,
+                (false, _) => ToneRole::Foreign,
+            }
+        };
+        self.pitches
+            .iter()
+            .map(|p| (*p, role(p.pitch_class())))
+            .collect()
+    }
+
+    /// Method that returns the pitch classes of the tensions of the chord, ninths, elevenths and thirteenths, every pitch class
+    /// that is not one of its `chord_tones`.
+    pub fn tensions(&self) -> PitchClassSet {
+        let chord_tones = self.chord_tones();
+        self.pitch_classes()
+            .iter()
+            .filter(|pc| !chord_tones.contains(*pc))
+            .collect()
+    }
+
+    /// Method that reduces the chord to at most `voices` pitch classes, keeping the lowest pitch of each. Pitch classes are
+    /// kept by priority: the root, the third and the seventh, then the tensions, thirteenths before ninths before elevenths,
+    /// and the fifth last, which is the first chord tone dropped, e.g. C13#11 reduces to C-E-Bb-A in four voices.
+    pub fn reduce(&self, voices: usize) -> Chord {
+        let root = self.root.or_else(|| Some(self.named_root()?.0));
+        let chord_tones = self.chord_tones();
+        let priority = |pc: u8| {
+            let interval = (pc + 12 - root.unwrap_or(pc)) % 12;
+            match (chord_tones.contains(pc), interval) {
+                (true, 0) => 0,
+                (true, 2..=5) => 1,
+                (true, 9..=11) => 2,
+                (false, 8 | 9) => 3,
+                (false, 1..=3) => 4,
+                (false, _) => 5,
+                (true, _) => 6,
+            }
+        };
+        let mut pitch_classes: Vec<u8> = self.pitch_classes().iter().collect();
+        pitch_classes.sort_by_key(|pc| priority(*pc));
+        pitch_classes.truncate(voices);
+        let mut reduced = Chord {
+            pitches: Vec::new(),
+            root: self.root,
+        };
+        for pc in pitch_classes {
+            if let Some(lowest) = self.pitches.iter().find(|p| p.pitch_class() == pc) {
+                reduced.push(*lowest);
+            }
+        }
+        reduced
+    }
+
+    /// Builder method that sets the root of the chord to the pitch class `root`, taken modulo 12.
+    pub fn with_root(mut self, root: u8) -> Self {
+        self.root = Some(root % 12);
+        self
+    }
+
+    /// Method that returns the chord with `bass` as its lowest pitch, e.g. to write C over E or over D. Pitches of the chord
+    /// below `bass` are raised by the fewest octaves that place them above it, and the root is kept, so the bass may be any
+    /// chord tone, giving an inversion, or a pitch foreign to the chord, see `has_foreign_bass`. Pitches that would rise above
+    /// octave 255 are dropped.
+    pub fn with_bass(&self, bass: Pitch) -> Chord {
+        let mut chord = Chord {
+            pitches: vec![bass],
+            root: self.root,
+        };
+        for pitch in &self.pitches {
+            let octaves = if *pitch >= bass {
+                0
+            } else {
+                (motion(pitch, &bass) / 12 + 1) * 12
+            };
+            if let Some(raised) = pitch.transpose(octaves) {
+                chord.push(raised);
+            }
+        }
+        chord
+    }
+
+    /// Method that returns true if the lowest pitch of the chord is foreign to it, a pedal or slash bass such as the D of C/D,
+    /// rather than its root, third, fifth or seventh, see `classify_tones`. Chords without a root have no foreign bass.
+    pub fn has_foreign_bass(&self) -> bool {
+        let root = self.root.or_else(|| Some(self.named_root()?.0));
+        root.is_some()
+            && self.classify_tones().first().is_some_and(|(_, role)| {
+                !matches!(
+                    role,
+                    ToneRole::Root | ToneRole::Third | ToneRole::Fifth | ToneRole::Seventh
+                )
+            })
+    }
+
+    /// Method for adding `pitch` to the chord, keeping the pitches ordered by height. A pitch equal to one the chord already
+    /// holds is ignored, while the same pitch class in another octave is kept as a doubling.
+    pub fn push(&mut self, pitch: Pitch) {
+        if let Err(index) = self.pitches.binary_search(&pitch) {
+            self.pitches.insert(index, pitch);
+        }
+    }
+
+    /// Method for accessing the pitches of the chord, ordered from the lowest to the highest.
+    pub fn pitches(&self) -> &[Pitch] {
+        &self.pitches
+    }
+
+    /// Method for accessing the pitch class of the root of the chord, if known.
+    pub fn root(&self) -> Option<u8> {
+        self.root
+    }
+
+    /// Method that returns the lowest pitch of the chord, or `None` if it is empty.
+    pub fn bass(&self) -> Option<Pitch> {
+        self.pitches.first().copied()
+    }
+
+    /// Method that returns the distinct pitch classes of the chord.
+    pub fn pitch_classes(&self) -> PitchClassSet {
+        self.pitches.iter().map(|p| p.pitch_class()).collect()
+    }
+
+    /// Method that returns the number of pitches in the chord.
+    pub fn len(&self) -> usize {
+        self.pitches.len()
+    }
+
+    /// Method that returns true if the chord holds no pitches.
+    pub fn is_empty(&self) -> bool {
+        self.pitches.is_empty()
+    }
+}
+
+impl Harmony for Chord {
+    fn frequencies(&self) -> Vec<f64> {
+        self.pitches.frequencies()
+    }
+}
+
+impl From<&SATB> for Chord {
+    fn from(satb: &SATB) -> Self {
+        Chord::from_pitches(satb.voices()).with_root(satb.root())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ChordMember, KeyMode, ValidationConfig};
+
+    #[test]
+    fn test_detect() {
+        let set = |pcs: &[u8]| pcs.iter().copied().collect::<PitchClassSet>();
+        assert_eq!(
+            ChordQuality::detect(&set(&[0, 3, 6, 9])),
+            [0, 3, 6, 9].map(|root| (root, ChordQuality::Diminished7))
+        );
+        assert_eq!(
+            ChordQuality::detect(&set(&[0, 4, 7, 9])),
+            [(9, ChordQuality::Minor7), (0, ChordQuality::Major6)]
+        );
+        assert_eq!(
+            ChordQuality::detect(&set(&[7, 11, 2])),
+            [(7, ChordQuality::Major)]
+        );
+        assert_eq!(
+            ChordQuality::detect(&set(&[9, 0, 4])),
+            [(9, ChordQuality::Minor)]
+        );
+        assert_eq!(
+            ChordQuality::detect(&set(&[0, 4, 8])),
+            [0, 4, 8].map(|root| (root, ChordQuality::Augmented))
+        );
+        // Suspended chords are inversions of each other, and a seventh chord may omit its fifth
+        assert_eq!(
+            ChordQuality::detect(&set(&[0, 2, 7])),
+            [(0, ChordQuality::Sus2), (7, ChordQuality::Sus4)]
+        );
+        assert_eq!(
+            ChordQuality::detect(&set(&[7, 11, 5])),
+            [(7, ChordQuality::Dominant7)]
+        );
+        assert!(ChordQuality::detect(&set(&[0, 1, 2])).is_empty());
+        assert!(ChordQuality::detect(&PitchClassSet::new()).is_empty());
+    }
+
+    #[test]
+    fn test_chord_qualities() {
+        for quality in ChordQuality::ALL {
+            let intervals = quality.intervals();
+            assert_eq!(intervals[0], 0);
+            assert!(intervals.windows(2).all(|w| w[0] < w[1] && w[1] < 12));
+            let triad = matches!(
+                quality,
+                ChordQuality::Major
+                    | ChordQuality::Minor
+                    | ChordQuality::Diminished
+                    | ChordQuality::Augmented
+                    | ChordQuality::Sus2
+                    | ChordQuality::Sus4
+            );
+            assert_eq!(intervals.len(), if triad { 3 } else { 4 });
+            assert_eq!(quality.is_seventh(), quality.is_tertian() && !triad);
+            // Each quality is recognized from its own pitch classes on any root
+            for root in 0..12 {
+                let pitch_classes: Vec<u8> = intervals.iter().map(|i| (root + i) % 12).collect();
+                assert_eq!(
+                    ChordQuality::from_pitch_classes(root, &pitch_classes),
+                    Some(quality)
+                );
+            }
+        }
+        assert_eq!(ChordQuality::Dominant7.intervals(), [0, 4, 7, 10]);
+        assert_eq!(ChordQuality::HalfDiminished7.intervals(), [0, 3, 6, 10]);
+        assert_eq!(ChordQuality::Sus4.intervals(), [0, 5, 7]);
+
+        let set = |pcs: &[u8]| pcs.iter().copied().collect::<PitchClassSet>();
+        assert_eq!(ChordQuality::Major.pitch_classes(2), set(&[2, 6, 9]));
+        assert_eq!(ChordQuality::Minor7.pitch_classes(9), set(&[9, 0, 4, 7]));
+        assert_eq!(ChordQuality::Sus2.pitch_classes(14), set(&[2, 4, 9]));
+        assert_eq!(
+            ChordQuality::Diminished7.pitch_classes(11).to_string(),
+            "{2, 5, 8, 11}"
+        );
+    }
+
+    #[test]
+    fn test_from_symbol() {
+        let table: [(&str, u8, &[u8], u8); 24] = [
+            ("C", 0, &[0, 4, 7], 0),
+            ("Cm", 0, &[0, 3, 7], 0),
+            ("Cmaj7", 0, &[0, 4, 7, 11], 0),
+            ("F#m7b5", 6, &[6, 9, 0, 4], 6),
+            ("Gbø7", 6, &[6, 9, 0, 4], 6),
+            ("G7/B", 7, &[7, 11, 2, 5], 11),
+            ("Bb7", 10, &[10, 2, 5, 8], 10),
+            ("A#7", 10, &[10, 2, 5, 8], 10),
+            ("Ebdim7", 3, &[3, 6, 9, 0], 3),
+            ("Caug", 0, &[0, 4, 8], 0),
+            ("Dsus4", 2, &[2, 7, 9], 2),
+            ("Dsus2", 2, &[2, 4, 9], 2),
+            ("Am7", 9, &[9, 0, 4, 7], 9),
+            ("Cadd9", 0, &[0, 4, 7, 2], 0),
+            ("D9", 2, &[2, 6, 9, 0, 4], 2),
+            ("C13", 0, &[0, 4, 7, 10, 2, 9], 0),
+            ("Cb", 11, &[11, 3, 6], 11),
+            ("E#m", 5, &[5, 8, 0], 5),
+            ("C/E", 0, &[0, 4, 7], 4),
+            ("Dm7/C", 2, &[2, 5, 9, 0], 0),
+            ("F♯m7♭5", 6, &[6, 9, 0, 4], 6),
+            ("B♭7♯9", 10, &[10, 2, 5, 8, 1], 10),
+            ("Fx", 7, &[7, 11, 2], 7),
+            ("E𝄫m/B𝄫", 2, &[2, 5, 9], 9),
+        ];
+        for (symbol, root, pitch_classes, bass) in table {
+            let chord = Chord::from_symbol(symbol).unwrap();
+            assert_eq!(chord.root(), Some(root), "{}", symbol);
+            assert_eq!(
+                chord.pitch_classes(),
+                pitch_classes
+                    .iter()
+                    .copied()
+                    .chain([bass])
+                    .collect::<PitchClassSet>(),
+                "{}",
+                symbol
+            );
+            assert_eq!(chord.bass().unwrap().pitch_class(), bass, "{}", symbol);
+        }
+        assert_eq!(
+            Chord::from_symbol("Cmaj7").unwrap().pitches()[1..],
+            [(0, 4), (4, 4), (7, 4), (11, 4)].map(Pitch::from)
+        );
+
+        assert_eq!(Chord::from_symbol("F♯m7♭5"), Chord::from_symbol("F#m7b5"));
+        assert_eq!(parse_pitch("Fx4"), Some(Pitch::from((7, 4))));
+        assert_eq!(parse_pitch("C𝄫4"), Some(Pitch::from((10, 3))));
+        assert_eq!(parse_pitch("B♯𝄪3"), Some(Pitch::from((2, 4))));
+
+        assert_eq!(
+            Chord::from_symbol("Cqz7"),
+            Err(ChordSymbolError::UnknownSuffix("qz7".to_string()))
+        );
+        assert_eq!(
+            Chord::from_symbol("H7"),
+            Err(ChordSymbolError::InvalidRoot("H7".to_string()))
+        );
+        assert_eq!(
+            Chord::from_symbol("G7/X"),
+            Err(ChordSymbolError::InvalidBass("X".to_string()))
+        );
+        assert_eq!(
+            Chord::from_symbol("Cqz7").unwrap_err().to_string(),
+            "unknown chord suffix \"qz7\""
+        );
+    }
+
+    #[test]
+    fn test_symbol() {
+        let symbols = [
+            "C", "Cm", "Cmaj7", "F#m7b5", "G7/B", "Bb7", "Ebdim7", "Caug", "Dsus4", "Dsus2", "Am7",
+            "Cadd9", "D9", "C13", "B", "Fm", "C/E", "Dm7/C", "Ab7sus4", "Bbmaj7",
+        ];
+        for symbol in symbols {
+            let spelling = if symbol.contains('#') {
+                Spelling::Sharps
+            } else {
+                Spelling::Flats
+            };
+            let chord = Chord::from_symbol(symbol).unwrap();
+            assert_eq!(chord.symbol(spelling).as_deref(), Some(symbol));
+        }
+        // Altered chords read back from their symbols, with the fewest alterations written in their listed order
+        let altered = [
+            "C13#11", "C7#11", "G7b9b13", "C9#11", "Bb7#9#11", "F#7b13", "Cmaj7#11", "Dm7b9",
+            "E7b9/G#",
+        ];
+        for symbol in altered {
+            let spelling = if symbol[1..].starts_with('b') {
+                Spelling::Flats
+            } else {
+                Spelling::Sharps
+            };
+            let chord = Chord::from_symbol(symbol).unwrap();
+            assert_eq!(chord.symbol(spelling).as_deref(), Some(symbol));
+        }
+        assert_eq!(
+            Chord::from_symbol("G7(b9)(b13)")
+                .unwrap()
+                .symbol(Spelling::Flats)
+                .as_deref(),
+            Some("G7b9b13")
+        );
+
+        // Alternative spellings are printed with the first suffix
+        assert_eq!(
+            Chord::from_symbol("Gbø7")
+                .unwrap()
+                .symbol(Spelling::Sharps)
+                .as_deref(),
+            Some("F#m7b5")
+        );
+
+        // Without a root, the quality and root come from the pitch content
+        let chord =
+            |pitches: &[(u8, u8)]| Chord::from_pitches(pitches.iter().map(|p| Pitch::from(*p)));
+        assert_eq!(
+            chord(&[(9, 3), (0, 4), (4, 4), (7, 4)])
+                .symbol(Spelling::Sharps)
+                .as_deref(),
+            Some("Am7")
+        );
+        assert_eq!(
+            chord(&[(6, 3), (2, 4), (9, 4), (0, 5)])
+                .symbol(Spelling::Sharps)
+                .as_deref(),
+            Some("D7/F#")
+        );
+        assert_eq!(
+            chord(&[(10, 3), (2, 4), (5, 4), (9, 4)])
+                .symbol(Spelling::Flats)
+                .as_deref(),
+            Some("Bbmaj7")
+        );
+        assert_eq!(
+            chord(&[(0, 4), (4, 4), (7, 4), (9, 4)])
+                .symbol(Spelling::Sharps)
+                .as_deref(),
+            Some("C6")
+        );
+        // A tone cluster has no name, while any member of an augmented triad could be its root, so the bass is taken
+        assert_eq!(
+            chord(&[(0, 4), (1, 4), (2, 4)]).symbol(Spelling::Sharps),
+            None
+        );
+        assert_eq!(
+            chord(&[(0, 4), (4, 4), (8, 4)])
+                .symbol(Spelling::Sharps)
+                .as_deref(),
+            Some("Caug")
+        );
+        assert_eq!(Chord::new().symbol(Spelling::Sharps), None);
+    }
+
+    #[test]
+    fn test_to_satb() {
+        let prefs = VoicingPreferences::default();
+        let dominant = Chord::from_symbol("G7/B").unwrap().to_satb(&prefs).unwrap();
+        assert_eq!(dominant.root(), 7);
+        assert_eq!(dominant.bass.pitch_class(), 11);
+        assert_eq!(dominant.role_of(Voice::Bass), ChordMember::Third);
+        assert!(dominant.contains_pitch_class(5));
+
+        // The ninth and thirteenth of C13 are dropped, keeping the third and seventh
+        let thirteenth = Chord::from_symbol("C13").unwrap().to_satb(&prefs).unwrap();
+        let pitch_classes: PitchClassSet = thirteenth
+            .voices()
+            .iter()
+            .map(|p| p.pitch_class())
+            .collect();
+        assert!(pitch_classes.contains(4) && pitch_classes.contains(10));
+        assert!(!pitch_classes.contains(2) && !pitch_classes.contains(9));
+        assert_eq!(thirteenth.role_of(Voice::Bass), ChordMember::Root);
+
+        // The sharpened eleventh of C13#11 is a tension, not a diminished fifth, so it is dropped with the other tensions
+        let altered = Chord::from_symbol("C13#11").unwrap();
+        assert_eq!(
+            altered.reduce(4).pitch_classes(),
+            [0, 4, 9, 10].into_iter().collect()
+        );
+        let satb = altered.to_satb(&prefs).unwrap();
+        assert!(!satb.contains_pitch_class(6));
+        assert!(satb.contains_pitch_class(4) && satb.contains_pitch_class(10));
+
+        // Root position symbols keep the root in the bass
+        for (symbol, root) in [("A", 9), ("Am", 9), ("Bb", 10), ("A7", 9), ("Am7", 9)] {
+            let satb = Chord::from_symbol(symbol).unwrap().to_satb(&prefs).unwrap();
+            assert_eq!(satb.root(), root, "{}", symbol);
+            assert_eq!(satb.bass.pitch_class(), root, "{}", symbol);
+        }
+        // The fifth is kept whenever it fits
+        for (symbol, fifth) in [("C", 7), ("C7", 7), ("Cm7b5", 6)] {
+            let satb = Chord::from_symbol(symbol).unwrap().to_satb(&prefs).unwrap();
+            assert!(satb.contains_pitch_class(fifth), "{} {:?}", symbol, satb);
+        }
+        let seventh = Chord::from_symbol("C7").unwrap().to_satb(&prefs).unwrap();
+        assert!(seventh.contains_pitch_class(10));
+        assert_eq!(
+            Chord::from_symbol("C7sus4").unwrap().to_satb(&prefs),
+            Err(SatbError::InvalidHarmony)
+        );
+        assert_eq!(Chord::new().to_satb(&prefs), Err(SatbError::InvalidHarmony));
+    }
+
+    #[test]
+    fn test_slash_chords() {
+        let prefs = VoicingPreferences::default();
+        let first_inversion = Chord::from_symbol("C/E").unwrap();
+        assert!(!first_inversion.has_foreign_bass());
+        let satb = first_inversion.to_satb(&prefs).unwrap();
+        assert_eq!(satb.role_of(Voice::Bass), ChordMember::Third);
+        assert_eq!(satb.root(), 0);
+
+        // The D of C/D is neither dropped nor an error, the upper voices keep the C major triad
+        let pedal = Chord::from_symbol("C/D").unwrap();
+        assert!(pedal.has_foreign_bass());
+        assert_eq!(pedal.symbol(Spelling::Sharps).as_deref(), Some("C/D"));
+        let satb = pedal.to_satb(&prefs).unwrap();
+        assert_eq!(satb.bass.pitch_class(), 2);
+        assert_eq!(satb.root(), 0);
+        for pc in [0, 4, 7] {
+            assert!([satb.soprano, satb.alto, satb.tenor]
+                .iter()
+                .any(|p| p.pitch_class() == pc));
+        }
+
+        // Setting the bass raises the pitches at or below it
+        let c = Chord::from_symbol("C").unwrap();
+        let over_e = c.with_bass(Pitch::from((4, 4)));
+        assert_eq!(
+            over_e.pitches(),
+            [
+                Pitch::from((4, 4)),
+                Pitch::from((7, 4)),
+                Pitch::from((0, 5))
+            ]
+        );
+        assert_eq!(over_e.symbol(Spelling::Sharps).as_deref(), Some("C/E"));
+        assert_eq!(c.with_bass(Pitch::from((2, 3))), pedal);
+        assert!(!Chord::from_symbol("G7/B").unwrap().has_foreign_bass());
+    }
+
+    #[test]
+    fn test_arpeggio() {
+        let c = Chord::from_pitches([
+            Pitch::from((0, 4)),
+            Pitch::from((4, 4)),
+            Pitch::from((7, 4)),
+        ]);
+        let up_down: Vec<Pitch> = c.arpeggio(ArpPattern::UpDown, 2).collect();
+        let expected = [
+            (0, 4),
+            (4, 4),
+            (7, 4),
+            (0, 5),
+            (4, 5),
+            (7, 5),
+            (4, 5),
+            (0, 5),
+            (7, 4),
+            (4, 4),
+            (0, 4),
+        ]
+        .map(Pitch::from);
+        assert_eq!(up_down, expected);
+        assert!(c
+            .arpeggio(ArpPattern::Down, 2)
+            .eq(expected[..6].iter().rev().copied()));
+
+        // An octave spanning chord is not doubled where the repeats meet
+        let mut spanning = c.clone();
+        spanning.push(Pitch::from((0, 5)));
+        assert_eq!(spanning.arpeggio(ArpPattern::Up, 2).count(), 7);
+
+        let alberti: Vec<Pitch> = c
+            .arpeggio(ArpPattern::Indices(vec![0, 2, 1, 3]), 2)
+            .collect();
+        assert_eq!(
+            alberti,
+            [
+                (0, 4),
+                (7, 4),
+                (4, 4),
+                (0, 5),
+                (0, 5),
+                (7, 5),
+                (4, 5),
+                (0, 6)
+            ]
+            .map(Pitch::from)
+        );
+        assert_eq!(c.arpeggio(ArpPattern::Up, 0).count(), 0);
+        assert_eq!(
+            Chord::new()
+                .arpeggio(ArpPattern::Indices(vec![0]), 1)
+                .count(),
+            0
+        );
+
+        // An indices pattern ends at the first pitch above octave 255 rather than wrapping around to a low pitch
+        let high = Chord::from_pitches([Pitch::from((0, 254)), Pitch::from((7, 254))]);
+        let pattern = ArpPattern::Indices(vec![0, 3, 1]);
+        assert_eq!(
+            high.arpeggio(pattern, 2).collect::<Vec<Pitch>>(),
+            [(0, 254), (7, 255), (7, 254), (0, 255)].map(Pitch::from)
+        );
+        assert_eq!(
+            c.arpeggio(ArpPattern::Indices(vec![0, usize::MAX, 1]), 255)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_stack() {
+        use Interval::*;
+        let c4 = Pitch::from((0, 4));
+        let dominant = Chord::stack(c4, &[MajorThird, MinorThird, MinorThird]).unwrap();
+        assert_eq!(
+            dominant.pitches(),
+            [(0, 4), (4, 4), (7, 4), (10, 4)].map(Pitch::from)
+        );
+        assert_eq!(dominant.symbol(Spelling::Flats).as_deref(), Some("C7"));
+        assert_eq!(
+            Chord::stack_from_root(c4, &[MajorThird, PerfectFifth, MinorSeventh]),
+            Ok(dominant)
+        );
+
+        let quartal = Chord::stack(c4, &[PerfectFourth; 3]).unwrap();
+        assert_eq!(
+            quartal.pitches(),
+            [(0, 4), (5, 4), (10, 4), (3, 5)].map(Pitch::from)
+        );
+        assert_eq!(quartal.root(), Some(0));
+
+        let top = Pitch::from((9, 255));
+        assert_eq!(
+            Chord::stack(Pitch::from((7, 255)), &[MajorSecond, PerfectFourth]),
+            Err(ChordError::OutOfRange { index: 1 })
+        );
+        assert_eq!(
+            Chord::stack_from_root(top, &[MajorThird]),
+            Err(ChordError::OutOfRange { index: 0 })
+        );
+    }
+
+    #[test]
+    fn test_similarity() {
+        let symbol = |s: &str| Chord::from_symbol(s).unwrap();
+        let (cmaj7, am7) = (symbol("Cmaj7"), symbol("Am7"));
+        assert_eq!(cmaj7.common_tone_count(&am7), 3);
+        // Am7 is voiced a sixth higher, so no pitches are shared exactly
+        assert!(cmaj7.shared_pitches(&am7).is_empty());
+        assert_eq!(
+            cmaj7.shared_pitches(&symbol("C6")),
+            [(0, 3), (0, 4), (4, 4), (7, 4)].map(Pitch::from)
+        );
+        // The tritone substitute of a dominant seventh shares its third and seventh
+        assert_eq!(symbol("C7").common_tone_count(&symbol("F#7")), 2);
+
+        assert_eq!(cmaj7.similarity(&cmaj7), 1.0);
+        assert_eq!(cmaj7.similarity(&am7), am7.similarity(&cmaj7));
+        assert!(cmaj7.similarity(&am7) > cmaj7.similarity(&symbol("F#7")));
+        assert!(cmaj7.similarity(&symbol("F#7")) > 0.0);
+        assert_eq!(cmaj7.similarity(&Chord::new()), 0.0);
+    }
+
+    #[test]
+    fn test_added_tone_voicings() {
+        let prefs = VoicingPreferences::default();
+        let pitch_classes = |satb: &SATB| {
+            satb.voices()
+                .iter()
+                .map(|p| p.pitch_class())
+                .collect::<PitchClassSet>()
+        };
+
+        // The suspended fourth takes the place of the third and the voicing validates as Gsus4
+        let sus = Chord::from_symbol("Gsus4")
+            .unwrap()
+            .to_satb(&prefs)
+            .unwrap();
+        assert_eq!(pitch_classes(&sus), [7, 0, 2].into_iter().collect());
+        assert!(SATB::try_with_quality(
+            7,
+            ChordQuality::Sus4,
+            sus.soprano,
+            sus.alto,
+            sus.tenor,
+            sus.bass,
+            &prefs.validation
+        )
+        .is_ok());
+        assert!(SATB::try_new(7, sus.soprano, sus.alto, sus.tenor, sus.bass).is_err());
+
+        let sixth = Chord::from_symbol("C6").unwrap().to_satb(&prefs).unwrap();
+        assert_eq!(pitch_classes(&sixth), [0, 4, 7, 9].into_iter().collect());
+        assert_eq!(sixth.root(), 0);
+        let add9 = Chord::from_symbol("Cadd9")
+            .unwrap()
+            .to_satb(&prefs)
+            .unwrap();
+        assert_eq!(pitch_classes(&add9), [0, 2, 4, 7].into_iter().collect());
+
+        // Declared qualities reject foreign members and, without omitted fifths, incomplete voicings
+        let (c4, e4, a3, c3) = (
+            Pitch::from((0, 4)),
+            Pitch::from((4, 4)),
+            Pitch::from((9, 3)),
+            Pitch::from((0, 3)),
+        );
+        let strict = ValidationConfig {
+            allow_omitted_fifth: false,
+        };
+        assert!(
+            SATB::try_with_quality(0, ChordQuality::Major6, e4, c4, a3, c3, &prefs.validation)
+                .is_ok()
+        );
+        assert_eq!(
+            SATB::try_with_quality(0, ChordQuality::Major6, e4, c4, a3, c3, &strict),
+            Err(SatbError::InvalidHarmony)
+        );
+        assert_eq!(
+            SATB::try_with_quality(0, ChordQuality::Sus2, e4, c4, a3, c3, &prefs.validation),
+            Err(SatbError::InvalidHarmony)
+        );
+    }
+
+    #[test]
+    fn test_transpose() {
+        let cmaj7 = Chord::from_pitches([
+            Pitch::from((0, 3)),
+            Pitch::from((4, 4)),
+            Pitch::from((7, 4)),
+            Pitch::from((11, 4)),
+        ])
+        .with_root(0);
+        let ebmaj7 = cmaj7.transpose(3).unwrap();
+        assert_eq!(
+            ebmaj7.pitches(),
+            [
+                Pitch::from((3, 3)),
+                Pitch::from((7, 4)),
+                Pitch::from((10, 4)),
+                Pitch::from((2, 5))
+            ]
+        );
+        assert_eq!(ebmaj7.root(), Some(3));
+        assert_eq!(ebmaj7.symbol(Spelling::Flats).as_deref(), Some("Ebmaj7"));
+        assert_eq!(ebmaj7.transpose(-3), Some(cmaj7));
+        assert_eq!(
+            Chord::from_pitches([Pitch::from((0, 0))]).transpose(-1),
+            None
+        );
+
+        // ii7-V7 in C major becomes ii7-V7 in Eb major, and moves down a fourth rather than up a fifth into G major
+        let fragment = [
+            Chord::from_symbol("Dm7").unwrap(),
+            Chord::from_symbol("G7").unwrap(),
+        ];
+        let (c, eb, g) = (
+            Key::new(0, KeyMode::Major),
+            Key::new(3, KeyMode::Major),
+            Key::new(7, KeyMode::Major),
+        );
+        let symbols = |key: &Key| {
+            fragment
+                .iter()
+                .map(|chord| {
+                    chord
+                        .transpose_to_degree(&c, key)
+                        .unwrap()
+                        .symbol(Spelling::Flats)
+                        .unwrap()
+                })
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(symbols(&eb), ["Fm7", "Bb7"]);
+        assert_eq!(symbols(&g), ["Am7", "D7"]);
+        assert_eq!(
+            fragment[0].transpose_to_degree(&c, &g).unwrap().pitches()[1],
+            Pitch::from((9, 3))
+        );
+    }
+
+    #[test]
+    fn test_classify_tones() {
+        let roles = |chord: &Chord| {
+            chord
+                .classify_tones()
+                .into_iter()
+                .map(|(_, role)| role)
+                .collect::<Vec<ToneRole>>()
+        };
+        let c13 = Chord::from_symbol("C13").unwrap();
+        assert_eq!(
+            c13.classify_tones()[0],
+            (Pitch::from((0, 3)), ToneRole::Root)
+        );
+        assert_eq!(
+            roles(&c13),
+            [
+                ToneRole::Root,
+                ToneRole::Root,
+                ToneRole::Third,
+                ToneRole::Fifth,
+                ToneRole::Seventh,
+                ToneRole::Ninth,
+                ToneRole::Thirteenth
+            ]
+        );
+
+        // The tritone is decided by the quality, the fifth of Cm7b5 but the sharpened eleventh of C7#11
+        assert_eq!(
+            roles(&Chord::from_symbol("Cm7b5").unwrap())[3],
+            ToneRole::Fifth
+        );
+        assert_eq!(
+            roles(&Chord::from_symbol("C7#11").unwrap()),
+            [
+                ToneRole::Root,
+                ToneRole::Root,
+                ToneRole::Third,
+                ToneRole::Seventh,
+                ToneRole::Eleventh
+            ]
+        );
+
+        // A major seventh over a dominant seventh chord is foreign
+        let mut clash = Chord::from_symbol("C7").unwrap();
+        clash.push(Pitch::from((11, 4)));
+        assert_eq!(
+            clash.classify_tones().last(),
+            Some(&(Pitch::from((11, 4)), ToneRole::Foreign))
+        );
+        let cluster = Chord::from_pitches([Pitch::from((0, 4)), Pitch::from((1, 4))]);
+        assert_eq!(roles(&cluster), [ToneRole::Foreign, ToneRole::Foreign]);
+    }
+
+    #[test]
+    fn test_inversions() {
+        let chord =
+            |pitches: &[(u8, u8)]| Chord::from_pitches(pitches.iter().map(|p| Pitch::from(*p)));
+        let c_major = chord(&[(0, 4), (4, 4), (7, 4)]).with_root(0);
+        let first = c_major.invert(1);
+        assert_eq!(first.pitches(), chord(&[(4, 4), (7, 4), (0, 5)]).pitches());
+        assert_eq!(first.root(), Some(0));
+        assert_eq!(
+            c_major.invert(2).pitches(),
+            chord(&[(7, 4), (0, 5), (4, 5)]).pitches()
+        );
+        let once_around: Vec<Pitch> = c_major
+            .pitches()
+            .iter()
+            .map(|p| p.transpose(12).unwrap())
+            .collect();
+        assert_eq!(c_major.invert(1).invert(1).invert(1).pitches(), once_around);
+        assert_eq!(c_major.invert(3), c_major.invert(1).invert(2));
+        assert_eq!(
+            c_major.inversions(),
+            vec![c_major.invert(1), c_major.invert(2)]
+        );
+
+        // Spread voicings move the bass above the top, and doublings give no extra inversions
+        let spread = chord(&[(0, 3), (7, 3), (0, 4), (4, 4)]);
+        assert_eq!(
+            spread.invert(1).pitches(),
+            chord(&[(7, 3), (0, 4), (4, 4), (0, 5)]).pitches()
+        );
+        let basses: Vec<u8> = spread
+            .inversions()
+            .iter()
+            .map(|c| c.bass().unwrap().pitch_class())
+            .collect();
+        assert_eq!(basses, [7, 4]);
+        assert!(Chord::new().invert(2).is_empty());
+    }
+
+    #[test]
+    fn test_extended_chords() {
+        let set = |pcs: &[u8]| pcs.iter().copied().collect::<PitchClassSet>();
+        let chord = Chord::from_symbol("C13#11").unwrap();
+        assert_eq!(chord.pitch_classes(), set(&[0, 4, 10, 2, 6, 9]));
+        assert_eq!(chord.tensions(), set(&[2, 6, 9]));
+        assert_eq!(chord.chord_tones(), set(&[0, 4, 10]));
+        let reduced = chord.reduce(4);
+        assert_eq!(reduced.pitch_classes(), set(&[0, 4, 10, 9]));
+        assert_eq!(reduced.len(), 4);
+        assert_eq!(reduced.bass(), chord.bass());
+
+        let altered = Chord::from_symbol("G7(b9)(b13)").unwrap();
+        assert_eq!(altered.pitch_classes(), set(&[7, 11, 2, 5, 8, 3]));
+        assert_eq!(altered.tensions(), set(&[8, 3]));
+        assert_eq!(Chord::from_symbol("C7#9").unwrap().tensions(), set(&[3]));
+        assert_eq!(Chord::from_symbol("Cm7b5").unwrap().tensions(), set(&[]));
+        assert_eq!(Chord::from_symbol("C6").unwrap().tensions(), set(&[]));
+        assert_eq!(Chord::from_symbol("Csus4").unwrap().tensions(), set(&[]));
+        assert_eq!(
+            Chord::from_symbol("C9").unwrap().reduce(4).pitch_classes(),
+            set(&[0, 4, 10, 2])
+        );
+        assert_eq!(
+            Chord::from_symbol("C7").unwrap().reduce(4).pitch_classes(),
+            set(&[0, 4, 7, 10])
+        );
+        assert_eq!(
+            Chord::from_symbol("C13b5"),
+            Err(ChordSymbolError::UnknownSuffix("13b5".to_string()))
+        );
+        assert!(Chord::from_symbol("C7(#11").is_err());
+    }
+
+    #[test]
+    fn test_chord() {
+        // A six note piano voicing of Cmaj9, with the G doubled
+        let mut chord = Chord::new().with_root(0);
+        for pitch in [(7, 4), (0, 2), (4, 3), (7, 2), (11, 3), (2, 4), (7, 4)] {
+            chord.push(Pitch::from(pitch));
+        }
+        assert_eq!(chord.len(), 6);
+        assert_eq!(chord.bass(), Some(Pitch::from((0, 2))));
+        assert!(chord.pitches().windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(
+            chord.pitch_classes(),
+            [0, 2, 4, 7, 11].into_iter().collect::<PitchClassSet>()
+        );
+        let wave = chord.sound_wave(1, 8000);
+        assert_eq!(wave.len(), 8000);
+        assert!(wave.iter().all(|s| s.abs() <= 1.0));
+        assert!(wave.iter().any(|s| s.abs() > 0.1));
+
+        let satb = SATB::new(
+            0,
+            Pitch::from((4, 5)),
+            Pitch::from((7, 4)),
+            Pitch::from((0, 4)),
+            Pitch::from((0, 3)),
+        );
+        let chord = Chord::from(&satb);
+        assert_eq!(chord.root(), Some(0));
+        assert_eq!(chord.len(), 4);
+        assert_eq!(
+            chord.frequencies(),
+            satb.voices()
+                .map(|p| p.frequency())
+                .iter()
+                .rev()
+                .copied()
+                .collect::<Vec<f64>>()
+        );
+        assert_eq!(chord.pitch_classes().len(), 3);
+    }
+}
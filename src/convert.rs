@@ -0,0 +1,308 @@
+//! Conversions between note names, frequencies, MIDI note numbers and intervals, working on strings so a command line or
+//! web tool can pass its input straight through.
+//!
+//! Note names are read in scientific pitch notation, a note name followed by the octave of its letter, e.g. "C4" for middle
+//! C, "F#3" or "B♭2", or in Helmholtz notation, where the octave from C2 is named by upper case letters, the octave from C3
+//! by lower case letters, each prime, `'` or `′`, after a lower case letter raises it an octave and each comma after an upper
+//! case letter lowers it an octave, e.g. "C," for C1, "c" for C3 and "c'" for middle C. Accidentals are read as by
+//! `SpelledPitchClass::parse`. Note names are written in scientific pitch notation, with sharps.
+use crate::chord::note_name;
+use crate::{Interval, SpelledPitchClass, Spelling, Tuning, A_440_OCTAVE};
+use std::fmt::Display;
+use std::num::IntErrorKind;
+
+/// Enumerates the errors returned by the conversion functions.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConvertError {
+    /// The string is not a note name in scientific or Helmholtz notation.
+    InvalidNote(String),
+    /// The note lies outside of octaves 0 to 255.
+    OutOfRange(String),
+    /// The frequency is not positive and finite, or lies outside of octaves 0 to 255.
+    InvalidFrequency(f64),
+}
+
+impl Display for ConvertError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConvertError::InvalidNote(note) => write!(
+                f,
+                "\"{}\" is not a note name, e.g. \"C4\" or \"c'\" for middle C",
+                note
+            ),
+            ConvertError::OutOfRange(note) => {
+                write!(f, "\"{}\" lies outside of octaves 0 to 255", note)
+            }
+            ConvertError::InvalidFrequency(frequency) => {
+                write!(f, "{} Hz is not the frequency of a note", frequency)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+/// Helper function that parses the note name `note`, as described in the `convert` module, returning its position in half
+/// steps above C0. Returns an error if `note` is not a note name, or its octave is so far outside of 0 to 255 that the
+/// position cannot be computed.
+fn parse_position(note: &str) -> Result<i32, ConvertError> {
+    let invalid = || ConvertError::InvalidNote(note.to_string());
+    let trimmed = note.trim();
+    let first = trimmed.chars().next().ok_or_else(invalid)?;
+    let upper = first.to_ascii_uppercase();
+    let capitalized = format!("{}{}", upper, &trimmed[first.len_utf8()..]);
+    let (spelled, rest) = SpelledPitchClass::parse_prefix(&capitalized).ok_or_else(invalid)?;
+    let octave = if rest.ends_with(|c: char| c.is_ascii_digit()) {
+        // Scientific pitch notation, with an upper case letter
+        if first != upper {
+            return Err(invalid());
+        }
+        match rest.parse::<i32>() {
+            Ok(octave) => octave,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    IntErrorKind::PosOverflow | IntErrorKind::NegOverflow
+                ) =>
+            {
+                return Err(ConvertError::OutOfRange(note.to_string()))
+            }
+            Err(_) => return Err(invalid()),
+        }
+    } else if first == upper {
+        // Helmholtz notation from the great octave down
+        rest.chars()
+            .all(|c| c == ',')
+            .then(|| 2 - rest.len() as i32)
+            .ok_or_else(invalid)?
+    } else {
+        // Helmholtz notation from the small octave up
+        let primes = rest.chars().filter(|c| matches!(c, '\'' | '′')).count();
+        (primes == rest.chars().count())
+            .then_some(3 + primes as i32)
+            .ok_or_else(invalid)?
+    };
+    // Bound the octave before computing the position, the accidental moves the note by at most two half steps
+    if !(-1..=256).contains(&octave) {
+        return Err(ConvertError::OutOfRange(note.to_string()));
+    }
+    let natural = SpelledPitchClass {
+        accidental: 0,
+        ..spelled
+    }
+    .pitch_class();
+    Ok(12 * octave + natural as i32 + spelled.accidental as i32)
+}
+
+/// Helper function that parses the note name `note` as for `parse_position`, returning its pitch class and octave.
+pub(crate) fn parse_note_name(note: &str) -> Result<(u8, u8), ConvertError> {
+    let position = parse_position(note)?;
+    match u8::try_from(position.div_euclid(12)) {
+        Ok(octave) => Ok((position.rem_euclid(12) as u8, octave)),
+        Err(_) => Err(ConvertError::OutOfRange(note.to_string())),
+    }
+}
+
+/// Helper function that names the note `position` half steps above C0 in scientific pitch notation, with sharps.
+fn position_name(position: i32) -> String {
+    format!(
+        "{}{}",
+        note_name(position.rem_euclid(12) as u8, Spelling::Sharps),
+        position.div_euclid(12)
+    )
+}
+
+/// A function that returns the frequency in Hz of the note named `note` under `tuning`, e.g. 440 for "A4" or "a'" tuned to
+/// 440 Hz, as computed by `Tuning::frequency`. Returns an error if `note` is not a note name or lies outside of octaves 0 to
+/// 255.
+pub fn note_to_frequency(note: &str, tuning: &Tuning) -> Result<f64, ConvertError> {
+    let (pc, octave) = parse_note_name(note)?;
+    Ok(tuning.frequency(pc, octave))
+}
+
+/// A function that returns the name of the equally tempered note nearest to `frequency` Hz under `tuning`, e.g. "A4" for
+/// 440 Hz tuned to 440 Hz, along with how far `frequency` lies from the note in cents, between -50 and 50. Returns an error
+/// if `frequency` is not positive and finite, or its nearest note lies outside of octaves 0 to 255.
+pub fn frequency_to_note(frequency: f64, tuning: &Tuning) -> Result<(String, f64), ConvertError> {
+    if !(frequency.is_finite() && frequency > 0.0) {
+        return Err(ConvertError::InvalidFrequency(frequency));
+    }
+    let semitones = 12.0 * (frequency / tuning.reference).log2();
+    let nearest = semitones.round();
+    let position = (12 * A_440_OCTAVE + 9) as f64 + nearest;
+    if !(0.0..256.0 * 12.0).contains(&position) {
+        return Err(ConvertError::InvalidFrequency(frequency));
+    }
+    Ok((
+        position_name(position as i32),
+        100.0 * (semitones - nearest),
+    ))
+}
+
+/// A function that returns the name of the MIDI note `note`, where note 60 is "C4", from "C-1" for note 0 upwards.
+pub fn midi_to_note(note: u8) -> String {
+    position_name(note as i32 - 12)
+}
+
+/// A function that returns the name of the interval between the notes named `from` and `to`, in either order, e.g. "m3"
+/// from "A3" to "C4" or "P5" from "c" to "g", as written by the `Display` implementation of `Interval`. Intervals wider than
+/// an octave are reduced to a simple interval, with whole octaves named "P8". Returns an error if either note is not a note
+/// name or lies outside of octaves 0 to 255.
+pub fn interval_between(from: &str, to: &str) -> Result<String, ConvertError> {
+    let position = |note: &str| -> Result<i32, ConvertError> {
+        let (pc, octave) = parse_note_name(note)?;
+        Ok(12 * octave as i32 + pc as i32)
+    };
+    let semitones = (position(to)? - position(from)?).unsigned_abs();
+    let simple = match semitones % 12 {
+        0 if semitones > 0 => 12,
+        simple => simple,
+    };
+    Ok(Interval::ALL[simple as usize].to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_note_to_frequency() {
+        let tuning = Tuning::default();
+        for note in ["A4", "a'", "a′", "G##4", "Gx4", "B𝄫4"] {
+            assert_eq!(note_to_frequency(note, &tuning), Ok(440.0), "{}", note);
+        }
+        for (note, pc, octave) in [
+            ("C4", 0, 4),
+            ("c'", 0, 4),
+            ("c", 0, 3),
+            ("C", 0, 2),
+            ("C,", 0, 1),
+            ("F#3", 6, 3),
+            ("f♯", 6, 3),
+            ("bb", 10, 3),
+            ("B#3", 0, 4),
+            ("Cb4", 11, 3),
+            ("eb''", 3, 5),
+        ] {
+            assert_eq!(
+                note_to_frequency(note, &tuning),
+                Ok(tuning.frequency(pc, octave)),
+                "{}",
+                note
+            );
+        }
+        assert_eq!(note_to_frequency("A4", &Tuning::new(415.0)), Ok(415.0));
+
+        for note in ["", "H4", "c4", "C'", "c,", "A4x", "4"] {
+            assert_eq!(
+                note_to_frequency(note, &tuning),
+                Err(ConvertError::InvalidNote(note.to_string()))
+            );
+        }
+        let primes = format!("c{}", "'".repeat(300));
+        for note in [
+            "C-1",
+            "C256",
+            "B#255",
+            "C200000000",
+            "C-200000000",
+            "C99999999999",
+            &primes,
+        ] {
+            assert_eq!(
+                note_to_frequency(note, &tuning),
+                Err(ConvertError::OutOfRange(note.to_string()))
+            );
+        }
+        assert_eq!(
+            note_to_frequency("Cb0", &tuning),
+            Err(ConvertError::OutOfRange("Cb0".to_string()))
+        );
+        assert!(note_to_frequency("B255", &tuning).is_ok());
+        assert_eq!(
+            note_to_frequency("H4", &tuning).unwrap_err().to_string(),
+            "\"H4\" is not a note name, e.g. \"C4\" or \"c'\" for middle C"
+        );
+        assert_eq!(
+            note_to_frequency("C,,,", &tuning).unwrap_err().to_string(),
+            "\"C,,,\" lies outside of octaves 0 to 255"
+        );
+    }
+
+    #[test]
+    fn test_frequency_to_note() {
+        let tuning = Tuning::default();
+        assert_eq!(
+            frequency_to_note(440.0, &tuning),
+            Ok(("A4".to_string(), 0.0))
+        );
+        for note in ["C4", "F#3", "B2", "C#7", "C1"] {
+            let frequency = note_to_frequency(note, &tuning).unwrap();
+            let (name, cents) = frequency_to_note(frequency, &tuning).unwrap();
+            assert_eq!(name, note);
+            assert!(cents.abs() < 1e-3, "{}: {}", note, cents);
+        }
+        let (name, cents) = frequency_to_note(450.0, &tuning).unwrap();
+        assert_eq!(name, "A4");
+        assert!((cents - 1200.0 * (450.0f64 / 440.0).log2()).abs() < 1e-9);
+        let (name, cents) = frequency_to_note(455.0, &tuning).unwrap();
+        assert_eq!(name, "A#4");
+        assert!(cents < 0.0);
+        assert_eq!(
+            frequency_to_note(415.0, &Tuning::new(415.0)),
+            Ok(("A4".to_string(), 0.0))
+        );
+
+        for frequency in [0.0, -440.0, f64::NAN, f64::INFINITY, 1.0] {
+            assert!(matches!(
+                frequency_to_note(frequency, &tuning),
+                Err(ConvertError::InvalidFrequency(_))
+            ));
+        }
+        assert_eq!(
+            frequency_to_note(-1.0, &tuning).unwrap_err().to_string(),
+            "-1 Hz is not the frequency of a note"
+        );
+    }
+
+    #[test]
+    fn test_midi_and_intervals() {
+        assert_eq!(midi_to_note(60), "C4");
+        assert_eq!(midi_to_note(69), "A4");
+        assert_eq!(midi_to_note(61), "C#4");
+        assert_eq!(midi_to_note(0), "C-1");
+        assert_eq!(midi_to_note(127), "G9");
+        let tuning = Tuning::default();
+        assert_eq!(
+            note_to_frequency(&midi_to_note(81), &tuning),
+            Ok(tuning.frequency(9, 5))
+        );
+
+        for (from, to, interval) in [
+            ("A3", "C4", "m3"),
+            ("C4", "A3", "m3"),
+            ("c", "g", "P5"),
+            ("C4", "C4", "P1"),
+            ("C4", "c''", "P8"),
+            ("C4", "E5", "M3"),
+            ("F#4", "C5", "TT"),
+            ("E♭4", "D#4", "P1"),
+        ] {
+            assert_eq!(
+                interval_between(from, to),
+                Ok(interval.to_string()),
+                "{} {}",
+                from,
+                to
+            );
+        }
+        assert_eq!(
+            interval_between("C4", "X4"),
+            Err(ConvertError::InvalidNote("X4".to_string()))
+        );
+        assert_eq!(
+            interval_between("C", "C-2").unwrap_err().to_string(),
+            "\"C-2\" lies outside of octaves 0 to 255"
+        );
+    }
+}
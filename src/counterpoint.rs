@@ -0,0 +1,297 @@
+//! Validation of species counterpoint between two voices, a cantus firmus and a counterpoint written against it.
+use crate::progression::motion;
+use crate::Pitch;
+use std::fmt::Display;
+
+/// The largest span in semitones, a tenth, that either voice of a first species counterpoint may cover.
+pub const FIRST_SPECIES_RANGE_LIMIT: u32 = 16;
+
+/// Enumerates the two voices of a two voice counterpoint.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CounterpointVoice {
+    /// The given melody
+    Cantus,
+    /// The melody written against the cantus firmus
+    Counter,
+}
+
+/// Enumerates the kinds of violations of the rules of first species counterpoint.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CounterpointViolationKind {
+    /// The voices form a dissonant interval, given in semitones modulo 12.
+    Dissonance { semitones: u8 },
+    /// The first interval is not a perfect consonance.
+    ImperfectBeginning,
+    /// The last interval is not a perfect consonance.
+    ImperfectEnding,
+    /// The voices move in the same direction from one perfect interval to another of the same size.
+    ParallelPerfect { semitones: u8 },
+    /// The voices move in the same direction into a perfect interval, where contrary motion is preferred.
+    SimilarMotionIntoPerfect { semitones: u8 },
+    /// The voices exchange which of them is the upper voice.
+    VoiceCrossing,
+    /// The voice spans more than `FIRST_SPECIES_RANGE_LIMIT` semitones.
+    RangeExceeded { voice: CounterpointVoice, span: u32 },
+}
+
+/// A violation of the rules of first species counterpoint, found at note `index` of the two voices.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CounterpointViolation {
+    /// The index of the note the violation occurs at. For rules concerning motion, this is the index of the note moved to.
+    pub index: usize,
+    /// The kind of violation.
+    pub kind: CounterpointViolationKind,
+}
+
+/// Enumerates the errors that prevent a counterpoint from being validated at all.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CounterpointError {
+    /// The cantus firmus and counterpoint have a different number of notes.
+    LengthMismatch { cantus: usize, counter: usize },
+}
+
+impl Display for CounterpointError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CounterpointError::LengthMismatch { cantus, counter } => write!(
+                f,
+                "the cantus firmus has {} notes but the counterpoint has {}",
+                cantus, counter
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CounterpointError {}
+
+/// Helper function that returns true if the interval of `semitones` modulo 12 is a perfect consonance, i.e. a unison, octave or fifth.
+fn is_perfect(semitones: u8) -> bool {
+    semitones == 0 || semitones == 7
+}
+
+/// Helper function that returns true if the interval of `semitones` modulo 12 is consonant in two voice counterpoint.
+/// Note the perfect fourth is treated as a dissonance.
+fn is_consonant(semitones: u8) -> bool {
+    matches!(semitones, 0 | 3 | 4 | 7 | 8 | 9)
+}
+
+/// Helper function that computes the span in semitones of the highest and lowest notes of `voice`.
+fn span(voice: &[Pitch]) -> u32 {
+    let positions = voice
+        .iter()
+        .map(|p| 12 * p.octave() as u32 + p.pitch_class() as u32);
+    match (positions.clone().min(), positions.max()) {
+        (Some(low), Some(high)) => high - low,
+        _ => 0,
+    }
+}
+
+/// A function that validates `counter` as a first species counterpoint against the cantus firmus `cantus`, note against note.
+/// Enforces that only consonances are formed, that the counterpoint begins and ends on perfect consonances, that there are
+/// no parallel perfect intervals, that the voices do not cross, that perfect intervals are approached by contrary motion
+/// and that neither voice spans more than `FIRST_SPECIES_RANGE_LIMIT` semitones.
+/// Returns all violations found, ordered by index, or an error if the two voices have a different number of notes.
+pub fn validate_first_species(
+    cantus: &[Pitch],
+    counter: &[Pitch],
+) -> Result<Vec<CounterpointViolation>, CounterpointError> {
+    if cantus.len() != counter.len() {
+        return Err(CounterpointError::LengthMismatch {
+            cantus: cantus.len(),
+            counter: counter.len(),
+        });
+    }
+    let mut violations = Vec::new();
+    if cantus.is_empty() {
+        return Ok(violations);
+    }
+
+    // The signed distance of the counterpoint above the cantus, and its size modulo 12
+    let distances: Vec<i32> = cantus
+        .iter()
+        .zip(counter)
+        .map(|(c, p)| motion(c, p))
+        .collect();
+    let intervals: Vec<u8> = distances.iter().map(|d| d.rem_euclid(12) as u8).collect();
+    let counter_above = distances[0] >= 0;
+
+    for (index, interval) in intervals.iter().enumerate() {
+        if !is_consonant(*interval) {
+            violations.push(CounterpointViolation {
+                index,
+                kind: CounterpointViolationKind::Dissonance {
+                    semitones: *interval,
+                },
+            });
+        }
+        let above = distances[index] >= 0;
+        if distances[index] != 0 && above != counter_above {
+            violations.push(CounterpointViolation {
+                index,
+                kind: CounterpointViolationKind::VoiceCrossing,
+            });
+        }
+    }
+    if !is_perfect(intervals[0]) {
+        violations.push(CounterpointViolation {
+            index: 0,
+            kind: CounterpointViolationKind::ImperfectBeginning,
+        });
+    }
+    let last = intervals.len() - 1;
+    if !is_perfect(intervals[last]) {
+        violations.push(CounterpointViolation {
+            index: last,
+            kind: CounterpointViolationKind::ImperfectEnding,
+        });
+    }
+
+    // Motion between successive intervals
+    for index in 1..intervals.len() {
+        let cantus_motion = motion(&cantus[index - 1], &cantus[index]);
+        let counter_motion = motion(&counter[index - 1], &counter[index]);
+        let similar = cantus_motion != 0
+            && counter_motion != 0
+            && cantus_motion.signum() == counter_motion.signum();
+        if !similar || !is_perfect(intervals[index]) {
+            continue;
+        }
+        let kind = if intervals[index - 1] == intervals[index] {
+            CounterpointViolationKind::ParallelPerfect {
+                semitones: intervals[index],
+            }
+        } else {
+            CounterpointViolationKind::SimilarMotionIntoPerfect {
+                semitones: intervals[index],
+            }
+        };
+        violations.push(CounterpointViolation { index, kind });
+    }
+
+    for (voice, notes) in [
+        (CounterpointVoice::Cantus, cantus),
+        (CounterpointVoice::Counter, counter),
+    ] {
+        let span = span(notes);
+        if span > FIRST_SPECIES_RANGE_LIMIT {
+            violations.push(CounterpointViolation {
+                index: 0,
+                kind: CounterpointViolationKind::RangeExceeded { voice, span },
+            });
+        }
+    }
+
+    violations.sort_by_key(|v| v.index);
+    Ok(violations)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pitches(notes: &[(u8, u8)]) -> Vec<Pitch> {
+        notes.iter().map(|n| Pitch::from(*n)).collect()
+    }
+
+    /// The dorian cantus firmus from Fux's Gradus ad Parnassum
+    fn fux_cantus() -> Vec<Pitch> {
+        pitches(&[
+            (2, 4),
+            (5, 4),
+            (4, 4),
+            (2, 4),
+            (7, 4),
+            (5, 4),
+            (9, 4),
+            (7, 4),
+            (5, 4),
+            (4, 4),
+            (2, 4),
+        ])
+    }
+
+    #[test]
+    fn test_fux_first_species() {
+        let counter = pitches(&[
+            (9, 4),
+            (9, 4),
+            (7, 4),
+            (9, 4),
+            (11, 4),
+            (0, 5),
+            (0, 5),
+            (11, 4),
+            (2, 5),
+            (1, 5),
+            (2, 5),
+        ]);
+        let violations = validate_first_species(&fux_cantus(), &counter).unwrap();
+        assert!(violations.is_empty(), "{:?}", violations);
+    }
+
+    #[test]
+    fn test_parallel_fifths_and_dissonance() {
+        let cantus = pitches(&[(2, 4), (5, 4), (4, 4), (2, 4)]);
+        // D/A, F/C parallel fifths, E/A a fourth, D/D
+        let counter = pitches(&[(9, 4), (0, 5), (9, 4), (2, 5)]);
+        let violations = validate_first_species(&cantus, &counter).unwrap();
+        println!("{:?}", violations);
+        assert!(violations.contains(&CounterpointViolation {
+            index: 1,
+            kind: CounterpointViolationKind::ParallelPerfect { semitones: 7 }
+        }));
+        assert!(violations.contains(&CounterpointViolation {
+            index: 2,
+            kind: CounterpointViolationKind::Dissonance { semitones: 5 }
+        }));
+    }
+
+    #[test]
+    fn test_imperfect_ending_and_crossing() {
+        let cantus = pitches(&[(2, 4), (5, 4), (4, 4), (2, 4)]);
+        // Begins a fifth above, crosses below the cantus, and ends on a third
+        let counter = pitches(&[(9, 4), (2, 4), (0, 4), (5, 4)]);
+        let violations = validate_first_species(&cantus, &counter).unwrap();
+        println!("{:?}", violations);
+        assert!(violations.contains(&CounterpointViolation {
+            index: 1,
+            kind: CounterpointViolationKind::VoiceCrossing
+        }));
+        assert!(violations.contains(&CounterpointViolation {
+            index: 3,
+            kind: CounterpointViolationKind::ImperfectEnding
+        }));
+    }
+
+    #[test]
+    fn test_similar_motion_and_range() {
+        let cantus = pitches(&[(2, 4), (4, 4), (2, 4)]);
+        // Octave, similar motion up into a fifth, then a leap of more than a tenth
+        let counter = pitches(&[(2, 5), (11, 5), (2, 4)]);
+        let violations = validate_first_species(&cantus, &counter).unwrap();
+        println!("{:?}", violations);
+        assert!(violations.contains(&CounterpointViolation {
+            index: 1,
+            kind: CounterpointViolationKind::SimilarMotionIntoPerfect { semitones: 7 }
+        }));
+        assert!(violations.contains(&CounterpointViolation {
+            index: 0,
+            kind: CounterpointViolationKind::RangeExceeded {
+                voice: CounterpointVoice::Counter,
+                span: 21
+            }
+        }));
+    }
+
+    #[test]
+    fn test_length_mismatch() {
+        let result = validate_first_species(&fux_cantus(), &pitches(&[(9, 4)]));
+        assert_eq!(
+            result,
+            Err(CounterpointError::LengthMismatch {
+                cantus: 11,
+                counter: 1
+            })
+        );
+    }
+}
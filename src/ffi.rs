@@ -0,0 +1,301 @@
+//! A C compatible interface to the pitch math, validation and rendering of `SATB` harmonies, for callers such as audio
+//! plugins written in C or C++. Build the crate as a C library with the `ffi` feature, e.g.
+//! `cargo rustc --release --features ffi --crate-type cdylib`, and include the header `include/twelve_et.h`, which
+//! `cbindgen` regenerates from this module with the configuration in `cbindgen.toml`.
+//!
+//! No function panics across the boundary: invalid arguments are reported through the return value, and any panic is caught
+//! and reported as `TWELVE_ET_ERROR_PANIC`, or as NaN by the functions returning a frequency.
+use crate::{Pitch, RenderState, SatbError, SATB};
+use std::panic::{catch_unwind, AssertUnwindSafe, UnwindSafe};
+
+/// Returned when the call succeeded and, for `twelve_et_validate_satb`, the harmony is valid.
+pub const TWELVE_ET_OK: i32 = 0;
+/// Returned when a voice of the harmony is outside of its range.
+pub const TWELVE_ET_ERROR_OUT_OF_RANGE: i32 = 1;
+/// Returned when a lower voice of the harmony sounds above an upper voice.
+pub const TWELVE_ET_ERROR_CROSSING: i32 = 2;
+/// Returned when two adjacent upper voices of the harmony are more than an octave apart.
+pub const TWELVE_ET_ERROR_SPACING: i32 = 3;
+/// Returned when the pitch classes of the harmony do not form a complete harmony on its root.
+pub const TWELVE_ET_ERROR_INVALID_HARMONY: i32 = 4;
+/// Returned when an argument is invalid, i.e. a pitch class or root of 12 or more, a null buffer or a sample rate of zero.
+pub const TWELVE_ET_ERROR_INVALID_ARGUMENT: i32 = -1;
+/// Returned when the call panicked.
+pub const TWELVE_ET_ERROR_PANIC: i32 = -2;
+
+/// A pitch given by its pitch class, from 0 for C to 11 for B, and its octave, where middle C is in octave 4.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TwelveEtPitch {
+    pub pitch_class: u8,
+    pub octave: u8,
+}
+
+/// A four part harmony given by the pitch class of its root and the pitch of each voice.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TwelveEtSatb {
+    pub root: u8,
+    pub soprano: TwelveEtPitch,
+    pub alto: TwelveEtPitch,
+    pub tenor: TwelveEtPitch,
+    pub bass: TwelveEtPitch,
+}
+
+impl TwelveEtPitch {
+    /// Method that converts the pitch to a `Pitch`, or `None` if its pitch class is 12 or more.
+    fn to_pitch(self) -> Option<Pitch> {
+        (self.pitch_class < 12).then(|| Pitch::from_pitch_class(self.pitch_class, self.octave))
+    }
+}
+
+impl TwelveEtSatb {
+    /// Method that validates the harmony and converts it to an `SATB`, or returns the error code describing why it is invalid.
+    fn to_satb(self) -> Result<SATB, i32> {
+        let pitches = [self.soprano, self.alto, self.tenor, self.bass].map(TwelveEtPitch::to_pitch);
+        let [Some(soprano), Some(alto), Some(tenor), Some(bass)] = pitches else {
+            return Err(TWELVE_ET_ERROR_INVALID_ARGUMENT);
+        };
+        if self.root >= 12 {
+            return Err(TWELVE_ET_ERROR_INVALID_ARGUMENT);
+        }
+        SATB::try_new(self.root, soprano, alto, tenor, bass).map_err(|e| error_code(&e))
+    }
+}
+
+/// Helper function that returns the error code reporting `error`.
+fn error_code(error: &SatbError) -> i32 {
+    match error {
+        SatbError::OutOfRange(_) => TWELVE_ET_ERROR_OUT_OF_RANGE,
+        SatbError::Crossing { .. } => TWELVE_ET_ERROR_CROSSING,
+        SatbError::Spacing { .. } => TWELVE_ET_ERROR_SPACING,
+        SatbError::InvalidHarmony => TWELVE_ET_ERROR_INVALID_HARMONY,
+    }
+}
+
+/// Helper function that calls `f`, returning `fallback` if it panics so the panic does not unwind into the caller.
+fn guard<T>(fallback: T, f: impl FnOnce() -> T + UnwindSafe) -> T {
+    catch_unwind(f).unwrap_or(fallback)
+}
+
+/// Returns the equally tempered frequency in Hz of the pitch with `pitch_class`, from 0 for C to 11 for B, in `octave`, where
+/// A4 is 440 Hz. Returns NaN if `pitch_class` is 12 or more.
+#[no_mangle]
+pub extern "C" fn twelve_et_frequency(pitch_class: u8, octave: u8) -> f64 {
+    guard(f64::NAN, || {
+        TwelveEtPitch {
+            pitch_class,
+            octave,
+        }
+        .to_pitch()
+        .map_or(f64::NAN, |p| p.frequency())
+    })
+}
+
+/// Returns the equally tempered frequency in Hz of the MIDI note `note`, where note 69 is A4 at 440 Hz and note 60 is middle
+/// C, agreeing with `twelve_et_frequency` from note 24, C1, upwards. Returns NaN if `note` is above 127.
+#[no_mangle]
+pub extern "C" fn twelve_et_midi_to_freq(note: u8) -> f64 {
+    guard(f64::NAN, || {
+        if note > 127 {
+            return f64::NAN;
+        }
+        // Notes below C1 are taken down from the octave above, keeping clear of octaves 0 and -1
+        let octaves_up = 2u8.saturating_sub(note / 12);
+        let pitch = Pitch::from_pitch_class(note % 12, note / 12 + octaves_up - 1);
+        pitch.frequency() / (1u32 << octaves_up) as f64
+    })
+}
+
+/// Validates `satb` as a four part harmony, returning `TWELVE_ET_OK` if it is valid, otherwise the error code of the first
+/// rule it breaks, or `TWELVE_ET_ERROR_INVALID_ARGUMENT` if its root or a pitch class is 12 or more.
+#[no_mangle]
+pub extern "C" fn twelve_et_validate_satb(satb: TwelveEtSatb) -> i32 {
+    guard(TWELVE_ET_ERROR_PANIC, || match satb.to_satb() {
+        Ok(_) => TWELVE_ET_OK,
+        Err(code) => code,
+    })
+}
+
+/// Fills the `length` floats of `buffer` with the sound wave of `satb` sampled at `sample_rate`, each voice a sine wave
+/// starting at phase zero and the sum normalized by the number of voices. Returns `TWELVE_ET_OK`, or the error code from
+/// `twelve_et_validate_satb` if the harmony is invalid, or `TWELVE_ET_ERROR_INVALID_ARGUMENT` if `buffer` is null or
+/// `sample_rate` is zero, in which cases the buffer is left untouched.
+///
+/// # Safety
+///
+/// Unless it is null, `buffer` must point to `length` floats that are valid for writes and not accessed by anything else for
+/// the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn twelve_et_render_satb(
+    satb: TwelveEtSatb,
+    sample_rate: u32,
+    buffer: *mut f32,
+    length: usize,
+) -> i32 {
+    if buffer.is_null() || sample_rate == 0 {
+        return TWELVE_ET_ERROR_INVALID_ARGUMENT;
+    }
+    // SAFETY: the caller guarantees `buffer` points to `length` floats valid for writes, and it is not null
+    let buffer = unsafe { std::slice::from_raw_parts_mut(buffer, length) };
+    // A panic can at worst leave the buffer partly filled, which is reported by the error code
+    guard(
+        TWELVE_ET_ERROR_PANIC,
+        AssertUnwindSafe(move || match satb.to_satb() {
+            Ok(satb) => {
+                satb.render_into(buffer, sample_rate, &mut RenderState::default());
+                TWELVE_ET_OK
+            }
+            Err(code) => code,
+        }),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The C major harmony E5, G4, C4, C3 with root C
+    const C_MAJOR: TwelveEtSatb = TwelveEtSatb {
+        root: 0,
+        soprano: TwelveEtPitch {
+            pitch_class: 4,
+            octave: 5,
+        },
+        alto: TwelveEtPitch {
+            pitch_class: 7,
+            octave: 4,
+        },
+        tenor: TwelveEtPitch {
+            pitch_class: 0,
+            octave: 4,
+        },
+        bass: TwelveEtPitch {
+            pitch_class: 0,
+            octave: 3,
+        },
+    };
+
+    #[test]
+    fn test_frequency() {
+        assert_eq!(twelve_et_frequency(9, 4), 440.0);
+        assert_eq!(
+            twelve_et_frequency(0, 4),
+            Pitch::from_pitch_class(0, 4).frequency()
+        );
+        assert!(twelve_et_frequency(12, 4).is_nan());
+
+        assert_eq!(twelve_et_midi_to_freq(69), 440.0);
+        assert_eq!(twelve_et_midi_to_freq(12), twelve_et_frequency(0, 1) / 2.0);
+        assert_eq!(twelve_et_midi_to_freq(9), twelve_et_frequency(9, 1) / 4.0);
+        for note in [24, 60, 61, 127] {
+            let pitch = Pitch::from_pitch_class(note % 12, note / 12 - 1);
+            assert_eq!(twelve_et_midi_to_freq(note), pitch.frequency());
+        }
+        assert!(twelve_et_midi_to_freq(128).is_nan());
+    }
+
+    #[test]
+    fn test_validate_satb() {
+        assert_eq!(twelve_et_validate_satb(C_MAJOR), TWELVE_ET_OK);
+        let out_of_range = TwelveEtSatb {
+            soprano: TwelveEtPitch {
+                pitch_class: 4,
+                octave: 7,
+            },
+            ..C_MAJOR
+        };
+        assert_eq!(
+            twelve_et_validate_satb(out_of_range),
+            TWELVE_ET_ERROR_OUT_OF_RANGE
+        );
+        let incomplete = TwelveEtSatb { root: 2, ..C_MAJOR };
+        assert_eq!(
+            twelve_et_validate_satb(incomplete),
+            TWELVE_ET_ERROR_INVALID_HARMONY
+        );
+        for invalid in [
+            TwelveEtSatb {
+                root: 12,
+                ..C_MAJOR
+            },
+            TwelveEtSatb {
+                bass: TwelveEtPitch {
+                    pitch_class: 255,
+                    octave: 255,
+                },
+                ..C_MAJOR
+            },
+        ] {
+            assert_eq!(
+                twelve_et_validate_satb(invalid),
+                TWELVE_ET_ERROR_INVALID_ARGUMENT
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_satb() {
+        let mut buffer = vec![2.0f32; 64];
+        let code =
+            unsafe { twelve_et_render_satb(C_MAJOR, 8000, buffer.as_mut_ptr(), buffer.len()) };
+        assert_eq!(code, TWELVE_ET_OK);
+        let mut expected = vec![0.0f32; 64];
+        SATB::new(
+            0,
+            (4, 5).into(),
+            (7, 4).into(),
+            (0, 4).into(),
+            (0, 3).into(),
+        )
+        .render_into(&mut expected, 8000, &mut RenderState::default());
+        assert_eq!(buffer, expected);
+
+        // Invalid arguments and harmonies leave the buffer untouched
+        let mut buffer = vec![2.0f32; 8];
+        // The tenor C4 above the alto G3
+        let crossed = TwelveEtSatb {
+            soprano: TwelveEtPitch {
+                pitch_class: 4,
+                octave: 4,
+            },
+            alto: TwelveEtPitch {
+                pitch_class: 7,
+                octave: 3,
+            },
+            ..C_MAJOR
+        };
+        for (satb, sample_rate, code) in [
+            (crossed, 8000, TWELVE_ET_ERROR_CROSSING),
+            (C_MAJOR, 0, TWELVE_ET_ERROR_INVALID_ARGUMENT),
+        ] {
+            let returned =
+                unsafe { twelve_et_render_satb(satb, sample_rate, buffer.as_mut_ptr(), 8) };
+            assert_eq!(returned, code);
+        }
+        assert!(buffer.iter().all(|s| *s == 2.0));
+        let null = unsafe { twelve_et_render_satb(C_MAJOR, 8000, std::ptr::null_mut(), 8) };
+        assert_eq!(null, TWELVE_ET_ERROR_INVALID_ARGUMENT);
+    }
+
+    #[test]
+    fn test_panics_are_caught() {
+        assert_eq!(
+            guard(TWELVE_ET_ERROR_PANIC, || -> i32 { panic!("caught") }),
+            TWELVE_ET_ERROR_PANIC
+        );
+        assert_eq!(guard(TWELVE_ET_ERROR_PANIC, || TWELVE_ET_OK), TWELVE_ET_OK);
+        // The highest octave transposes nothing out of range
+        assert!(twelve_et_frequency(11, 255).is_finite());
+        assert_eq!(
+            twelve_et_validate_satb(TwelveEtSatb {
+                soprano: TwelveEtPitch {
+                    pitch_class: 4,
+                    octave: 255,
+                },
+                ..C_MAJOR
+            }),
+            TWELVE_ET_ERROR_OUT_OF_RANGE
+        );
+    }
+}
@@ -0,0 +1,330 @@
+//! Harmonization of a soprano melody into a `Progression` of diatonic harmonies in a given `Key`.
+use crate::voicing::{connection_cost, enumerate_voicings, inversion_cost, VoicingPreferences};
+use crate::{ChordMember, Key, Pitch, Progression, Voice, SATB};
+use std::fmt::Display;
+
+/// Enumerates the reasons a melody could not be harmonized.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum HarmonizationError {
+    /// The melody has no notes.
+    EmptyMelody,
+    /// The melody note at `index` lies outside of the soprano range.
+    OutOfRange { index: usize },
+    /// No diatonic triad containing the melody note at `index` can be voiced beneath it.
+    NoHarmony { index: usize },
+    /// The melody cannot end on a cadence: the last note cannot be harmonized by the tonic or dominant in root position, or
+    /// the note before a last note of the tonic triad by the dominant or leading tone triad.
+    NoCadence,
+    /// The harmonies cannot be connected without breaking a voice leading rule.
+    NoProgression,
+}
+
+impl Display for HarmonizationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HarmonizationError::EmptyMelody => write!(f, "the melody has no notes"),
+            HarmonizationError::OutOfRange { index } => {
+                write!(f, "melody note {} is outside of the soprano range", index)
+            }
+            HarmonizationError::NoHarmony { index } => {
+                write!(
+                    f,
+                    "no diatonic harmony can be voiced under melody note {}",
+                    index
+                )
+            }
+            HarmonizationError::NoCadence => {
+                write!(f, "the last melody note cannot end on a cadence")
+            }
+            HarmonizationError::NoProgression => write!(
+                f,
+                "the harmonies cannot be connected without voice leading violations"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HarmonizationError {}
+
+/// A candidate harmony for a single melody note, along with the cheapest way of reaching it from the start of the melody.
+struct Candidate {
+    /// The scale degree the harmony is built on
+    degree: u8,
+    /// The voicing of the harmony
+    voicing: SATB,
+    /// The total cost of the cheapest sequence of harmonies ending with this one
+    cost: u32,
+    /// The index of the previous candidate in that sequence
+    back: usize,
+}
+
+/// Helper function that computes the cost of using the triad on `degree`, favoring the primary triads.
+fn degree_cost(degree: u8) -> u32 {
+    match degree {
+        1 | 4 | 5 => 0,
+        2 | 6 => 2,
+        _ => 5,
+    }
+}
+
+/// Helper function that computes the cost of the root progression from the triad on scale degree `from` to the triad on `to`.
+/// Progressions that follow the usual tonic, predominant, dominant order are free.
+fn progression_cost(from: u8, to: u8) -> u32 {
+    let preferred: &[u8] = match from {
+        1 => &[2, 4, 5, 6],
+        2 => &[5, 7],
+        3 => &[4, 6],
+        4 => &[1, 2, 5, 7],
+        5 => &[1, 6],
+        6 => &[2, 4, 5],
+        _ => &[1],
+    };
+    if preferred.contains(&to) {
+        0
+    } else if from == to {
+        3
+    } else {
+        8
+    }
+}
+
+/// Helper function that collects the candidate harmonies of the melody note `note` in `key`. When `degrees` is given only the
+/// triads on those scale degrees are candidates, and only in root position if `root_position` is set.
+fn candidates(
+    key: &Key,
+    note: &Pitch,
+    degrees: Option<&[u8]>,
+    root_position: bool,
+    prefs: &VoicingPreferences,
+) -> Vec<Candidate> {
+    let mut candidates = Vec::new();
+    for degree in 1..=7 {
+        if degrees.is_some_and(|d| !d.contains(&degree)) {
+            continue;
+        }
+        let triad = key.triad_pitch_classes(degree);
+        if !triad.contains(&note.pitch_class()) {
+            continue;
+        }
+        for voicing in enumerate_voicings(triad[0], &triad, Some(note), prefs) {
+            if root_position && voicing.role_of(Voice::Bass) != ChordMember::Root {
+                continue;
+            }
+            candidates.push(Candidate {
+                degree,
+                cost: degree_cost(degree),
+                voicing,
+                back: 0,
+            });
+        }
+    }
+    candidates
+}
+
+/// A function that harmonizes the melody `soprano` in `key`, choosing a diatonic triad containing each melody note, voicing it
+/// beneath the melody and connecting the voicings with the least voice leading distance. Standard root progressions are preferred,
+/// the first harmony prefers the tonic and the melody ends with a cadence: an authentic cadence, the dominant or leading tone triad
+/// resolving to the tonic in root position, when the last note belongs to the tonic triad, otherwise a half cadence on the
+/// dominant in root position. Returns `HarmonizationError::NoCadence` if the melody cannot be harmonized by its cadence. The
+/// returned progression has no parallel fifths or octaves and keeps `soprano` exactly.
+pub fn harmonize_melody(
+    key: &Key,
+    soprano: &[Pitch],
+    prefs: &VoicingPreferences,
+) -> Result<Progression, HarmonizationError> {
+    if soprano.is_empty() {
+        return Err(HarmonizationError::EmptyMelody);
+    }
+    if let Some(index) = soprano.iter().position(|p| !Voice::Soprano.contains(p)) {
+        return Err(HarmonizationError::OutOfRange { index });
+    }
+
+    let last = soprano.len() - 1;
+    let authentic = key
+        .triad_pitch_classes(1)
+        .contains(&soprano[last].pitch_class());
+    let mut layers: Vec<Vec<Candidate>> = Vec::with_capacity(soprano.len());
+    for (index, note) in soprano.iter().enumerate() {
+        let cadence: Option<&[u8]> = if index == last {
+            Some(if authentic { &[1] } else { &[5] })
+        } else if index + 1 == last && authentic {
+            Some(&[5, 7])
+        } else {
+            None
+        };
+        let mut layer = candidates(key, note, cadence, index == last, prefs);
+        if layer.is_empty() {
+            // A note outside of every diatonic triad has no harmony at all, cadential or not
+            return Err(
+                if cadence.is_some() && !candidates(key, note, None, false, prefs).is_empty() {
+                    HarmonizationError::NoCadence
+                } else {
+                    HarmonizationError::NoHarmony { index }
+                },
+            );
+        }
+
+        match layers.last() {
+            None => {
+                // Later harmonies pay for their inversion in `connection_cost`, the first has no connection to pay it in
+                for candidate in layer.iter_mut() {
+                    candidate.cost += inversion_cost(&candidate.voicing);
+                    if candidate.degree != 1 {
+                        candidate.cost += 4;
+                    }
+                }
+            }
+            Some(previous) => {
+                layer = layer
+                    .into_iter()
+                    .filter_map(|mut candidate| {
+                        let (back, cost) = previous
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(i, p)| {
+                                let cost = connection_cost(&p.voicing, &candidate.voicing, prefs)?
+                                    + progression_cost(p.degree, candidate.degree);
+                                Some((i, p.cost + cost))
+                            })
+                            .min_by_key(|(_, cost)| *cost)?;
+                        candidate.cost += cost;
+                        candidate.back = back;
+                        Some(candidate)
+                    })
+                    .collect();
+                if layer.is_empty() {
+                    return Err(HarmonizationError::NoProgression);
+                }
+            }
+        }
+        layers.push(layer);
+    }
+
+    // Trace the cheapest sequence back from the final harmony
+    let mut index = layers[last]
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, c)| c.cost)
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let mut chords = Vec::with_capacity(layers.len());
+    for layer in layers.iter().rev() {
+        chords.push(layer[index].voicing.clone());
+        index = layer[index].back;
+    }
+    Ok(chords.into_iter().rev().collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::KeyMode;
+
+    fn pitches(notes: &[(u8, u8)]) -> Vec<Pitch> {
+        notes.iter().map(|n| Pitch::from(*n)).collect()
+    }
+
+    /// Helper function that returns the first phrase of "Ode to Joy" in G major, ending on the second scale degree.
+    fn ode_to_joy() -> Vec<Pitch> {
+        pitches(&[
+            (11, 4),
+            (11, 4),
+            (0, 5),
+            (2, 5),
+            (2, 5),
+            (0, 5),
+            (11, 4),
+            (9, 4),
+            (7, 4),
+            (7, 4),
+            (9, 4),
+            (11, 4),
+            (11, 4),
+            (9, 4),
+            (9, 4),
+        ])
+    }
+
+    #[test]
+    fn test_harmonize_ode_to_joy() {
+        let melody = ode_to_joy();
+        let key = Key::new(7, KeyMode::Major);
+        let progression = harmonize_melody(&key, &melody, &VoicingPreferences::default()).unwrap();
+
+        assert_eq!(progression.chords().len(), melody.len());
+        assert!(progression.violations().is_empty());
+        for (chord, note) in progression.chords().iter().zip(&melody) {
+            assert_eq!(chord.soprano, *note);
+        }
+        // The phrase ends on A, so with a half cadence on the dominant in root position
+        let last = progression.chords().last().unwrap();
+        assert_eq!(last.root(), 2);
+        assert_eq!(last.role_of(Voice::Bass), ChordMember::Root);
+    }
+
+    #[test]
+    fn test_harmonize_authentic_cadence() {
+        // The first phrase closed on the tonic by the cadence of the second, A resolving to G
+        let mut melody = ode_to_joy();
+        melody.pop();
+        melody.push(Pitch::from((7, 4)));
+        let key = Key::new(7, KeyMode::Major);
+        let progression = harmonize_melody(&key, &melody, &VoicingPreferences::default()).unwrap();
+
+        assert!(progression.violations().is_empty());
+        for (chord, note) in progression.chords().iter().zip(&melody) {
+            assert_eq!(chord.soprano, *note);
+        }
+        let chords = progression.chords();
+        let (dominant, tonic) = (&chords[chords.len() - 2], &chords[chords.len() - 1]);
+        assert_eq!(dominant.root(), 2);
+        assert_eq!(tonic.root(), 7);
+        assert_eq!(tonic.role_of(Voice::Bass), ChordMember::Root);
+    }
+
+    #[test]
+    fn test_harmonize_half_cadence_in_minor() {
+        // A minor melody ending on the second scale degree
+        let melody = pitches(&[(9, 4), (0, 5), (11, 4), (9, 4), (8, 4), (11, 4)]);
+        let key = Key::new(9, KeyMode::Minor);
+        let progression = harmonize_melody(&key, &melody, &VoicingPreferences::default()).unwrap();
+        assert!(progression.violations().is_empty());
+        assert_eq!(progression.chords().last().unwrap().root(), 4);
+    }
+
+    #[test]
+    fn test_harmonize_errors() {
+        let key = Key::new(0, KeyMode::Major);
+        let prefs = VoicingPreferences::default();
+        assert_eq!(
+            harmonize_melody(&key, &[], &prefs),
+            Err(HarmonizationError::EmptyMelody)
+        );
+        assert_eq!(
+            harmonize_melody(&key, &pitches(&[(0, 5), (0, 6)]), &prefs),
+            Err(HarmonizationError::OutOfRange { index: 1 })
+        );
+        // F sharp belongs to no diatonic triad of C major
+        assert_eq!(
+            harmonize_melody(&key, &pitches(&[(6, 4), (0, 5)]), &prefs),
+            Err(HarmonizationError::NoHarmony { index: 0 })
+        );
+        // E cannot be harmonized by the dominant or leading tone triad before the closing tonic
+        assert_eq!(
+            harmonize_melody(&key, &pitches(&[(4, 5), (0, 5)]), &prefs),
+            Err(HarmonizationError::NoCadence)
+        );
+    }
+
+    #[test]
+    fn test_inversion_cost_counted_once() {
+        // A candidate costs only its degree, its inversion is charged by `connection_cost` or, for the first harmony, on its own
+        let key = Key::new(0, KeyMode::Major);
+        let prefs = VoicingPreferences::default();
+        let layer = candidates(&key, &Pitch::from((4, 5)), None, false, &prefs);
+        assert!(layer.iter().any(|c| inversion_cost(&c.voicing) > 0));
+        for candidate in &layer {
+            assert_eq!(candidate.cost, degree_cost(candidate.degree));
+        }
+    }
+}
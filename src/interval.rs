@@ -0,0 +1,75 @@
+//! Types for the intervals between two pitches, measured in equally tempered half steps.
+use core::fmt::Display;
+
+/// Enumerates the simple intervals, from the unison to the octave, each named by its most common spelling.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Interval {
+    Unison,
+    MinorSecond,
+    MajorSecond,
+    MinorThird,
+    MajorThird,
+    PerfectFourth,
+    /// The augmented fourth or diminished fifth, six half steps
+    Tritone,
+    PerfectFifth,
+    MinorSixth,
+    MajorSixth,
+    MinorSeventh,
+    MajorSeventh,
+    Octave,
+}
+
+impl Interval {
+    /// All simple intervals, ordered by size from the unison to the octave.
+    pub const ALL: [Interval; 13] = [
+        Interval::Unison,
+        Interval::MinorSecond,
+        Interval::MajorSecond,
+        Interval::MinorThird,
+        Interval::MajorThird,
+        Interval::PerfectFourth,
+        Interval::Tritone,
+        Interval::PerfectFifth,
+        Interval::MinorSixth,
+        Interval::MajorSixth,
+        Interval::MinorSeventh,
+        Interval::MajorSeventh,
+        Interval::Octave,
+    ];
+
+    /// Method that returns the number of half steps spanned by the interval.
+    pub fn semitones(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Associated method that returns the simple interval spanning `semitones` half steps, or `None` if it is more than an octave.
+    pub fn from_semitones(semitones: u8) -> Option<Interval> {
+        Interval::ALL.get(semitones as usize).copied()
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let name = [
+            "P1", "m2", "M2", "m3", "M3", "P4", "TT", "P5", "m6", "M6", "m7", "M7", "P8",
+        ][*self as usize];
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_intervals() {
+        for (semitones, interval) in Interval::ALL.into_iter().enumerate() {
+            assert_eq!(interval.semitones(), semitones as u8);
+            assert_eq!(Interval::from_semitones(semitones as u8), Some(interval));
+        }
+        assert_eq!(Interval::from_semitones(13), None);
+        assert_eq!(Interval::MinorThird.to_string(), "m3");
+        assert_eq!(Interval::PerfectFifth.to_string(), "P5");
+    }
+}
@@ -0,0 +1,1180 @@
+//! Types for representing a key, i.e. a tonic together with a major or minor mode.
+use crate::scale::Direction;
+use crate::{ChordQuality, Pitch, Scale};
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+/// The note letters sharpened by a key signature, in the order the sharps are added.
+const SHARP_ORDER: [&str; 7] = ["F#", "C#", "G#", "D#", "A#", "E#", "B#"];
+/// The note letters flattened by a key signature, in the order the flats are added.
+const FLAT_ORDER: [&str; 7] = ["Bb", "Eb", "Ab", "Db", "Gb", "Cb", "Fb"];
+/// The note letters in ascending order from C, along with the pitch class of each natural note.
+const LETTERS: [(char, u8); 7] = [
+    ('C', 0),
+    ('D', 2),
+    ('E', 4),
+    ('F', 5),
+    ('G', 7),
+    ('A', 9),
+    ('B', 11),
+];
+
+/// The Krumhansl-Kessler probe tone ratings of each pitch class above the tonic in a major key.
+const MAJOR_PROFILE: [f64; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+/// The Krumhansl-Kessler probe tone ratings of each pitch class above the tonic in a minor key.
+const MINOR_PROFILE: [f64; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+/// Enumerates the modes a `Key` can be in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum KeyMode {
+    Major,
+    Minor,
+}
+
+/// A struct that represents a key, given by the pitch class of its tonic and its mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Key {
+    /// The pitch class of the tonic
+    pub tonic: u8,
+    /// The mode of the key
+    pub mode: KeyMode,
+}
+
+impl Key {
+    /// Associated method for creating a new `Key`, `tonic` is taken modulo 12.
+    pub fn new(tonic: u8, mode: KeyMode) -> Self {
+        Key {
+            tonic: tonic % 12,
+            mode,
+        }
+    }
+
+    /// Method that returns the name of the 1-based scale `degree` of the key, wrapping past the seventh degree. The seventh
+    /// degree is the leading tone in major keys and the subtonic in minor keys, whose natural minor scale places it a whole step
+    /// below the tonic.
+    pub fn degree_name(&self, degree: u8) -> DegreeName {
+        match (degree.max(1) - 1) % 7 {
+            0 => DegreeName::Tonic,
+            1 => DegreeName::Supertonic,
+            2 => DegreeName::Mediant,
+            3 => DegreeName::Subdominant,
+            4 => DegreeName::Dominant,
+            5 => DegreeName::Submediant,
+            _ => match self.mode {
+                KeyMode::Major => DegreeName::LeadingTone,
+                KeyMode::Minor => DegreeName::Subtonic,
+            },
+        }
+    }
+
+    /// Method that returns the 1-based scale degree of the pitch class `pc`, taken modulo 12, or `None` if it is not in the
+    /// scale of the key. In minor keys the raised leading tone, used by the dominant and leading tone harmonies, is also
+    /// degree 7, so both G and G# are degree 7 of A minor.
+    pub fn degree_of(&self, pc: u8) -> Option<u8> {
+        let pc = pc % 12;
+        let degree = (1..=7).find(|d| self.degree(*d) == pc);
+        match self.mode {
+            KeyMode::Minor if pc == (self.tonic + 11) % 12 => Some(7),
+            _ => degree,
+        }
+    }
+
+    /// Method that returns the 1-based scale degree of `p` in the key along with its chromatic alteration in semitones from
+    /// that degree, 0 for pitches in the scale of the key. Chromatic pitches are taken as raised from the degree below, so F#
+    /// in C major is (4, 1) and Eb is (2, 1); in minor keys the raised leading tone is (7, 1). Returns `None` only if the pitch
+    /// lies more than a semitone above every degree, which cannot happen in a major or minor key.
+    pub fn degree_of_pitch(&self, p: &Pitch) -> Option<(u8, i8)> {
+        let pc = p.pitch_class() % 12;
+        [0, 1].into_iter().find_map(|alteration| {
+            let degree = (1..=7).find(|d| (self.degree(*d) + alteration) % 12 == pc)?;
+            Some((degree, alteration as i8))
+        })
+    }
+
+    /// Method that returns the indices of the notes of the melodic `line` that are chromatic in the key. In minor keys the
+    /// raised leading tone of the harmonic minor scale is diatonic, and so is the raised sixth degree when the line ascends
+    /// from it, following the melodic minor scale, so an ascending F#-G#-A in A minor has no chromatic notes while a
+    /// descending A-G#-F# has one, the F#. A note moves in the direction of the next note of the line, or the direction it
+    /// was approached from when the line stops or repeats.
+    pub fn chromatic_notes(&self, line: &[Pitch]) -> Vec<usize> {
+        let harmonic = Scale::harmonic_minor(self.tonic);
+        let melodic = Scale::melodic_minor(self.tonic);
+        let mut direction = Direction::Any;
+        (0..line.len())
+            .filter(|i| {
+                let pc = line[*i].pitch_class();
+                if let Some(next) = line.get(i + 1) {
+                    direction = match next.cmp(&line[*i]) {
+                        Ordering::Greater => Direction::Ascending,
+                        Ordering::Less => Direction::Descending,
+                        Ordering::Equal => direction,
+                    };
+                }
+                let diatonic = match self.mode {
+                    KeyMode::Major => self.scale().contains(pc),
+                    KeyMode::Minor => {
+                        self.scale().contains(pc)
+                            || harmonic.contains(pc)
+                            || melodic.contains_in(pc, direction)
+                    }
+                };
+                !diatonic
+            })
+            .collect()
+    }
+
+    /// Method that returns the pitch in the scale of the key nearest to `p`, `p` itself if it is already in the key. A pitch
+    /// midway between two pitches of the key, e.g. C#4 in C major, snaps down, to C4, unless the lower pitch would lie below
+    /// octave 0. Pitches that are snapped are equally tempered.
+    pub fn snap(&self, p: &Pitch) -> Pitch {
+        if self.scale().contains(p.pitch_class()) {
+            return *p;
+        }
+        (1..=6)
+            .flat_map(|distance| [-distance, distance])
+            .filter_map(|semitones| p.transpose(semitones))
+            .find(|candidate| self.scale().contains(candidate.pitch_class()))
+            .unwrap_or(*p)
+    }
+
+    /// Method that spells the pitch class `pc`, taken modulo 12, in the key, e.g. G# in E major but Ab in Eb major. Pitch
+    /// classes in the scale of the key take the letter of their degree. Chromatic pitch classes in the scale of the parallel
+    /// key take the letter of their degree there, so borrowed tones are lowered in major keys, Eb and Ab in C major, and
+    /// raised in minor keys, C# in A minor. Any other chromatic pitch class is raised from the degree below, as a leading tone
+    /// to the degree above, so F# in C major. See `spell_with` to choose the spelling of chromatic pitch classes.
+    pub fn spell(&self, pc: u8) -> SpelledPitchClass {
+        let pc = pc % 12;
+        let parallel = self.parallel();
+        match (1..=7).find(|d| parallel.degree(*d) == pc) {
+            Some(degree) if !self.scale().contains(pc) => self.spelled_degree(degree, pc),
+            _ => self.spell_with(pc, Spelling::Sharps),
+        }
+    }
+
+    /// Method that spells the pitch class `pc`, taken modulo 12, in the key, raising chromatic pitch classes from the degree
+    /// below for `Spelling::Sharps` and lowering them from the degree above for `Spelling::Flats`, e.g. F# or Gb in C major.
+    /// Pitch classes in the scale of the key take the letter of their degree whatever the spelling.
+    pub fn spell_with(&self, pc: u8, spelling: Spelling) -> SpelledPitchClass {
+        let pc = pc % 12;
+        let degree = (1..=7)
+            .find(|d| self.degree(*d) == pc)
+            .or_else(|| match spelling {
+                Spelling::Sharps => (1..=7).find(|d| (self.degree(*d) + 1) % 12 == pc),
+                Spelling::Flats => (1..=7).find(|d| (self.degree(*d) + 11) % 12 == pc),
+            });
+        // Every chromatic pitch class lies a half step from a degree on either side
+        self.spelled_degree(degree.unwrap_or(1), pc)
+    }
+
+    /// Helper method that spells `pc` with the letter of the 1-based scale `degree` of the key.
+    fn spelled_degree(&self, degree: u8, pc: u8) -> SpelledPitchClass {
+        let (letter, natural) = LETTERS[(self.tonic_letter() + degree as usize - 1) % 7];
+        SpelledPitchClass {
+            letter,
+            accidental: ((pc as i8 - natural as i8 + 18) % 12) - 6,
+        }
+    }
+
+    /// Helper method that returns the index into `LETTERS` of the letter of the tonic, e.g. F for F# major, following the
+    /// key signature.
+    fn tonic_letter(&self) -> usize {
+        let signature = self.signature();
+        let offset = match signature.spelling {
+            Spelling::Sharps => 1,
+            Spelling::Flats => 11,
+        };
+        LETTERS
+            .iter()
+            .position(|(letter, natural)| {
+                let altered = signature.letters().iter().any(|l| l.starts_with(*letter));
+                (natural + if altered { offset } else { 0 }) % 12 == self.tonic
+            })
+            .unwrap_or(0)
+    }
+
+    /// Method that returns the movable do solfège syllable of the pitch class `pc` in the key, naming chromatic pitch classes
+    /// with the lowered syllables, e.g. Me for Eb in C major. See `solfege_with`.
+    pub fn solfege(&self, pc: u8) -> Option<Solfege> {
+        self.solfege_with(pc, ChromaticSyllables::default())
+    }
+
+    /// Method that returns the movable do solfège syllable of the pitch class `pc` in the key, with pitch classes outside of the
+    /// scale named according to `chromatic`. Returns `None` only for a chromatic pitch class when `chromatic` is
+    /// `ChromaticSyllables::Disabled`. Minor keys are sung la based, with do on the tonic of the relative major, so A minor runs
+    /// La, Ti, Do, Re, Mi, Fa, Sol and its raised leading tone, part of the harmonic and melodic minor scales, is Si whatever
+    /// `chromatic` is.
+    pub fn solfege_with(&self, pc: u8, chromatic: ChromaticSyllables) -> Option<Solfege> {
+        let ascending = chromatic == ChromaticSyllables::Ascending;
+        let degree = (pc % 12 + 12 - self.relative_major_tonic()) % 12;
+        if self.mode == KeyMode::Minor && degree == 8 {
+            return Some(Solfege::Si);
+        }
+        let syllable = match degree {
+            0 => Solfege::Do,
+            2 => Solfege::Re,
+            4 => Solfege::Mi,
+            5 => Solfege::Fa,
+            7 => Solfege::Sol,
+            9 => Solfege::La,
+            11 => Solfege::Ti,
+            _ if chromatic == ChromaticSyllables::Disabled => return None,
+            1 if ascending => Solfege::Di,
+            1 => Solfege::Ra,
+            3 if ascending => Solfege::Ri,
+            3 => Solfege::Me,
+            6 if ascending => Solfege::Fi,
+            6 => Solfege::Se,
+            8 if ascending => Solfege::Si,
+            8 => Solfege::Le,
+            _ if ascending => Solfege::Li,
+            _ => Solfege::Te,
+        };
+        Some(syllable)
+    }
+
+    /// Helper method that returns the tonic of the key if it is major, or the tonic of its relative major if it is minor.
+    fn relative_major_tonic(&self) -> u8 {
+        match self.mode {
+            KeyMode::Major => self.tonic,
+            KeyMode::Minor => (self.tonic + 3) % 12,
+        }
+    }
+
+    /// Method that returns the scale of the key, the major scale for major keys and the natural minor scale for minor keys.
+    pub fn scale(&self) -> Scale {
+        match self.mode {
+            KeyMode::Major => Scale::major(self.tonic),
+            KeyMode::Minor => Scale::natural_minor(self.tonic),
+        }
+    }
+
+    /// Associated method that ranks all 24 major and minor keys by how well they fit the pitch class content `classes`, taken
+    /// modulo 12, where repeated pitch classes weigh more, e.g. the pitch classes of every note of a progression or melody.
+    /// Keys are scored with the Krumhansl-Schmuckler algorithm: the Pearson correlation, from -1 to 1, between the count of each
+    /// pitch class and the Krumhansl-Kessler key profile of the key. The keys are returned from the best fit to the worst, with
+    /// ties in key order, major before minor and by tonic. Content without variation, e.g. no pitch classes, scores 0 for every key.
+    pub fn infer(classes: &[u8]) -> Vec<(Key, f64)> {
+        let mut counts = [0.0; 12];
+        for pc in classes {
+            counts[(pc % 12) as usize] += 1.0;
+        }
+        let mut ranked: Vec<(Key, f64)> = [KeyMode::Major, KeyMode::Minor]
+            .into_iter()
+            .flat_map(|mode| (0..12).map(move |tonic| Key::new(tonic, mode)))
+            .map(|key| {
+                let profile = match key.mode {
+                    KeyMode::Major => &MAJOR_PROFILE,
+                    KeyMode::Minor => &MINOR_PROFILE,
+                };
+                // The profile rotated so each entry lines up with the pitch class it rates
+                let rotated: Vec<f64> = (0..12)
+                    .map(|pc| profile[(pc + 12 - key.tonic as usize) % 12])
+                    .collect();
+                (key, correlation(&counts, &rotated))
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked
+    }
+
+    /// Method that returns the pitch class of the 1-based scale `degree` of the key, wrapping past the seventh degree.
+    /// Minor keys use the natural minor scale.
+    pub fn degree(&self, degree: u8) -> u8 {
+        self.scale().degree(degree)
+    }
+
+    /// Method that returns the pitch classes of the diatonic triad built on the 1-based scale `degree`, ordered root, third, fifth.
+    /// In minor keys the leading tone is raised in the triads built on the fifth and seventh degrees, following the harmonic minor scale.
+    pub fn triad_pitch_classes(&self, degree: u8) -> [u8; 3] {
+        let mut triad = self.scale().triad(degree);
+        let raises_leading_tone = matches!((degree.max(1) - 1) % 7, 4 | 6);
+        if self.mode == KeyMode::Minor && raises_leading_tone {
+            let subtonic = self.degree(7);
+            for pc in triad.iter_mut().filter(|pc| **pc == subtonic) {
+                *pc = (*pc + 1) % 12;
+            }
+        }
+        triad
+    }
+
+    /// Method that returns the pitch classes of the diatonic seventh chord built on the 1-based scale `degree`, ordered root,
+    /// third, fifth, seventh. As for triads, minor keys raise the leading tone in the chords built on the fifth and seventh degrees.
+    /// Degrees wrap past the seventh and a degree of 0 is treated as 1.
+    pub fn seventh_pitch_classes(&self, degree: u8) -> [u8; 4] {
+        let degree = (degree.max(1) - 1) % 7 + 1;
+        let [root, third, fifth] = self.triad_pitch_classes(degree);
+        [root, third, fifth, self.degree(degree + 6)]
+    }
+
+    /// Method that returns the root and quality of the diatonic triad built on the 1-based scale `degree`, e.g. a diminished
+    /// triad on the seventh degree of a major key, ready to be voiced by `auto_voice`. In minor keys the triads on the fifth and
+    /// seventh degrees take the leading tone of the harmonic minor scale, so they are major and diminished.
+    pub fn triad(&self, degree: u8) -> (u8, ChordQuality) {
+        let triad = self.triad_pitch_classes(degree);
+        (triad[0], quality_of(&triad))
+    }
+
+    /// Method that returns the root and quality of the triad built on the 1-based scale `degree` from the form of the minor
+    /// scale given by `variant`, e.g. VII, the major triad on the natural subtonic, for `MinorVariant::Natural` on the seventh
+    /// degree of a minor key, or III+ for `MinorVariant::Harmonic` on the third. `MinorVariant::Auto` gives `triad`, as do
+    /// major keys whatever the variant.
+    pub fn triad_with(&self, degree: u8, variant: MinorVariant) -> (u8, ChordQuality) {
+        match self.variant_scale(variant) {
+            Some(scale) => {
+                let triad = scale.triad(degree);
+                (triad[0], quality_of(&triad))
+            }
+            None => self.triad(degree),
+        }
+    }
+
+    /// Method that returns the root and quality of the seventh chord built on the 1-based scale `degree` from the form of the
+    /// minor scale given by `variant`, as for `triad_with`. `MinorVariant::Auto` gives `seventh`, as do major keys.
+    pub fn seventh_with(&self, degree: u8, variant: MinorVariant) -> (u8, ChordQuality) {
+        match self.variant_scale(variant) {
+            Some(scale) => {
+                let degree = (degree.max(1) - 1) % 7 + 1;
+                let [root, third, fifth] = scale.triad(degree);
+                let seventh = [root, third, fifth, scale.degree(degree + 6)];
+                (root, quality_of(&seventh))
+            }
+            None => self.seventh(degree),
+        }
+    }
+
+    /// Helper method that returns the form of the minor scale given by `variant`, or `None` for major keys and
+    /// `MinorVariant::Auto`.
+    pub(crate) fn variant_scale(&self, variant: MinorVariant) -> Option<Scale> {
+        match (self.mode, variant) {
+            (KeyMode::Major, _) | (_, MinorVariant::Auto) => None,
+            (KeyMode::Minor, MinorVariant::Natural) => Some(Scale::natural_minor(self.tonic)),
+            (KeyMode::Minor, MinorVariant::Harmonic) => Some(Scale::harmonic_minor(self.tonic)),
+            (KeyMode::Minor, MinorVariant::Melodic) => Some(Scale::melodic_minor(self.tonic)),
+        }
+    }
+
+    /// Method that returns the key signature of the key, that of its relative major for minor keys, spelled with whichever of
+    /// sharps or flats needs fewer accidentals, e.g. 2 sharps for D major and 3 flats for Eb major. Keys needing six either way,
+    /// F# or Gb major and D# or Eb minor, are spelled with sharps, see `signature_spelled` to choose.
+    pub fn signature(&self) -> KeySignature {
+        if self.sharps() <= 6 {
+            self.signature_spelled(Spelling::Sharps)
+        } else {
+            self.signature_spelled(Spelling::Flats)
+        }
+    }
+
+    /// Method that returns the key signature of the key spelled with `spelling` where the key has an enharmonic spelling
+    /// needing at most seven accidentals of that kind, e.g. Gb major rather than F# major, otherwise spelled as it must be.
+    pub fn signature_spelled(&self, spelling: Spelling) -> KeySignature {
+        let sharps = self.sharps();
+        let flats = (12 - sharps) % 12;
+        match spelling {
+            Spelling::Sharps if sharps <= 7 => KeySignature {
+                count: sharps,
+                spelling: Spelling::Sharps,
+            },
+            Spelling::Flats if flats <= 7 => KeySignature {
+                count: flats,
+                spelling: if flats == 0 {
+                    Spelling::Sharps
+                } else {
+                    Spelling::Flats
+                },
+            },
+            Spelling::Sharps => KeySignature {
+                count: flats,
+                spelling: Spelling::Flats,
+            },
+            Spelling::Flats => KeySignature {
+                count: sharps,
+                spelling: Spelling::Sharps,
+            },
+        }
+    }
+
+    /// Method that returns the root and quality of the triad on the 1-based scale `degree` of the parallel key, a chord borrowed
+    /// by mode mixture, e.g. iv, ♭VI and ♭VII in a major key. Borrowed chords are stacked from the natural minor scale, so
+    /// unlike `triad` the leading tone is not raised.
+    pub fn borrowed(&self, degree: u8) -> (u8, ChordQuality) {
+        let triad = self.parallel().scale().triad(degree);
+        (triad[0], quality_of(&triad))
+    }
+
+    /// Method that returns the root and quality of the secondary dominant of the 1-based scale degree `of_degree`, V/x, the
+    /// major triad a fifth above the root of the triad on that degree, e.g. a D major triad for V/V in C major.
+    /// Returns `TonicizationError::DiminishedTarget` if the triad on the degree is diminished.
+    pub fn secondary_dominant(
+        &self,
+        of_degree: u8,
+    ) -> Result<(u8, ChordQuality), TonicizationError> {
+        let target = self.tonicized_root(of_degree)?;
+        Ok(((target + 7) % 12, ChordQuality::Major))
+    }
+
+    /// Method that returns the root and quality of the secondary dominant seventh of the 1-based scale degree `of_degree`,
+    /// V7/x, the dominant seventh chord a fifth above the root of the triad on that degree.
+    /// Returns `TonicizationError::DiminishedTarget` if the triad on the degree is diminished.
+    pub fn secondary_dominant_seventh(
+        &self,
+        of_degree: u8,
+    ) -> Result<(u8, ChordQuality), TonicizationError> {
+        let target = self.tonicized_root(of_degree)?;
+        Ok(((target + 7) % 12, ChordQuality::Dominant7))
+    }
+
+    /// Method that returns the root and quality of the secondary leading tone triad of the 1-based scale degree `of_degree`,
+    /// vii°/x, the diminished triad a half step below the root of the triad on that degree.
+    /// Returns `TonicizationError::DiminishedTarget` if the triad on the degree is diminished.
+    pub fn secondary_leading_tone(
+        &self,
+        of_degree: u8,
+    ) -> Result<(u8, ChordQuality), TonicizationError> {
+        let target = self.tonicized_root(of_degree)?;
+        Ok(((target + 11) % 12, ChordQuality::Diminished))
+    }
+
+    /// Helper method that returns the root of the triad on the 1-based scale degree `degree`, or an error if the triad is
+    /// diminished and so cannot be tonicized.
+    fn tonicized_root(&self, degree: u8) -> Result<u8, TonicizationError> {
+        match self.triad(degree) {
+            (_, ChordQuality::Diminished) => Err(TonicizationError::DiminishedTarget { degree }),
+            (root, _) => Ok(root),
+        }
+    }
+
+    /// Method that returns the relative key, sharing the key signature in the other mode, e.g. A minor for C major and C major
+    /// for A minor.
+    pub fn relative(&self) -> Key {
+        match self.mode {
+            KeyMode::Major => Key::new(self.tonic + 9, KeyMode::Minor),
+            KeyMode::Minor => Key::new(self.tonic + 3, KeyMode::Major),
+        }
+    }
+
+    /// Method that returns the parallel key, sharing the tonic in the other mode, e.g. C minor for C major.
+    pub fn parallel(&self) -> Key {
+        match self.mode {
+            KeyMode::Major => Key::new(self.tonic, KeyMode::Minor),
+            KeyMode::Minor => Key::new(self.tonic, KeyMode::Major),
+        }
+    }
+
+    /// Method that returns the dominant key, the key of the same mode on the fifth degree, the same as `next_sharpward`.
+    pub fn dominant(&self) -> Key {
+        self.next_sharpward()
+    }
+
+    /// Method that returns the subdominant key, the key of the same mode on the fourth degree, the same as `next_flatward`.
+    pub fn subdominant(&self) -> Key {
+        self.next_flatward()
+    }
+
+    /// Method that returns the key of the same mode a fifth above, one step clockwise around the circle of fifths, adding a sharp.
+    pub fn next_sharpward(&self) -> Key {
+        Key::new(self.tonic + 7, self.mode)
+    }
+
+    /// Method that returns the key of the same mode a fifth below, one step anticlockwise around the circle of fifths, adding a flat.
+    pub fn next_flatward(&self) -> Key {
+        Key::new(self.tonic + 5, self.mode)
+    }
+
+    /// Method that returns the fewest steps around the circle of fifths between the key signatures of the key and `other`, from
+    /// 0 to 6. Relative keys, e.g. C major and A minor, share a signature so are 0 steps apart.
+    pub fn fifths_distance(&self, other: &Key) -> u8 {
+        let steps = (self.sharps() + 12 - other.sharps()) % 12;
+        steps.min(12 - steps)
+    }
+
+    /// Method that returns an iterator over the twelve keys of the same mode around the circle of fifths, starting with the
+    /// key and stepping sharpward.
+    pub fn circle_of_fifths(&self) -> impl Iterator<Item = Key> {
+        std::iter::successors(Some(*self), |key| Some(key.next_sharpward())).take(12)
+    }
+
+    /// Helper method that returns the number of sharps of the key spelled with sharps alone, from 0 to 11, its number of steps
+    /// clockwise around the circle of fifths from C major, or A minor.
+    fn sharps(&self) -> u8 {
+        self.relative_major_tonic() * 7 % 12
+    }
+
+    /// Method that returns the root and quality of the diatonic seventh chord built on the 1-based scale `degree`, e.g. a
+    /// dominant seventh on the fifth degree. In minor keys the leading tone is raised as for `triad`, giving a dominant seventh
+    /// on the fifth degree and a fully diminished seventh on the seventh degree.
+    pub fn seventh(&self, degree: u8) -> (u8, ChordQuality) {
+        let seventh = self.seventh_pitch_classes(degree);
+        (seventh[0], quality_of(&seventh))
+    }
+}
+
+/// Helper function that computes the Pearson correlation between `a` and `b`, or 0 if either does not vary.
+fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    let mean = |x: &[f64]| x.iter().sum::<f64>() / x.len() as f64;
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    let (mut covariance, mut variance_a, mut variance_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        covariance += (x - mean_a) * (y - mean_b);
+        variance_a += (x - mean_a).powi(2);
+        variance_b += (y - mean_b).powi(2);
+    }
+    if variance_a == 0.0 || variance_b == 0.0 {
+        return 0.0;
+    }
+    covariance / (variance_a * variance_b).sqrt()
+}
+
+/// Enumerates the names of the scale degrees of a key.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DegreeName {
+    Tonic,
+    Supertonic,
+    Mediant,
+    Subdominant,
+    Dominant,
+    Submediant,
+    /// The seventh degree a half step below the tonic
+    LeadingTone,
+    /// The seventh degree a whole step below the tonic
+    Subtonic,
+}
+
+impl Display for DegreeName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DegreeName::Tonic => "tonic",
+            DegreeName::Supertonic => "supertonic",
+            DegreeName::Mediant => "mediant",
+            DegreeName::Subdominant => "subdominant",
+            DegreeName::Dominant => "dominant",
+            DegreeName::Submediant => "submediant",
+            DegreeName::LeadingTone => "leading tone",
+            DegreeName::Subtonic => "subtonic",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Enumerates the syllables of movable do solfège, the seven diatonic syllables along with the raised and lowered
+/// chromatic syllables.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Solfege {
+    Do,
+    Di,
+    Ra,
+    Re,
+    Ri,
+    Me,
+    Mi,
+    Fa,
+    Fi,
+    Se,
+    Sol,
+    Si,
+    Le,
+    La,
+    Li,
+    Te,
+    Ti,
+}
+
+impl Display for Solfege {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Solfege::Do => "Do",
+            Solfege::Di => "Di",
+            Solfege::Ra => "Ra",
+            Solfege::Re => "Re",
+            Solfege::Ri => "Ri",
+            Solfege::Me => "Me",
+            Solfege::Mi => "Mi",
+            Solfege::Fa => "Fa",
+            Solfege::Fi => "Fi",
+            Solfege::Se => "Se",
+            Solfege::Sol => "Sol",
+            Solfege::Si => "Si",
+            Solfege::Le => "Le",
+            Solfege::La => "La",
+            Solfege::Li => "Li",
+            Solfege::Te => "Te",
+            Solfege::Ti => "Ti",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Enumerates how pitch classes outside of the scale are named by `Key::solfege_with`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ChromaticSyllables {
+    /// As raised diatonic syllables, Di, Ri, Fi, Si and Li, as sung in ascending chromatic lines
+    Ascending,
+    /// As lowered diatonic syllables, Ra, Me, Se, Le and Te, as sung in descending chromatic lines
+    #[default]
+    Descending,
+    /// Not at all, only the pitch classes of the scale are named
+    Disabled,
+}
+
+/// Enumerates the forms of the minor scale harmonies of a minor key may be built from, see `Key::triad_with`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum MinorVariant {
+    /// The natural minor scale, with the subtonic, e.g. v and VII
+    Natural,
+    /// The harmonic minor scale, with the leading tone raised throughout, e.g. III+, V and vii°
+    Harmonic,
+    /// The ascending melodic minor scale, with the sixth and seventh degrees raised, e.g. IV and vi°
+    Melodic,
+    /// The leading tone raised only in the dominant and leading tone harmonies, as `Key::triad`, and when labelling harmonies
+    /// whichever form of the scale the harmony belongs to
+    #[default]
+    Auto,
+}
+
+/// Enumerates the reasons a scale degree cannot be tonicized by a secondary harmony.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TonicizationError {
+    /// The triad on the degree is diminished, so it cannot act as a temporary tonic.
+    DiminishedTarget { degree: u8 },
+}
+
+impl Display for TonicizationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TonicizationError::DiminishedTarget { degree } => write!(
+                f,
+                "the triad on degree {} is diminished and cannot be tonicized",
+                degree
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TonicizationError {}
+
+/// Enumerates the accidentals a key signature may be spelled with, choosing between enharmonic keys such as F# and Gb major.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Spelling {
+    Sharps,
+    Flats,
+}
+
+/// Enumerates the symbols accidentals may be written with, see `SpelledPitchClass::name`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AccidentalStyle {
+    /// `#` for a sharp and `b` for a flat, repeated for double accidentals, e.g. F## or Bbb
+    Ascii,
+    /// `♯` for a sharp, `♭` for a flat, `𝄪` for a double sharp and `𝄫` for a double flat, e.g. F𝄪 or B𝄫
+    Unicode,
+}
+
+/// A pitch class spelled with a note letter and an accidental, e.g. F# or Bb.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SpelledPitchClass {
+    /// The note letter, from A to G
+    pub letter: char,
+    /// The semitones the natural note of the letter is raised, or lowered when negative, e.g. 1 for a sharp and -2 for a double flat
+    pub accidental: i8,
+}
+
+impl SpelledPitchClass {
+    /// Method that returns the pitch class of the spelled note.
+    pub fn pitch_class(&self) -> u8 {
+        let natural = LETTERS
+            .iter()
+            .find(|(letter, _)| *letter == self.letter)
+            .map_or(0, |(_, natural)| *natural);
+        (natural as i8 + self.accidental).rem_euclid(12) as u8
+    }
+
+    /// Method that names the spelled note with its accidental written in `style`, e.g. "F##" or "F𝄪". In the Unicode style
+    /// accidentals beyond a double sharp or double flat are written with a single sharp or flat before the double ones,
+    /// e.g. "B♯𝄪". The `Display` implementation writes the ASCII style, or the Unicode style with the alternate flag, `{:#}`.
+    pub fn name(&self, style: AccidentalStyle) -> String {
+        let count = self.accidental.unsigned_abs() as usize;
+        let accidental = match (style, self.accidental < 0) {
+            (AccidentalStyle::Ascii, false) => "#".repeat(count),
+            (AccidentalStyle::Ascii, true) => "b".repeat(count),
+            (AccidentalStyle::Unicode, false) => "♯".repeat(count % 2) + &"𝄪".repeat(count / 2),
+            (AccidentalStyle::Unicode, true) => "♭".repeat(count % 2) + &"𝄫".repeat(count / 2),
+        };
+        format!("{}{}", self.letter, accidental)
+    }
+
+    /// Associated method for parsing a spelled note, a letter from A to G followed by any number of accidentals written in
+    /// either style of `AccidentalStyle`, or `x` for a double sharp, mixed freely, e.g. "F#", "F♯", "Fx", "F𝄪", "Bbb" or
+    /// "B♭𝄫". Returns `None` if `s` is not exactly such a note name.
+    pub fn parse(s: &str) -> Option<SpelledPitchClass> {
+        match SpelledPitchClass::parse_prefix(s)? {
+            (spelled, "") => Some(spelled),
+            _ => None,
+        }
+    }
+
+    /// Helper method that parses the spelled note at the start of `s`, as for `parse`, returning it along with the rest of
+    /// `s`, or `None` if `s` does not start with a note name or its accidentals alter the letter by more than an `i8` holds.
+    pub(crate) fn parse_prefix(s: &str) -> Option<(SpelledPitchClass, &str)> {
+        let letter = s.chars().next()?;
+        if !LETTERS.iter().any(|(l, _)| *l == letter) {
+            return None;
+        }
+        let mut rest = &s[letter.len_utf8()..];
+        let mut accidental: i8 = 0;
+        while let Some(c) = rest.chars().next() {
+            let alteration = match c {
+                '#' | '♯' => 1,
+                'x' | '𝄪' => 2,
+                'b' | '♭' => -1,
+                '𝄫' => -2,
+                _ => break,
+            };
+            accidental = accidental.checked_add(alteration)?;
+            rest = &rest[c.len_utf8()..];
+        }
+        Some((SpelledPitchClass { letter, accidental }, rest))
+    }
+}
+
+impl Display for SpelledPitchClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let style = if f.alternate() {
+            AccidentalStyle::Unicode
+        } else {
+            AccidentalStyle::Ascii
+        };
+        write!(f, "{}", self.name(style))
+    }
+}
+
+/// The key signature of a `Key`, the number of sharps or flats it carries.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct KeySignature {
+    /// The number of accidentals, from 0 to 7
+    pub count: u8,
+    /// Whether the accidentals are sharps or flats. A signature without accidentals is spelled with sharps.
+    pub spelling: Spelling,
+}
+
+impl KeySignature {
+    /// Method that returns the note letters altered by the signature, in the order the accidentals are written,
+    /// e.g. F#, C#, G# for three sharps or Bb, Eb for two flats.
+    pub fn letters(&self) -> &'static [&'static str] {
+        let count = self.count.min(7) as usize;
+        match self.spelling {
+            Spelling::Sharps => &SHARP_ORDER[..count],
+            Spelling::Flats => &FLAT_ORDER[..count],
+        }
+    }
+}
+
+impl Display for KeySignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.count == 0 {
+            return write!(f, "no sharps or flats");
+        }
+        let accidental = match (self.spelling, self.count) {
+            (Spelling::Sharps, 1) => "sharp",
+            (Spelling::Sharps, _) => "sharps",
+            (Spelling::Flats, 1) => "flat",
+            (Spelling::Flats, _) => "flats",
+        };
+        write!(
+            f,
+            "{} {} ({})",
+            self.count,
+            accidental,
+            self.letters().join(", ")
+        )
+    }
+}
+
+/// Helper function that names the quality of the diatonic chord `pitch_classes`, ordered from its root up.
+fn quality_of(pitch_classes: &[u8]) -> ChordQuality {
+    // Every stack of diatonic thirds, with or without a raised leading tone, is one of the named qualities
+    ChordQuality::from_pitch_classes(pitch_classes[0], pitch_classes).unwrap_or(ChordQuality::Major)
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let tonic = match self.tonic {
+            0 => "C",
+            1 => "C#/Db",
+            2 => "D",
+            3 => "D#/Eb",
+            4 => "E",
+            5 => "F",
+            6 => "F#/Gb",
+            7 => "G",
+            8 => "G#/Ab",
+            9 => "A",
+            10 => "A#/Bb",
+            _ => "B",
+        };
+        let mode = match self.mode {
+            KeyMode::Major => "major",
+            KeyMode::Minor => "minor",
+        };
+        write!(f, "{} {}", tonic, mode)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_triad_pitch_classes() {
+        let c_major = Key::new(0, KeyMode::Major);
+        assert_eq!(c_major.triad_pitch_classes(1), [0, 4, 7]);
+        assert_eq!(c_major.triad_pitch_classes(5), [7, 11, 2]);
+        assert_eq!(c_major.triad_pitch_classes(7), [11, 2, 5]);
+
+        let a_minor = Key::new(9, KeyMode::Minor);
+        assert_eq!(a_minor.triad_pitch_classes(1), [9, 0, 4]);
+        assert_eq!(a_minor.triad_pitch_classes(5), [4, 8, 11]);
+        assert_eq!(a_minor.triad_pitch_classes(7), [8, 11, 2]);
+        assert_eq!(a_minor.triad_pitch_classes(3), [0, 4, 7]);
+    }
+
+    #[test]
+    fn test_diatonic_qualities() {
+        use ChordQuality::*;
+        let c_major = Key::new(0, KeyMode::Major);
+        let triads: Vec<(u8, ChordQuality)> = (1..=7).map(|d| c_major.triad(d)).collect();
+        assert_eq!(
+            triads,
+            [
+                (0, Major),
+                (2, Minor),
+                (4, Minor),
+                (5, Major),
+                (7, Major),
+                (9, Minor),
+                (11, Diminished)
+            ]
+        );
+        let sevenths: Vec<(u8, ChordQuality)> = (1..=7).map(|d| c_major.seventh(d)).collect();
+        // Degrees wrap past the seventh, without overflowing, and 0 is the first degree
+        assert_eq!(c_major.seventh_pitch_classes(0), [0, 4, 7, 11]);
+        assert_eq!(c_major.seventh_pitch_classes(8), [0, 4, 7, 11]);
+        assert_eq!(
+            c_major.seventh_pitch_classes(255),
+            c_major.seventh_pitch_classes(3)
+        );
+        assert_eq!(
+            Key::new(9, KeyMode::Minor).seventh_with(250, MinorVariant::Natural),
+            Key::new(9, KeyMode::Minor).seventh_with(5, MinorVariant::Natural)
+        );
+        assert_eq!(
+            sevenths,
+            [
+                (0, Major7),
+                (2, Minor7),
+                (4, Minor7),
+                (5, Major7),
+                (7, Dominant7),
+                (9, Minor7),
+                (11, HalfDiminished7)
+            ]
+        );
+
+        let a_minor = Key::new(9, KeyMode::Minor);
+        let triads: Vec<(u8, ChordQuality)> = (1..=7).map(|d| a_minor.triad(d)).collect();
+        assert_eq!(
+            triads,
+            [
+                (9, Minor),
+                (11, Diminished),
+                (0, Major),
+                (2, Minor),
+                (4, Major),
+                (5, Major),
+                (8, Diminished)
+            ]
+        );
+        let sevenths: Vec<(u8, ChordQuality)> = (1..=7).map(|d| a_minor.seventh(d)).collect();
+        assert_eq!(
+            sevenths,
+            [
+                (9, Minor7),
+                (11, HalfDiminished7),
+                (0, Major7),
+                (2, Minor7),
+                (4, Dominant7),
+                (5, Major7),
+                (8, Diminished7)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_key_signature() {
+        let d_major = Key::new(2, KeyMode::Major).signature();
+        assert_eq!(d_major.count, 2);
+        assert_eq!(d_major.spelling, Spelling::Sharps);
+        assert_eq!(d_major.letters(), ["F#", "C#"]);
+
+        let e_flat_major = Key::new(3, KeyMode::Major).signature();
+        assert_eq!(e_flat_major.to_string(), "3 flats (Bb, Eb, Ab)");
+        assert_eq!(
+            Key::new(6, KeyMode::Minor).signature().to_string(),
+            "3 sharps (F#, C#, G#)"
+        );
+        assert_eq!(
+            Key::new(9, KeyMode::Minor).signature().to_string(),
+            "no sharps or flats"
+        );
+        assert_eq!(
+            Key::new(5, KeyMode::Major).signature().to_string(),
+            "1 flat (Bb)"
+        );
+
+        // F# major and Gb major are the same key spelled either way, while C# major needs seven sharps
+        let f_sharp = Key::new(6, KeyMode::Major);
+        assert_eq!(
+            f_sharp.signature().to_string(),
+            "6 sharps (F#, C#, G#, D#, A#, E#)"
+        );
+        assert_eq!(
+            f_sharp.signature_spelled(Spelling::Flats).to_string(),
+            "6 flats (Bb, Eb, Ab, Db, Gb, Cb)"
+        );
+        let c_sharp = Key::new(1, KeyMode::Major);
+        assert_eq!(c_sharp.signature().count, 5);
+        assert_eq!(c_sharp.signature_spelled(Spelling::Sharps).count, 7);
+        assert_eq!(
+            Key::new(0, KeyMode::Major).signature_spelled(Spelling::Flats),
+            Key::new(0, KeyMode::Major).signature()
+        );
+    }
+
+    #[test]
+    fn test_circle_of_fifths() {
+        let c_major = Key::new(0, KeyMode::Major);
+        assert_eq!(c_major.next_sharpward(), Key::new(7, KeyMode::Major));
+        assert_eq!(c_major.next_flatward(), Key::new(5, KeyMode::Major));
+        assert_eq!(c_major.fifths_distance(&Key::new(7, KeyMode::Major)), 1);
+        assert_eq!(c_major.fifths_distance(&Key::new(6, KeyMode::Major)), 6);
+        assert_eq!(c_major.fifths_distance(&Key::new(3, KeyMode::Major)), 3);
+        assert_eq!(c_major.fifths_distance(&Key::new(9, KeyMode::Minor)), 0);
+
+        let circle: Vec<Key> = Key::new(2, KeyMode::Minor).circle_of_fifths().collect();
+        assert_eq!(circle.len(), 12);
+        let tonics: HashSet<u8> = circle.iter().map(|k| k.tonic).collect();
+        assert_eq!(tonics.len(), 12);
+        assert_eq!(circle[1], Key::new(9, KeyMode::Minor));
+        assert_eq!(circle[11].next_sharpward(), circle[0]);
+    }
+
+    #[test]
+    fn test_related_keys() {
+        let c_major = Key::new(0, KeyMode::Major);
+        let a_minor = Key::new(9, KeyMode::Minor);
+        assert_eq!(c_major.relative(), a_minor);
+        assert_eq!(a_minor.relative(), c_major);
+        assert_eq!(c_major.parallel(), Key::new(0, KeyMode::Minor));
+        assert_eq!(a_minor.parallel(), Key::new(9, KeyMode::Major));
+        for key in [c_major, a_minor, Key::new(3, KeyMode::Minor)] {
+            assert_eq!(key.relative().relative(), key);
+            assert_eq!(key.parallel().parallel(), key);
+            assert_eq!(key.relative().signature(), key.signature());
+        }
+        assert_eq!(c_major.dominant(), Key::new(7, KeyMode::Major));
+        assert_eq!(c_major.subdominant(), Key::new(5, KeyMode::Major));
+        assert_eq!(a_minor.dominant(), Key::new(4, KeyMode::Minor));
+    }
+
+    #[test]
+    fn test_infer_key() {
+        let c_major_scale = [0, 2, 4, 5, 7, 9, 11];
+        let ranked = Key::infer(&c_major_scale);
+        assert_eq!(ranked.len(), 24);
+        assert_eq!(ranked[0].0, Key::new(0, KeyMode::Major));
+        assert!(ranked.windows(2).all(|w| w[0].1 >= w[1].1));
+
+        // The raised leading tone of A harmonic minor points away from the relative major
+        let a_harmonic_minor = [9, 11, 0, 2, 4, 5, 8];
+        let ranked = Key::infer(&a_harmonic_minor);
+        let position = |key: Key| ranked.iter().position(|(k, _)| *k == key).unwrap();
+        assert_eq!(ranked[0].0, Key::new(9, KeyMode::Minor));
+        assert!(position(Key::new(9, KeyMode::Minor)) < position(Key::new(0, KeyMode::Major)));
+
+        assert!(Key::infer(&[]).iter().all(|(_, score)| *score == 0.0));
+    }
+
+    #[test]
+    fn test_degree_names() {
+        let c_major = Key::new(0, KeyMode::Major);
+        let a_minor = Key::new(9, KeyMode::Minor);
+        assert_eq!(c_major.degree_name(1), DegreeName::Tonic);
+        assert_eq!(c_major.degree_name(5), DegreeName::Dominant);
+        assert_eq!(c_major.degree_name(7), DegreeName::LeadingTone);
+        assert_eq!(a_minor.degree_name(7), DegreeName::Subtonic);
+        assert_eq!(a_minor.degree_name(6).to_string(), "submediant");
+        assert_eq!(c_major.degree_name(11), DegreeName::Subdominant);
+
+        assert_eq!(c_major.degree_of(7), Some(5));
+        assert_eq!(c_major.degree_of(11), Some(7));
+        assert_eq!(c_major.degree_of(6), None);
+        assert_eq!(a_minor.degree_of(0), Some(3));
+        assert_eq!(a_minor.degree_of(7), Some(7));
+        assert_eq!(a_minor.degree_of(8), Some(7));
+        assert_eq!(a_minor.degree_of(6), None);
+    }
+
+    #[test]
+    fn test_chromatic_notes() {
+        let a_minor = Key::new(9, KeyMode::Minor);
+        let ascending: Vec<Pitch> = [(6, 4), (8, 4), (9, 4)].map(Pitch::from).to_vec();
+        assert!(a_minor.chromatic_notes(&ascending).is_empty());
+        let descending: Vec<Pitch> = ascending.iter().rev().copied().collect();
+        assert_eq!(a_minor.chromatic_notes(&descending), [2]);
+        let natural: Vec<Pitch> = [(9, 4), (7, 4), (5, 4)].map(Pitch::from).to_vec();
+        assert!(a_minor.chromatic_notes(&natural).is_empty());
+
+        let c_major = Key::new(0, KeyMode::Major);
+        let line: Vec<Pitch> = [(7, 4), (6, 4), (7, 4)].map(Pitch::from).to_vec();
+        assert_eq!(c_major.chromatic_notes(&line), [1]);
+    }
+
+    #[test]
+    fn test_spelling() {
+        assert_eq!(Key::new(4, KeyMode::Major).spell(8).to_string(), "G#");
+        assert_eq!(Key::new(3, KeyMode::Major).spell(8).to_string(), "Ab");
+        let c_major = Key::new(0, KeyMode::Major);
+        assert_eq!(c_major.spell(6).to_string(), "F#");
+        assert_eq!(c_major.spell_with(6, Spelling::Flats).to_string(), "Gb");
+        assert_eq!(c_major.spell_with(7, Spelling::Flats).to_string(), "G");
+        let chromatic: Vec<String> = [1, 3, 8, 10]
+            .iter()
+            .map(|pc| c_major.spell(*pc).to_string())
+            .collect();
+        assert_eq!(chromatic, ["C#", "Eb", "Ab", "Bb"]);
+
+        let a_minor = Key::new(9, KeyMode::Minor);
+        assert_eq!(a_minor.spell(8).to_string(), "G#");
+        assert_eq!(a_minor.spell(1).to_string(), "C#");
+        // The raised leading tone of G# minor is a double sharp
+        let g_sharp_minor = Key::new(8, KeyMode::Minor);
+        assert_eq!(g_sharp_minor.spell(7).to_string(), "F##");
+        assert_eq!(Key::new(6, KeyMode::Major).spell(11).to_string(), "B");
+        assert_eq!(Key::new(6, KeyMode::Major).spell(5).to_string(), "E#");
+        for pc in 0..12 {
+            assert_eq!(Key::new(5, KeyMode::Major).spell(pc).pitch_class(), pc);
+        }
+    }
+
+    #[test]
+    fn test_accidentals() {
+        for (name, letter, accidental, pc) in [
+            ("F#", 'F', 1, 6),
+            ("F♯", 'F', 1, 6),
+            ("Bb", 'B', -1, 10),
+            ("B♭", 'B', -1, 10),
+            ("Fx", 'F', 2, 7),
+            ("F𝄪", 'F', 2, 7),
+            ("F##", 'F', 2, 7),
+            ("Bbb", 'B', -2, 9),
+            ("B𝄫", 'B', -2, 9),
+            ("C𝄫", 'C', -2, 10),
+            ("B𝄪", 'B', 2, 1),
+            ("E♭𝄫", 'E', -3, 1),
+        ] {
+            let spelled = SpelledPitchClass::parse(name).unwrap();
+            assert_eq!(
+                spelled,
+                SpelledPitchClass { letter, accidental },
+                "{}",
+                name
+            );
+            assert_eq!(spelled.pitch_class(), pc, "{}", name);
+        }
+        assert_eq!(SpelledPitchClass::parse("H"), None);
+        assert_eq!(SpelledPitchClass::parse("F#m"), None);
+
+        // The raised leading tone of G# minor in each style
+        let leading_tone = Key::new(8, KeyMode::Minor).spell(7);
+        assert_eq!(leading_tone.name(AccidentalStyle::Unicode), "F𝄪");
+        assert_eq!(format!("{:#}", leading_tone), "F𝄪");
+        assert_eq!(
+            format!("{:#}", SpelledPitchClass::parse("A#").unwrap()),
+            "A♯"
+        );
+        for letter in ['C', 'D', 'E', 'F', 'G', 'A', 'B'] {
+            for accidental in -3..=3 {
+                let spelled = SpelledPitchClass { letter, accidental };
+                for style in [AccidentalStyle::Ascii, AccidentalStyle::Unicode] {
+                    assert_eq!(
+                        SpelledPitchClass::parse(&spelled.name(style)),
+                        Some(spelled)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_degree_of_pitch_and_snap() {
+        let c_major = Key::new(0, KeyMode::Major);
+        assert_eq!(c_major.degree_of_pitch(&Pitch::from((6, 4))), Some((4, 1)));
+        assert_eq!(c_major.degree_of_pitch(&Pitch::from((7, 2))), Some((5, 0)));
+        let a_minor = Key::new(9, KeyMode::Minor);
+        assert_eq!(a_minor.degree_of_pitch(&Pitch::from((8, 4))), Some((7, 1)));
+        assert_eq!(a_minor.degree_of_pitch(&Pitch::from((9, 4))), Some((1, 0)));
+
+        // Ties snap down
+        assert_eq!(c_major.snap(&Pitch::from((1, 4))), Pitch::from((0, 4)));
+        assert_eq!(c_major.snap(&Pitch::from((10, 3))), Pitch::from((9, 3)));
+        for pitch in c_major.scale().pitches(3, 2) {
+            assert_eq!(c_major.snap(&pitch), pitch);
+        }
+        let d_major = Key::new(2, KeyMode::Major);
+        assert_eq!(d_major.snap(&Pitch::from((0, 5))), Pitch::from((11, 4)));
+    }
+
+    #[test]
+    fn test_solfege() {
+        let g_major = Key::new(7, KeyMode::Major);
+        assert_eq!(g_major.solfege(6), Some(Solfege::Ti));
+        assert_eq!(g_major.solfege(7), Some(Solfege::Do));
+
+        let c_major = Key::new(0, KeyMode::Major);
+        assert_eq!(c_major.solfege(3), Some(Solfege::Me));
+        assert_eq!(
+            c_major.solfege_with(3, ChromaticSyllables::Ascending),
+            Some(Solfege::Ri)
+        );
+        assert_eq!(
+            c_major.solfege_with(6, ChromaticSyllables::Ascending),
+            Some(Solfege::Fi)
+        );
+        assert_eq!(c_major.solfege(10).unwrap().to_string(), "Te");
+        assert_eq!(c_major.solfege_with(3, ChromaticSyllables::Disabled), None);
+        assert_eq!(
+            c_major.solfege_with(4, ChromaticSyllables::Disabled),
+            Some(Solfege::Mi)
+        );
+
+        let a_minor = Key::new(9, KeyMode::Minor);
+        assert_eq!(a_minor.solfege(9), Some(Solfege::La));
+        // The raised leading tone is Si whichever syllables chromatic pitch classes are given
+        assert_eq!(a_minor.solfege(8), Some(Solfege::Si));
+        for chromatic in [
+            ChromaticSyllables::Ascending,
+            ChromaticSyllables::Descending,
+            ChromaticSyllables::Disabled,
+        ] {
+            assert_eq!(a_minor.solfege_with(8, chromatic), Some(Solfege::Si));
+        }
+        assert_eq!(Key::new(0, KeyMode::Minor).solfege(11), Some(Solfege::Si));
+        // Outside of minor keys the same syllable is still lowered by default, G# in C major is Le
+        assert_eq!(c_major.solfege(8), Some(Solfege::Le));
+    }
+}
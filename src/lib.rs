@@ -1,9 +1,127 @@
 //! A library that provides simple types and traits for representing pitch, where the octave is divided into twelve equally tempered parts
-use std::collections::HashSet;
-use std::fmt::Display;
-use std::iter::FromIterator;
-use std::ops::Range;
-use std::ops::{Add, Rem, Sub};
+//!
+//! The pitch types, pitch class sets, scales, intervals and the validation of `SATB` harmonies build without `std`, using
+//! `alloc`, when the default `std` feature is disabled and the `libm` feature is enabled.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("twelve_et needs either the `std` feature or, without `std`, the `libm` feature");
+
+extern crate alloc;
+
+use alloc::format;
+use core::cmp::Ordering;
+use core::fmt::Display;
+use core::ops::Range;
+use core::ops::{Add, Rem, Sub};
+
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "std")]
+pub mod audio;
+#[cfg(feature = "std")]
+pub mod chord;
+#[cfg(feature = "std")]
+pub mod convert;
+#[cfg(feature = "std")]
+pub mod counterpoint;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod harmonize;
+pub mod interval;
+#[cfg(feature = "std")]
+pub mod key;
+mod math;
+#[cfg(feature = "std")]
+pub mod melody;
+#[cfg(feature = "midi")]
+pub mod midi;
+#[cfg(feature = "std")]
+pub mod notation;
+#[cfg(feature = "std")]
+pub mod ornament;
+#[cfg(feature = "rodio")]
+pub mod playback;
+#[cfg(feature = "std")]
+pub mod progression;
+#[cfg(feature = "serde")]
+pub mod project;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(feature = "std")]
+pub mod rhythm;
+#[cfg(feature = "std")]
+pub mod roman;
+pub mod scale;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+#[cfg(feature = "std")]
+pub mod text;
+pub mod tuning;
+#[cfg(feature = "std")]
+pub mod voicing;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "wav")]
+pub mod wav;
+
+#[cfg(feature = "std")]
+pub use analysis::{find_pivots, BassNote, ModulationStep, PivotChord, RomanNumeral};
+#[cfg(feature = "std")]
+pub use audio::{
+    apply_delay, apply_lowpass, crossfade, equal_loudness_gain, fade_edges, resample, to_i16_pcm,
+    to_i16_pcm_seeded, ArpDirection, Envelope, Normalization, Oscillator, ProgressionSamples,
+    RenderError, RenderOptions, RenderState, RenderWarning, Rendered, Sample, SatbSamples, Timbre,
+    Vibrato, VoiceGains, VoicePans, Waveform, DEFAULT_DECLICK, DELAY_TAIL_CAP, DITHER_SEED,
+    PARALLEL_CHUNK_SAMPLES,
+};
+#[cfg(feature = "std")]
+pub use chord::{ArpPattern, Chord, ChordError, ChordQuality, ChordSymbolError, ToneRole};
+#[cfg(feature = "std")]
+pub use convert::ConvertError;
+#[cfg(feature = "std")]
+pub use harmonize::{harmonize_melody, HarmonizationError};
+pub use interval::Interval;
+#[cfg(feature = "std")]
+pub use key::{
+    AccidentalStyle, ChromaticSyllables, DegreeName, Key, KeyMode, KeySignature, MinorVariant,
+    Solfege, SpelledPitchClass, Spelling, TonicizationError,
+};
+#[cfg(feature = "std")]
+pub use melody::{Contour, Melody};
+#[cfg(feature = "midi")]
+pub use midi::MidiError;
+#[cfg(feature = "std")]
+pub use notation::lilypond::LilyPond;
+#[cfg(feature = "std")]
+pub use ornament::{
+    NeighborDirection, NoteEvent, OrnamentError, OrnamentStyle, OrnamentedProgression,
+};
+#[cfg(feature = "rodio")]
+pub use playback::PlaybackSource;
+#[cfg(feature = "std")]
+pub use progression::{
+    Progression, ProgressionCheckConfig, SixFourUsage, Violation, ViolationKind,
+};
+#[cfg(feature = "serde")]
+pub use project::{ChordEntry, ProjectDocument, ProjectError};
+#[cfg(feature = "std")]
+pub use report::{AnalysisReport, ChordReport, ViolationReport, REPORT_VERSION};
+#[cfg(feature = "std")]
+pub use rhythm::{NoteValue, Rest, Tempo};
+#[cfg(feature = "std")]
+pub use roman::ParseError;
+pub use scale::{Direction, Mode, Octatonic, PitchClassSet, Scale, ScaleError};
+#[cfg(feature = "std")]
+pub use text::{TextError, TextErrorKind};
+pub use tuning::{export_frequency_table, frequency_table_csv, Tuning};
+#[cfg(feature = "std")]
+pub use voicing::VoicingPreferences;
+#[cfg(feature = "wasm")]
+pub use wasm::{JsPitch, JsProgression, JsSatb, JsTheoryError};
+#[cfg(feature = "wav")]
+pub use wav::{write_wav, WavError, WavFormat};
 
 pub mod prelude {
     pub use super::*;
@@ -13,20 +131,61 @@ pub const A_440_FREQUENCY: f64 = 440.0;
 pub const A_440_OCTAVE: u8 = 4;
 pub const A_440_HALFSTEPS_FROM_0: u32 = 45;
 pub const SEMITONE_FREQUENCY_RATIO: f64 = 1.059463094;
-pub const BASS_VOICE_OCTAVE_RANGE: Range<u8> = 2..5;
-pub const BASS_VOICE_PITCH_CLASS_LOWER_BOUND: u8 = 4;
-pub const BASS_VOICE_PITCH_CLASS_UPPER_BOUND: u8 = 0;
-pub const TENOR_VOICE_OCTAVE_RANGE: Range<u8> = 3..5;
-pub const TENOR_VOICE_PITCH_CLASS_LOWER_BOUND: u8 = 3;
-pub const TENOR_VOICE_PITCH_CLASS_UPPER_BOUND: u8 = 6;
-pub const ALTO_VOICE_OCTAVE_RANGE: Range<u8> = 3..6;
-pub const ALTO_VOICE_PITCH_CLASS_LOWER_BOUND: u8 = 7;
-pub const ALTO_VOICE_PITCH_CLASS_UPPER_BOUND: u8 = 1;
-pub const SOPRANO_VOICE_OCTAVE_RANGE: Range<u8> = 4..6;
-pub const SOPRANO_VOICE_PITCH_CLASS_LOWER_BOUND: u8 = 2;
-pub const SOPRANO_VOICE_PITCH_CLASS_UPPER_BOUND: u8 = 6;
-
-#[derive(Debug, Copy, Clone, PartialEq)]
+
+/// The range of each voice, given as inclusive lowest and highest `Pitch`es, ordered soprano, alto, tenor, bass.
+pub const VOICE_RANGES: [(Voice, Pitch, Pitch); 4] = [
+    (
+        Voice::Soprano,
+        Pitch::from_pitch_class(2, 4),
+        Pitch::from_pitch_class(6, 5),
+    ),
+    (
+        Voice::Alto,
+        Pitch::from_pitch_class(7, 3),
+        Pitch::from_pitch_class(1, 5),
+    ),
+    (
+        Voice::Tenor,
+        Pitch::from_pitch_class(3, 3),
+        Pitch::from_pitch_class(6, 4),
+    ),
+    (
+        Voice::Bass,
+        Pitch::from_pitch_class(4, 2),
+        Pitch::from_pitch_class(0, 4),
+    ),
+];
+
+#[deprecated(note = "use `VOICE_RANGES` or `Voice::range` instead")]
+pub const BASS_VOICE_OCTAVE_RANGE: Range<u8> =
+    VOICE_RANGES[3].1.octave..VOICE_RANGES[3].2.octave + 1;
+#[deprecated(note = "use `VOICE_RANGES` or `Voice::range` instead")]
+pub const BASS_VOICE_PITCH_CLASS_LOWER_BOUND: u8 = VOICE_RANGES[3].1.pitch_class;
+#[deprecated(note = "use `VOICE_RANGES` or `Voice::range` instead")]
+pub const BASS_VOICE_PITCH_CLASS_UPPER_BOUND: u8 = VOICE_RANGES[3].2.pitch_class;
+#[deprecated(note = "use `VOICE_RANGES` or `Voice::range` instead")]
+pub const TENOR_VOICE_OCTAVE_RANGE: Range<u8> =
+    VOICE_RANGES[2].1.octave..VOICE_RANGES[2].2.octave + 1;
+#[deprecated(note = "use `VOICE_RANGES` or `Voice::range` instead")]
+pub const TENOR_VOICE_PITCH_CLASS_LOWER_BOUND: u8 = VOICE_RANGES[2].1.pitch_class;
+#[deprecated(note = "use `VOICE_RANGES` or `Voice::range` instead")]
+pub const TENOR_VOICE_PITCH_CLASS_UPPER_BOUND: u8 = VOICE_RANGES[2].2.pitch_class;
+#[deprecated(note = "use `VOICE_RANGES` or `Voice::range` instead")]
+pub const ALTO_VOICE_OCTAVE_RANGE: Range<u8> =
+    VOICE_RANGES[1].1.octave..VOICE_RANGES[1].2.octave + 1;
+#[deprecated(note = "use `VOICE_RANGES` or `Voice::range` instead")]
+pub const ALTO_VOICE_PITCH_CLASS_LOWER_BOUND: u8 = VOICE_RANGES[1].1.pitch_class;
+#[deprecated(note = "use `VOICE_RANGES` or `Voice::range` instead")]
+pub const ALTO_VOICE_PITCH_CLASS_UPPER_BOUND: u8 = VOICE_RANGES[1].2.pitch_class;
+#[deprecated(note = "use `VOICE_RANGES` or `Voice::range` instead")]
+pub const SOPRANO_VOICE_OCTAVE_RANGE: Range<u8> =
+    VOICE_RANGES[0].1.octave..VOICE_RANGES[0].2.octave + 1;
+#[deprecated(note = "use `VOICE_RANGES` or `Voice::range` instead")]
+pub const SOPRANO_VOICE_PITCH_CLASS_LOWER_BOUND: u8 = VOICE_RANGES[0].1.pitch_class;
+#[deprecated(note = "use `VOICE_RANGES` or `Voice::range` instead")]
+pub const SOPRANO_VOICE_PITCH_CLASS_UPPER_BOUND: u8 = VOICE_RANGES[0].2.pitch_class;
+
+#[derive(Debug, Copy, Clone)]
 pub struct Pitch {
     /// The frequency of the pitch
     frequency: f64,
@@ -40,7 +199,7 @@ pub struct Pitch {
 
 impl Pitch {
     /// Associated method to create a new `Ptich`.
-    pub fn new(frequency: f64, pitch_class: u8, octave: u8) -> Self {
+    pub const fn new(frequency: f64, pitch_class: u8, octave: u8) -> Self {
         let half_steps_from_0 = Pitch::compute_half_steps_from_zero(pitch_class, octave);
         Pitch {
             frequency,
@@ -50,8 +209,56 @@ impl Pitch {
         }
     }
 
+    /// Method for accessing the frequency of the pitch.
+    pub fn frequency(&self) -> f64 {
+        self.frequency
+    }
+
+    /// Method for accessing the pitch class of the pitch.
+    pub fn pitch_class(&self) -> u8 {
+        self.pitch_class
+    }
+
+    /// Method for accessing the octave of the pitch.
+    pub fn octave(&self) -> u8 {
+        self.octave
+    }
+
+    /// Method that returns the MIDI note number of the pitch, where middle C, C4, is note 60.
+    /// Returns `None` if the pitch lies above the MIDI note range.
+    pub fn midi_note(&self) -> Option<u8> {
+        let note = 12 * (self.octave as u32 + 1) + self.pitch_class as u32;
+        u8::try_from(note).ok().filter(|n| *n <= 127)
+    }
+
+    /// Method that returns how far the frequency of the pitch lies from the equally tempered frequency of its pitch class and
+    /// octave, in cents.
+    pub fn cents_offset(&self) -> f64 {
+        let tempered = Pitch::compute_frequency(self.pitch_class, self.octave);
+        1200.0 * math::log2(self.frequency / tempered)
+    }
+
+    /// Associated method to create a new `Pitch` from a `pitch_class` and an `octave`, with its equally tempered frequency.
+    pub const fn from_pitch_class(pitch_class: u8, octave: u8) -> Self {
+        let frequency = Pitch::compute_frequency(pitch_class, octave);
+        Pitch::new(frequency, pitch_class, octave)
+    }
+
+    /// Method that returns the equally tempered pitch `semitones` half steps above (or below when negative) the pitch.
+    /// Returns `None` if the transposed pitch would lie below octave 0 or above octave 255.
+    pub fn transpose(&self, semitones: i32) -> Option<Pitch> {
+        let position = 12 * self.octave as i32 + self.pitch_class as i32 + semitones;
+        if position < 0 || position / 12 > u8::MAX as i32 {
+            return None;
+        }
+        Some(Pitch::from_pitch_class(
+            (position % 12) as u8,
+            (position / 12) as u8,
+        ))
+    }
+
     /// Associated method for computing the number of half steps away from zero given a `pitch_class` and an `octave`
-    pub fn compute_half_steps_from_zero(pitch_class: u8, octave: u8) -> u32 {
+    pub const fn compute_half_steps_from_zero(pitch_class: u8, octave: u8) -> u32 {
         if octave > 0 {
             (octave as u32 - 1) * 12 + (pitch_class as u32)
         } else {
@@ -60,20 +267,37 @@ impl Pitch {
     }
 
     /// Associated method to compute the frequency of a new pitch given an octave and a pitch class
-    pub fn compute_frequency(pitch_class: u8, octave: u8) -> f64 {
-        // Compute number of half steps away from 0
-        let num_semitones = Pitch::compute_half_steps_from_zero(pitch_class, octave);
-        // Compute and return frequency
-        A_440_FREQUENCY
-            * f64::powi(
-                SEMITONE_FREQUENCY_RATIO,
-                (num_semitones as i32) - (A_440_HALFSTEPS_FROM_0 as i32),
-            )
+    pub const fn compute_frequency(pitch_class: u8, octave: u8) -> f64 {
+        Tuning::STANDARD.frequency(pitch_class, octave)
+    }
+}
+
+impl PartialEq for Pitch {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Pitch {}
+
+impl PartialOrd for Pitch {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Pitch {
+    /// Pitches are ordered by height, i.e. by frequency, with pitches of equal frequency ordered by their position in half steps and then by octave.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.frequency
+            .total_cmp(&other.frequency)
+            .then(self.half_steps_from_0.cmp(&other.half_steps_from_0))
+            .then(self.octave.cmp(&other.octave))
     }
 }
 
 impl Display for Pitch {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let note = match self.pitch_class {
             0 => format!("{}{}", "C", self.octave),
             1 => format!("{}{}", "C#/Db", self.octave),
@@ -99,8 +323,27 @@ impl Display for Pitch {
 impl From<(u8, u8)> for Pitch {
     fn from(value: (u8, u8)) -> Self {
         let (pitch_class, octave) = value;
-        let freq = Pitch::compute_frequency(pitch_class, octave);
-        Pitch::new(freq, pitch_class, octave)
+        Pitch::from_pitch_class(pitch_class, octave)
+    }
+}
+
+/// Private helper function that raises `base` to the integer power `exp` by repeated squaring, usable in constant expressions.
+const fn const_powi(mut base: f64, exp: i32) -> f64 {
+    let mut n = exp.unsigned_abs();
+    let mut result = 1.0;
+    while n > 0 {
+        if n & 1 == 1 {
+            result *= base;
+        }
+        n /= 2;
+        if n > 0 {
+            base *= base;
+        }
+    }
+    if exp < 0 {
+        1.0 / result
+    } else {
+        result
     }
 }
 
@@ -153,15 +396,377 @@ impl PitchClassArithmetic<u8> for u8 {
 }
 
 /// A trait that all harmonies, implement.
+///
+/// Implementors only supply the frequencies of their voices, every rendering method is provided in terms of `frequencies`.
+///
+/// Amplitude contract: each voice of a harmony is rendered as a unit amplitude wave, and unless a method states otherwise
+/// the samples it returns lie within [-1, 1], so they can be written to an audio file or device without clipping.
+#[cfg(feature = "std")]
 pub trait Harmony {
-    /// Required method, each `Harmony` must implement a method to return duration of a sound wave.
+    /// Required method, each `Harmony` must implement a method to return the frequency of each of its voices.
+    fn frequencies(&self) -> Vec<f64>;
+
+    /// Provided method that returns `duration` seconds of the harmony's sound wave, normalized by the number of voices.
     /// `duration` represents the time in seconds of the requested harmony, `sample_freq` represents the rate at which the
     /// sound wave is sampled.
-    fn sound_wave(&self, duration: u32, sample_freq: u32) -> Vec<f64>;
+    fn sound_wave(&self, duration: u32, sample_freq: u32) -> Vec<f64> {
+        self.sound_wave_samples(duration as usize * sample_freq as usize, sample_freq)
+    }
+
+    /// Provided method that returns the value of the harmony's sound wave at `t` seconds, normalized by the number of voices. Each
+    /// voice is a sine starting at zero phase at `t = 0`, so the wave can be evaluated at any instant, e.g. at a varying rate when
+    /// pitch shifting or from within an external signal graph. Sampling at `k / sample_freq` agrees with `sound_wave` up to
+    /// rounding, which generates the wave sample by sample rather than evaluating each instant independently.
+    fn sample(&self, t: f64) -> f64 {
+        let frequencies = self.frequencies();
+        let sum: f64 = frequencies
+            .iter()
+            .map(|f| Waveform::Sine.value((f * t).rem_euclid(1.0)))
+            .sum();
+        sum / frequencies.len().max(1) as f64
+    }
+
+    /// Provided method that returns exactly `num_samples` samples of the harmony's sound wave sampled at `sample_freq`,
+    /// normalized by the number of voices. The seconds based methods are defined in terms of this method.
+    fn sound_wave_samples(&self, num_samples: usize, sample_freq: u32) -> Vec<f64> {
+        let frequencies = self.frequencies();
+        let mut wave = audio::sum_waves(
+            &frequencies,
+            num_samples,
+            sample_freq,
+            Waveform::Sine.into(),
+        );
+        Normalization::default().apply(&mut wave, frequencies.len());
+        wave
+    }
+
+    /// Provided method that returns `duration` seconds of the harmony's sound wave sampled at `sample_freq`, normalized by the
+    /// number of voices, rendered in chunks of `PARALLEL_CHUNK_SAMPLES` samples on the rayon thread pool. The phase of each voice
+    /// at the start of a chunk is computed directly, so the result matches `sound_wave` up to rounding.
+    #[cfg(feature = "rayon")]
+    fn sound_wave_parallel(&self, duration: u32, sample_freq: u32) -> Vec<f64> {
+        use rayon::prelude::*;
+        let frequencies = self.frequencies();
+        let mut wave = vec![0.0; duration as usize * sample_freq as usize];
+        wave.par_chunks_mut(audio::PARALLEL_CHUNK_SAMPLES)
+            .enumerate()
+            .for_each(|(i, chunk)| {
+                audio::fill_waves(
+                    chunk,
+                    &frequencies,
+                    i * audio::PARALLEL_CHUNK_SAMPLES,
+                    sample_freq,
+                    Waveform::Sine.into(),
+                )
+            });
+        Normalization::default().apply(&mut wave, frequencies.len());
+        wave
+    }
+
+    /// Provided method that returns `duration` seconds of the harmony's sound wave sampled at `sample_freq`, with the sum of the
+    /// voices scaled according to `normalization`. Note `Normalization::None` does not satisfy the amplitude contract.
+    fn sound_wave_normalized(
+        &self,
+        duration: u32,
+        sample_freq: u32,
+        normalization: Normalization,
+    ) -> Vec<f64> {
+        let frequencies = self.frequencies();
+        let num_samples = duration as usize * sample_freq as usize;
+        let mut wave = audio::sum_waves(
+            &frequencies,
+            num_samples,
+            sample_freq,
+            Waveform::Sine.into(),
+        );
+        normalization.apply(&mut wave, frequencies.len());
+        wave
+    }
+
+    /// Provided method that returns `duration` seconds of the harmony's sound wave sampled at `sample_freq`, where `duration` may
+    /// be fractional, normalized by the number of voices. The wave has `duration * sample_freq` samples rounded to the nearest
+    /// integer, and is empty if `duration` is negative, zero or not finite.
+    fn sound_wave_secs(&self, duration: f64, sample_freq: u32) -> Vec<f64> {
+        self.sound_wave_samples(audio::sample_count(duration, sample_freq), sample_freq)
+    }
+
+    /// Provided method that renders `duration` seconds of the harmony's sound wave sampled at `sample_freq` according to `options`,
+    /// normalized by the number of voices. With the default options this equals `sound_wave_secs` faded in and out over
+    /// `DEFAULT_DECLICK`, and without a declick it equals `sound_wave_secs`.
+    fn render(&self, duration: f64, sample_freq: u32, options: &RenderOptions) -> Vec<f64> {
+        let num_samples = audio::sample_count(duration, sample_freq);
+        let mut wave = audio::render_voices(
+            &self.frequencies(),
+            num_samples,
+            sample_freq,
+            options,
+            &mut Vec::new(),
+            0,
+            None,
+        );
+        options.apply_output(&mut wave, sample_freq, &mut RenderState::default());
+        wave
+    }
+
+    /// Provided method that renders `duration` seconds of the harmony's sound wave sampled at `sample_freq` according to `options`
+    /// in stereo, as frames of left and right samples. When `options` sets pans each voice is placed with constant power panning
+    /// and each channel runs through the signal chain separately, otherwise both channels carry the mono wave of `render`.
+    fn render_stereo(
+        &self,
+        duration: f64,
+        sample_freq: u32,
+        options: &RenderOptions,
+    ) -> Vec<[f64; 2]> {
+        if !options.is_panned() {
+            return self
+                .render(duration, sample_freq, options)
+                .into_iter()
+                .map(|s| [s, s])
+                .collect();
+        }
+        let left = self.render(duration, sample_freq, &options.for_channel(0));
+        let right = self.render(duration, sample_freq, &options.for_channel(1));
+        left.into_iter().zip(right).map(|(l, r)| [l, r]).collect()
+    }
+
+    /// Provided method that renders `duration` seconds of the harmony's sound wave sampled at `sample_freq` according to `options`
+    /// as interleaved stereo `f32` samples, the layout expected by Web Audio and most audio devices. Frame `i` occupies indices
+    /// `2 * i` and `2 * i + 1`, holding the left and right samples, so the even indices are the left channel and the result
+    /// holds twice as many samples as frames. The channels are rendered as by `render_stereo`.
+    fn render_interleaved_f32(
+        &self,
+        duration: f64,
+        sample_freq: u32,
+        options: &RenderOptions,
+    ) -> Vec<f32> {
+        self.render_stereo(duration, sample_freq, options)
+            .iter()
+            .flat_map(|[l, r]| [*l as f32, *r as f32])
+            .collect()
+    }
+
+    /// Provided method that returns an endless iterator over the samples of the harmony's sustained sound wave at `sample_freq`,
+    /// normalized by the number of voices. Each voice keeps its phase for as long as the iterator is polled, so the wave never
+    /// repeats with a seam, use `take` to bound it.
+    fn loop_samples(&self, sample_freq: u32) -> impl Iterator<Item = f64>
+    where
+        Self: Sized,
+    {
+        let frequencies = self.frequencies();
+        let divisor = frequencies.len().max(1) as f64;
+        let mut phasors: Vec<audio::Phasor> = frequencies
+            .iter()
+            .map(|f| audio::Phasor::new(0.0, f / sample_freq as f64))
+            .collect();
+        std::iter::repeat_with(move || {
+            phasors
+                .iter_mut()
+                .map(audio::Phasor::next_value)
+                .sum::<f64>()
+                / divisor
+        })
+    }
+
+    /// Provided method that returns `duration` seconds of the harmony's sound wave sampled at `sample_freq`, normalized by the
+    /// number of voices, built by repeating a pre-rendered loop of about `loop_region` seconds. The loop length is chosen near
+    /// `loop_region` so that each voice completes as close to a whole number of cycles as possible, and the end of the loop is
+    /// crossfaded into its start, so the repeats join without clicks. A `loop_region` shorter than a sample renders without looping.
+    fn render_looped(&self, duration: f64, sample_freq: u32, loop_region: f64) -> Vec<f64> {
+        let frequencies = self.frequencies();
+        let mut wave = audio::render_loop(
+            &frequencies,
+            audio::sample_count(duration, sample_freq),
+            audio::sample_count(loop_region, sample_freq),
+            sample_freq,
+        );
+        Normalization::default().apply(&mut wave, frequencies.len());
+        wave
+    }
+
+    /// Provided method that returns `duration` seconds of the harmony's sound wave sampled at `sample_freq`, converted to the
+    /// sample type `T`, e.g. `f32` or `i16`, for writing to audio files and devices.
+    fn sound_wave_as<T: Sample>(&self, duration: u32, sample_freq: u32) -> Vec<T>
+    where
+        Self: Sized,
+    {
+        self.sound_wave(duration, sample_freq)
+            .into_iter()
+            .map(T::from_f64)
+            .collect()
+    }
+
+    /// Provided method that returns `duration` seconds of the harmony's sound wave sampled at `sample_freq`, with every voice
+    /// generated by `oscillator`, normalized by the number of voices. A `Waveform` may be passed directly, and is band limited.
+    fn sound_wave_with(
+        &self,
+        duration: u32,
+        sample_freq: u32,
+        oscillator: impl Into<Oscillator>,
+    ) -> Vec<f64>
+    where
+        Self: Sized,
+    {
+        let frequencies = self.frequencies();
+        let num_samples = duration as usize * sample_freq as usize;
+        let mut wave = audio::sum_waves(&frequencies, num_samples, sample_freq, oscillator.into());
+        Normalization::default().apply(&mut wave, frequencies.len());
+        wave
+    }
+
+    /// Provided method that returns `duration` seconds of the harmony's sound wave sampled at `sample_freq`, with every voice
+    /// rendered with the harmonics of `timbre`, normalized by the number of voices and the total amplitude of the harmonics.
+    fn sound_wave_with_timbre(&self, duration: u32, sample_freq: u32, timbre: &Timbre) -> Vec<f64> {
+        let frequencies = self.frequencies();
+        let num_samples = duration as usize * sample_freq as usize;
+        let mut wave = audio::sum_timbres(&frequencies, num_samples, sample_freq, timbre);
+        Normalization::default().apply(&mut wave, frequencies.len());
+        wave
+    }
+
+    /// Provided method that writes `duration` seconds of the harmony's sound wave, sampled at `sample_freq`, to a 32 bit floating
+    /// point WAV file at `path`.
+    #[cfg(feature = "wav")]
+    fn render_to_wav(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        duration: u32,
+        sample_freq: u32,
+    ) -> Result<(), WavError>
+    where
+        Self: Sized,
+    {
+        write_wav(
+            path,
+            &self.sound_wave(duration, sample_freq),
+            sample_freq,
+            WavFormat::Float32,
+        )
+    }
+
+    /// Provided method that returns `duration` seconds of the harmony's sound wave sampled at `sample_freq`, with its amplitude
+    /// shaped over time by `envelope`. The release of the envelope ends at the end of `duration`.
+    fn sound_wave_with_envelope(
+        &self,
+        duration: u32,
+        sample_freq: u32,
+        envelope: &Envelope,
+    ) -> Vec<f64> {
+        let mut wave = self.sound_wave(duration, sample_freq);
+        for (i, sample) in wave.iter_mut().enumerate() {
+            *sample *= envelope.amplitude(i as f64 / sample_freq as f64, duration as f64);
+        }
+        wave
+    }
+}
+
+/// Enumerates the four voices of an `SATB` harmony.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Voice {
+    Soprano,
+    Alto,
+    Tenor,
+    Bass,
+}
+
+impl Voice {
+    /// All four voices, ordered from the highest voice to the lowest.
+    pub const ALL: [Voice; 4] = [Voice::Soprano, Voice::Alto, Voice::Tenor, Voice::Bass];
+
+    /// Method that returns the inclusive lowest and highest `Pitch` the voice may sing, as given by `VOICE_RANGES`.
+    pub fn range(&self) -> (Pitch, Pitch) {
+        let (_, low, high) = VOICE_RANGES[*self as usize];
+        (low, high)
+    }
+
+    /// Method that returns true if `p` lies within the range of the voice, false otherwise.
+    pub fn contains(&self, p: &Pitch) -> bool {
+        self.contains_position(p.pitch_class, p.octave)
+    }
+
+    /// Private helper method that returns true if the pitch given by `pitch_class` and `octave` lies within the range of the voice.
+    fn contains_position(&self, pitch_class: u8, octave: u8) -> bool {
+        let (low, high) = self.range();
+        let position = (octave, pitch_class);
+        (low.octave, low.pitch_class) <= position && position <= (high.octave, high.pitch_class)
+    }
+}
+
+impl Display for Voice {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            Voice::Soprano => "soprano",
+            Voice::Alto => "alto",
+            Voice::Tenor => "tenor",
+            Voice::Bass => "bass",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Enumerates the role a pitch class can play within a harmony, relative to the root of the harmony.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ChordMember {
+    Root,
+    Third,
+    Fifth,
+    Seventh,
+    NonChordTone,
+}
+
+/// Enumerates the reasons an `SATB` harmony can be invalid.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SatbError {
+    /// The voice is outside of its range.
+    OutOfRange(Voice),
+    /// The lower voice sounds above the upper voice.
+    Crossing { upper: Voice, lower: Voice },
+    /// The two adjacent upper voices are more than an octave apart.
+    Spacing { upper: Voice, lower: Voice },
+    /// The pitch classes of the voices do not form a complete harmony with the given root.
+    InvalidHarmony,
+}
+
+impl Display for SatbError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SatbError::OutOfRange(voice) => write!(f, "the {} is out of range", voice),
+            SatbError::Crossing { upper, lower } => {
+                write!(f, "the {} is crossed above the {}", lower, upper)
+            }
+            SatbError::Spacing { upper, lower } => write!(
+                f,
+                "the {} and {} are more than an octave apart",
+                upper, lower
+            ),
+            SatbError::InvalidHarmony => {
+                write!(f, "the voices do not form a complete harmony")
+            }
+        }
+    }
+}
+
+impl core::error::Error for SatbError {}
+
+/// Configures the policy used when validating a harmony.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ValidationConfig {
+    /// When true, voicings that omit the fifth are accepted, i.e. a triad voiced as a tripled root and a third,
+    /// or a seventh chord voiced as a doubled root, a third and a seventh. Defaults to true, matching common practice.
+    /// Other voicings of only the root and third, e.g. with both doubled, are accepted either way.
+    pub allow_omitted_fifth: bool,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        ValidationConfig {
+            allow_omitted_fifth: true,
+        }
+    }
 }
 
 /// A struct that represents a traditional harmony comprised of alto, soprano, tenor and bass voices. In short it represents a harmony
 /// used in traditional four part voice leading.
+#[derive(Debug, Clone, PartialEq)]
 pub struct SATB {
     /// Soprano voice
     pub soprano: Pitch,
@@ -174,78 +779,47 @@ pub struct SATB {
     /// The root of the harmony
     root: u8,
     /// Collection of all possible pitch classes
-    pitch_classes: HashSet<u8>,
+    pitch_classes: PitchClassSet,
 }
 
 impl SATB {
     /// Private helper method to validate the range of each voice in the given harmony comprised of `soprano`, `alto`, `tenor` and `bass`.
-    /// Returns a boolean, true if all voices are within valid ranges and adjacent voices have a distance no greater than an octave between them, false otherwise.
-    fn validate_voice_ranges(soprano: &Pitch, alto: &Pitch, tenor: &Pitch, bass: &Pitch) -> bool {
-        // Check the bass
-        if bass.octave < 2 || bass.octave > 4 {
-            return false;
-        } else {
-            // Check basses end points
-            if bass.octave == 2 && bass.pitch_class < 4 {
-                return false;
-            } else if bass.octave == 4 && bass.pitch_class > 0 {
-                return false;
-            } else if (bass.octave.abs_diff(tenor.octave) == 1
-                && bass.octave.dist(&tenor.octave) > 7)
-                || (bass.octave.abs_diff(tenor.octave) == 0 && bass.pitch_class > tenor.pitch_class)
-            {
-                return false;
-            }
-        }
-        // Check the tenor
-        if tenor.octave < 3 || tenor.octave > 4 {
-            return false;
-        } else {
-            // Check the end points
-            if tenor.octave == 3 && tenor.pitch_class < 3 {
-                return false;
-            } else if tenor.octave == 4 && tenor.pitch_class > 6 {
-                return false;
-            } else if compute_semi_tone_dist(
-                (tenor.pitch_class, tenor.octave),
-                (alto.pitch_class, alto.octave),
-            ) > 12
-                || (tenor.octave.abs_diff(alto.octave) == 0 && tenor.pitch_class > alto.pitch_class)
-            {
-                return false;
+    /// Returns an error if a voice is outside of its range, adjacent voices are crossed, or adjacent upper voices have a distance
+    /// greater than an octave between them.
+    fn validate_voice_ranges(
+        soprano: &Pitch,
+        alto: &Pitch,
+        tenor: &Pitch,
+        bass: &Pitch,
+    ) -> Result<(), SatbError> {
+        let voices = [soprano, alto, tenor, bass];
+        // Check each voice, including its end points
+        for (voice, pitch) in Voice::ALL.iter().zip(voices) {
+            if !voice.contains(pitch) {
+                return Err(SatbError::OutOfRange(*voice));
             }
         }
-        // Check alto
-        if alto.octave < 3 || alto.octave > 5 {
-            return false;
-        } else {
-            // Check the end points of the alot voice
-            if alto.octave == 3 && alto.pitch_class < 7 {
-                return false;
-            } else if alto.octave == 5 && alto.pitch_class > 1 {
-                return false;
-            } else if compute_semi_tone_dist(
-                (alto.pitch_class, alto.octave),
-                (soprano.pitch_class, soprano.octave),
-            ) > 12
-                || (alto.octave.abs_diff(soprano.octave) == 0
-                    && alto.pitch_class > soprano.pitch_class)
-            {
-                return false;
+        // Check adjacent voices are not crossed, and that the upper voices are no more than an octave apart
+        for i in 0..3 {
+            let (upper, lower) = (voices[i], voices[i + 1]);
+            let interval = compute_semi_tone_dist_signed(
+                (upper.pitch_class, upper.octave),
+                (lower.pitch_class, lower.octave),
+            );
+            if interval < 0 {
+                return Err(SatbError::Crossing {
+                    upper: Voice::ALL[i],
+                    lower: Voice::ALL[i + 1],
+                });
             }
-        }
-        // Check soprano
-        if soprano.octave < 4 || soprano.octave > 5 {
-            return false;
-        } else {
-            // Check the end points of the valid range
-            if soprano.octave == 4 && soprano.pitch_class < 2 {
-                return false;
-            } else if soprano.octave == 5 && soprano.pitch_class > 6 {
-                return false;
+            if Voice::ALL[i + 1] != Voice::Bass && interval > 12 {
+                return Err(SatbError::Spacing {
+                    upper: Voice::ALL[i],
+                    lower: Voice::ALL[i + 1],
+                });
             }
         }
-        true
+        Ok(())
     }
 
     /// Associated helper  method to validate a given harmony, each voice is represented as a `Pitch`.
@@ -255,11 +829,26 @@ impl SATB {
         alto: &Pitch,
         tenor: &Pitch,
         bass: &Pitch,
-    ) -> bool {
+        config: &ValidationConfig,
+    ) -> Result<(), SatbError> {
         // Validate the range for each voice
-        if !SATB::validate_voice_ranges(&soprano, &alto, &tenor, &bass) {
-            return false;
+        SATB::validate_voice_ranges(soprano, alto, tenor, bass)?;
+        if SATB::validate_chord_members(root, soprano, alto, tenor, bass, config) {
+            Ok(())
+        } else {
+            Err(SatbError::InvalidHarmony)
         }
+    }
+
+    /// Associated helper method to validate the pitch classes of a given harmony form a complete voicing of a harmony with the given `root`.
+    fn validate_chord_members(
+        root: u8,
+        soprano: &Pitch,
+        alto: &Pitch,
+        tenor: &Pitch,
+        bass: &Pitch,
+        config: &ValidationConfig,
+    ) -> bool {
         // Ensure that atleast one voice is the root of the harmony
         if !(soprano.pitch_class == root
             || alto.pitch_class == root
@@ -290,24 +879,40 @@ impl SATB {
             distinct_voices += 1;
         }
 
+        // Voicings that omit the fifth are decided by which chord members are present, rather than by the count of distinct voices
+        if config.allow_omitted_fifth
+            && is_omitted_fifth_voicing(
+                root,
+                [
+                    soprano.pitch_class,
+                    alto.pitch_class,
+                    tenor.pitch_class,
+                    bass.pitch_class,
+                ],
+            )
+        {
+            return true;
+        }
+
         // Ensure we have either 2, 3 or 4 distinct voices, all other cases are invalid harmonies.
-        // The case where we have two distinc voices, all voices need to be either the root or the third only.
+        // The case where we have two distinct voices, all voices need to be either the root or the third only. A tripled root
+        // and a third only reaches this point when omitted fifths are disallowed, and is then rejected.
+        let pitch_classes = [
+            soprano.pitch_class,
+            alto.pitch_class,
+            tenor.pitch_class,
+            bass.pitch_class,
+        ];
         if distinct_voices == 2 {
-            if !((soprano.pitch_class == root || root.is_third(&soprano.pitch_class))
-                && (alto.pitch_class == root || root.is_third(&alto.pitch_class))
-                && (tenor.pitch_class == root || root.is_third(&tenor.pitch_class))
-                && (bass.pitch_class == root || root.is_third(&bass.pitch_class)))
-            {
-                return false;
-            } else {
-                return true;
-            }
+            pitch_classes
+                .iter()
+                .all(|pc| *pc == root || root.is_third(pc))
+                && !is_omitted_fifth_voicing(root, pitch_classes)
         } else if distinct_voices == 3 {
             // We have a triad in this case, check that the voicing is valid for its inversion
             if bass.pitch_class == root {
-                return (tenor.pitch_class == root
-                    && ((root.is_third(&alto.pitch_class)
-                        && root.is_fifth(&soprano.pitch_class))
+                (tenor.pitch_class == root
+                    && ((root.is_third(&alto.pitch_class) && root.is_fifth(&soprano.pitch_class))
                         || (root.is_third(&soprano.pitch_class)
                             && root.is_fifth(&alto.pitch_class))))
                     || (alto.pitch_class == root
@@ -319,7 +924,7 @@ impl SATB {
                         && ((root.is_third(&tenor.pitch_class)
                             && root.is_fifth(&alto.pitch_class))
                             || (root.is_third(&alto.pitch_class)
-                                && root.is_fifth(&tenor.pitch_class))));
+                                && root.is_fifth(&tenor.pitch_class))))
             } else if root.is_third(&bass.pitch_class) {
                 // Check if we have a diminished triad of some kind
                 if (root.is_fifth(&soprano.pitch_class) && root.dist(&soprano.pitch_class) == 6)
@@ -327,13 +932,13 @@ impl SATB {
                     || (root.is_fifth(&tenor.pitch_class) && root.dist(&tenor.pitch_class) == 6)
                 {
                     // Validate that atleast one voice is the third, i.e that the bass is doubled
-                    return root.is_third(&soprano.pitch_class)
+                    root.is_third(&soprano.pitch_class)
                         || root.is_third(&alto.pitch_class)
-                        || root.is_third(&tenor.pitch_class);
+                        || root.is_third(&tenor.pitch_class)
                 } else {
                     // Validate that the bass is not doubled in this case, that one voice is the root and other two are fifths
                     // or two voices are the root and one voice is the fifth
-                    return (!root.is_third(&soprano.pitch_class)
+                    (!root.is_third(&soprano.pitch_class)
                         && !root.is_third(&alto.pitch_class)
                         && !root.is_third(&tenor.pitch_class))
                         && ((root.is_fifth(&soprano.pitch_class)
@@ -341,11 +946,11 @@ impl SATB {
                             || root.is_fifth(&tenor.pitch_class))
                             && (root == soprano.pitch_class
                                 || root == alto.pitch_class
-                                || root == tenor.pitch_class));
+                                || root == tenor.pitch_class))
                 }
             } else if root.is_fifth(&bass.pitch_class) {
                 // Ensure that atleast one other voice is the bass
-                return (root.is_fifth(&soprano.pitch_class)
+                (root.is_fifth(&soprano.pitch_class)
                     || root.is_fifth(&alto.pitch_class)
                     || root.is_fifth(&tenor.pitch_class))
                     && ((root.is_third(&soprano.pitch_class)
@@ -353,12 +958,12 @@ impl SATB {
                         || root.is_third(&tenor.pitch_class))
                         && (root == soprano.pitch_class
                             || root == alto.pitch_class
-                            || root == tenor.pitch_class));
+                            || root == tenor.pitch_class))
             } else {
-                return false;
+                false
             }
         } else if distinct_voices == 4 {
-            return (root == bass.pitch_class
+            (root == bass.pitch_class
                 || root == tenor.pitch_class
                 || root == alto.pitch_class
                 || root == soprano.pitch_class)
@@ -373,9 +978,9 @@ impl SATB {
                 && (root.is_seventh(&bass.pitch_class)
                     || root.is_seventh(&tenor.pitch_class)
                     || root.is_seventh(&alto.pitch_class)
-                    || root.is_seventh(&soprano.pitch_class));
+                    || root.is_seventh(&soprano.pitch_class))
         } else {
-            return false;
+            false
         }
     }
 
@@ -385,22 +990,152 @@ impl SATB {
     /// If the supplied pitches do not form a valid satb harmony, i.e. there is no third or
     /// there is a pitch that is not contained within a valid satb harmony with the supplied `root`.
     pub fn new(root: u8, soprano: Pitch, alto: Pitch, tenor: Pitch, bass: Pitch) -> Self {
-        if !SATB::validate_harmony(root, &soprano, &alto, &tenor, &bass) {
+        SATB::new_with_config(
+            root,
+            soprano,
+            alto,
+            tenor,
+            bass,
+            &ValidationConfig::default(),
+        )
+    }
+
+    /// Associated method for creating a new `SATB` harmony, validated according to the policy given by `config`.
+    ///
+    /// `Panics`
+    /// If the supplied pitches do not form a valid satb harmony under `config`.
+    pub fn new_with_config(
+        root: u8,
+        soprano: Pitch,
+        alto: Pitch,
+        tenor: Pitch,
+        bass: Pitch,
+        config: &ValidationConfig,
+    ) -> Self {
+        if let Err(e) = SATB::validate_harmony(root, &soprano, &alto, &tenor, &bass, config) {
             panic!(
-                "invalid harmony created with voices S: {}, A: {}, T: {}, B: {}",
-                soprano, alto, tenor, bass
+                "invalid harmony created with voices S: {}, A: {}, T: {}, B: {}: {}",
+                soprano, alto, tenor, bass, e
             );
         }
         SATB::new_unchecked(root, soprano, alto, tenor, bass)
     }
 
+    /// Associated method for creating a new `SATB` harmony, returning an error describing why the harmony is invalid
+    /// rather than panicking.
+    pub fn try_new(
+        root: u8,
+        soprano: Pitch,
+        alto: Pitch,
+        tenor: Pitch,
+        bass: Pitch,
+    ) -> Result<Self, SatbError> {
+        SATB::try_new_with_config(
+            root,
+            soprano,
+            alto,
+            tenor,
+            bass,
+            &ValidationConfig::default(),
+        )
+    }
+
+    /// Associated method for creating a new `SATB` harmony validated according to `config`, returning an error describing
+    /// why the harmony is invalid rather than panicking.
+    pub fn try_new_with_config(
+        root: u8,
+        soprano: Pitch,
+        alto: Pitch,
+        tenor: Pitch,
+        bass: Pitch,
+        config: &ValidationConfig,
+    ) -> Result<Self, SatbError> {
+        SATB::validate_harmony(root, &soprano, &alto, &tenor, &bass, config)?;
+        Ok(SATB::new_unchecked(root, soprano, alto, tenor, bass))
+    }
+
+    /// Associated method for creating a new `SATB` harmony declared to have `quality` on `root`, validated according to
+    /// `config`, so suspended and added tone harmonies, whose members the validation of `try_new_with_config` would reject as
+    /// incomplete or non chord tones, are accepted. Each voice must be a member of the harmony, see
+    /// `ChordQuality::pitch_classes`, and each member must sound, except that the fifth of an added tone harmony, such as Cadd9
+    /// or C6, may be dropped to double the root when `config.allow_omitted_fifth` is set. Triads and seventh chords must also
+    /// pass the validation of `try_new_with_config`. Returns an error describing why the harmony is invalid.
+    #[cfg(feature = "std")]
+    pub fn try_with_quality(
+        root: u8,
+        quality: ChordQuality,
+        soprano: Pitch,
+        alto: Pitch,
+        tenor: Pitch,
+        bass: Pitch,
+        config: &ValidationConfig,
+    ) -> Result<Self, SatbError> {
+        let root = root % 12;
+        let voices = [soprano, alto, tenor, bass];
+        if quality.is_tertian() {
+            SATB::validate_harmony(root, &soprano, &alto, &tenor, &bass, config)?;
+        } else {
+            SATB::validate_voice_ranges(&soprano, &alto, &tenor, &bass)?;
+        }
+        let members = quality.pitch_classes(root);
+        let sounding: PitchClassSet = voices.iter().map(|p| p.pitch_class).collect();
+        let omitted_fifth = !quality.is_tertian()
+            && members.len() == 4
+            && config.allow_omitted_fifth
+            && sounding.contains(root)
+            && voices.iter().filter(|p| p.pitch_class == root).count() == 2;
+        let complete = members
+            .iter()
+            .all(|pc| sounding.contains(pc) || (omitted_fifth && pc == (root + 7) % 12));
+        if voices.iter().all(|p| members.contains(p.pitch_class)) && complete {
+            Ok(SATB::new_unchecked(root, soprano, alto, tenor, bass))
+        } else {
+            Err(SatbError::InvalidHarmony)
+        }
+    }
+
+    /// Associated method for creating a new `SATB` harmony from four pitches given in any order. The pitches are sorted
+    /// from lowest to highest and assigned to the bass, tenor, alto and soprano voices respectively, before being validated.
+    pub fn from_unordered_pitches(root: u8, mut pitches: [Pitch; 4]) -> Result<Self, SatbError> {
+        pitches.sort_by_key(|p| (p.octave, p.pitch_class));
+        let [bass, tenor, alto, soprano] = pitches;
+        SATB::try_new(root, soprano, alto, tenor, bass)
+    }
+
+    /// Method that repairs crossed voices in the harmony. When the harmony is invalid and two adjacent voices are crossed,
+    /// e.g. the alto sounds above the soprano, the pitches are swapped between the voices. Returns the repaired harmony if
+    /// it is valid, otherwise returns the error the original harmony was invalid with.
+    pub fn fix_crossings(&self) -> Result<SATB, SatbError> {
+        let config = ValidationConfig::default();
+        let error = match SATB::validate_harmony(
+            self.root,
+            &self.soprano,
+            &self.alto,
+            &self.tenor,
+            &self.bass,
+            &config,
+        ) {
+            Ok(()) => return Ok(self.clone()),
+            Err(e) => e,
+        };
+        let crossed = self.voices().windows(2).any(|pair| {
+            compute_semi_tone_dist_signed(
+                (pair[0].pitch_class, pair[0].octave),
+                (pair[1].pitch_class, pair[1].octave),
+            ) < 0
+        });
+        if !crossed {
+            return Err(error);
+        }
+        SATB::from_unordered_pitches(self.root, self.voices()).map_err(|_| error)
+    }
+
     /// Associated method for creating a new `SATB` harmony, without the checks for validity.
     pub fn new_unchecked(root: u8, soprano: Pitch, alto: Pitch, tenor: Pitch, bass: Pitch) -> Self {
-        let mut pitch_classes = HashSet::new();
-        pitch_classes.insert(soprano.pitch_class);
-        pitch_classes.insert(alto.pitch_class);
-        pitch_classes.insert(tenor.pitch_class);
-        pitch_classes.insert(bass.pitch_class);
+        let pitch_classes: PitchClassSet = [soprano, alto, tenor, bass]
+            .iter()
+            .map(|p| p.pitch_class)
+            .collect();
 
         SATB {
             soprano,
@@ -411,24 +1146,116 @@ impl SATB {
             pitch_classes,
         }
     }
-}
 
-use std::f64::consts::PI;
+    /// Method for accessing the `Pitch` sung by the given `voice`.
+    pub fn voice(&self, voice: Voice) -> &Pitch {
+        match voice {
+            Voice::Soprano => &self.soprano,
+            Voice::Alto => &self.alto,
+            Voice::Tenor => &self.tenor,
+            Voice::Bass => &self.bass,
+        }
+    }
+
+    /// Method for accessing the pitches of all four voices, ordered soprano, alto, tenor, bass.
+    pub fn voices(&self) -> [Pitch; 4] {
+        [self.soprano, self.alto, self.tenor, self.bass]
+    }
+
+    /// Method for computing the role the given `voice` plays within the harmony, i.e. whether it is the root, third, fifth or seventh.
+    /// Returns `ChordMember::NonChordTone` if the pitch class of the voice is none of these.
+    pub fn role_of(&self, voice: Voice) -> ChordMember {
+        chord_member(self.root, self.voice(voice).pitch_class)
+    }
+
+    /// Method for accessing the root of the harmony.
+    pub fn root(&self) -> u8 {
+        self.root
+    }
 
+    /// Method that returns true if any voice of the harmony has the pitch class `pc`, false otherwise.
+    pub fn contains_pitch_class(&self, pc: u8) -> bool {
+        self.pitch_classes.contains(pc)
+    }
+}
+
+#[cfg(feature = "std")]
 impl Harmony for SATB {
-    fn sound_wave(&self, duration: u32, sample_freq: u32) -> Vec<f64> {
-        let mut wave = Vec::new();
-        for _ in 0..duration {
-            for t in (0..sample_freq).map(|x| (x as f64) / (sample_freq as f64)) {
-                wave.push(
-                    f64::sin(self.soprano.frequency * 2.0 * PI * (t as f64))
-                        + f64::sin(self.alto.frequency * 2.0 * PI * (t as f64))
-                        + f64::sin(self.tenor.frequency * 2.0 * PI * (t as f64))
-                        + f64::sin(self.bass.frequency * 2.0 * PI * (t as f64)),
-                );
+    fn frequencies(&self) -> Vec<f64> {
+        self.voices().iter().map(|p| p.frequency).collect()
+    }
+
+    fn sound_wave_samples(&self, num_samples: usize, sample_freq: u32) -> Vec<f64> {
+        self.samples(sample_freq).take(num_samples).collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Harmony for Pitch {
+    fn frequencies(&self) -> Vec<f64> {
+        vec![self.frequency]
+    }
+}
+
+#[cfg(feature = "std")]
+impl Harmony for [Pitch] {
+    fn frequencies(&self) -> Vec<f64> {
+        self.iter().map(|p| p.frequency).collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Harmony for Vec<Pitch> {
+    fn frequencies(&self) -> Vec<f64> {
+        self.as_slice().frequencies()
+    }
+}
+
+/// A function that computes the role the pitch class `pc` plays in a harmony whose root is `root`.
+pub fn chord_member(root: u8, pc: u8) -> ChordMember {
+    if pc == root {
+        ChordMember::Root
+    } else if root.is_third(&pc) {
+        ChordMember::Third
+    } else if root.is_fifth(&pc) {
+        ChordMember::Fifth
+    } else if root.is_seventh(&pc) {
+        ChordMember::Seventh
+    } else {
+        ChordMember::NonChordTone
+    }
+}
+
+/// Private helper function that determines whether the given pitch classes form a complete voicing of a harmony
+/// with its fifth omitted. That is either a triad voiced as a tripled root and a third, or a seventh chord voiced as
+/// a doubled root, a third and a seventh.
+fn is_omitted_fifth_voicing(root: u8, pitch_classes: [u8; 4]) -> bool {
+    // Tally which members are present, and how many voices sound each one
+    let mut roots = 0;
+    let mut thirds = PitchClassSet::new();
+    let mut sevenths = PitchClassSet::new();
+    for pc in pitch_classes {
+        match chord_member(root, pc) {
+            ChordMember::Root => roots += 1,
+            ChordMember::Third => {
+                thirds.insert(pc);
             }
+            ChordMember::Seventh => {
+                sevenths.insert(pc);
+            }
+            ChordMember::Fifth | ChordMember::NonChordTone => return false,
         }
-        wave
+    }
+    // There must be exactly one kind of third, and at most one kind of seventh
+    if thirds.len() != 1 || sevenths.len() > 1 {
+        return false;
+    }
+    if sevenths.is_empty() {
+        // Tripled root and a third
+        roots == 3
+    } else {
+        // Doubled root, a third and a seventh
+        roots == 2
     }
 }
 
@@ -441,7 +1268,7 @@ pub fn compute_semi_tone_dist(pitch1: (u8, u8), pitch2: (u8, u8)) -> u32 {
         } else {
             (pitch2, pitch1)
         };
-        return low.0.dist(&high.0) as u32;
+        low.0.dist(&high.0) as u32
     } else {
         let (high, low) = if pitch1.1 > pitch2.1 {
             (pitch1, pitch2)
@@ -451,7 +1278,7 @@ pub fn compute_semi_tone_dist(pitch1: (u8, u8), pitch2: (u8, u8)) -> u32 {
         // convert to semitones
         let high_semi_tones = 12 * (high.1 as u32) + (high.0 as u32);
         let low_semi_tones = 12 * (low.1 as u32) + (low.0 as u32);
-        return high_semi_tones - low_semi_tones;
+        high_semi_tones - low_semi_tones
     }
 }
 
@@ -472,28 +1299,21 @@ pub fn validate_voice_ranges(
     bass: (u8, u8),
 ) -> bool {
     // Check the bass
-    if (bass.1 < 2 || bass.1 > 4)
-        || (bass.1 == 2 && bass.0 < 4)
-        || (bass.1 == 4 && bass.0 > 0)
+    if !Voice::Bass.contains_position(bass.0, bass.1)
         || (bass.1 == tenor.1 && bass.0 > tenor.0)
         || (compute_semi_tone_dist(bass, tenor) > 19)
-
     {
         return false;
     }
     // Check the tenor
-    if (tenor.1 < 3 || tenor.1 > 4)
-        || (tenor.1 == 3 && tenor.0 < 3)
-        || (tenor.1 == 4 && tenor.0 > 6)
+    if !Voice::Tenor.contains_position(tenor.0, tenor.1)
         || (tenor.1 == alto.1 && tenor.0 > alto.0)
         || (compute_semi_tone_dist(tenor, alto) > 12)
     {
         return false;
     }
     // Check alto
-    if (alto.1 < 3 || alto.1 > 5)
-        || (alto.1 == 3 && alto.0 < 7)
-        || (alto.1 == 5 && alto.0 > 1)
+    if !Voice::Alto.contains_position(alto.0, alto.1)
         || (alto.1 == soprano.1 && alto.0 > soprano.0)
         || (compute_semi_tone_dist(alto, soprano) > 12)
     {
@@ -501,14 +1321,7 @@ pub fn validate_voice_ranges(
     }
 
     // Check soprano
-    if (soprano.1 < 4 || soprano.1 > 5)
-        || (soprano.1 == 4 && soprano.0 < 2)
-        || (soprano.1 == 5 && soprano.0 > 6)
-    {
-        return false;
-    }
-
-    true
+    Voice::Soprano.contains_position(soprano.0, soprano.1)
 }
 
 /// A function for determining whether or not that the given tuples of (pitch_class, octave) form a valid SATB harmony in classical voice leading.
@@ -520,6 +1333,26 @@ pub fn validate_harmony(
     alto: (u8, u8),
     tenor: (u8, u8),
     bass: (u8, u8),
+) -> bool {
+    validate_harmony_with_config(
+        root,
+        soprano,
+        alto,
+        tenor,
+        bass,
+        &ValidationConfig::default(),
+    )
+}
+
+/// A function for determining whether or not the given tuples of (pitch_class, octave) form a valid SATB harmony,
+/// under the validation policy given by `config`.
+pub fn validate_harmony_with_config(
+    root: u8,
+    soprano: (u8, u8),
+    alto: (u8, u8),
+    tenor: (u8, u8),
+    bass: (u8, u8),
+    config: &ValidationConfig,
 ) -> bool {
     // Validate the range for each voice
     if !validate_voice_ranges(soprano, alto, tenor, bass) {
@@ -543,14 +1376,25 @@ pub fn validate_harmony(
     if soprano.0 != root && soprano.0 != alto.0 && soprano.0 != tenor.0 && soprano.0 != bass.0 {
         distinct_voices += 1;
     }
+    // Voicings that omit the fifth are decided by which chord members are present, rather than by the count of distinct voices
+    if config.allow_omitted_fifth
+        && is_omitted_fifth_voicing(root, [soprano.0, alto.0, tenor.0, bass.0])
+    {
+        return true;
+    }
     // Ensure we have either 2, 3 or 4 distinct voices, all other cases are invalid harmonies.
-    // The case where we have two distinc voices, all voices need to be either the root or the third only.
-    if distinct_voices < 3 {
-        return false;
+    // The case where we have two distinct voices, all voices need to be either the root or the third only. A tripled root
+    // and a third only reaches this point when omitted fifths are disallowed, and is then rejected.
+    let pitch_classes = [soprano.0, alto.0, tenor.0, bass.0];
+    if distinct_voices == 2 {
+        pitch_classes
+            .iter()
+            .all(|pc| *pc == root || root.is_third(pc))
+            && !is_omitted_fifth_voicing(root, pitch_classes)
     } else if distinct_voices == 3 {
         // We have a triad in this case, check that the voicing is valid for its inversion
         if bass.0 == root {
-            return (tenor.0 == root
+            (tenor.0 == root
                 && ((root.is_third(&alto.0) && root.is_fifth(&soprano.0))
                     || (root.is_third(&soprano.0) && root.is_fifth(&alto.0))))
                 || (alto.0 == root
@@ -558,7 +1402,7 @@ pub fn validate_harmony(
                         || (root.is_third(&soprano.0) && root.is_fifth(&tenor.0))))
                 || (soprano.0 == root
                     && ((root.is_third(&tenor.0) && root.is_fifth(&alto.0))
-                        || (root.is_third(&alto.0) && root.is_fifth(&tenor.0))));
+                        || (root.is_third(&alto.0) && root.is_fifth(&tenor.0))))
         } else if root.is_third(&bass.0) {
             // Check if we have a diminished triad of some kind
             if (root.is_fifth(&soprano.0) && root.dist(&soprano.0) == 6)
@@ -566,34 +1410,28 @@ pub fn validate_harmony(
                 || (root.is_fifth(&tenor.0) && root.dist(&tenor.0) == 6)
             {
                 // Validate that at least one voice is the third, i.e that the bass is doubled
-                return root.is_third(&soprano.0)
-                    || root.is_third(&alto.0)
-                    || root.is_third(&tenor.0);
+                root.is_third(&soprano.0) || root.is_third(&alto.0) || root.is_third(&tenor.0)
             } else {
                 // Validate that the bass is not doubled in this case, that one voice is the root and other two are fifths
                 // or two voices are the root and one voice is the fifth
-                return (!root.is_third(&soprano.0)
-                    && !root.is_third(&alto.0)
-                    && !root.is_third(&tenor.0))
+                (!root.is_third(&soprano.0) && !root.is_third(&alto.0) && !root.is_third(&tenor.0))
                     && ((root.is_fifth(&soprano.0)
                         || root.is_fifth(&alto.0)
                         || root.is_fifth(&tenor.0))
-                        && (root == soprano.0 || root == alto.0 || root == tenor.0));
+                        && (root == soprano.0 || root == alto.0 || root == tenor.0))
             }
         } else if root.is_fifth(&bass.0) {
             // Ensure that atleast one other voice is the bass
-            return (root.is_fifth(&soprano.0)
-                || root.is_fifth(&alto.0)
-                || root.is_fifth(&tenor.0))
+            (root.is_fifth(&soprano.0) || root.is_fifth(&alto.0) || root.is_fifth(&tenor.0))
                 && ((root.is_third(&soprano.0)
                     || root.is_third(&alto.0)
                     || root.is_third(&tenor.0))
-                    && (root == soprano.0 || root == alto.0 || root == tenor.0));
+                    && (root == soprano.0 || root == alto.0 || root == tenor.0))
         } else {
-            return false;
+            false
         }
     } else if distinct_voices == 4 {
-        return (root == bass.0 || root == tenor.0 || root == alto.0 || root == soprano.0)
+        (root == bass.0 || root == tenor.0 || root == alto.0 || root == soprano.0)
             && (root.is_third(&bass.0)
                 || root.is_third(&tenor.0)
                 || root.is_third(&alto.0)
@@ -605,9 +1443,9 @@ pub fn validate_harmony(
             && (root.is_seventh(&bass.0)
                 || root.is_seventh(&tenor.0)
                 || root.is_seventh(&alto.0)
-                || root.is_seventh(&soprano.0));
+                || root.is_seventh(&soprano.0))
     } else {
-        return false;
+        false
     }
 }
 
@@ -667,4 +1505,534 @@ mod test {
         assert!(result);
     }
 
+    #[test]
+    fn test_role_of_seventh_chord_inversions() {
+        // G7 in root position, first, second and third inversion
+        let root_position = SATB::new(
+            7,
+            (2, 4).into(),
+            (11, 3).into(),
+            (5, 3).into(),
+            (7, 2).into(),
+        );
+        assert_eq!(root_position.role_of(Voice::Bass), ChordMember::Root);
+        assert_eq!(root_position.role_of(Voice::Tenor), ChordMember::Seventh);
+        assert_eq!(root_position.role_of(Voice::Alto), ChordMember::Third);
+        assert_eq!(root_position.role_of(Voice::Soprano), ChordMember::Fifth);
+
+        let first_inversion = SATB::new(
+            7,
+            (2, 4).into(),
+            (7, 3).into(),
+            (5, 3).into(),
+            (11, 2).into(),
+        );
+        assert_eq!(first_inversion.role_of(Voice::Bass), ChordMember::Third);
+        assert_eq!(first_inversion.role_of(Voice::Tenor), ChordMember::Seventh);
+        assert_eq!(first_inversion.role_of(Voice::Alto), ChordMember::Root);
+        assert_eq!(first_inversion.role_of(Voice::Soprano), ChordMember::Fifth);
+
+        let second_inversion = SATB::new(
+            7,
+            (7, 4).into(),
+            (11, 3).into(),
+            (5, 3).into(),
+            (2, 3).into(),
+        );
+        assert_eq!(second_inversion.role_of(Voice::Bass), ChordMember::Fifth);
+        assert_eq!(second_inversion.role_of(Voice::Tenor), ChordMember::Seventh);
+        assert_eq!(second_inversion.role_of(Voice::Alto), ChordMember::Third);
+        assert_eq!(second_inversion.role_of(Voice::Soprano), ChordMember::Root);
+
+        let third_inversion = SATB::new(
+            7,
+            (2, 4).into(),
+            (11, 3).into(),
+            (7, 3).into(),
+            (5, 2).into(),
+        );
+        assert_eq!(third_inversion.role_of(Voice::Bass), ChordMember::Seventh);
+        assert_eq!(third_inversion.role_of(Voice::Tenor), ChordMember::Root);
+        assert_eq!(third_inversion.role_of(Voice::Alto), ChordMember::Third);
+        assert_eq!(third_inversion.role_of(Voice::Soprano), ChordMember::Fifth);
+    }
+
+    #[test]
+    fn test_contains_pitch_class() {
+        let dominant_seventh = SATB::new(
+            7,
+            (2, 4).into(),
+            (11, 3).into(),
+            (5, 3).into(),
+            (7, 2).into(),
+        );
+        for pc in [7, 11, 2, 5] {
+            assert!(dominant_seventh.contains_pitch_class(pc));
+        }
+        assert!(!dominant_seventh.contains_pitch_class(0));
+        assert!(!dominant_seventh.contains_pitch_class(4));
+    }
+
+    #[test]
+    fn test_omitted_fifth_tripled_root() {
+        // Final cadence I chord with a tripled root and no fifth
+        let result = validate_harmony(0, (0, 5), (4, 4), (0, 4), (0, 3));
+        assert!(result);
+
+        let config = ValidationConfig {
+            allow_omitted_fifth: false,
+        };
+        let result = validate_harmony_with_config(0, (0, 5), (4, 4), (0, 4), (0, 3), &config);
+        assert!(!result);
+
+        // A doubled root and doubled third without a fifth is accepted regardless of the policy
+        assert!(validate_harmony(0, (4, 5), (4, 4), (0, 4), (0, 3)));
+        assert!(validate_harmony_with_config(
+            0,
+            (4, 5),
+            (4, 4),
+            (0, 4),
+            (0, 3),
+            &config
+        ));
+    }
+
+    #[test]
+    fn test_doubled_third_without_fifth() {
+        // C-E-E-C, the root and third each doubled, as accepted before omitted fifths were configurable
+        let satb = SATB::new(
+            0,
+            (4, 5).into(),
+            (4, 4).into(),
+            (0, 4).into(),
+            (0, 3).into(),
+        );
+        assert_eq!(satb.role_of(Voice::Soprano), ChordMember::Third);
+        let config = ValidationConfig {
+            allow_omitted_fifth: false,
+        };
+        assert!(SATB::try_new_with_config(
+            0,
+            (4, 5).into(),
+            (4, 4).into(),
+            (0, 4).into(),
+            (0, 3).into(),
+            &config
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_omitted_fifth_seventh_chord() {
+        // G7 with a doubled root, the third and the seventh
+        let result = validate_harmony(7, (5, 4), (11, 3), (7, 3), (7, 2));
+        assert!(result);
+        let satb = SATB::new(
+            7,
+            (5, 4).into(),
+            (11, 3).into(),
+            (7, 3).into(),
+            (7, 2).into(),
+        );
+        assert_eq!(satb.role_of(Voice::Soprano), ChordMember::Seventh);
+
+        let config = ValidationConfig {
+            allow_omitted_fifth: false,
+        };
+        let result = validate_harmony_with_config(7, (5, 4), (11, 3), (7, 3), (7, 2), &config);
+        assert!(!result);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_omitted_fifth_disallowed_panics() {
+        let config = ValidationConfig {
+            allow_omitted_fifth: false,
+        };
+        SATB::new_with_config(
+            0,
+            (0, 5).into(),
+            (4, 4).into(),
+            (0, 4).into(),
+            (0, 3).into(),
+            &config,
+        );
+    }
+
+    #[test]
+    fn test_spacing_with_tenor_in_unison_with_bass() {
+        // The tenor doubles the bass, while the alto lies more than an octave above the tenor
+        let result = SATB::try_new(
+            4,
+            (11, 4).into(),
+            (7, 4).into(),
+            (4, 3).into(),
+            (4, 3).into(),
+        );
+        assert_eq!(
+            result,
+            Err(SatbError::Spacing {
+                upper: Voice::Alto,
+                lower: Voice::Tenor
+            })
+        );
+    }
+
+    #[test]
+    fn test_voice_ranges_table() {
+        for (i, (voice, low, high)) in VOICE_RANGES.iter().enumerate() {
+            assert_eq!(Voice::ALL[i], *voice);
+            assert_eq!(voice.range(), (*low, *high));
+            assert_eq!(*low, Pitch::from((low.pitch_class, low.octave)));
+        }
+        assert_eq!(
+            Voice::Bass.range(),
+            (Pitch::from((4, 2)), Pitch::from((0, 4)))
+        );
+    }
+
+    #[test]
+    fn test_voice_contains_agrees_with_validate_voice_ranges() {
+        // For each voice a voicing with that voice on a boundary of its range, and the same voicing with the voice
+        // moved one semitone outside of its range
+        let cases = [
+            (Voice::Bass, [(4, 4), (0, 4), (7, 3), (4, 2)], (3, 2)),
+            (Voice::Bass, [(0, 5), (7, 4), (4, 4), (0, 4)], (1, 4)),
+            (Voice::Tenor, [(3, 4), (7, 3), (3, 3), (0, 3)], (2, 3)),
+            (Voice::Tenor, [(2, 5), (9, 4), (6, 4), (0, 3)], (7, 4)),
+            (Voice::Alto, [(4, 4), (7, 3), (4, 3), (0, 3)], (6, 3)),
+            (Voice::Alto, [(4, 5), (1, 5), (4, 4), (0, 3)], (2, 5)),
+            (Voice::Soprano, [(2, 4), (7, 3), (4, 3), (0, 3)], (1, 4)),
+            (Voice::Soprano, [(6, 5), (0, 5), (0, 4), (0, 3)], (7, 5)),
+        ];
+        for (voice, voicing, outside) in cases {
+            let index = voice as usize;
+            let [s, a, t, b] = voicing;
+            assert!(validate_voice_ranges(s, a, t, b));
+            assert!(voice.contains(&Pitch::from(voicing[index])));
+
+            let mut moved = voicing;
+            moved[index] = outside;
+            let [s, a, t, b] = moved;
+            assert!(!validate_voice_ranges(s, a, t, b));
+            assert!(!voice.contains(&Pitch::from(outside)));
+        }
+    }
+
+    #[test]
+    fn test_try_new_errors() {
+        let result = SATB::try_new(
+            0,
+            (0, 5).into(),
+            (4, 4).into(),
+            (7, 3).into(),
+            (0, 1).into(),
+        );
+        assert_eq!(result, Err(SatbError::OutOfRange(Voice::Bass)));
+
+        let result = SATB::try_new(
+            0,
+            (0, 5).into(),
+            (4, 4).into(),
+            (7, 3).into(),
+            (0, 3).into(),
+        );
+        assert!(result.is_ok());
+
+        // Alto more than an octave below the soprano
+        let result = SATB::try_new(
+            0,
+            (4, 5).into(),
+            (0, 4).into(),
+            (7, 3).into(),
+            (0, 3).into(),
+        );
+        assert_eq!(
+            result,
+            Err(SatbError::Spacing {
+                upper: Voice::Soprano,
+                lower: Voice::Alto
+            })
+        );
+    }
+
+    #[test]
+    fn test_fix_crossings() {
+        // The alto sounds above the soprano
+        let crossed = SATB::new_unchecked(
+            0,
+            (4, 4).into(),
+            (0, 5).into(),
+            (7, 3).into(),
+            (0, 3).into(),
+        );
+        assert_eq!(
+            SATB::try_new(
+                0,
+                crossed.soprano,
+                crossed.alto,
+                crossed.tenor,
+                crossed.bass
+            ),
+            Err(SatbError::Crossing {
+                upper: Voice::Soprano,
+                lower: Voice::Alto
+            })
+        );
+        let fixed = crossed.fix_crossings().unwrap();
+        assert_eq!(fixed.soprano, Pitch::from((0, 5)));
+        assert_eq!(fixed.alto, Pitch::from((4, 4)));
+        assert_eq!(fixed.tenor, crossed.tenor);
+        assert_eq!(fixed.bass, crossed.bass);
+    }
+
+    #[test]
+    fn test_fix_crossings_unfixable_spacing() {
+        // The alto is crossed above the soprano, but swapping leaves the tenor more than an octave below the alto
+        let crossed = SATB::new_unchecked(
+            0,
+            (7, 4).into(),
+            (0, 5).into(),
+            (4, 3).into(),
+            (0, 3).into(),
+        );
+        let error = SATB::try_new(
+            0,
+            crossed.soprano,
+            crossed.alto,
+            crossed.tenor,
+            crossed.bass,
+        )
+        .unwrap_err();
+        assert_eq!(crossed.fix_crossings(), Err(error));
+    }
+
+    #[test]
+    fn test_from_unordered_pitches() {
+        let pitches = [
+            Pitch::from((4, 4)),
+            Pitch::from((0, 5)),
+            Pitch::from((0, 3)),
+            Pitch::from((7, 3)),
+        ];
+        let satb = SATB::from_unordered_pitches(0, pitches).unwrap();
+        assert_eq!(
+            satb.voices(),
+            [
+                Pitch::from((0, 5)),
+                Pitch::from((4, 4)),
+                Pitch::from((7, 3)),
+                Pitch::from((0, 3))
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "std"))]
+    fn test_core_without_std() {
+        // The floating point math of the core types falls back on libm
+        let a_440 = Pitch::from_pitch_class(9, 4);
+        assert!(a_440.cents_offset().abs() < 1e-9);
+        let sharp = Pitch::new(a_440.frequency() * 1.01, 9, 4);
+        assert!((sharp.cents_offset() - 17.226).abs() < 1e-3);
+
+        let chord = SATB::new(
+            0,
+            (4, 5).into(),
+            (7, 4).into(),
+            (0, 4).into(),
+            (0, 3).into(),
+        );
+        assert!(chord.contains_pitch_class(7));
+        assert!(!chord.contains_pitch_class(2));
+        assert_eq!(chord.role_of(Voice::Soprano), ChordMember::Third);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_pitch_harmony() {
+        let a = Pitch::from((9, 4));
+        let wave = a.sound_wave_secs(0.1, 44100);
+        assert_eq!(wave.len(), 4410);
+        // A 440 Hz sine crosses zero twice per cycle, 88 times in a tenth of a second
+        let crossings = wave
+            .windows(2)
+            .filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0))
+            .count();
+        assert!((87..=89).contains(&crossings), "{}", crossings);
+
+        let cluster: Vec<Pitch> = (0..5).map(|pc| Pitch::from((pc, 4))).collect();
+        let wave = cluster.sound_wave(1, 8000);
+        assert!(wave.iter().all(|s| s.abs() <= 1.0));
+        assert_eq!(wave, cluster[..].sound_wave(1, 8000));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_harmony_frequencies() {
+        let chord = SATB::new(
+            0,
+            (0, 5).into(),
+            (7, 4).into(),
+            (4, 4).into(),
+            (0, 3).into(),
+        );
+        let frequencies = chord.frequencies();
+        let expected = [523.25, 392.0, 329.63, 130.81];
+        assert_eq!(frequencies.len(), 4);
+        for (f, e) in frequencies.iter().zip(expected) {
+            assert!((f - e).abs() < 0.01, "{} {}", f, e);
+        }
+
+        /// A harmony implementing only the required method
+        struct Drone(Vec<f64>);
+        impl Harmony for Drone {
+            fn frequencies(&self) -> Vec<f64> {
+                self.0.clone()
+            }
+        }
+        let drone = Drone(frequencies);
+        let wave = drone.sound_wave(1, 8000);
+        let expected = chord.sound_wave(1, 8000);
+        assert_eq!(wave.len(), expected.len());
+        assert!(wave
+            .iter()
+            .zip(&expected)
+            .all(|(w, e)| (w - e).abs() < 1e-12));
+
+        // Harmony stays dyn compatible, so different harmonies can be rendered through one trait object
+        let harmonies: [&dyn Harmony; 3] = [&chord, &drone, &Pitch::from((9, 4))];
+        for harmony in harmonies {
+            assert_eq!(harmony.sound_wave(1, 8000).len(), 8000);
+        }
+        let boxed: Box<dyn Harmony> = Box::new(drone);
+        assert_eq!(boxed.sound_wave(1, 8000), wave);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_sound_wave_parallel_matches_serial() {
+        let chord = SATB::new(
+            0,
+            (0, 5).into(),
+            (7, 4).into(),
+            (4, 4).into(),
+            (0, 3).into(),
+        );
+        let serial = chord.voices()[..].sound_wave(10, 44100);
+        let parallel = chord.sound_wave_parallel(10, 44100);
+        assert_eq!(serial.len(), parallel.len());
+        assert_eq!(
+            serial[..PARALLEL_CHUNK_SAMPLES],
+            parallel[..PARALLEL_CHUNK_SAMPLES]
+        );
+        assert!(serial
+            .iter()
+            .zip(&parallel)
+            .all(|(s, p)| (s - p).abs() < 1e-9));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_loops_are_seamless() {
+        let chord = SATB::new(
+            0,
+            (0, 5).into(),
+            (7, 4).into(),
+            (4, 4).into(),
+            (0, 3).into(),
+        );
+        let streamed: Vec<f64> = chord.loop_samples(44100).take(44100).collect();
+        assert_eq!(streamed, chord.voices()[..].sound_wave(1, 44100));
+
+        // The largest step between consecutive samples of the continuous wave
+        let steepest = streamed
+            .windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0, f64::max);
+        for loop_region in [0.3, 0.5] {
+            let wave = chord.render_looped(2.0, 44100, loop_region);
+            assert_eq!(wave.len(), 88200);
+            let length = audio::seamless_loop_length(
+                &chord.frequencies(),
+                audio::sample_count(loop_region, 44100),
+                44100,
+            );
+            for junction in (length..wave.len()).step_by(length) {
+                let step = (wave[junction] - wave[junction - 1]).abs();
+                assert!(step <= steepest, "{} {}", step, steepest);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_render_interleaved_f32() {
+        let chord = SATB::new(
+            0,
+            (0, 5).into(),
+            (7, 4).into(),
+            (4, 4).into(),
+            (0, 3).into(),
+        );
+        let options = RenderOptions::new();
+        let mono = chord.render(0.5, 8000, &options);
+        let stereo = chord.render_interleaved_f32(0.5, 8000, &options);
+        assert_eq!(stereo.len(), 2 * mono.len());
+        for (frame, sample) in stereo.chunks(2).zip(&mono) {
+            assert_eq!(frame, [*sample as f32; 2]);
+        }
+
+        // Centered voices sound equally in both channels, at the mono level scaled by 1 / sqrt(2)
+        let centered = chord.render_interleaved_f32(0.5, 8000, &options.pan(VoicePans::centered()));
+        assert_eq!(centered.len(), stereo.len());
+        for (frame, sample) in centered.chunks(2).zip(&mono) {
+            assert_eq!(frame[0], frame[1]);
+            assert!((frame[0] as f64 - sample / 2f64.sqrt()).abs() < 1e-6);
+        }
+
+        // The soprano panned hard left only sounds at the even indices
+        let soprano_left = VoicePans {
+            soprano: -1.0,
+            ..VoicePans::centered()
+        };
+        let panned = chord.render_interleaved_f32(
+            0.5,
+            8000,
+            &options.solo(Voice::Soprano).pan(soprano_left),
+        );
+        assert!(panned.iter().step_by(2).any(|s| s.abs() > 0.5));
+        assert!(panned.iter().skip(1).step_by(2).all(|s| s.abs() < 1e-6));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_sample_matches_sound_wave() {
+        let chord = SATB::new(
+            0,
+            (0, 5).into(),
+            (7, 4).into(),
+            (4, 4).into(),
+            (0, 3).into(),
+        );
+        let wave = chord.sound_wave(2, 8000);
+        for (k, expected) in wave.iter().enumerate().step_by(7) {
+            let value = chord.sample(k as f64 / 8000.0);
+            assert!(
+                (value - expected).abs() < 1e-9,
+                "{} {} {}",
+                k,
+                value,
+                expected
+            );
+        }
+        // The wave runs on through each second boundary rather than restarting
+        for (k, expected) in wave.iter().enumerate().skip(7990).take(20) {
+            let value = chord.sample(k as f64 / 8000.0);
+            assert!((value - expected).abs() < 1e-9);
+        }
+        assert!((chord.sample(1.0) - chord.sample(0.0)).abs() > 1e-3);
+        assert_eq!(Vec::<Pitch>::new().sample(0.5), 0.0);
+    }
 }
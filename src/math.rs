@@ -0,0 +1,37 @@
+//! Floating point functions missing from `core`, provided by `std` when it is enabled and by `libm` otherwise.
+
+/// A function that returns the base 2 logarithm of `x`.
+#[cfg(feature = "std")]
+pub(crate) fn log2(x: f64) -> f64 {
+    x.log2()
+}
+
+/// A function that returns the base 2 logarithm of `x`.
+#[cfg(not(feature = "std"))]
+pub(crate) fn log2(x: f64) -> f64 {
+    libm::log2(x)
+}
+
+/// A function that rounds `x` to the nearest integer, rounding half way cases away from zero.
+#[cfg(feature = "std")]
+pub(crate) fn round(x: f64) -> f64 {
+    x.round()
+}
+
+/// A function that rounds `x` to the nearest integer, rounding half way cases away from zero.
+#[cfg(not(feature = "std"))]
+pub(crate) fn round(x: f64) -> f64 {
+    libm::round(x)
+}
+
+/// A function that returns 2 raised to the power `x`.
+#[cfg(feature = "std")]
+pub(crate) fn exp2(x: f64) -> f64 {
+    x.exp2()
+}
+
+/// A function that returns 2 raised to the power `x`.
+#[cfg(not(feature = "std"))]
+pub(crate) fn exp2(x: f64) -> f64 {
+    libm::exp2(x)
+}
@@ -0,0 +1,182 @@
+//! A single melodic line, a timed sequence of pitches and rests given as note values at a tempo.
+use crate::progression::motion;
+use crate::{NoteValue, Pitch, Rest, Tempo};
+use std::cmp::Ordering;
+
+/// Enumerates the directions a melody can move in from one note to the next.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Contour {
+    /// The next note is higher
+    Up,
+    /// The next note is lower
+    Down,
+    /// The next note repeats the pitch
+    Same,
+}
+
+/// A struct that represents a melody, a sequence of single pitches and rests, each lasting for a note value at the melody's tempo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Melody {
+    /// The pitch of each event, or `None` for a rest, along with its note value
+    events: Vec<(Option<Pitch>, NoteValue)>,
+    /// The tempo the note values are measured at
+    tempo: Tempo,
+    /// When true consecutive notes are joined without being re-attacked
+    legato: bool,
+}
+
+impl Melody {
+    /// Associated method for creating a new, empty `Melody` at `tempo`, with each note re-attacked.
+    pub fn new(tempo: Tempo) -> Self {
+        Melody {
+            events: Vec::new(),
+            tempo,
+            legato: false,
+        }
+    }
+
+    /// Builder method that joins consecutive notes when `legato` is true, so each note continues the phase of the note before it
+    /// rather than being re-attacked, with any declick fade applied only where the line starts and stops. Rests break the line.
+    pub fn legato(mut self, legato: bool) -> Self {
+        self.legato = legato;
+        self
+    }
+
+    /// Method for appending a note of `pitch` lasting for the note value `value` to the end of the melody.
+    pub fn push(&mut self, pitch: Pitch, value: NoteValue) {
+        self.events.push((Some(pitch), value));
+    }
+
+    /// Method for appending `rest` to the end of the melody.
+    pub fn push_rest(&mut self, rest: Rest) {
+        self.events.push((None, rest.0));
+    }
+
+    /// Method that returns an iterator over the events of the melody in order, each the pitch of a note, or `None` for a rest,
+    /// along with its note value.
+    pub fn iter(&self) -> impl Iterator<Item = &(Option<Pitch>, NoteValue)> {
+        self.events.iter()
+    }
+
+    /// Method that returns the pitches of the notes of the melody in order, skipping rests, e.g. to harmonize or check it.
+    pub fn pitches(&self) -> Vec<Pitch> {
+        self.events.iter().filter_map(|(pitch, _)| *pitch).collect()
+    }
+
+    /// Method for accessing the tempo of the melody.
+    pub fn tempo(&self) -> Tempo {
+        self.tempo
+    }
+
+    /// Method for accessing whether consecutive notes are joined.
+    pub fn is_legato(&self) -> bool {
+        self.legato
+    }
+
+    /// Method that returns the number of events, notes and rests, in the melody.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Method that returns true if the melody has no events.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Method that returns the total duration of the melody in seconds, including rests.
+    pub fn duration(&self) -> f64 {
+        self.events
+            .iter()
+            .map(|(_, value)| value.seconds(self.tempo))
+            .sum()
+    }
+
+    /// Method that returns the lowest and highest pitches of the melody, or `None` if it has no notes.
+    pub fn range(&self) -> Option<(Pitch, Pitch)> {
+        let pitches = self.pitches();
+        Some((*pitches.iter().min()?, *pitches.iter().max()?))
+    }
+
+    /// Method that returns the largest interval in semitones between consecutive notes of the melody, ignoring rests,
+    /// or `None` if it has fewer than two notes.
+    pub fn largest_leap(&self) -> Option<u32> {
+        self.pitches()
+            .windows(2)
+            .map(|w| motion(&w[0], &w[1]).unsigned_abs())
+            .max()
+    }
+
+    /// Method that returns the direction the melody moves in from each note to the next, ignoring rests.
+    pub fn contour(&self) -> Vec<Contour> {
+        self.pitches()
+            .windows(2)
+            .map(|w| match motion(&w[0], &w[1]).cmp(&0) {
+                Ordering::Greater => Contour::Up,
+                Ordering::Less => Contour::Down,
+                Ordering::Equal => Contour::Same,
+            })
+            .collect()
+    }
+}
+
+impl<'a> IntoIterator for &'a Melody {
+    type Item = &'a (Option<Pitch>, NoteValue);
+    type IntoIter = std::slice::Iter<'a, (Option<Pitch>, NoteValue)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.events.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The opening of "Twinkle, Twinkle, Little Star" in C major
+    fn twinkle() -> Melody {
+        let mut melody = Melody::new(Tempo::new(120.0).unwrap());
+        for (pitch, value) in [
+            ((0, 4), NoteValue::Quarter),
+            ((0, 4), NoteValue::Quarter),
+            ((7, 4), NoteValue::Quarter),
+            ((7, 4), NoteValue::Quarter),
+            ((9, 4), NoteValue::Quarter),
+            ((9, 4), NoteValue::Quarter),
+            ((7, 4), NoteValue::Half),
+        ] {
+            melody.push(Pitch::from(pitch), value);
+        }
+        melody
+    }
+
+    #[test]
+    fn test_melody_analysis() {
+        let mut melody = twinkle();
+        assert_eq!(melody.largest_leap(), Some(7));
+        assert_eq!(
+            melody.range(),
+            Some((Pitch::from((0, 4)), Pitch::from((9, 4))))
+        );
+        assert_eq!(
+            melody.contour(),
+            vec![
+                Contour::Same,
+                Contour::Up,
+                Contour::Same,
+                Contour::Up,
+                Contour::Same,
+                Contour::Down
+            ]
+        );
+        assert_eq!(melody.duration(), 4.0);
+
+        // Rests take time but are skipped by the analysis
+        melody.push_rest(Rest(NoteValue::Half));
+        melody.push(Pitch::from((0, 6)), NoteValue::Quarter);
+        assert_eq!(melody.len(), 9);
+        assert_eq!(melody.duration(), 5.5);
+        assert_eq!(melody.largest_leap(), Some(17));
+        assert_eq!(melody.pitches().len(), 8);
+        assert_eq!(Melody::new(Tempo::new(60.0).unwrap()).range(), None);
+    }
+}
@@ -0,0 +1,376 @@
+//! Export of a `Progression` to a standard MIDI file, available with the `midi` feature.
+use crate::{Progression, Voice};
+use std::fmt::Display;
+use std::path::Path;
+
+/// The range in semitones a pitch bend of full deflection is assumed to cover, the General MIDI default.
+pub const PITCH_BEND_RANGE_SEMITONES: f64 = 2.0;
+
+/// The velocity every note is written with.
+const NOTE_VELOCITY: u8 = 80;
+
+/// Enumerates the errors that can occur when exporting a progression to MIDI.
+#[derive(Debug)]
+pub enum MidiError {
+    /// The tempo is not a positive, finite number of beats per minute.
+    InvalidTempo,
+    /// The ticks per beat is zero or does not fit in the 15 bits of a metrical time division.
+    InvalidDivision(u16),
+    /// The pitch sung by `voice` in harmony `index` lies outside of the MIDI note range.
+    NoteOutOfRange { index: usize, voice: Voice },
+    /// The file could not be written.
+    Io(std::io::Error),
+}
+
+impl Display for MidiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MidiError::InvalidTempo => {
+                write!(f, "the tempo must be a positive number of beats per minute")
+            }
+            MidiError::InvalidDivision(ticks_per_beat) => write!(
+                f,
+                "{} ticks per beat does not lie within 1 to 32767",
+                ticks_per_beat
+            ),
+            MidiError::NoteOutOfRange { index, voice } => write!(
+                f,
+                "the {} of harmony {} is outside of the midi note range",
+                voice, index
+            ),
+            MidiError::Io(e) => write!(f, "failed to write midi file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for MidiError {}
+
+impl From<std::io::Error> for MidiError {
+    fn from(e: std::io::Error) -> Self {
+        MidiError::Io(e)
+    }
+}
+
+/// Helper function that appends `value` to `bytes` as a MIDI variable length quantity.
+fn write_variable_length(bytes: &mut Vec<u8>, value: u32) {
+    let mut groups = vec![(value & 0x7f) as u8];
+    let mut value = value >> 7;
+    while value > 0 {
+        groups.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.extend(groups.iter().rev());
+}
+
+/// Helper function that appends a track chunk holding `events` to `bytes`, terminated by an end of track event `end_delta`
+/// ticks after the last event, so silence at the end of the track is kept.
+fn write_track(bytes: &mut Vec<u8>, mut events: Vec<u8>, end_delta: u32) {
+    write_variable_length(&mut events, end_delta);
+    events.extend_from_slice(&[0xff, 0x2f, 0x00]);
+    bytes.extend_from_slice(b"MTrk");
+    bytes.extend_from_slice(&(events.len() as u32).to_be_bytes());
+    bytes.extend(events);
+}
+
+impl Progression {
+    /// Method that encodes the progression as a format 1 standard MIDI file. The first track holds the tempo, followed by one
+    /// track per voice ordered soprano, alto, tenor, bass, each on its own channel. Harmony durations in seconds are converted
+    /// to ticks at `tempo_bpm` beats per minute and `ticks_per_beat`. When `pitch_bend` is set, a pitch bend is sent before each
+    /// note whose deviation from equal temperament differs from the bend in effect on its channel, assuming a bend range of
+    /// `PITCH_BEND_RANGE_SEMITONES`. Channels start centered, so notes in equal temperament need no bend unless they follow a
+    /// bent note, which is then undone. Every track ends after the progression's trailing rest. Returns an error if the tempo
+    /// is not positive and finite, or if `ticks_per_beat` is 0 or 0x8000 or more, which would not encode ticks per beat.
+    pub fn to_midi(
+        &self,
+        ticks_per_beat: u16,
+        tempo_bpm: f64,
+        pitch_bend: bool,
+    ) -> Result<Vec<u8>, MidiError> {
+        if !(tempo_bpm.is_finite() && tempo_bpm > 0.0) {
+            return Err(MidiError::InvalidTempo);
+        }
+        if ticks_per_beat == 0 || ticks_per_beat >= 0x8000 {
+            return Err(MidiError::InvalidDivision(ticks_per_beat));
+        }
+        let ticks_per_second = tempo_bpm / 60.0 * ticks_per_beat as f64;
+        // The tick every track ends at, after the trailing rest
+        let end = (self.duration() * ticks_per_second).round() as u32;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"MThd");
+        bytes.extend_from_slice(&6u32.to_be_bytes());
+        bytes.extend_from_slice(&1u16.to_be_bytes());
+        bytes.extend_from_slice(&5u16.to_be_bytes());
+        bytes.extend_from_slice(&ticks_per_beat.to_be_bytes());
+
+        let micros_per_beat = (60_000_000.0 / tempo_bpm).round() as u32;
+        let mut tempo = vec![0x00, 0xff, 0x51, 0x03];
+        tempo.extend_from_slice(&micros_per_beat.to_be_bytes()[1..]);
+        write_track(&mut bytes, tempo, end);
+
+        for (channel, voice) in Voice::ALL.iter().enumerate() {
+            let channel = channel as u8;
+            let mut events = Vec::new();
+            // The time in seconds each harmony starts at, rounded to ticks so durations do not drift
+            let mut start = 0.0;
+            // The tick the previous note ended at, so a rest before a note is written as its delta time
+            let mut previous_off = 0;
+            // The pitch bend in effect on the channel, starting centered
+            let mut current_bend = 8192;
+            for (index, ((chord, duration), rest)) in self
+                .chords()
+                .iter()
+                .zip(self.durations())
+                .zip(self.rests())
+                .enumerate()
+            {
+                start += rest;
+                let pitch = chord.voice(*voice);
+                let note = pitch.midi_note().ok_or(MidiError::NoteOutOfRange {
+                    index,
+                    voice: *voice,
+                })?;
+                let on = (start * ticks_per_second).round() as u32;
+                start += duration;
+                let off = (start * ticks_per_second).round() as u32;
+
+                write_variable_length(&mut events, on - previous_off);
+                if pitch_bend {
+                    let bend = 8192.0
+                        + pitch.cents_offset() / (100.0 * PITCH_BEND_RANGE_SEMITONES) * 8192.0;
+                    let bend = bend.round().clamp(0.0, 16383.0) as u16;
+                    if bend != current_bend {
+                        events.extend_from_slice(&[
+                            0xe0 | channel,
+                            (bend & 0x7f) as u8,
+                            (bend >> 7) as u8,
+                        ]);
+                        write_variable_length(&mut events, 0);
+                        current_bend = bend;
+                    }
+                }
+                events.extend_from_slice(&[0x90 | channel, note, NOTE_VELOCITY]);
+                write_variable_length(&mut events, off - on);
+                events.extend_from_slice(&[0x80 | channel, note, 0]);
+                previous_off = off;
+            }
+            write_track(&mut bytes, events, end.saturating_sub(previous_off));
+        }
+        Ok(bytes)
+    }
+
+    /// Method that writes the progression to a standard MIDI file at `path`, as encoded by `to_midi` without pitch bends.
+    pub fn write_midi(
+        &self,
+        path: impl AsRef<Path>,
+        ticks_per_beat: u16,
+        tempo_bpm: f64,
+    ) -> Result<(), MidiError> {
+        let bytes = self.to_midi(ticks_per_beat, tempo_bpm, false)?;
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Pitch, SATB};
+
+    /// A MIDI channel event read back from a track, with its absolute time in ticks.
+    #[derive(Debug, PartialEq)]
+    struct Event {
+        time: u32,
+        status: u8,
+        data: [u8; 2],
+    }
+
+    fn read_variable_length(bytes: &[u8], pos: &mut usize) -> u32 {
+        let mut value = 0;
+        loop {
+            let byte = bytes[*pos];
+            *pos += 1;
+            value = (value << 7) | (byte & 0x7f) as u32;
+            if byte & 0x80 == 0 {
+                return value;
+            }
+        }
+    }
+
+    /// Helper function that reads the channel events of each track of a standard MIDI file.
+    fn read_tracks(bytes: &[u8]) -> Vec<Vec<Event>> {
+        read_tracks_with_ends(bytes).0
+    }
+
+    /// Helper function that reads the channel events of each track of a standard MIDI file, along with the time in ticks
+    /// each track ends at.
+    fn read_tracks_with_ends(bytes: &[u8]) -> (Vec<Vec<Event>>, Vec<u32>) {
+        assert_eq!(&bytes[..4], b"MThd");
+        let mut pos = 14;
+        let mut tracks = Vec::new();
+        let mut ends = Vec::new();
+        while pos < bytes.len() {
+            assert_eq!(&bytes[pos..pos + 4], b"MTrk");
+            let len = u32::from_be_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+            let end = pos + 8 + len;
+            pos += 8;
+            let mut time = 0;
+            let mut events = Vec::new();
+            while pos < end {
+                time += read_variable_length(bytes, &mut pos);
+                let status = bytes[pos];
+                if status == 0xff {
+                    if bytes[pos + 1] == 0x2f {
+                        ends.push(time);
+                    }
+                    let len = bytes[pos + 2] as usize;
+                    pos += 3 + len;
+                } else {
+                    events.push(Event {
+                        time,
+                        status,
+                        data: [bytes[pos + 1], bytes[pos + 2]],
+                    });
+                    pos += 3;
+                }
+            }
+            tracks.push(events);
+        }
+        (tracks, ends)
+    }
+
+    fn two_chords() -> Progression {
+        let mut progression = Progression::new();
+        progression.push_with_duration(
+            SATB::new(
+                0,
+                (0, 5).into(),
+                (7, 4).into(),
+                (4, 4).into(),
+                (0, 3).into(),
+            ),
+            1.0,
+        );
+        progression.push_with_duration(
+            SATB::new(
+                7,
+                (11, 4).into(),
+                (7, 4).into(),
+                (2, 4).into(),
+                (7, 2).into(),
+            ),
+            0.5,
+        );
+        progression
+    }
+
+    #[test]
+    fn test_midi_notes_and_timing() {
+        // At 120 bpm and 480 ticks per beat a second lasts 960 ticks
+        let bytes = two_chords().to_midi(480, 120.0, false).unwrap();
+        assert_eq!(u16::from_be_bytes([bytes[12], bytes[13]]), 480);
+        let tracks = read_tracks(&bytes);
+        assert_eq!(tracks.len(), 5);
+        assert!(tracks[0].is_empty());
+
+        let soprano = &tracks[1];
+        assert_eq!(
+            soprano,
+            &vec![
+                Event {
+                    time: 0,
+                    status: 0x90,
+                    data: [72, NOTE_VELOCITY]
+                },
+                Event {
+                    time: 960,
+                    status: 0x80,
+                    data: [72, 0]
+                },
+                Event {
+                    time: 960,
+                    status: 0x90,
+                    data: [71, NOTE_VELOCITY]
+                },
+                Event {
+                    time: 1440,
+                    status: 0x80,
+                    data: [71, 0]
+                },
+            ]
+        );
+        let bass = &tracks[4];
+        assert_eq!(bass[0].status, 0x93);
+        assert_eq!(bass[0].data[0], 48);
+        assert_eq!(bass[2].data[0], 43);
+    }
+
+    #[test]
+    fn test_midi_pitch_bend() {
+        // An A4 tuned 50 cents sharp
+        let sharp = Pitch::new(440.0 * 2f64.powf(50.0 / 1200.0), 9, 4);
+        let mut progression = Progression::new();
+        progression.push(SATB::new(
+            5,
+            sharp,
+            (0, 4).into(),
+            (5, 3).into(),
+            (5, 2).into(),
+        ));
+        let bytes = progression.to_midi(96, 60.0, true).unwrap();
+        let tracks = read_tracks(&bytes);
+        let bend = &tracks[1][0];
+        assert_eq!(bend.status, 0xe0);
+        let value = bend.data[0] as u16 | (bend.data[1] as u16) << 7;
+        assert!((value as i32 - (8192 + 2048)).abs() <= 1);
+        assert_eq!(tracks[1][1].data[0], 69);
+        // Voices in equal temperament are never bent
+        assert!(tracks[2..]
+            .iter()
+            .flatten()
+            .all(|e| e.status & 0xf0 != 0xe0));
+
+        // A following note in equal temperament returns the bend to the center, and a repeated bend is not sent again
+        for chord in two_chords().chords() {
+            progression.push(chord.clone());
+        }
+        let tracks = read_tracks(&progression.to_midi(96, 60.0, true).unwrap());
+        let bends: Vec<u16> = tracks[1]
+            .iter()
+            .filter(|e| e.status == 0xe0)
+            .map(|e| e.data[0] as u16 | (e.data[1] as u16) << 7)
+            .collect();
+        assert_eq!(bends.len(), 2);
+        assert_eq!(bends[1], 8192);
+
+        assert!(matches!(
+            progression.to_midi(96, 0.0, false),
+            Err(MidiError::InvalidTempo)
+        ));
+    }
+
+    #[test]
+    fn test_midi_trailing_rest() {
+        // At 120 bpm and 480 ticks per beat the half second trailing rest lasts 480 ticks past the last note off at 1440
+        let mut progression = two_chords();
+        progression.push_rest_with_duration(0.5);
+        let (tracks, ends) =
+            read_tracks_with_ends(&progression.to_midi(480, 120.0, false).unwrap());
+        assert_eq!(ends, vec![1920; 5]);
+        assert_eq!(tracks[1].last().unwrap().time, 1440);
+
+        let (_, ends) = read_tracks_with_ends(&two_chords().to_midi(480, 120.0, false).unwrap());
+        assert_eq!(ends, vec![1440; 5]);
+    }
+
+    #[test]
+    fn test_midi_invalid_division() {
+        for ticks_per_beat in [0, 0x8000, u16::MAX] {
+            assert!(matches!(
+                two_chords().to_midi(ticks_per_beat, 120.0, false),
+                Err(MidiError::InvalidDivision(t)) if t == ticks_per_beat
+            ));
+        }
+        assert!(two_chords().to_midi(0x7fff, 120.0, false).is_ok());
+    }
+}
@@ -0,0 +1,29 @@
+//! Export of pitches, harmonies and progressions to text based music notation formats.
+use crate::{Key, KeyMode, Pitch, SpelledPitchClass, Spelling, Tempo};
+
+pub mod abc;
+pub mod lilypond;
+pub mod musicxml;
+
+/// Helper function that spells `pitch` in `key`, see `Key::spell`, or with sharps off the white keys when `key` is `None`,
+/// returning the spelled note along with the octave of its letter, which differs from that of `pitch` for notes spelled
+/// across C, e.g. B#3 sounds as C4 and Cb4 as B3.
+pub(crate) fn spell_pitch(pitch: &Pitch, key: Option<&Key>) -> (SpelledPitchClass, i32) {
+    let spelled = match key {
+        Some(key) => key.spell(pitch.pitch_class()),
+        None => Key::new(0, KeyMode::Major).spell_with(pitch.pitch_class(), Spelling::Sharps),
+    };
+    let natural = SpelledPitchClass {
+        letter: spelled.letter,
+        accidental: 0,
+    }
+    .pitch_class() as i32;
+    let octave = pitch.octave() as i32 - (natural + spelled.accidental as i32).div_euclid(12);
+    (spelled, octave)
+}
+
+/// Helper function that returns the number of sixteenth notes, at least one, nearest to `seconds` at `tempo`, so durations
+/// that are not a whole number of sixteenths, such as triplets, are rounded.
+pub(crate) fn sixteenths(seconds: f64, tempo: Tempo) -> u32 {
+    ((seconds / tempo.seconds_per_beat() * 4.0).round() as u32).max(1)
+}
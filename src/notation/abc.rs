@@ -0,0 +1,304 @@
+//! Export to ABC notation, where notes from middle C upwards are named by upper case letters, those an octave higher by lower
+//! case letters, with an apostrophe for each further octave up or a comma for each octave down, and note lengths are written
+//! as multiples of a unit note length.
+use crate::notation::spell_pitch;
+use crate::{
+    Chord, Key, KeyMode, Melody, NoteValue, Pitch, Progression, SpelledPitchClass, Spelling, Tempo,
+};
+use std::collections::HashMap;
+
+/// The tolerance used when comparing lengths in beats.
+const EPSILON: f64 = 1e-9;
+
+/// Enumerates the events of an ABC tune body, see `body`.
+enum Event {
+    /// A note of the pitch
+    Note(Pitch),
+    /// A rest
+    Rest,
+    /// An invisible rest carrying the chord symbol, if any
+    Chord(Option<String>),
+}
+
+/// Helper function that returns `value` as the fraction with the smallest denominator, up to 96, that it rounds to.
+fn fraction(value: f64) -> (u32, u32) {
+    let den = (1..=96)
+        .find(|d| {
+            let scaled = value * *d as f64;
+            (scaled - scaled.round()).abs() < 1e-6
+        })
+        .unwrap_or(96);
+    ((value * den as f64).round() as u32, den)
+}
+
+/// Helper function that writes a length of `beats` quarter note beats as an ABC multiplier of the unit note length `unit`, e.g.
+/// "" for one unit, "3" for three units, "/" for half a unit or "3/2".
+fn length(beats: f64, unit: NoteValue) -> String {
+    match fraction(beats / unit.beats()) {
+        (1, 1) => String::new(),
+        (n, 1) => n.to_string(),
+        (1, 2) => "/".to_string(),
+        (1, d) => format!("/{}", d),
+        (n, d) => format!("{}/{}", n, d),
+    }
+}
+
+/// Helper function that respells `spelled` in `octave` enharmonically when its accidental lies beyond a double sharp or double
+/// flat, which ABC cannot write, as the plain sharp spelling for raised notes or flat spelling for lowered notes, e.g. E###4
+/// as G4. The octave follows the new letter, so the note keeps its height.
+fn respell(spelled: SpelledPitchClass, octave: i32) -> (SpelledPitchClass, i32) {
+    if spelled.accidental.abs() <= 2 {
+        return (spelled, octave);
+    }
+    let spelling = if spelled.accidental > 0 {
+        Spelling::Sharps
+    } else {
+        Spelling::Flats
+    };
+    let respelled = Key::new(0, KeyMode::Major).spell_with(spelled.pitch_class(), spelling);
+    // The height in semitones of a spelled note above C0, with its accidental
+    let height = |s: &SpelledPitchClass, octave: i32| {
+        let natural = SpelledPitchClass {
+            letter: s.letter,
+            accidental: 0,
+        };
+        12 * octave + natural.pitch_class() as i32 + s.accidental as i32
+    };
+    let octave = (height(&spelled, octave) - height(&respelled, 0)).div_euclid(12);
+    (respelled, octave)
+}
+
+/// Helper function that writes `pitch` as an ABC note spelled in `key`, respelled by `respell` if need be, writing its accidental only if it differs from the
+/// accidental in effect for its letter and octave, that of the last note of the bar in `bar_accidentals`, or otherwise that of
+/// the key signature in `signature`.
+fn note(
+    pitch: &Pitch,
+    key: &Key,
+    signature: &HashMap<char, i8>,
+    bar_accidentals: &mut HashMap<(char, i32), i8>,
+) -> String {
+    let (spelled, octave) = spell_pitch(pitch, Some(key));
+    let (spelled, octave) = respell(spelled, octave);
+    let in_effect = bar_accidentals
+        .get(&(spelled.letter, octave))
+        .or_else(|| signature.get(&spelled.letter))
+        .copied()
+        .unwrap_or(0);
+    let accidental = match spelled.accidental {
+        a if a == in_effect => "",
+        0 => "=",
+        a if a > 0 => &"^^"[..a as usize],
+        a => &"__"[..a.unsigned_abs() as usize],
+    };
+    bar_accidentals.insert((spelled.letter, octave), spelled.accidental);
+    let (letter, marks) = if octave >= 5 {
+        (
+            spelled.letter.to_ascii_lowercase(),
+            "'".repeat((octave - 5) as usize),
+        )
+    } else {
+        (spelled.letter, ",".repeat((4 - octave) as usize))
+    };
+    format!("{}{}{}", accidental, letter, marks)
+}
+
+/// Helper function that writes `events`, each lasting for a length in quarter note beats, as an ABC tune body in `key` with
+/// the lengths given as multiples of `unit`. Bar lines are placed after each bar of `meter`, given as the number of beats in a
+/// bar and the note value of a beat, and events crossing a bar line are split there, with notes tied across it.
+fn body(events: Vec<(Event, f64)>, key: &Key, meter: (u8, u8), unit: NoteValue) -> String {
+    let bar = meter.0 as f64 * 4.0 / meter.1.max(1) as f64;
+    let signature = key.signature();
+    let signature: HashMap<char, i8> = signature
+        .letters()
+        .iter()
+        .filter_map(|l| l.chars().next())
+        .map(|letter| {
+            (
+                letter,
+                if signature.spelling == Spelling::Sharps {
+                    1
+                } else {
+                    -1
+                },
+            )
+        })
+        .collect();
+    let mut bar_accidentals = HashMap::new();
+    let mut tokens: Vec<String> = Vec::new();
+    let mut position = 0.0;
+    for (event, mut beats) in events {
+        let mut first = true;
+        while beats > EPSILON {
+            let part = beats.min(bar - position);
+            beats -= part;
+            let mut token = match &event {
+                Event::Note(pitch) => note(pitch, key, &signature, &mut bar_accidentals),
+                Event::Rest => "z".to_string(),
+                Event::Chord(Some(symbol)) if first => format!("\"{}\"x", symbol),
+                Event::Chord(_) => "x".to_string(),
+            };
+            token += &length(part, unit);
+            if beats > EPSILON && matches!(event, Event::Note(_)) {
+                token += "-";
+            }
+            tokens.push(token);
+            position += part;
+            if position > bar - EPSILON {
+                tokens.push("|".to_string());
+                position = 0.0;
+                bar_accidentals.clear();
+            }
+            first = false;
+        }
+    }
+    if tokens.last().is_some_and(|t| t == "|") {
+        tokens.pop();
+    }
+    tokens.push("|]".to_string());
+    tokens.join(" ")
+}
+
+impl Melody {
+    /// Method that writes the melody as an ABC tune in `key` and the meter `meter`, given as the number of beats in a bar and
+    /// the note value of a beat, e.g. (6, 8), with note lengths given as multiples of the unit note length `unit`. The tune
+    /// has the reference number, meter, unit note length and key fields followed by the body on a single line, with notes
+    /// spelled in `key`, see `Key::spell`, and accidentals written wherever they differ from the key signature or an earlier
+    /// note of the bar. Notes crossing a bar line are split and tied across it.
+    pub fn to_abc(&self, key: &Key, meter: (u8, u8), unit: NoteValue) -> String {
+        let events = self
+            .iter()
+            .map(|(pitch, value)| (pitch.map_or(Event::Rest, Event::Note), value.beats()))
+            .collect();
+        let (n, d) = fraction(unit.beats() / 4.0);
+        let mode = match key.mode {
+            KeyMode::Major => "",
+            KeyMode::Minor => "m",
+        };
+        format!(
+            "X:1\nM:{}/{}\nL:{}/{}\nK:{}{}\n{}\n",
+            meter.0,
+            meter.1,
+            n,
+            d,
+            key.spell(key.tonic),
+            mode,
+            body(events, key, meter, unit)
+        )
+    }
+}
+
+impl Progression {
+    /// Method that writes the chord symbols of the progression as a line of an ABC tune body in `key` and the meter `meter`,
+    /// e.g. to place above a melody written by `Melody::to_abc`, reading the duration of each harmony and rest at `tempo`.
+    /// Each harmony is an invisible rest carrying its chord symbol, see `Chord::symbol`, spelled with the accidentals of the
+    /// key signature, and each rest an invisible rest, with lengths given as multiples of `unit`.
+    pub fn to_abc_chords(
+        &self,
+        key: &Key,
+        meter: (u8, u8),
+        unit: NoteValue,
+        tempo: Tempo,
+    ) -> String {
+        let beats = |seconds: f64| seconds / tempo.seconds_per_beat();
+        let spelling = key.signature().spelling;
+        let mut events = Vec::new();
+        for (i, chord) in self.chords().iter().enumerate() {
+            if self.rests()[i] > 0.0 {
+                events.push((Event::Chord(None), beats(self.rests()[i])));
+            }
+            let symbol = Chord::from(chord).symbol(spelling);
+            events.push((Event::Chord(symbol), beats(self.durations()[i])));
+        }
+        if self.trailing_rest() > 0.0 {
+            events.push((Event::Chord(None), beats(self.trailing_rest())));
+        }
+        body(events, key, meter, unit)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Rest, SATB};
+
+    #[test]
+    fn test_melody() {
+        let mut melody = Melody::new(Tempo::new(120.0).unwrap());
+        for (pitch, value) in [
+            ((7, 4), NoteValue::Quarter),
+            ((9, 4), NoteValue::Eighth),
+            ((11, 4), NoteValue::Eighth),
+            ((0, 5), NoteValue::Quarter),
+            ((2, 5), NoteValue::DottedQuarter),
+            ((5, 5), NoteValue::Eighth),
+            ((4, 5), NoteValue::Quarter),
+        ] {
+            melody.push(Pitch::from(pitch), value);
+        }
+        melody.push_rest(Rest(NoteValue::Quarter));
+        melody.push(Pitch::from((5, 5)), NoteValue::Quarter);
+        melody.push(Pitch::from((7, 5)), NoteValue::Quarter);
+
+        // F natural needs an accidental in G major, again in the next bar but not within the same bar
+        let g_major = Key::new(7, KeyMode::Major);
+        assert_eq!(
+            melody.to_abc(&g_major, (3, 4), NoteValue::Eighth),
+            "X:1\nM:3/4\nL:1/8\nK:G\nG2 A B c2 | d3 =f e2 | z2 =f2 g2 |]\n"
+        );
+
+        // Notes crossing a bar line are tied across it
+        let mut tied = Melody::new(Tempo::new(60.0).unwrap());
+        tied.push(Pitch::from((0, 4)), NoteValue::Half);
+        tied.push(Pitch::from((10, 3)), NoteValue::DottedHalf);
+        tied.push(Pitch::from((10, 3)), NoteValue::Eighth);
+        let f_minor = Key::new(5, KeyMode::Minor);
+        assert_eq!(
+            tied.to_abc(&f_minor, (4, 4), NoteValue::Quarter),
+            "X:1\nM:4/4\nL:1/4\nK:Fm\nC2 B,2- | B, B,/ |]\n"
+        );
+    }
+
+    #[test]
+    fn test_chord_line() {
+        let mut progression = Progression::new();
+        progression.push(SATB::new(
+            0,
+            (4, 5).into(),
+            (7, 4).into(),
+            (0, 4).into(),
+            (0, 3).into(),
+        ));
+        progression.push_rest_with_duration(1.0);
+        progression.push_with_duration(
+            SATB::new(
+                7,
+                (11, 4).into(),
+                (7, 4).into(),
+                (2, 4).into(),
+                (7, 3).into(),
+            ),
+            2.0,
+        );
+        let c_major = Key::new(0, KeyMode::Major);
+        assert_eq!(
+            progression.to_abc_chords(
+                &c_major,
+                (2, 4),
+                NoteValue::Quarter,
+                Tempo::new(60.0).unwrap()
+            ),
+            "\"C\"x x | \"G\"x2 |]"
+        );
+    }
+
+    #[test]
+    fn test_respell_beyond_double_accidentals() {
+        let spelled = |letter, accidental| SpelledPitchClass { letter, accidental };
+        assert_eq!(respell(spelled('F', 2), 4), (spelled('F', 2), 4));
+        assert_eq!(respell(spelled('E', 3), 4), (spelled('G', 0), 4));
+        // B### lies above the C that starts the next octave
+        assert_eq!(respell(spelled('B', 3), 3), (spelled('D', 0), 4));
+        assert_eq!(respell(spelled('C', -3), 4), (spelled('A', 0), 3));
+        assert_eq!(respell(spelled('D', -4), 4), (spelled('B', -1), 3));
+    }
+}
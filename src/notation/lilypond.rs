@@ -0,0 +1,197 @@
+//! Export to LilyPond, where notes are named by lower case letters with "is" for each sharp and "es" for each flat, followed
+//! by an apostrophe for each octave above the octave below middle C, or a comma for each octave below it, so C4 is c' and
+//! Bb2 is bes,.
+use crate::notation::{sixteenths, spell_pitch};
+use crate::{Key, KeyMode, Pitch, Progression, Tempo, Voice, SATB};
+
+/// The LilyPond durations a note or rest can be written with, along with their lengths in sixteenth notes, longest first.
+const DURATIONS: [(u32, &str); 9] = [
+    (24, "1."),
+    (16, "1"),
+    (12, "2."),
+    (8, "2"),
+    (6, "4."),
+    (4, "4"),
+    (3, "8."),
+    (2, "8"),
+    (1, "16"),
+];
+
+/// A trait for types that can be written in LilyPond notation.
+pub trait LilyPond {
+    /// Method that writes the value in LilyPond notation, spelling notes off the white keys with sharps.
+    fn to_lilypond(&self) -> String;
+
+    /// Method that writes the value in LilyPond notation, spelling notes in `key`, see `Key::spell`.
+    fn to_lilypond_in(&self, key: &Key) -> String;
+}
+
+/// Helper function that writes `pitch` as a LilyPond note name with octave marks, spelled in `key` if given.
+fn note(pitch: &Pitch, key: Option<&Key>) -> String {
+    let (spelled, octave) = spell_pitch(pitch, key);
+    let accidental = if spelled.accidental < 0 { "es" } else { "is" };
+    let marks = if octave >= 3 { "'" } else { "," };
+    format!(
+        "{}{}{}",
+        spelled.letter.to_ascii_lowercase(),
+        accidental.repeat(spelled.accidental.unsigned_abs() as usize),
+        marks.repeat((octave - 3).unsigned_abs() as usize)
+    )
+}
+
+/// Helper function that splits a length of `sixteenths` sixteenth notes into LilyPond durations, longest first.
+fn durations(mut sixteenths: u32) -> Vec<&'static str> {
+    let mut durations = Vec::new();
+    while let Some((length, duration)) = DURATIONS.iter().find(|(length, _)| *length <= sixteenths)
+    {
+        durations.push(*duration);
+        sixteenths -= length;
+    }
+    durations
+}
+
+/// Helper function that writes the four voices of `chord` as a LilyPond chord of four simultaneous voices, highest first.
+fn chord(chord: &SATB, key: Option<&Key>) -> String {
+    let voices: Vec<String> = chord
+        .voices()
+        .iter()
+        .map(|p| format!("{{ {} }}", note(p, key)))
+        .collect();
+    format!("<< {} >>", voices.join(" \\\\ "))
+}
+
+impl LilyPond for Pitch {
+    fn to_lilypond(&self) -> String {
+        note(self, None)
+    }
+
+    fn to_lilypond_in(&self, key: &Key) -> String {
+        note(self, Some(key))
+    }
+}
+
+impl LilyPond for SATB {
+    fn to_lilypond(&self) -> String {
+        chord(self, None)
+    }
+
+    fn to_lilypond_in(&self, key: &Key) -> String {
+        chord(self, Some(key))
+    }
+}
+
+impl Progression {
+    /// Method that writes the progression as a LilyPond score in `key` and the time signature `time`, given as the number of
+    /// beats in a bar and the note value of a beat, e.g. (3, 4), reading the duration of each harmony and rest at 60 beats
+    /// per minute, so a harmony lasting `DEFAULT_CHORD_DURATION` is a quarter note. See `to_lilypond_with_tempo`.
+    pub fn to_lilypond(&self, key: &Key, time: (u8, u8)) -> String {
+        self.to_lilypond_with_tempo(key, time, Tempo::default())
+    }
+
+    /// Method that writes the progression as a LilyPond score in `key` and the time signature `time`, reading the duration of
+    /// each harmony and rest at `tempo`. The soprano and alto share a treble staff and the tenor and bass a bass staff, with
+    /// notes spelled in `key`, see `Key::spell`. Durations are rounded to the nearest sixteenth note, and those no single
+    /// note value gives are written as tied notes, or consecutive rests.
+    pub fn to_lilypond_with_tempo(&self, key: &Key, time: (u8, u8), tempo: Tempo) -> String {
+        let mode = match key.mode {
+            KeyMode::Major => "major",
+            KeyMode::Minor => "minor",
+        };
+        let tonic = note(&Pitch::from_pitch_class(key.tonic, 3), Some(key));
+        let global = format!(
+            "\\key {} \\{} \\time {}/{} \\tempo 4 = {}",
+            tonic,
+            mode,
+            time.0,
+            time.1,
+            tempo.bpm()
+        );
+        let music = |voice: Voice| {
+            let mut tokens: Vec<String> = Vec::new();
+            let rest = |tokens: &mut Vec<String>, seconds: f64| {
+                if seconds > 0.0 {
+                    tokens.extend(
+                        durations(sixteenths(seconds, tempo))
+                            .iter()
+                            .map(|d| format!("r{}", d)),
+                    );
+                }
+            };
+            for (i, chord) in self.chords().iter().enumerate() {
+                rest(&mut tokens, self.rests()[i]);
+                let name = note(chord.voice(voice), Some(key));
+                let tied: Vec<String> = durations(sixteenths(self.durations()[i], tempo))
+                    .iter()
+                    .map(|d| format!("{}{}", name, d))
+                    .collect();
+                tokens.push(tied.join("~ "));
+            }
+            rest(&mut tokens, self.trailing_rest());
+            tokens.join(" ")
+        };
+        let staff = |clef: &str, upper: Voice, lower: Voice| {
+            format!(
+                "    \\new Staff <<\n      \\clef {}\n      \\new Voice {{ \\voiceOne \\global {} }}\n      \\new Voice {{ \\voiceTwo \\global {} }}\n    >>\n",
+                clef,
+                music(upper),
+                music(lower)
+            )
+        };
+        format!(
+            "\\version \"2.24.0\"\nglobal = {{ {} }}\n\\score {{\n  \\new ChoirStaff <<\n{}{}  >>\n  \\layout {{ }}\n}}\n",
+            global,
+            staff("treble", Voice::Soprano, Voice::Alto),
+            staff("bass", Voice::Tenor, Voice::Bass)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pitches() {
+        assert_eq!(Pitch::from((0, 4)).to_lilypond(), "c'");
+        assert_eq!(Pitch::from((11, 3)).to_lilypond(), "b");
+        assert_eq!(Pitch::from((0, 3)).to_lilypond(), "c");
+        assert_eq!(Pitch::from((1, 5)).to_lilypond(), "cis''");
+        assert_eq!(Pitch::from((4, 1)).to_lilypond(), "e,,");
+        let f_major = Key::new(5, KeyMode::Major);
+        assert_eq!(Pitch::from((10, 2)).to_lilypond_in(&f_major), "bes,");
+        // The leading tone B# of C# minor sounds in the octave above its letter
+        let c_sharp_minor = Key::new(1, KeyMode::Minor);
+        assert_eq!(Pitch::from((0, 4)).to_lilypond_in(&c_sharp_minor), "bis");
+    }
+
+    #[test]
+    fn test_progression() {
+        let tonic = SATB::new(
+            0,
+            (4, 5).into(),
+            (7, 4).into(),
+            (0, 4).into(),
+            (0, 3).into(),
+        );
+        assert_eq!(
+            tonic.to_lilypond(),
+            "<< { e'' } \\\\ { g' } \\\\ { c' } \\\\ { c } >>"
+        );
+
+        let mut progression = Progression::new();
+        progression.push(tonic.clone());
+        progression.push_rest_with_duration(0.5);
+        progression.push_with_duration(tonic, 2.5);
+        let score = progression.to_lilypond(&Key::new(0, KeyMode::Major), (4, 4));
+        assert!(score.starts_with(
+            "\\version \"2.24.0\"\nglobal = { \\key c \\major \\time 4/4 \\tempo 4 = 60 }\n"
+        ));
+        assert!(score.contains("\\new Voice { \\voiceOne \\global e''4 r8 e''2~ e''8 }"));
+        assert!(score.contains("\\new Voice { \\voiceTwo \\global c4 r8 c2~ c8 }"));
+        assert!(score.contains("\\clef bass"));
+        assert_eq!(score.matches("\\new Staff").count(), 2);
+
+        let minor = Progression::new().to_lilypond(&Key::new(3, KeyMode::Minor), (3, 4));
+        assert!(minor.contains("\\key dis \\minor \\time 3/4"));
+    }
+}
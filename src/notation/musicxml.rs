@@ -0,0 +1,295 @@
+//! Export to MusicXML, covering the subset of the partwise format needed to open four part writing in a score editor: parts,
+//! measures, key and time signatures, clefs, notes, rests and ties.
+use crate::notation::spell_pitch;
+use crate::{Key, KeyMode, Pitch, Progression, Spelling, Tempo, Voice};
+
+/// The MusicXML note types a note or rest can be written with, along with their lengths in sixteenth notes and whether they
+/// are dotted, longest first.
+const NOTE_TYPES: [(u32, &str, bool); 8] = [
+    (16, "whole", false),
+    (12, "half", true),
+    (8, "half", false),
+    (6, "quarter", true),
+    (4, "quarter", false),
+    (3, "eighth", true),
+    (2, "eighth", false),
+    (1, "16th", false),
+];
+
+/// Helper function that splits a length of `length` divisions, with `divisions` divisions per quarter note, into the lengths
+/// of MusicXML note types, longest first. A remainder that no note type gives is returned as a length without a note type.
+fn note_types(mut length: u32, divisions: u32) -> Vec<(u32, Option<(&'static str, bool)>)> {
+    let mut pieces = Vec::new();
+    while length > 0 {
+        let fitting = NOTE_TYPES.iter().find(|(sixteenths, _, _)| {
+            (sixteenths * divisions).is_multiple_of(4) && sixteenths * divisions / 4 <= length
+        });
+        match fitting {
+            Some((sixteenths, name, dotted)) => {
+                let piece = sixteenths * divisions / 4;
+                pieces.push((piece, Some((*name, *dotted))));
+                length -= piece;
+            }
+            None => {
+                pieces.push((length, None));
+                length = 0;
+            }
+        }
+    }
+    pieces
+}
+
+/// Helper function that writes a MusicXML note element for `pitch`, or a rest if `None`, spelled in `key` and lasting
+/// `duration` divisions, with the note type given by `note_type` if any, and ties `into` the previous note and `onward` to
+/// the next note.
+fn note(
+    pitch: Option<&Pitch>,
+    key: &Key,
+    duration: u32,
+    note_type: Option<(&str, bool)>,
+    into: bool,
+    onward: bool,
+) -> String {
+    let mut xml = String::from("<note>");
+    match pitch {
+        Some(pitch) => {
+            let (spelled, octave) = spell_pitch(pitch, Some(key));
+            xml += &format!("<pitch><step>{}</step>", spelled.letter);
+            if spelled.accidental != 0 {
+                xml += &format!("<alter>{}</alter>", spelled.accidental);
+            }
+            xml += &format!("<octave>{}</octave></pitch>", octave);
+        }
+        None => xml += "<rest/>",
+    }
+    xml += &format!("<duration>{}</duration>", duration);
+    if into {
+        xml += "<tie type=\"stop\"/>";
+    }
+    if onward {
+        xml += "<tie type=\"start\"/>";
+    }
+    if let Some((name, dotted)) = note_type {
+        xml += &format!("<type>{}</type>", name);
+        if dotted {
+            xml += "<dot/>";
+        }
+    }
+    if into || onward {
+        xml += "<notations>";
+        if into {
+            xml += "<tied type=\"stop\"/>";
+        }
+        if onward {
+            xml += "<tied type=\"start\"/>";
+        }
+        xml += "</notations>";
+    }
+    xml + "</note>"
+}
+
+impl Progression {
+    /// Method that writes the progression as a MusicXML score in `key` and 4/4 time, with `divisions` divisions per quarter
+    /// note, reading the duration of each harmony and rest at 60 beats per minute, so a harmony lasting
+    /// `DEFAULT_CHORD_DURATION` is a quarter note. See `to_musicxml_with_tempo`.
+    pub fn to_musicxml(&self, key: &Key, divisions: u32) -> String {
+        self.to_musicxml_with_tempo(key, divisions, (4, 4), Tempo::default())
+    }
+
+    /// Method that writes the progression as a partwise MusicXML score in `key` and the time signature `time`, given as the
+    /// number of beats in a bar and the note value of a beat, e.g. (3, 4), with `divisions` divisions per quarter note and
+    /// the duration of each harmony and rest read at `tempo`. Each voice is written as its own part, with notes spelled in
+    /// `key`, see `Key::spell`. Notes crossing a bar line, or lasting a duration no single note type gives, are split into
+    /// tied notes, a voice holding a common tone between consecutive harmonies is tied across them, and the last measure is
+    /// filled with a rest.
+    pub fn to_musicxml_with_tempo(
+        &self,
+        key: &Key,
+        divisions: u32,
+        time: (u8, u8),
+        tempo: Tempo,
+    ) -> String {
+        let divisions = divisions.max(1);
+        let length = |seconds: f64| {
+            ((seconds / tempo.seconds_per_beat() * divisions as f64).round() as u32).max(1)
+        };
+        let measure = ((time.0 as u32 * divisions * 4) / time.1.max(1) as u32).max(1);
+        let signature = key.signature();
+        let fifths = match signature.spelling {
+            Spelling::Sharps => signature.count as i32,
+            Spelling::Flats => -(signature.count as i32),
+        };
+        let mode = match key.mode {
+            KeyMode::Major => "major",
+            KeyMode::Minor => "minor",
+        };
+        let ids = ["P1", "P2", "P3", "P4"];
+
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE score-partwise PUBLIC \"-//Recordare//DTD MusicXML 4.0 Partwise//EN\" \"http://www.musicxml.org/dtds/partwise.dtd\">\n<score-partwise version=\"4.0\">\n<part-list>\n",
+        );
+        for (id, voice) in ids.iter().zip(Voice::ALL) {
+            let name = voice.to_string();
+            xml += &format!(
+                "<score-part id=\"{}\"><part-name>{}{}</part-name></score-part>\n",
+                id,
+                name[..1].to_uppercase(),
+                &name[1..]
+            );
+        }
+        xml += "</part-list>\n";
+
+        for (id, voice) in ids.iter().zip(Voice::ALL) {
+            // The events of the voice, each a pitch or rest, its length and whether it is tied to the next event
+            let mut events: Vec<(Option<Pitch>, u32, bool)> = Vec::new();
+            for (i, chord) in self.chords().iter().enumerate() {
+                if self.rests()[i] > 0.0 {
+                    events.push((None, length(self.rests()[i]), false));
+                }
+                let pitch = *chord.voice(voice);
+                if let Some(last) = events.last_mut() {
+                    last.2 = last.0 == Some(pitch);
+                }
+                events.push((Some(pitch), length(self.durations()[i]), false));
+            }
+            if self.trailing_rest() > 0.0 {
+                events.push((None, length(self.trailing_rest()), false));
+            }
+
+            let clef = match voice {
+                Voice::Soprano | Voice::Alto => "<clef><sign>G</sign><line>2</line></clef>",
+                Voice::Tenor => "<clef><sign>G</sign><line>2</line><clef-octave-change>-1</clef-octave-change></clef>",
+                Voice::Bass => "<clef><sign>F</sign><line>4</line></clef>",
+            };
+            xml += &format!(
+                "<part id=\"{}\">\n<measure number=\"1\">\n<attributes><divisions>{}</divisions><key><fifths>{}</fifths><mode>{}</mode></key><time><beats>{}</beats><beat-type>{}</beat-type></time>{}</attributes>\n",
+                id, divisions, fifths, mode, time.0, time.1, clef
+            );
+            let mut number = 1;
+            let mut position = 0;
+            let mut tied_in = false;
+            for (pitch, mut remaining, tied_on) in events {
+                while remaining > 0 {
+                    let part = remaining.min(measure - position);
+                    remaining -= part;
+                    let pieces = note_types(part, divisions);
+                    for (j, (piece, note_type)) in pieces.iter().enumerate() {
+                        let more = remaining > 0 || j + 1 < pieces.len();
+                        let onward = pitch.is_some() && (more || tied_on);
+                        xml += &note(pitch.as_ref(), key, *piece, *note_type, tied_in, onward);
+                        xml += "\n";
+                        tied_in = onward;
+                    }
+                    position += part;
+                    if position == measure {
+                        position = 0;
+                        number += 1;
+                        xml += &format!("</measure>\n<measure number=\"{}\">\n", number);
+                    }
+                }
+            }
+            if position > 0 || number == 1 {
+                for (piece, note_type) in note_types(measure - position, divisions) {
+                    xml += &note(None, key, piece, note_type, false, false);
+                    xml += "\n";
+                }
+                xml += "</measure>\n";
+            } else {
+                // Drop the measure opened after the last bar line
+                let opened = format!("<measure number=\"{}\">\n", number);
+                xml.truncate(xml.len() - opened.len());
+            }
+            xml += "</part>\n";
+        }
+        xml + "</score-partwise>\n"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SATB;
+
+    #[test]
+    fn test_progression() {
+        let tonic = SATB::new(
+            0,
+            (4, 5).into(),
+            (7, 4).into(),
+            (0, 4).into(),
+            (0, 3).into(),
+        );
+        let dominant = SATB::new(
+            7,
+            (2, 5).into(),
+            (7, 4).into(),
+            (11, 3).into(),
+            (7, 2).into(),
+        );
+        let mut progression = Progression::new();
+        progression.push_with_duration(tonic.clone(), 2.0);
+        progression.push_with_duration(dominant, 2.0);
+        progression.push_with_duration(tonic, 2.0);
+        let xml = progression.to_musicxml(&Key::new(0, KeyMode::Major), 4);
+
+        // String level well formedness: a single root element and balanced elements
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        assert!(xml.trim_end().ends_with("</score-partwise>"));
+        for element in [
+            "part",
+            "measure",
+            "note",
+            "pitch",
+            "attributes",
+            "notations",
+        ] {
+            assert_eq!(
+                xml.matches(&format!("<{}>", element)).count()
+                    + xml.matches(&format!("<{} ", element)).count(),
+                xml.matches(&format!("</{}>", element)).count(),
+                "unbalanced <{}>",
+                element
+            );
+        }
+        assert_eq!(xml.matches("<part id=").count(), 4);
+        assert!(xml.contains("<key><fifths>0</fifths><mode>major</mode></key>"));
+        assert!(xml.contains("<time><beats>4</beats><beat-type>4</beat-type></time>"));
+
+        // Each voice of the C major chord is spelled with its step and octave
+        for (step, octave) in [("E", 5), ("G", 4), ("C", 4), ("C", 3)] {
+            assert!(xml.contains(&format!(
+                "<pitch><step>{}</step><octave>{}</octave></pitch><duration>8</duration>",
+                step, octave
+            )));
+        }
+
+        // The alto holds G4 throughout, tied across the harmonies and the bar line, and the last measure is filled
+        let alto =
+            &xml[xml.find("<part id=\"P2\">").unwrap()..xml.find("<part id=\"P3\">").unwrap()];
+        assert_eq!(alto.matches("<tied type=\"start\"/>").count(), 2);
+        assert_eq!(alto.matches("<measure number=").count(), 2);
+        assert!(alto.contains("<rest/><duration>8</duration><type>half</type>"));
+        // The bass leaps from C3 to G2 without a tie
+        let bass = &xml[xml.find("<part id=\"P4\">").unwrap()..];
+        assert!(bass.contains("<pitch><step>G</step><octave>2</octave></pitch><duration>8</duration><type>half</type></note>"));
+    }
+
+    #[test]
+    fn test_key_signature() {
+        let progression = Progression::new();
+        let xml = progression.to_musicxml(&Key::new(5, KeyMode::Minor), 2);
+        assert!(xml.contains("<key><fifths>-4</fifths><mode>minor</mode></key>"));
+        assert!(xml.contains("<rest/><duration>8</duration><type>whole</type>"));
+
+        let mut progression = Progression::new();
+        progression.push(SATB::new(
+            10,
+            (10, 4).into(),
+            (5, 4).into(),
+            (2, 4).into(),
+            (10, 2).into(),
+        ));
+        let xml = progression.to_musicxml(&Key::new(5, KeyMode::Major), 1);
+        assert!(xml.contains("<step>B</step><alter>-1</alter><octave>4</octave>"));
+    }
+}
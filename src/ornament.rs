@@ -0,0 +1,491 @@
+//! Ornamentation of a `Progression` with passing and neighbor tones, producing a timed surface of note events per voice
+//! rather than block chords.
+use crate::progression::{check_voicing_pair, motion};
+use crate::{
+    Key, NoteValue, Pitch, Progression, RenderOptions, Tempo, Violation, ViolationKind, Voice,
+};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+/// Enumerates the direction of an inserted neighbor tone.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NeighborDirection {
+    /// A whole step above the repeated pitch.
+    Upper,
+    /// A half step below the repeated pitch.
+    Lower,
+}
+
+/// Enumerates the errors that prevent a `Progression` from being ornamented.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum OrnamentError {
+    /// The ornament fraction of the style is not finite or does not lie within [0, 1].
+    InvalidFraction(f64),
+}
+
+impl Display for OrnamentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrnamentError::InvalidFraction(fraction) => write!(
+                f,
+                "the ornament fraction {} does not lie within [0, 1]",
+                fraction
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OrnamentError {}
+
+/// Configures which ornaments are inserted between the harmonies of a `Progression`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OrnamentStyle {
+    /// When true, a voice moving by a third between harmonies gains the passing tone between the two pitches, the pitch
+    /// between them in the scale of the key, or a whole step from the first pitch if none is.
+    pub passing_tones: bool,
+    /// When set, a voice repeating its pitch between harmonies gains a neighbor tone in the given direction.
+    pub neighbor_tones: Option<NeighborDirection>,
+    /// The fraction of a harmony's duration, taken from its end, given to a tone inserted after it, which must lie within
+    /// [0, 1].
+    pub ornament_fraction: f64,
+    /// The key passing tones are taken from the scale of, or `None` to infer it from the progression with `Key::infer`.
+    pub key: Option<Key>,
+}
+
+impl Default for OrnamentStyle {
+    fn default() -> Self {
+        OrnamentStyle {
+            passing_tones: true,
+            neighbor_tones: None,
+            ornament_fraction: 0.5,
+            key: None,
+        }
+    }
+}
+
+/// A single pitch sounded by one voice, starting at `start` seconds and lasting for `duration` seconds.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NoteEvent {
+    /// The pitch sounded
+    pub pitch: Pitch,
+    /// The time in seconds the note starts at
+    pub start: f64,
+    /// The length of the note in seconds
+    pub duration: f64,
+    /// True if the note is an inserted non-chord tone
+    pub ornament: bool,
+}
+
+impl NoteEvent {
+    /// Associated method for creating a new `NoteEvent` of `pitch`, starting at `start` seconds and lasting for the note value
+    /// `value` at `tempo`. The note is not an ornament.
+    pub fn with_note_value(pitch: Pitch, start: f64, value: NoteValue, tempo: Tempo) -> Self {
+        NoteEvent {
+            pitch,
+            start,
+            duration: value.seconds(tempo),
+            ornament: false,
+        }
+    }
+}
+
+/// A progression rendered as a sequence of timed note events for each voice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrnamentedProgression {
+    /// The note events of each voice, ordered soprano, alto, tenor, bass
+    voices: [Vec<NoteEvent>; 4],
+    /// The time in seconds each harmony of the progression starts at
+    starts: Vec<f64>,
+}
+
+impl OrnamentedProgression {
+    /// Method for accessing the note events sung by `voice`, in order.
+    pub fn voice(&self, voice: Voice) -> &[NoteEvent] {
+        &self.voices[voice as usize]
+    }
+
+    /// Method that returns the total duration in seconds of the ornamented progression.
+    pub fn duration(&self) -> f64 {
+        self.voices[0]
+            .last()
+            .map(|e| e.start + e.duration)
+            .unwrap_or(0.0)
+    }
+
+    /// Helper method that returns the distinct times in seconds any voice starts a note at, in order.
+    fn onsets(&self) -> Vec<f64> {
+        let mut onsets: Vec<f64> = self
+            .voices
+            .iter()
+            .flat_map(|events| events.iter().map(|e| e.start))
+            .collect();
+        onsets.sort_by(|a, b| a.total_cmp(b));
+        onsets.dedup();
+        onsets
+    }
+
+    /// Method that returns the successive voicings of the surface, i.e. the pitches of all four voices each time any voice
+    /// moves to a new note, ordered soprano, alto, tenor, bass.
+    pub fn surface(&self) -> Vec<[Pitch; 4]> {
+        let onsets = self.onsets();
+        let mut positions = [0; 4];
+        let mut surface = Vec::with_capacity(onsets.len());
+        for onset in onsets {
+            let mut voicing = [self.voices[0][0].pitch; 4];
+            for (i, events) in self.voices.iter().enumerate() {
+                while positions[i] + 1 < events.len() && events[positions[i] + 1].start <= onset {
+                    positions[i] += 1;
+                }
+                voicing[i] = events[positions[i]].pitch;
+            }
+            surface.push(voicing);
+        }
+        surface
+    }
+
+    /// Method that checks the pairwise voice leading rules between each successive voicing of the surface.
+    /// The index of each violation is the index of the first voicing of the pair within `surface()`.
+    pub fn violations(&self) -> Vec<Violation> {
+        let surface = self.surface();
+        let mut violations = Vec::new();
+        for (index, pair) in surface.windows(2).enumerate() {
+            violations.extend(
+                check_voicing_pair(&pair[0], &pair[1])
+                    .into_iter()
+                    .map(|kind| Violation { index, kind }),
+            );
+        }
+        violations
+    }
+
+    /// Helper method that tallies the violations of the surface by the harmony sounding at the first voicing of each pair and
+    /// the kind of violation, which names the voices involved.
+    fn violations_by_harmony(&self) -> HashMap<(usize, ViolationKind), usize> {
+        let onsets = self.onsets();
+        let mut tally = HashMap::new();
+        for violation in self.violations() {
+            let onset = onsets[violation.index];
+            let harmony = self
+                .starts
+                .partition_point(|start| *start <= onset)
+                .saturating_sub(1);
+            *tally.entry((harmony, violation.kind)).or_insert(0) += 1;
+        }
+        tally
+    }
+
+    /// Method that renders the ornamented progression as a sound wave sampled at `sample_freq` with the default options,
+    /// see `render`.
+    pub fn sound_wave(&self, sample_freq: u32) -> Vec<f64> {
+        self.render(sample_freq, &RenderOptions::default())
+    }
+}
+
+impl Progression {
+    /// Method that inserts passing and neighbor tones, according to `style`, into the individual voices between each pair
+    /// of consecutive harmonies. An ornament is only kept if the pairwise voice leading violations found on the ornamented
+    /// surface are unchanged, between the same voices at the same harmonies, so an ornament can neither add a violation nor
+    /// hide one already present between the harmonies. Returns an error if the ornament fraction of `style` does not lie
+    /// within [0, 1].
+    pub fn ornament(&self, style: OrnamentStyle) -> Result<OrnamentedProgression, OrnamentError> {
+        if !(0.0..=1.0).contains(&style.ornament_fraction) {
+            return Err(OrnamentError::InvalidFraction(style.ornament_fraction));
+        }
+        let chords = self.chords();
+        let mut ornaments: Vec<[Option<Pitch>; 4]> = vec![[None; 4]; chords.len()];
+        let mut ornamented = build_events(self, &ornaments, style.ornament_fraction);
+        let violations = ornamented.violations_by_harmony();
+        let key = style.key.unwrap_or_else(|| {
+            let classes: Vec<u8> = chords
+                .iter()
+                .flat_map(|chord| chord.voices().map(|p| p.pitch_class()))
+                .collect();
+            Key::infer(&classes)[0].0
+        });
+
+        for i in 0..chords.len().saturating_sub(1) {
+            for (v, voice) in Voice::ALL.iter().enumerate() {
+                let from = chords[i].voice(*voice);
+                let to = chords[i + 1].voice(*voice);
+                let step = motion(from, to);
+                let candidate = match (step.abs(), style.neighbor_tones) {
+                    (3 | 4, _) if style.passing_tones => passing_tone(from, step, &key),
+                    (0, Some(NeighborDirection::Upper)) => from.transpose(2),
+                    (0, Some(NeighborDirection::Lower)) => from.transpose(-1),
+                    _ => None,
+                };
+                if candidate.is_none() {
+                    continue;
+                }
+                ornaments[i][v] = candidate;
+                let attempt = build_events(self, &ornaments, style.ornament_fraction);
+                if attempt.violations_by_harmony() != violations {
+                    // The ornament adds or hides a violation, so leave the voice unornamented
+                    ornaments[i][v] = None;
+                } else {
+                    ornamented = attempt;
+                }
+            }
+        }
+        Ok(ornamented)
+    }
+}
+
+/// Helper function that returns the passing tone filling in the leap of `step` semitones, a third, from `from`: the pitch
+/// between them in the scale of `key`, the one nearest the middle of the leap if several are, or a whole step from `from` if
+/// none is, e.g. F between E and G in C major.
+fn passing_tone(from: &Pitch, step: i32, key: &Key) -> Option<Pitch> {
+    let scale = key.scale();
+    let mut offsets: Vec<i32> = (1..step.abs()).collect();
+    // Nearest the middle first, with a whole step before a half step
+    offsets.sort_by_key(|offset| ((2 * offset - step.abs()).abs(), -offset));
+    offsets
+        .into_iter()
+        .filter_map(|offset| from.transpose(offset * step.signum()))
+        .find(|p| scale.contains(p.pitch_class()))
+        .or_else(|| from.transpose(2 * step.signum()))
+}
+
+/// Helper function that builds the note events of each voice of `progression`, where `ornaments[i]` gives the tone inserted
+/// into each voice at the end of harmony `i`, taking up `fraction` of its duration.
+fn build_events(
+    progression: &Progression,
+    ornaments: &[[Option<Pitch>; 4]],
+    fraction: f64,
+) -> OrnamentedProgression {
+    let mut voices: [Vec<NoteEvent>; 4] = Default::default();
+    let mut starts = Vec::with_capacity(ornaments.len());
+    let mut start = 0.0;
+    for (((chord, duration), rest), ornament) in progression
+        .chords()
+        .iter()
+        .zip(progression.durations())
+        .zip(progression.rests())
+        .zip(ornaments)
+    {
+        start += rest;
+        starts.push(start);
+        for (v, pitch) in chord.voices().into_iter().enumerate() {
+            match ornament[v] {
+                Some(tone) => {
+                    let split = duration * (1.0 - fraction);
+                    voices[v].push(NoteEvent {
+                        pitch,
+                        start,
+                        duration: split,
+                        ornament: false,
+                    });
+                    voices[v].push(NoteEvent {
+                        pitch: tone,
+                        start: start + split,
+                        duration: duration - split,
+                        ornament: true,
+                    });
+                }
+                None => voices[v].push(NoteEvent {
+                    pitch,
+                    start,
+                    duration: *duration,
+                    ornament: false,
+                }),
+            }
+        }
+        start += duration;
+    }
+    OrnamentedProgression { voices, starts }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SATB;
+
+    fn satb(root: u8, s: (u8, u8), a: (u8, u8), t: (u8, u8), b: (u8, u8)) -> SATB {
+        SATB::new(root, s.into(), a.into(), t.into(), b.into())
+    }
+
+    #[test]
+    fn test_passing_tone() {
+        let progression: Progression = vec![
+            satb(0, (0, 5), (7, 4), (4, 4), (0, 3)),
+            satb(0, (4, 5), (7, 4), (0, 4), (0, 3)),
+        ]
+        .into_iter()
+        .collect();
+        let ornamented = progression.ornament(OrnamentStyle::default()).unwrap();
+
+        let soprano = ornamented.voice(Voice::Soprano);
+        assert_eq!(soprano.len(), 3);
+        assert_eq!(soprano[0].pitch, Pitch::from((0, 5)));
+        assert_eq!(soprano[1].pitch, Pitch::from((2, 5)));
+        assert!(soprano[1].ornament);
+        assert_eq!(soprano[0].duration, 0.5);
+        assert_eq!(soprano[1].duration, 0.5);
+        assert_eq!(soprano[2].pitch, Pitch::from((4, 5)));
+
+        // The tenor moving down a third gains a passing tone too, the held voices do not
+        assert_eq!(ornamented.voice(Voice::Tenor)[1].pitch, Pitch::from((2, 4)));
+        assert_eq!(ornamented.voice(Voice::Alto).len(), 2);
+        assert!(ornamented.violations().len() <= progression.violations().len());
+        assert_eq!(ornamented.duration(), 2.0);
+        let wave = ornamented.sound_wave(100);
+        assert_eq!(wave.len(), 200);
+        assert!(wave.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_neighbor_tone() {
+        let progression: Progression = vec![
+            satb(0, (0, 5), (7, 4), (4, 4), (0, 3)),
+            satb(0, (4, 5), (7, 4), (0, 4), (0, 3)),
+        ]
+        .into_iter()
+        .collect();
+        let style = OrnamentStyle {
+            passing_tones: false,
+            neighbor_tones: Some(NeighborDirection::Upper),
+            ornament_fraction: 0.25,
+            key: None,
+        };
+        let ornamented = progression.ornament(style).unwrap();
+        let alto = ornamented.voice(Voice::Alto);
+        assert_eq!(alto[1].pitch, Pitch::from((9, 4)));
+        assert_eq!(alto[1].duration, 0.25);
+        assert_eq!(ornamented.voice(Voice::Soprano).len(), 2);
+    }
+
+    #[test]
+    fn test_ornament_keeps_violations() {
+        // The tenor and bass both move up a third in parallel fifths, a passing tone in either voice would break up the
+        // fifths on the surface and hide the violation, so neither is kept
+        let progression: Progression = vec![
+            satb(0, (0, 5), (4, 4), (7, 3), (0, 3)),
+            satb(4, (7, 4), (4, 4), (11, 3), (4, 3)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(progression.violations().len(), 1);
+
+        let ornamented = progression.ornament(OrnamentStyle::default()).unwrap();
+        assert_eq!(ornamented.voice(Voice::Tenor).len(), 2);
+        assert_eq!(ornamented.voice(Voice::Bass).len(), 2);
+        assert_eq!(ornamented.violations(), progression.violations());
+    }
+
+    #[test]
+    fn test_ornament_does_not_add_parallel_fifths() {
+        // The tenor rises a minor third and the bass a major third from a fifth apart, so their passing tones would move in
+        // parallel fifths, G3 and C3 to A3 and D3, and only the tenor keeps its passing tone
+        let progression: Progression = vec![
+            satb(0, (0, 5), (4, 4), (7, 3), (0, 3)),
+            satb(0, (0, 5), (7, 4), (10, 3), (4, 3)),
+        ]
+        .into_iter()
+        .collect();
+        assert!(progression.violations().is_empty());
+
+        let ornamented = progression.ornament(OrnamentStyle::default()).unwrap();
+        assert_eq!(ornamented.voice(Voice::Tenor)[1].pitch, Pitch::from((9, 3)));
+        assert_eq!(ornamented.voice(Voice::Bass).len(), 2);
+        assert!(ornamented.violations().is_empty());
+    }
+
+    #[test]
+    fn test_passing_tone_in_key() {
+        // The tenor falls a minor third from G3 to E3 and the soprano rises one from E4 to G4, filled by F rather than F#
+        let progression: Progression = vec![
+            satb(0, (4, 4), (0, 4), (7, 3), (0, 3)),
+            satb(0, (7, 4), (0, 4), (4, 3), (0, 3)),
+        ]
+        .into_iter()
+        .collect();
+        let ornamented = progression.ornament(OrnamentStyle::default()).unwrap();
+        assert_eq!(
+            ornamented.voice(Voice::Soprano)[1].pitch,
+            Pitch::from((5, 4))
+        );
+        assert_eq!(ornamented.voice(Voice::Tenor)[1].pitch, Pitch::from((5, 3)));
+
+        // In E major the same leaps are filled by F#
+        let style = OrnamentStyle {
+            key: Some(Key::new(4, crate::KeyMode::Major)),
+            ..OrnamentStyle::default()
+        };
+        let ornamented = progression.ornament(style).unwrap();
+        assert_eq!(
+            ornamented.voice(Voice::Soprano)[1].pitch,
+            Pitch::from((6, 4))
+        );
+        assert_eq!(ornamented.voice(Voice::Tenor)[1].pitch, Pitch::from((6, 3)));
+    }
+
+    #[test]
+    fn test_invalid_fraction() {
+        let progression: Progression = vec![
+            satb(0, (4, 4), (0, 4), (7, 3), (0, 3)),
+            satb(0, (7, 4), (0, 4), (4, 3), (0, 3)),
+        ]
+        .into_iter()
+        .collect();
+        for fraction in [-0.1, 1.5, f64::NAN, f64::INFINITY] {
+            let style = OrnamentStyle {
+                ornament_fraction: fraction,
+                ..OrnamentStyle::default()
+            };
+            assert!(matches!(
+                progression.ornament(style),
+                Err(OrnamentError::InvalidFraction(_))
+            ));
+        }
+        for fraction in [0.0, 1.0] {
+            let style = OrnamentStyle {
+                ornament_fraction: fraction,
+                ..OrnamentStyle::default()
+            };
+            assert!(progression.ornament(style).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let progression: Progression = vec![
+            satb(0, (0, 5), (7, 4), (4, 4), (0, 3)),
+            satb(0, (4, 5), (7, 4), (0, 4), (0, 3)),
+        ]
+        .into_iter()
+        .collect();
+        let ornamented = progression.ornament(OrnamentStyle::default()).unwrap();
+        let wave = ornamented.sound_wave(8000);
+        assert_eq!(wave, ornamented.render(8000, &RenderOptions::default()));
+
+        // The options apply to the whole surface, so halving the gain halves every sample
+        let quiet = ornamented.render(8000, &RenderOptions::new().gain(0.5));
+        for (s, q) in wave.iter().zip(&quiet) {
+            assert!((s / 2.0 - q).abs() < 1e-12);
+        }
+        let muted = Voice::ALL
+            .iter()
+            .fold(RenderOptions::new(), |options, voice| options.mute(*voice));
+        assert!(ornamented.render(8000, &muted).iter().all(|s| *s == 0.0));
+
+        // Events lying past the end of the surface are clamped to it rather than panicking
+        let mut voices: [Vec<NoteEvent>; 4] = Default::default();
+        voices[0] = vec![NoteEvent {
+            pitch: Pitch::from((0, 5)),
+            start: 0.0,
+            duration: 1.0,
+            ornament: false,
+        }];
+        voices[1] = vec![NoteEvent {
+            pitch: Pitch::from((7, 4)),
+            start: 2.0,
+            duration: 1.0,
+            ornament: false,
+        }];
+        let clipped = OrnamentedProgression {
+            voices,
+            starts: vec![0.0],
+        };
+        assert_eq!(clipped.render(100, &RenderOptions::default()).len(), 100);
+    }
+}
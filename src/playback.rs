@@ -0,0 +1,155 @@
+//! Live playback of harmonies and progressions through rodio, available with the `rodio` feature.
+use crate::audio::{sample_count, ProgressionSamples, SatbSamples};
+use crate::{Progression, SATB};
+use rodio::{ChannelCount, SampleRate, Source};
+use std::iter::Take;
+use std::num::NonZero;
+use std::time::Duration;
+
+/// A mono `rodio::Source` that streams the samples of a sound wave, computed lazily as they are played.
+/// Created by `SATB::source` and `Progression::source`, and may be appended to a `rodio::Sink` directly.
+#[derive(Debug, Clone)]
+pub struct PlaybackSource<I> {
+    /// The samples of the sound wave
+    samples: I,
+    /// The sample rate in samples per second
+    sample_rate: SampleRate,
+    /// The total number of samples of the sound wave
+    num_samples: usize,
+}
+
+impl<I: Iterator<Item = f64>> PlaybackSource<I> {
+    /// Associated method for creating a new `PlaybackSource` of the `num_samples` samples yielded by `samples` at `sample_freq`.
+    ///
+    /// # Panics
+    /// Panics if `sample_freq` is zero.
+    fn new(samples: I, sample_freq: u32, num_samples: usize) -> Self {
+        PlaybackSource {
+            samples,
+            sample_rate: NonZero::new(sample_freq).expect("the sample rate must not be zero"),
+            num_samples,
+        }
+    }
+}
+
+impl<I: Iterator<Item = f64>> Iterator for PlaybackSource<I> {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<rodio::Sample> {
+        self.samples.next().map(|s| s as rodio::Sample)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.samples.size_hint()
+    }
+}
+
+impl<I: Iterator<Item = f64>> Source for PlaybackSource<I> {
+    fn current_span_len(&self) -> Option<usize> {
+        // The channel count and sample rate never change
+        None
+    }
+
+    fn channels(&self) -> ChannelCount {
+        NonZero::<u16>::MIN
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f64(
+            self.num_samples as f64 / self.sample_rate.get() as f64,
+        ))
+    }
+}
+
+impl SATB {
+    /// Method that returns a source playing the harmony for `duration` at `sample_freq`, normalized by the number of voices.
+    /// The samples equal those of `sound_wave`, converted to rodio's sample type.
+    ///
+    /// # Panics
+    /// Panics if `sample_freq` is zero.
+    pub fn source(
+        &self,
+        duration: Duration,
+        sample_freq: u32,
+    ) -> PlaybackSource<Take<SatbSamples>> {
+        let num_samples = sample_count(duration.as_secs_f64(), sample_freq);
+        PlaybackSource::new(
+            self.samples(sample_freq).take(num_samples),
+            sample_freq,
+            num_samples,
+        )
+    }
+}
+
+impl Progression {
+    /// Method that returns a source playing the whole progression as one continuous sound wave at `sample_freq`, with each
+    /// harmony sounding for its duration. The source owns a copy of the progression.
+    ///
+    /// # Panics
+    /// Panics if `sample_freq` is zero.
+    pub fn source(&self, sample_freq: u32) -> PlaybackSource<ProgressionSamples<'static>> {
+        let samples = self.clone().into_samples(sample_freq);
+        let num_samples = samples.len();
+        PlaybackSource::new(samples, sample_freq, num_samples)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Harmony;
+
+    fn c_major() -> SATB {
+        SATB::new(
+            0,
+            (0, 5).into(),
+            (7, 4).into(),
+            (4, 4).into(),
+            (0, 3).into(),
+        )
+    }
+
+    #[test]
+    fn test_satb_source() {
+        let chord = c_major();
+        let source = chord.source(Duration::from_secs(3), 44100);
+        assert_eq!(source.channels().get(), 1);
+        assert_eq!(source.sample_rate().get(), 44100);
+        assert_eq!(source.total_duration(), Some(Duration::from_secs(3)));
+
+        let expected: Vec<rodio::Sample> = chord
+            .sound_wave(3, 44100)
+            .into_iter()
+            .map(|s| s as rodio::Sample)
+            .collect();
+        assert_eq!(source.collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_progression_source() {
+        let mut progression = Progression::new();
+        progression.push_with_duration(c_major(), 0.5);
+        progression.push_with_duration(
+            SATB::new(
+                7,
+                (2, 5).into(),
+                (7, 4).into(),
+                (11, 3).into(),
+                (7, 3).into(),
+            ),
+            0.25,
+        );
+        let source = progression.source(8000);
+        assert_eq!(source.total_duration(), Some(Duration::from_millis(750)));
+        let expected: Vec<rodio::Sample> = progression
+            .samples(8000)
+            .map(|s| s as rodio::Sample)
+            .collect();
+        assert_eq!(expected.len(), 6000);
+        assert_eq!(source.collect::<Vec<_>>(), expected);
+    }
+}
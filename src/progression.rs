@@ -0,0 +1,489 @@
+//! Types for representing a progression of `SATB` harmonies, and the voice leading rules checked between them.
+use crate::{
+    compute_semi_tone_dist_signed, ChordMember, NoteValue, Pitch, PitchClassArithmetic, Rest,
+    Tempo, Voice, SATB,
+};
+use std::fmt::Display;
+
+/// Enumerates the idiomatic usages of a second inversion (six-four) harmony.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SixFourUsage {
+    /// The six-four resolves to the dominant, with the sixth and fourth above the bass resolving down by step.
+    Cadential,
+    /// The bass moves by step through the six-four in a single direction.
+    Passing,
+    /// The bass is shared with both the preceding and the following harmony.
+    Pedal,
+}
+
+impl Display for SixFourUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SixFourUsage::Cadential => "cadential",
+            SixFourUsage::Passing => "passing",
+            SixFourUsage::Pedal => "pedal",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Enumerates the kinds of voice leading violations that can be found in a `Progression`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ViolationKind {
+    /// Two voices a perfect fifth apart move in the same direction to another perfect fifth.
+    ParallelFifths { upper: Voice, lower: Voice },
+    /// Two voices an octave (or unison) apart move in the same direction to another octave.
+    ParallelOctaves { upper: Voice, lower: Voice },
+    /// A second inversion harmony that is not used idiomatically, `expected` is the usage that most nearly fits its context.
+    UnidiomaticSixFour { expected: SixFourUsage },
+}
+
+impl ViolationKind {
+    /// Method that returns the name of the rule violated, in snake case, e.g. "parallel_fifths".
+    pub fn rule(&self) -> &'static str {
+        match self {
+            ViolationKind::ParallelFifths { .. } => "parallel_fifths",
+            ViolationKind::ParallelOctaves { .. } => "parallel_octaves",
+            ViolationKind::UnidiomaticSixFour { .. } => "unidiomatic_six_four",
+        }
+    }
+}
+
+/// A violation of a voice leading rule found in a `Progression`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Violation {
+    /// The index of the harmony the violation was found at. For rules checked between a pair of harmonies
+    /// this is the index of the first harmony of the pair.
+    pub index: usize,
+    /// The kind of violation.
+    pub kind: ViolationKind,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            ViolationKind::ParallelFifths { upper, lower } => write!(
+                f,
+                "parallel fifths between {} and {} from harmony {} to {}",
+                upper,
+                lower,
+                self.index,
+                self.index + 1
+            ),
+            ViolationKind::ParallelOctaves { upper, lower } => write!(
+                f,
+                "parallel octaves between {} and {} from harmony {} to {}",
+                upper,
+                lower,
+                self.index,
+                self.index + 1
+            ),
+            ViolationKind::UnidiomaticSixFour { expected } => write!(
+                f,
+                "second inversion harmony {} is not used idiomatically, expected a {} six-four",
+                self.index, expected
+            ),
+        }
+    }
+}
+
+/// The duration in seconds given to harmonies pushed onto a `Progression` without an explicit duration.
+pub const DEFAULT_CHORD_DURATION: f64 = 1.0;
+
+/// Configures how the voice leading rules are checked over a `Progression`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct ProgressionCheckConfig {
+    /// When true, the harmonies either side of a rest are checked as if the rest were absent. Defaults to false, since the
+    /// voices do not move from one harmony to the other when a silence separates them.
+    pub check_across_rests: bool,
+}
+
+/// A struct that represents a sequence of `SATB` harmonies, each lasting for a duration, optionally separated by rests.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Progression {
+    /// The harmonies of the progression, in order
+    chords: Vec<SATB>,
+    /// The duration in seconds of each harmony
+    durations: Vec<f64>,
+    /// The duration in seconds of the silence before each harmony
+    rests: Vec<f64>,
+    /// The duration in seconds of the silence after the last harmony
+    trailing_rest: f64,
+}
+
+impl Progression {
+    /// Associated method for creating a new, empty `Progression`.
+    pub fn new() -> Self {
+        Progression {
+            chords: Vec::new(),
+            durations: Vec::new(),
+            rests: Vec::new(),
+            trailing_rest: 0.0,
+        }
+    }
+
+    /// Method for appending `chord` to the end of the progression, lasting for `DEFAULT_CHORD_DURATION` seconds.
+    pub fn push(&mut self, chord: SATB) {
+        self.push_with_duration(chord, DEFAULT_CHORD_DURATION);
+    }
+
+    /// Method for appending `chord` to the end of the progression, lasting for `duration` seconds.
+    ///
+    /// `Panics`
+    /// If `duration` is not a positive, finite number of seconds.
+    pub fn push_with_duration(&mut self, chord: SATB, duration: f64) {
+        if !(duration.is_finite() && duration > 0.0) {
+            panic!("invalid chord duration {}", duration);
+        }
+        self.chords.push(chord);
+        self.durations.push(duration);
+        self.rests.push(self.trailing_rest);
+        self.trailing_rest = 0.0;
+    }
+
+    /// Method for appending `chord` to the end of the progression, lasting for the note value `value` at `tempo`.
+    ///
+    /// `Panics`
+    /// If the tempo is not a positive, finite number of beats per minute.
+    pub fn push_note_value(&mut self, chord: SATB, value: NoteValue, tempo: Tempo) {
+        self.push_with_duration(chord, value.seconds(tempo));
+    }
+
+    /// Method for appending `rest` at `tempo` to the end of the progression, silencing every voice. Consecutive rests join
+    /// into a single silence.
+    ///
+    /// `Panics`
+    /// If the tempo is not a positive, finite number of beats per minute.
+    pub fn push_rest(&mut self, rest: Rest, tempo: Tempo) {
+        self.push_rest_with_duration(rest.seconds(tempo));
+    }
+
+    /// Method for appending a silence lasting for `duration` seconds to the end of the progression.
+    ///
+    /// `Panics`
+    /// If `duration` is not a positive, finite number of seconds.
+    pub fn push_rest_with_duration(&mut self, duration: f64) {
+        if !(duration.is_finite() && duration > 0.0) {
+            panic!("invalid rest duration {}", duration);
+        }
+        self.trailing_rest += duration;
+    }
+
+    /// Method for accessing the harmonies of the progression.
+    pub fn chords(&self) -> &[SATB] {
+        &self.chords
+    }
+
+    /// Method for accessing the duration in seconds of each harmony of the progression.
+    pub fn durations(&self) -> &[f64] {
+        &self.durations
+    }
+
+    /// Method for accessing the duration in seconds of the silence before each harmony of the progression, 0 where the
+    /// harmony directly follows the one before it.
+    pub fn rests(&self) -> &[f64] {
+        &self.rests
+    }
+
+    /// Method for accessing the duration in seconds of the silence after the last harmony of the progression.
+    pub fn trailing_rest(&self) -> f64 {
+        self.trailing_rest
+    }
+
+    /// Method that returns the total duration in seconds of the progression, its harmonies and rests.
+    pub fn duration(&self) -> f64 {
+        self.durations.iter().chain(&self.rests).sum::<f64>() + self.trailing_rest
+    }
+
+    /// Method that checks every voice leading rule over the progression, returning all violations found ordered by index.
+    /// Rules are either checked between each pair of consecutive harmonies, or over each harmony together with its neighbors.
+    /// Harmonies separated by a rest are not checked against each other.
+    pub fn violations(&self) -> Vec<Violation> {
+        self.violations_with_config(&ProgressionCheckConfig::default())
+    }
+
+    /// Method that checks every voice leading rule over the progression as `violations` does, treating rests according to `config`.
+    pub fn violations_with_config(&self, config: &ProgressionCheckConfig) -> Vec<Violation> {
+        // Whether the harmony at each index is checked against the harmony before it
+        let joined = |index: usize| config.check_across_rests || self.rests[index] == 0.0;
+        let mut violations = Vec::new();
+        for (index, pair) in self.chords.windows(2).enumerate() {
+            if !joined(index + 1) {
+                continue;
+            }
+            violations.extend(
+                check_pair(&pair[0], &pair[1])
+                    .into_iter()
+                    .map(|kind| Violation { index, kind }),
+            );
+        }
+        for index in 0..self.chords.len() {
+            let prev = index
+                .checked_sub(1)
+                .filter(|_| joined(index))
+                .map(|i| &self.chords[i]);
+            let next = self.chords.get(index + 1).filter(|_| joined(index + 1));
+            violations.extend(
+                check_neighborhood(prev, &self.chords[index], next)
+                    .into_iter()
+                    .map(|kind| Violation { index, kind }),
+            );
+        }
+        violations.sort_by_key(|v| v.index);
+        violations
+    }
+}
+
+impl FromIterator<SATB> for Progression {
+    fn from_iter<I: IntoIterator<Item = SATB>>(iter: I) -> Self {
+        let mut progression = Progression::new();
+        for chord in iter {
+            progression.push(chord);
+        }
+        progression
+    }
+}
+
+/// Helper function to compute the signed number of semitones `to` lies above `from`.
+pub(crate) fn motion(from: &Pitch, to: &Pitch) -> i32 {
+    compute_semi_tone_dist_signed((to.pitch_class, to.octave), (from.pitch_class, from.octave))
+}
+
+/// A function that checks the rules between a pair of voicings, each given as its four voices ordered soprano, alto, tenor, bass.
+/// Returns the kinds of all violations found moving from `prev` to `next`.
+pub fn check_voicing_pair(prev: &[Pitch; 4], next: &[Pitch; 4]) -> Vec<ViolationKind> {
+    let mut violations = Vec::new();
+    for upper in 0..4 {
+        for lower in (upper + 1)..4 {
+            let upper_motion = motion(&prev[upper], &next[upper]);
+            let lower_motion = motion(&prev[lower], &next[lower]);
+            // Both voices must move, in the same direction
+            if upper_motion == 0
+                || lower_motion == 0
+                || upper_motion.signum() != lower_motion.signum()
+            {
+                continue;
+            }
+            let prev_interval = prev[lower].pitch_class.dist(&prev[upper].pitch_class);
+            let next_interval = next[lower].pitch_class.dist(&next[upper].pitch_class);
+            if prev_interval == 7 && next_interval == 7 {
+                violations.push(ViolationKind::ParallelFifths {
+                    upper: Voice::ALL[upper],
+                    lower: Voice::ALL[lower],
+                });
+            } else if prev_interval == 0 && next_interval == 0 {
+                violations.push(ViolationKind::ParallelOctaves {
+                    upper: Voice::ALL[upper],
+                    lower: Voice::ALL[lower],
+                });
+            }
+        }
+    }
+    violations
+}
+
+/// Helper function that checks the pairwise rules between the harmonies `prev` and `next`.
+fn check_pair(prev: &SATB, next: &SATB) -> Vec<ViolationKind> {
+    check_voicing_pair(&prev.voices(), &next.voices())
+}
+
+/// Helper function that checks the rules that require the context of a harmony's neighbors. `prev` and `next` are `None`
+/// when `chord` begins or ends the progression respectively.
+fn check_neighborhood(
+    prev: Option<&SATB>,
+    chord: &SATB,
+    next: Option<&SATB>,
+) -> Vec<ViolationKind> {
+    let mut violations = Vec::new();
+    if let Some(expected) = check_six_four(prev, chord, next) {
+        violations.push(ViolationKind::UnidiomaticSixFour { expected });
+    }
+    violations
+}
+
+/// Helper function that checks whether `chord` is a second inversion triad used outside of a cadential, passing or pedal context.
+/// Returns the usage that most nearly fits the context if the six-four is unidiomatic, `None` otherwise.
+fn check_six_four(prev: Option<&SATB>, chord: &SATB, next: Option<&SATB>) -> Option<SixFourUsage> {
+    let is_six_four = chord.role_of(Voice::Bass) == ChordMember::Fifth
+        && !Voice::ALL
+            .iter()
+            .any(|v| chord.role_of(*v) == ChordMember::Seventh);
+    if !is_six_four {
+        return None;
+    }
+    let bass = chord.bass;
+
+    // Cadential, the next harmony is built on the bass of the six-four, and the sixth and fourth above the bass resolve down by step
+    let resolves_to_dominant = next.is_some_and(|next| {
+        next.root() == bass.pitch_class && next.bass.pitch_class == bass.pitch_class
+    });
+    if let Some(next) = next.filter(|_| resolves_to_dominant) {
+        let resolves = Voice::ALL[..3].iter().all(|v| {
+            let interval = bass.pitch_class.dist(&chord.voice(*v).pitch_class);
+            let step = motion(chord.voice(*v), next.voice(*v));
+            match interval {
+                // The fourth and sixth above the bass
+                5 | 8 | 9 => step == -1 || step == -2,
+                _ => true,
+            }
+        });
+        if resolves {
+            return None;
+        }
+        return Some(SixFourUsage::Cadential);
+    }
+
+    let prev_bass = prev.map(|p| motion(&p.bass, &bass));
+    let next_bass = next.map(|n| motion(&bass, &n.bass));
+    // Pedal, the bass is shared with both neighbors
+    if prev_bass == Some(0) && next_bass == Some(0) {
+        return None;
+    }
+    // Passing, the bass moves by step through the six-four in a single direction
+    let is_step = |m: i32| m.abs() == 1 || m.abs() == 2;
+    if let (Some(into), Some(out)) = (prev_bass, next_bass) {
+        if is_step(into) && is_step(out) && into.signum() == out.signum() {
+            return None;
+        }
+    }
+
+    // Report the usage that most nearly fits the context
+    if prev_bass == Some(0) || next_bass == Some(0) {
+        Some(SixFourUsage::Pedal)
+    } else if prev_bass.is_some_and(is_step) || next_bass.is_some_and(is_step) {
+        Some(SixFourUsage::Passing)
+    } else {
+        Some(SixFourUsage::Cadential)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn satb(root: u8, s: (u8, u8), a: (u8, u8), t: (u8, u8), b: (u8, u8)) -> SATB {
+        SATB::new(root, s.into(), a.into(), t.into(), b.into())
+    }
+
+    #[test]
+    fn test_parallel_fifths() {
+        // C major to D minor in root position, with fifths between the bass and tenor
+        let progression: Progression = vec![
+            satb(0, (4, 4), (0, 4), (7, 3), (0, 3)),
+            satb(2, (5, 4), (2, 4), (9, 3), (2, 3)),
+        ]
+        .into_iter()
+        .collect();
+        let violations = progression.violations();
+        println!("{:?}", violations);
+        assert!(violations.contains(&Violation {
+            index: 0,
+            kind: ViolationKind::ParallelFifths {
+                upper: Voice::Tenor,
+                lower: Voice::Bass
+            }
+        }));
+    }
+
+    #[test]
+    fn test_cadential_six_four() {
+        let progression: Progression = vec![
+            satb(0, (4, 4), (0, 4), (7, 3), (7, 2)),
+            satb(7, (2, 4), (11, 3), (7, 3), (7, 2)),
+        ]
+        .into_iter()
+        .collect();
+        assert!(progression.violations().is_empty());
+    }
+
+    #[test]
+    fn test_cadential_six_four_without_resolution() {
+        // The sixth above the bass leaps up rather than resolving down by step
+        let progression: Progression = vec![
+            satb(0, (4, 4), (0, 4), (7, 3), (7, 2)),
+            satb(7, (7, 4), (2, 4), (11, 3), (7, 2)),
+        ]
+        .into_iter()
+        .collect();
+        let violations = progression.violations();
+        assert_eq!(
+            violations,
+            vec![Violation {
+                index: 0,
+                kind: ViolationKind::UnidiomaticSixFour {
+                    expected: SixFourUsage::Cadential
+                }
+            }]
+        );
+    }
+
+    #[test]
+    fn test_pedal_six_four() {
+        let progression: Progression = vec![
+            satb(0, (0, 5), (4, 4), (7, 3), (0, 3)),
+            satb(5, (0, 5), (5, 4), (9, 3), (0, 3)),
+            satb(0, (0, 5), (4, 4), (7, 3), (0, 3)),
+        ]
+        .into_iter()
+        .collect();
+        assert!(progression.violations().is_empty());
+    }
+
+    #[test]
+    fn test_passing_six_four() {
+        let progression: Progression = vec![
+            satb(0, (4, 4), (0, 4), (7, 3), (0, 3)),
+            satb(7, (2, 4), (11, 3), (7, 3), (2, 3)),
+            satb(0, (0, 5), (0, 4), (7, 3), (4, 3)),
+        ]
+        .into_iter()
+        .collect();
+        assert!(progression.violations().is_empty());
+    }
+
+    #[test]
+    fn test_isolated_six_four() {
+        let progression: Progression = vec![
+            satb(0, (4, 4), (0, 4), (7, 3), (0, 3)),
+            satb(0, (4, 4), (0, 4), (7, 3), (7, 2)),
+            satb(0, (4, 4), (0, 4), (7, 3), (0, 3)),
+        ]
+        .into_iter()
+        .collect();
+        let violations = progression.violations();
+        assert_eq!(
+            violations,
+            vec![Violation {
+                index: 1,
+                kind: ViolationKind::UnidiomaticSixFour {
+                    expected: SixFourUsage::Cadential
+                }
+            }]
+        );
+        println!("{}", violations[0]);
+    }
+
+    #[test]
+    fn test_rests_break_voice_leading() {
+        let mut progression = Progression::new();
+        progression.push(satb(0, (4, 4), (0, 4), (7, 3), (0, 3)));
+        progression.push_rest(Rest(NoteValue::Half), Tempo::new(120.0).unwrap());
+        progression.push(satb(2, (5, 4), (2, 4), (9, 3), (2, 3)));
+        assert_eq!(progression.rests(), [0.0, 1.0]);
+        assert_eq!(progression.duration(), 3.0);
+        assert!(progression.violations().is_empty());
+
+        let config = ProgressionCheckConfig {
+            check_across_rests: true,
+        };
+        assert!(progression
+            .violations_with_config(&config)
+            .contains(&Violation {
+                index: 0,
+                kind: ViolationKind::ParallelFifths {
+                    upper: Voice::Tenor,
+                    lower: Voice::Bass
+                }
+            }));
+    }
+}
@@ -0,0 +1,378 @@
+//! A versioned JSON document format for saving and loading progressions, available with the `serde` feature.
+//!
+//! A project document is a JSON object with the fields
+//!
+//! - `version`: the version of the format, currently `PROJECT_VERSION`
+//! - `tuning`: the frequency of A4 in Hz, 440 if missing
+//! - `key`: the key of the progression, e.g. `{ "tonic": 9, "mode": "minor" }`, or `null`
+//! - `chords`: the harmonies, in order, each either given by explicit voices, `{ "root": 0, "voices": ["E5", "G4", "C4",
+//!   "C3"] }` from the soprano down to the bass, or by a chord symbol voiced automatically, `{ "symbol": "G7/B" }`, with an
+//!   optional `duration` in seconds, `DEFAULT_CHORD_DURATION` if missing, and `rest`, the silence in seconds before it
+//! - `trailing_rest`: the silence in seconds after the last harmony, 0 if missing
+//!
+//! Fields unknown to this version of the format are ignored, so documents written by later versions can still be loaded.
+use crate::chord::{note_name, parse_pitch};
+use crate::progression::DEFAULT_CHORD_DURATION;
+use crate::{
+    Chord, ChordSymbolError, Key, Pitch, Progression, SatbError, Spelling, VoicingPreferences,
+    A_440_FREQUENCY, SATB,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::path::Path;
+
+/// The version of the project document format written by `Progression::save`. Documents of later versions are rejected.
+pub const PROJECT_VERSION: u32 = 1;
+
+/// A struct that represents a project document, see the module documentation for the format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectDocument {
+    /// The version of the document format
+    pub version: u32,
+    /// The frequency of A4 in Hz
+    #[serde(default = "default_tuning")]
+    pub tuning: f64,
+    /// The key of the progression, if known
+    #[serde(default)]
+    pub key: Option<Key>,
+    /// The harmonies of the progression, in order
+    #[serde(default)]
+    pub chords: Vec<ChordEntry>,
+    /// The duration in seconds of the silence after the last harmony
+    #[serde(default)]
+    pub trailing_rest: f64,
+}
+
+/// A struct that represents a harmony of a project document, given either by its root and voices or by a chord symbol.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChordEntry {
+    /// The pitch class of the root, required along with `voices`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<u8>,
+    /// The pitches of the soprano, alto, tenor and bass, written as note names with octaves, e.g. "F#4"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voices: Option<[String; 4]>,
+    /// The chord symbol of the harmony, see `Chord::from_symbol`, used when `voices` is missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// The duration of the harmony in seconds
+    #[serde(default = "default_duration")]
+    pub duration: f64,
+    /// The duration in seconds of the silence before the harmony
+    #[serde(default)]
+    pub rest: f64,
+}
+
+/// Helper function that returns the tuning of a document missing one.
+fn default_tuning() -> f64 {
+    A_440_FREQUENCY
+}
+
+/// Helper function that returns the duration of a harmony missing one.
+fn default_duration() -> f64 {
+    DEFAULT_CHORD_DURATION
+}
+
+/// Enumerates the reasons a project document cannot be saved or loaded.
+#[derive(Debug)]
+pub enum ProjectError {
+    /// The file could not be read or written.
+    Io(std::io::Error),
+    /// The file is not a well formed project document.
+    Format(serde_json::Error),
+    /// The document was written by a later, unsupported version of the format.
+    UnsupportedVersion { version: u32 },
+    /// The tuning is not a positive, finite frequency.
+    InvalidTuning,
+    /// The tonic of the key is not a pitch class from 0 to 11.
+    InvalidKey,
+    /// The harmony at `index` has neither a root and voices nor a chord symbol, a voice that is not a note name with an
+    /// octave, or a duration or rest that is not a finite number of seconds, positive for durations and not negative for rests.
+    InvalidEntry { index: usize },
+    /// The chord symbol of the harmony at `index` cannot be parsed.
+    InvalidSymbol {
+        index: usize,
+        error: ChordSymbolError,
+    },
+    /// The harmony at `index` fails validation, or its chord symbol has no valid voicing.
+    InvalidChord { index: usize, error: SatbError },
+}
+
+impl Display for ProjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectError::Io(e) => write!(f, "failed to access project file: {}", e),
+            ProjectError::Format(e) => write!(f, "malformed project document: {}", e),
+            ProjectError::UnsupportedVersion { version } => write!(
+                f,
+                "project version {} is newer than the supported version {}",
+                version, PROJECT_VERSION
+            ),
+            ProjectError::InvalidTuning => write!(f, "the tuning is not a positive frequency"),
+            ProjectError::InvalidKey => write!(f, "the tonic of the key is not a pitch class"),
+            ProjectError::InvalidEntry { index } => write!(f, "chord {} is malformed", index),
+            ProjectError::InvalidSymbol { index, error } => write!(f, "chord {}: {}", index, error),
+            ProjectError::InvalidChord { index, error } => {
+                write!(f, "chord {} is invalid: {}", index, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProjectError::Io(e) => Some(e),
+            ProjectError::Format(e) => Some(e),
+            ProjectError::InvalidSymbol { error, .. } => Some(error),
+            ProjectError::InvalidChord { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ProjectError {
+    fn from(e: std::io::Error) -> Self {
+        ProjectError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ProjectError {
+    fn from(e: serde_json::Error) -> Self {
+        ProjectError::Format(e)
+    }
+}
+
+/// Helper function that returns the pitch of the same pitch class and octave as `pitch` with A4 tuned to `tuning` Hz.
+fn tuned(pitch: &Pitch, tuning: f64) -> Pitch {
+    let tempered = Pitch::compute_frequency(pitch.pitch_class(), pitch.octave());
+    Pitch::new(
+        tempered * tuning / A_440_FREQUENCY,
+        pitch.pitch_class(),
+        pitch.octave(),
+    )
+}
+
+impl ProjectDocument {
+    /// Associated method for creating the document of `progression` in `key`, if known, with each harmony given by its root
+    /// and voices. The tuning is taken from the soprano of the first harmony, see `Pitch::cents_offset`.
+    pub fn from_progression(progression: &Progression, key: Option<Key>) -> Self {
+        let tuning = progression.chords().first().map_or(A_440_FREQUENCY, |c| {
+            A_440_FREQUENCY * (c.soprano.cents_offset() / 1200.0).exp2()
+        });
+        let chords = progression
+            .chords()
+            .iter()
+            .enumerate()
+            .map(|(i, chord)| ChordEntry {
+                root: Some(chord.root()),
+                voices: Some(chord.voices().map(|p| {
+                    format!(
+                        "{}{}",
+                        note_name(p.pitch_class(), Spelling::Sharps),
+                        p.octave()
+                    )
+                })),
+                symbol: None,
+                duration: progression.durations()[i],
+                rest: progression.rests()[i],
+            })
+            .collect();
+        ProjectDocument {
+            version: PROJECT_VERSION,
+            tuning,
+            key,
+            chords,
+            trailing_rest: progression.trailing_rest(),
+        }
+    }
+
+    /// Method that builds the progression described by the document, validating each harmony given by its voices, see
+    /// `SATB::try_new`, and voicing each harmony given by a chord symbol with `Chord::to_satb`. Returns an error pointing to
+    /// the first harmony that is malformed or invalid.
+    pub fn to_progression(&self) -> Result<Progression, ProjectError> {
+        if self.version > PROJECT_VERSION {
+            return Err(ProjectError::UnsupportedVersion {
+                version: self.version,
+            });
+        }
+        if !(self.tuning.is_finite() && self.tuning > 0.0) {
+            return Err(ProjectError::InvalidTuning);
+        }
+        if self.key.is_some_and(|key| key.tonic >= 12) {
+            return Err(ProjectError::InvalidKey);
+        }
+        let seconds = |s: f64| s.is_finite() && s >= 0.0;
+        if !seconds(self.trailing_rest) {
+            return Err(ProjectError::InvalidEntry {
+                index: self.chords.len(),
+            });
+        }
+
+        let mut progression = Progression::new();
+        for (index, entry) in self.chords.iter().enumerate() {
+            if !(seconds(entry.rest) && seconds(entry.duration) && entry.duration > 0.0) {
+                return Err(ProjectError::InvalidEntry { index });
+            }
+            let chord = match (&entry.root, &entry.voices, &entry.symbol) {
+                (Some(root), Some(voices), _) => {
+                    let pitches: Option<Vec<Pitch>> =
+                        voices.iter().map(|v| parse_pitch(v.trim())).collect();
+                    let [s, a, t, b] = pitches
+                        .and_then(|p| <[Pitch; 4]>::try_from(p).ok())
+                        .ok_or(ProjectError::InvalidEntry { index })?;
+                    SATB::try_new(*root, s, a, t, b)
+                }
+                (_, None, Some(symbol)) => Chord::from_symbol(symbol)
+                    .map_err(|error| ProjectError::InvalidSymbol { index, error })?
+                    .to_satb(&VoicingPreferences::default()),
+                _ => return Err(ProjectError::InvalidEntry { index }),
+            }
+            .map_err(|error| ProjectError::InvalidChord { index, error })?;
+            let [s, a, t, b] = chord.voices().map(|p| tuned(&p, self.tuning));
+            if entry.rest > 0.0 {
+                progression.push_rest_with_duration(entry.rest);
+            }
+            progression.push_with_duration(
+                SATB::new_unchecked(chord.root(), s, a, t, b),
+                entry.duration,
+            );
+        }
+        if self.trailing_rest > 0.0 {
+            progression.push_rest_with_duration(self.trailing_rest);
+        }
+        Ok(progression)
+    }
+}
+
+impl Progression {
+    /// Method that saves the progression to a project document at `path`, see the `project` module for the format, with
+    /// each harmony written by its root and voices. See `ProjectDocument::from_progression` to also record the key.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ProjectError> {
+        let document = ProjectDocument::from_progression(self, None);
+        std::fs::write(path, serde_json::to_string_pretty(&document)?)?;
+        Ok(())
+    }
+
+    /// Associated method for loading the progression saved in the project document at `path`, see `ProjectDocument::to_progression`.
+    /// Fields unknown to this version of the format are ignored.
+    pub fn load(path: impl AsRef<Path>) -> Result<Progression, ProjectError> {
+        let document: ProjectDocument = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        document.to_progression()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::KeyMode;
+
+    /// Helper function that returns a path in the temporary directory unique to the test `name`.
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("twelve_et_{}_{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut progression = Progression::new();
+        progression.push(SATB::new(
+            0,
+            (4, 5).into(),
+            (7, 4).into(),
+            (0, 4).into(),
+            (0, 3).into(),
+        ));
+        progression.push_rest_with_duration(0.5);
+        progression.push_with_duration(
+            SATB::new(
+                7,
+                (11, 4).into(),
+                (7, 4).into(),
+                (2, 4).into(),
+                (7, 3).into(),
+            ),
+            2.0,
+        );
+        progression.push_rest_with_duration(0.25);
+
+        let path = temp_path("round_trip");
+        progression.save(&path).unwrap();
+        let loaded = Progression::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, progression);
+
+        let document =
+            ProjectDocument::from_progression(&progression, Some(Key::new(0, KeyMode::Major)));
+        assert_eq!(document.chords[0].voices.as_ref().unwrap()[0], "E5");
+        let json = serde_json::to_string(&document).unwrap();
+        assert!(json.contains("\"key\":{\"tonic\":0,\"mode\":\"major\"}"));
+        let parsed: ProjectDocument = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, document);
+    }
+
+    #[test]
+    fn test_load_unknown_fields_and_symbols() {
+        let path = temp_path("unknown_fields");
+        std::fs::write(
+            &path,
+            r#"{
+                "version": 1,
+                "tuning": 432.0,
+                "author": "someone",
+                "chords": [
+                    { "root": 0, "voices": ["E5", "G4", "C4", "C3"], "velocity": 90 },
+                    { "symbol": "G7/B", "duration": 2.0 }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let loaded = Progression::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.chords().len(), 2);
+        assert_eq!(loaded.durations(), &[DEFAULT_CHORD_DURATION, 2.0]);
+        assert!(
+            (loaded.chords()[0].tenor.frequency() - 432.0 * 2f64.powf(-9.0 / 12.0)).abs() < 1e-6
+        );
+        assert_eq!(loaded.chords()[1].bass.pitch_class(), 11);
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let document = |chords: &str| {
+            serde_json::from_str::<ProjectDocument>(&format!(
+                "{{ \"version\": 1, \"chords\": [{}] }}",
+                chords
+            ))
+            .unwrap()
+        };
+        // The second harmony doubles the third of C major
+        let invalid = document(
+            r#"{ "root": 0, "voices": ["E5", "G4", "C4", "C3"] },
+               { "root": 0, "voices": ["E5", "G4", "E4", "C3"] }"#,
+        );
+        assert!(matches!(
+            invalid.to_progression(),
+            Err(ProjectError::InvalidChord { index: 1, .. })
+        ));
+        let malformed = document(r#"{ "root": 0, "voices": ["E5", "G4", "C4", "H3"] }"#);
+        assert!(matches!(
+            malformed.to_progression(),
+            Err(ProjectError::InvalidEntry { index: 0 })
+        ));
+        let symbol = document(r#"{ "symbol": "Cxyz" }"#);
+        assert!(matches!(
+            symbol.to_progression(),
+            Err(ProjectError::InvalidSymbol { index: 0, .. })
+        ));
+        let mut future = document("");
+        future.version = PROJECT_VERSION + 1;
+        assert!(matches!(
+            future.to_progression(),
+            Err(ProjectError::UnsupportedVersion { .. })
+        ));
+        assert!(matches!(
+            Progression::load(temp_path("missing")),
+            Err(ProjectError::Io(_))
+        ));
+    }
+}
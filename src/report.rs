@@ -0,0 +1,343 @@
+//! A machine readable report of the harmonic analysis of a progression, for grading tools and dashboards.
+//!
+//! With the `serde` feature a report is written to JSON by `AnalysisReport::to_json` as an object with the fields
+//!
+//! - `version`: the version of the format, currently `REPORT_VERSION`
+//! - `key`: the key the progression is analyzed in, e.g. `{ "tonic": 0, "mode": "major" }`
+//! - `chords`: a report for each harmony, in order, with its `index`, its `root` and `voices` spelled in the key from the
+//!   soprano down to the bass, e.g. `["E5", "G4", "C4", "C3"]`, its `roman_numeral`, e.g. `"V7"`, its `quality`, e.g.
+//!   `"dominant seventh"`, its `inversion`, 0 for root position, its `figured_bass`, e.g. `"65"`, the chord members it
+//!   `doubling`s, e.g. `["root"]`, its `spacing`, `"close"` or `"open"`, and the `violations` found at it, each with the
+//!   `rule` violated, e.g. `"parallel_fifths"`, the `voices` involved and a `message` describing it. The labels of a harmony
+//!   that cannot be analyzed are `null`.
+//!
+//! Violations of rules checked between a pair of harmonies are reported at the first harmony of the pair, as by
+//! `Progression::violations`.
+use crate::notation::spell_pitch;
+use crate::{
+    ChordMember, ChordQuality, Key, MinorVariant, Pitch, PitchClassSet, Progression, ViolationKind,
+    Voice, SATB,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The version of the report format written by `AnalysisReport::to_json`.
+pub const REPORT_VERSION: u32 = 1;
+
+/// A struct that represents the analysis of a progression in a key, see the module documentation for the format.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AnalysisReport {
+    /// The version of the report format
+    pub version: u32,
+    /// The key the progression is analyzed in
+    pub key: Key,
+    /// The analysis of each harmony, in order
+    pub chords: Vec<ChordReport>,
+}
+
+/// A struct that represents the analysis of a single harmony of a progression.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ChordReport {
+    /// The index of the harmony in the progression
+    pub index: usize,
+    /// The root of the harmony, spelled in the key
+    pub root: String,
+    /// The pitches of the harmony from the soprano down to the bass, spelled in the key with the octave of their letter
+    pub voices: Vec<String>,
+    /// The roman numeral of the harmony in the key, e.g. "V7", or `None` if it has none
+    pub roman_numeral: Option<String>,
+    /// The name of the quality of the harmony, e.g. "dominant seventh", or `None` if it is not a triad or seventh chord
+    pub quality: Option<String>,
+    /// The inversion of the harmony, 0 for root position up to 3 for the third inversion of a seventh chord, or `None` if
+    /// the bass is not a chord member
+    pub inversion: Option<u8>,
+    /// The figures of the inversion, e.g. "6" or "43", empty for a root position triad, or `None` without an inversion
+    pub figured_bass: Option<String>,
+    /// The chord members sounded by more than one voice, e.g. "root"
+    pub doubling: Vec<String>,
+    /// "close" if the soprano lies within an octave of the tenor, "open" otherwise
+    pub spacing: String,
+    /// The violations found at the harmony
+    pub violations: Vec<ViolationReport>,
+}
+
+/// A struct that represents a voice leading violation found at a harmony of a progression.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ViolationReport {
+    /// The name of the rule violated, see `ViolationKind::rule`
+    pub rule: String,
+    /// The voices involved, upper first, empty for rules over the whole harmony
+    pub voices: Vec<String>,
+    /// The description of the violation
+    pub message: String,
+}
+
+/// Helper function that names the chord member `member`.
+fn member_name(member: ChordMember) -> &'static str {
+    match member {
+        ChordMember::Root => "root",
+        ChordMember::Third => "third",
+        ChordMember::Fifth => "fifth",
+        ChordMember::Seventh => "seventh",
+        ChordMember::NonChordTone => "non-chord tone",
+    }
+}
+
+/// Helper function that finds the quality of `chord` on its root, also when its fifth is omitted, e.g. a dominant seventh
+/// voiced as a doubled root, a third and a seventh.
+fn chord_quality(chord: &SATB) -> Option<ChordQuality> {
+    let root = chord.root();
+    let classes: PitchClassSet = chord.voices().iter().map(|p| p.pitch_class()).collect();
+    ChordQuality::ALL.into_iter().find(|quality| {
+        let mut members = classes;
+        if quality.intervals()[2] == 7 {
+            members.insert((root + 7) % 12);
+        }
+        quality.is_tertian() && quality.pitch_classes(root) == members
+    })
+}
+
+/// Helper function that spells `pitch` in `key` with the octave of its letter.
+fn pitch_name(pitch: &Pitch, key: &Key) -> String {
+    let (spelled, octave) = spell_pitch(pitch, Some(key));
+    format!("{}{}", spelled, octave)
+}
+
+/// Helper function that returns the position of `pitch` in half steps above C0.
+fn position(pitch: &Pitch) -> i32 {
+    12 * pitch.octave() as i32 + pitch.pitch_class() as i32
+}
+
+impl Progression {
+    /// Method that analyzes each harmony of the progression in `key`, labelling it with its roman numeral, quality, inversion
+    /// and figured bass, the chord members it doubles and its spacing, along with the voice leading violations found at it,
+    /// see the `report` module. Harmonies voiced with their fifth omitted are labelled as the complete harmony.
+    pub fn analysis_report(&self, key: &Key) -> AnalysisReport {
+        let mut violations: Vec<Vec<ViolationReport>> = vec![Vec::new(); self.chords().len()];
+        for violation in self.violations() {
+            let voices = match violation.kind {
+                ViolationKind::ParallelFifths { upper, lower }
+                | ViolationKind::ParallelOctaves { upper, lower } => {
+                    vec![upper.to_string(), lower.to_string()]
+                }
+                ViolationKind::UnidiomaticSixFour { .. } => Vec::new(),
+            };
+            violations[violation.index].push(ViolationReport {
+                rule: violation.kind.rule().to_string(),
+                voices,
+                message: violation.to_string(),
+            });
+        }
+
+        let chords = self
+            .chords()
+            .iter()
+            .zip(violations)
+            .enumerate()
+            .map(|(index, (chord, violations))| {
+                let quality = chord_quality(chord);
+                let roman_numeral = quality.and_then(|quality| {
+                    key.roman_numeral_with(chord.root(), quality, MinorVariant::Auto)
+                });
+                let inversion = match chord.role_of(Voice::Bass) {
+                    ChordMember::Root => Some(0),
+                    ChordMember::Third => Some(1),
+                    ChordMember::Fifth => Some(2),
+                    ChordMember::Seventh => Some(3),
+                    ChordMember::NonChordTone => None,
+                };
+                let figured_bass = match (quality, inversion) {
+                    (Some(quality), Some(inversion)) if quality.is_seventh() => {
+                        Some(["7", "65", "43", "42"][inversion as usize].to_string())
+                    }
+                    (Some(_), Some(inversion @ 0..=2)) => {
+                        Some(["", "6", "64"][inversion as usize].to_string())
+                    }
+                    _ => None,
+                };
+                let mut doubling = Vec::new();
+                for member in [
+                    ChordMember::Root,
+                    ChordMember::Third,
+                    ChordMember::Fifth,
+                    ChordMember::Seventh,
+                    ChordMember::NonChordTone,
+                ] {
+                    let count = Voice::ALL
+                        .iter()
+                        .filter(|voice| chord.role_of(**voice) == member)
+                        .count();
+                    if count > 1 {
+                        doubling.push(member_name(member).to_string());
+                    }
+                }
+                let spacing = if position(&chord.soprano) - position(&chord.tenor) <= 12 {
+                    "close"
+                } else {
+                    "open"
+                };
+                ChordReport {
+                    index,
+                    root: key.spell(chord.root()).to_string(),
+                    voices: chord.voices().iter().map(|p| pitch_name(p, key)).collect(),
+                    roman_numeral: roman_numeral.map(|numeral| numeral.to_string()),
+                    quality: quality.map(|quality| quality.to_string()),
+                    inversion,
+                    figured_bass,
+                    doubling,
+                    spacing: spacing.to_string(),
+                    violations,
+                }
+            })
+            .collect();
+
+        AnalysisReport {
+            version: REPORT_VERSION,
+            key: *key,
+            chords,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl AnalysisReport {
+    /// Method that writes the report as pretty printed JSON, see the `report` module for the format.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("a report always serializes to JSON")
+    }
+
+    /// Associated method for reading a report back from the JSON written by `to_json`.
+    pub fn from_json(json: &str) -> Result<AnalysisReport, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::KeyMode;
+
+    /// Helper function that builds I ii6 V7 I in C major, the last harmony with its fifth omitted.
+    fn cadence() -> Progression {
+        let mut progression = Progression::new();
+        progression.push(SATB::new(
+            0,
+            (0, 5).into(),
+            (7, 4).into(),
+            (4, 4).into(),
+            (0, 3).into(),
+        ));
+        progression.push(SATB::new(
+            2,
+            (2, 5).into(),
+            (9, 4).into(),
+            (2, 4).into(),
+            (5, 3).into(),
+        ));
+        progression.push(SATB::new(
+            7,
+            (2, 5).into(),
+            (5, 4).into(),
+            (11, 3).into(),
+            (7, 2).into(),
+        ));
+        progression.push(SATB::new(
+            0,
+            (0, 5).into(),
+            (4, 4).into(),
+            (0, 4).into(),
+            (0, 3).into(),
+        ));
+        progression
+    }
+
+    #[test]
+    fn test_analysis_report() {
+        let key = Key::new(0, KeyMode::Major);
+        let report = cadence().analysis_report(&key);
+        assert_eq!(report.version, REPORT_VERSION);
+        assert_eq!(report.key, key);
+        assert_eq!(report.chords.len(), 4);
+
+        let numerals: Vec<Option<&str>> = report
+            .chords
+            .iter()
+            .map(|chord| chord.roman_numeral.as_deref())
+            .collect();
+        assert_eq!(numerals, [Some("I"), Some("ii"), Some("V7"), Some("I")]);
+
+        let ii = &report.chords[1];
+        assert_eq!(ii.root, "D");
+        assert_eq!(ii.voices, ["D5", "A4", "D4", "F3"]);
+        assert_eq!(ii.quality.as_deref(), Some("minor"));
+        assert_eq!(ii.inversion, Some(1));
+        assert_eq!(ii.figured_bass.as_deref(), Some("6"));
+        assert_eq!(ii.doubling, ["root"]);
+        assert_eq!(ii.spacing, "close");
+
+        let v7 = &report.chords[2];
+        assert_eq!(v7.quality.as_deref(), Some("dominant seventh"));
+        assert_eq!(v7.figured_bass.as_deref(), Some("7"));
+        assert!(v7.doubling.is_empty());
+        assert_eq!(v7.spacing, "open");
+
+        // The final tonic triples its root in place of the fifth
+        let i = &report.chords[3];
+        assert_eq!(i.quality.as_deref(), Some("major"));
+        assert_eq!(i.figured_bass.as_deref(), Some(""));
+        assert_eq!(i.doubling, ["root"]);
+        assert!(report
+            .chords
+            .iter()
+            .all(|chord| chord.violations.is_empty()));
+    }
+
+    #[test]
+    fn test_report_violations() {
+        // C to D in root position with the fifth above the bass in the tenor moves in parallel fifths
+        let mut progression = Progression::new();
+        progression.push(SATB::new(
+            0,
+            (0, 5).into(),
+            (4, 4).into(),
+            (7, 3).into(),
+            (0, 3).into(),
+        ));
+        progression.push(SATB::new(
+            2,
+            (2, 5).into(),
+            (5, 4).into(),
+            (9, 3).into(),
+            (2, 3).into(),
+        ));
+        let report = progression.analysis_report(&Key::new(0, KeyMode::Major));
+        let violations = &report.chords[0].violations;
+        assert!(violations
+            .iter()
+            .any(|violation| violation.rule == "parallel_fifths"
+                && violation.voices == ["tenor", "bass"]
+                && violation
+                    .message
+                    .starts_with("parallel fifths between tenor and bass")));
+        assert!(report.chords[1].violations.is_empty());
+        assert_eq!(report.chords[1].roman_numeral.as_deref(), Some("ii"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_round_trip() {
+        let report = cadence().analysis_report(&Key::new(9, KeyMode::Minor));
+        let json = report.to_json();
+        assert!(json.contains("\"version\": 1"));
+        assert!(json.contains("\"figured_bass\": \"6\""));
+        assert_eq!(AnalysisReport::from_json(&json).unwrap(), report);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["key"]["mode"], "minor");
+        assert_eq!(value["chords"][2]["quality"], "dominant seventh");
+        assert_eq!(value["chords"][2]["voices"][3], "G2");
+    }
+}
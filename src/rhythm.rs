@@ -0,0 +1,144 @@
+//! Durations given as note values at a tempo, converted to seconds for progressions and note events.
+use std::fmt::Display;
+
+/// A tempo, given in quarter note beats per minute, always positive and finite.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tempo(f64);
+
+impl Default for Tempo {
+    /// A tempo of 60 BPM, one beat a second.
+    fn default() -> Self {
+        Tempo(60.0)
+    }
+}
+
+impl Tempo {
+    /// Associated method for creating a new `Tempo` of `bpm` quarter note beats per minute. Returns `None` unless `bpm` is
+    /// positive and finite.
+    pub fn new(bpm: f64) -> Option<Self> {
+        (bpm.is_finite() && bpm > 0.0).then_some(Tempo(bpm))
+    }
+
+    /// Method for accessing the tempo in quarter note beats per minute.
+    pub fn bpm(&self) -> f64 {
+        self.0
+    }
+
+    /// Method that returns the length of one beat, a quarter note, in seconds.
+    pub fn seconds_per_beat(&self) -> f64 {
+        60.0 / self.0
+    }
+}
+
+impl Display for Tempo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} BPM", self.0)
+    }
+}
+
+/// Enumerates the note values durations can be given in. A dotted value lasts half as long again as the plain value, and a
+/// triplet value lasts two thirds as long, so three triplets fill the time of two plain notes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NoteValue {
+    Whole,
+    Half,
+    Quarter,
+    Eighth,
+    Sixteenth,
+    DottedWhole,
+    DottedHalf,
+    DottedQuarter,
+    DottedEighth,
+    HalfTriplet,
+    QuarterTriplet,
+    EighthTriplet,
+}
+
+impl NoteValue {
+    /// Method that returns the length of the note value in beats, where a quarter note is one beat.
+    pub fn beats(&self) -> f64 {
+        match self {
+            NoteValue::Whole => 4.0,
+            NoteValue::Half => 2.0,
+            NoteValue::Quarter => 1.0,
+            NoteValue::Eighth => 0.5,
+            NoteValue::Sixteenth => 0.25,
+            NoteValue::DottedWhole => 6.0,
+            NoteValue::DottedHalf => 3.0,
+            NoteValue::DottedQuarter => 1.5,
+            NoteValue::DottedEighth => 0.75,
+            NoteValue::HalfTriplet => 4.0 / 3.0,
+            NoteValue::QuarterTriplet => 2.0 / 3.0,
+            NoteValue::EighthTriplet => 1.0 / 3.0,
+        }
+    }
+
+    /// Method that returns the length of the note value in seconds at `tempo`.
+    pub fn seconds(&self, tempo: Tempo) -> f64 {
+        self.beats() * tempo.seconds_per_beat()
+    }
+}
+
+/// A rest, a silence lasting for a note value, usable in a `Progression` or a `Melody`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Rest(pub NoteValue);
+
+impl Rest {
+    /// Method that returns the length of the rest in seconds at `tempo`.
+    pub fn seconds(&self, tempo: Tempo) -> f64 {
+        self.0.seconds(tempo)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{NoteEvent, Pitch, Progression, RenderOptions, SATB};
+
+    #[test]
+    fn test_note_value_seconds() {
+        assert_eq!(NoteValue::DottedQuarter.seconds(Tempo(120.0)), 0.75);
+        assert_eq!(NoteValue::Quarter.seconds(Tempo(90.0)), 2.0 / 3.0);
+        // Three eighth note triplets fill one beat, three quarter note triplets fill two
+        assert!((NoteValue::EighthTriplet.seconds(Tempo(90.0)) - 2.0 / 9.0).abs() < 1e-12);
+        assert!((NoteValue::QuarterTriplet.seconds(Tempo(90.0)) - 4.0 / 9.0).abs() < 1e-12);
+        assert_eq!(NoteValue::Whole.seconds(Tempo(60.0)), 4.0);
+
+        // Tempos must be positive and finite
+        assert_eq!(Tempo::new(120.0).map(|t| t.bpm()), Some(120.0));
+        for bpm in [0.0, -60.0, f64::NAN, f64::INFINITY] {
+            assert_eq!(Tempo::new(bpm), None);
+        }
+
+        let event =
+            NoteEvent::with_note_value(Pitch::from((0, 4)), 1.0, NoteValue::Half, Tempo(120.0));
+        assert_eq!(event.duration, 1.0);
+    }
+
+    #[test]
+    fn test_progression_note_values() {
+        let chord = SATB::new(
+            0,
+            (0, 5).into(),
+            (7, 4).into(),
+            (4, 4).into(),
+            (0, 3).into(),
+        );
+        let tempo = Tempo(120.0);
+        let values = [
+            NoteValue::Quarter,
+            NoteValue::DottedHalf,
+            NoteValue::Eighth,
+            NoteValue::Whole,
+        ];
+        let mut progression = Progression::new();
+        for value in values {
+            progression.push_note_value(chord.clone(), value, tempo);
+        }
+        let total: f64 = values.iter().map(|v| v.seconds(tempo)).sum();
+        assert_eq!(total, 4.25);
+        let rendered = progression.render(8000, &RenderOptions::new());
+        assert_eq!(rendered.samples.len(), (total * 8000.0) as usize);
+        assert_eq!(progression.samples(8000).len(), (total * 8000.0) as usize);
+    }
+}
@@ -0,0 +1,316 @@
+//! Parsing of roman numeral progressions, such as "I vi IV V7 I", into realized four part `Progression`s in a given `Key`.
+use crate::voicing::{connection_cost, enumerate_voicings, standalone_cost, VoicingPreferences};
+use crate::{ChordQuality, Key, KeyMode, Progression, Voice, SATB};
+use std::fmt::Display;
+
+/// Enumerates the reasons a roman numeral progression cannot be parsed and realized.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParseError {
+    /// The string has no roman numeral symbols.
+    Empty,
+    /// The symbol `token`, at `position` counting the whitespace separated symbols from 0, is not a roman numeral symbol.
+    UnknownToken { position: usize, token: String },
+    /// The harmony at `position` has no valid voicing with the chord member given by its figure in the bass.
+    NoVoicing { position: usize },
+    /// The harmony at `position` cannot follow the previous harmony without breaking a voice leading rule.
+    NoProgression { position: usize },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "the progression has no roman numerals"),
+            ParseError::UnknownToken { position, token } => {
+                write!(
+                    f,
+                    "symbol {} \"{}\" is not a roman numeral",
+                    position, token
+                )
+            }
+            ParseError::NoVoicing { position } => {
+                write!(f, "harmony {} has no valid voicing", position)
+            }
+            ParseError::NoProgression { position } => write!(
+                f,
+                "harmony {} cannot follow the previous harmony without voice leading violations",
+                position
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Helper function that parses the roman numeral at the start of `s`, preceded by any number of flats, `b` or `♭`, or sharps,
+/// `#` or `♯`, returning the semitones it is altered by, its 1-based scale degree, whether it is upper case and the rest of
+/// `s`, or `None` if `s` does not start with a roman numeral of a single case.
+fn parse_numeral(s: &str) -> Option<(i32, u8, bool, &str)> {
+    let mut alteration = 0;
+    let mut rest = s;
+    loop {
+        match rest.chars().next() {
+            Some(c @ ('b' | '♭')) => {
+                alteration -= 1;
+                rest = &rest[c.len_utf8()..];
+            }
+            Some(c @ ('#' | '♯')) => {
+                alteration += 1;
+                rest = &rest[c.len_utf8()..];
+            }
+            _ => break,
+        }
+    }
+    let length = rest
+        .find(|c| !matches!(c, 'I' | 'V' | 'i' | 'v'))
+        .unwrap_or(rest.len());
+    let (numeral, rest) = rest.split_at(length);
+    let upper = numeral.chars().all(|c| c.is_ascii_uppercase());
+    if !upper && !numeral.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    let degree = ["I", "II", "III", "IV", "V", "VI", "VII"]
+        .iter()
+        .position(|n| *n == numeral.to_ascii_uppercase())?;
+    Some((alteration, degree as u8 + 1, upper, rest))
+}
+
+/// Helper function that returns the root of the numeral on the 1-based scale `degree` of `key`, altered by `alteration`
+/// semitones. In minor keys a lower case numeral on the seventh degree, e.g. vii°, is built on the leading tone and an upper
+/// case one, VII, on the subtonic.
+fn numeral_root(key: &Key, degree: u8, upper: bool, alteration: i32) -> u8 {
+    let leading_tone = key.mode == KeyMode::Minor && degree == 7 && !upper;
+    (key.degree(degree) as i32 + alteration + leading_tone as i32).rem_euclid(12) as u8
+}
+
+/// Helper function that parses a single roman numeral symbol in `key`, e.g. "V65", "ii°6", "♭VI" or "V7/V", returning the root
+/// and quality of the harmony it names along with its inversion, 0 for root position, 1 for the third in the bass, 2 for the
+/// fifth and 3 for the seventh, or `None` if `symbol` is not a roman numeral symbol.
+///
+/// The case of the numeral gives the third of the harmony, followed by "°" or "o" for a diminished, "ø" for a half diminished
+/// or "+" for an augmented harmony, "M" for a major seventh and the figure giving the inversion: none, "6" or "64" for triads
+/// and "7", "65", "43", "42" or "2" for seventh chords. Secondary harmonies name the degree they tonicize after a slash.
+fn parse_symbol(key: &Key, symbol: &str) -> Option<(u8, ChordQuality, usize)> {
+    let (primary, key) = match symbol.split_once('/') {
+        Some((primary, target)) => {
+            let (alteration, degree, upper, rest) = parse_numeral(target)?;
+            if !rest.is_empty() {
+                return None;
+            }
+            let mode = if upper {
+                KeyMode::Major
+            } else {
+                KeyMode::Minor
+            };
+            let tonic = numeral_root(key, degree, upper, alteration);
+            (primary, Key::new(tonic, mode))
+        }
+        None => (symbol, *key),
+    };
+    let (alteration, degree, upper, rest) = parse_numeral(primary)?;
+    let (mark, rest) = match rest.chars().next() {
+        Some(c @ ('°' | 'o' | 'ø' | '+')) => (Some(c), &rest[c.len_utf8()..]),
+        _ => (None, rest),
+    };
+    let (major_seventh, figure) = match rest.strip_prefix('M') {
+        Some(figure) => (true, figure),
+        None => (false, rest),
+    };
+    let (seventh, inversion) = match figure {
+        "" => (false, 0),
+        "6" => (false, 1),
+        "64" => (false, 2),
+        "7" => (true, 0),
+        "65" => (true, 1),
+        "43" => (true, 2),
+        "42" | "2" => (true, 3),
+        _ => return None,
+    };
+    if major_seventh && !seventh {
+        return None;
+    }
+    let quality = match (upper, mark, seventh, major_seventh) {
+        (true, None, false, _) => ChordQuality::Major,
+        (true, Some('+'), false, _) => ChordQuality::Augmented,
+        (false, None, false, _) => ChordQuality::Minor,
+        (false, Some('°' | 'o'), false, _) => ChordQuality::Diminished,
+        (true, None, true, false) => ChordQuality::Dominant7,
+        (true, None, true, true) => ChordQuality::Major7,
+        (true, Some('+'), true, true) => ChordQuality::AugmentedMajor7,
+        (false, None, true, false) => ChordQuality::Minor7,
+        (false, None, true, true) => ChordQuality::MinorMajor7,
+        (false, Some('°' | 'o'), true, false) => ChordQuality::Diminished7,
+        (false, Some('ø'), true, false) => ChordQuality::HalfDiminished7,
+        _ => return None,
+    };
+    Some((
+        numeral_root(&key, degree, upper, alteration),
+        quality,
+        inversion,
+    ))
+}
+
+impl Progression {
+    /// Associated method for parsing a progression of whitespace separated roman numeral symbols in `key`, e.g. "I vi IV V7 I"
+    /// or "i iv V65 i", and realizing it in four parts with the voicing engine. Upper case numerals are major and lower case
+    /// numerals minor, modified by "°", "ø" or "+" and "M" for a major seventh, with figured bass inversions such as "6",
+    /// "64", "65", "43" and "42", and secondary harmonies such as "V7/V". In minor keys vii° is built on the leading tone.
+    ///
+    /// Each harmony is voiced with the member given by its figure in the bass, and the voicings are chosen together to
+    /// minimize the total voice leading distance without parallel fifths or octaves, as for `harmonize_melody`.
+    /// Returns a `ParseError` naming the first symbol that cannot be parsed, voiced or connected.
+    pub fn from_roman(
+        key: &Key,
+        s: &str,
+        prefs: &VoicingPreferences,
+    ) -> Result<Progression, ParseError> {
+        let harmonies = s
+            .split_whitespace()
+            .enumerate()
+            .map(|(position, token)| {
+                parse_symbol(key, token).ok_or_else(|| ParseError::UnknownToken {
+                    position,
+                    token: token.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if harmonies.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        // The figure decides the bass, so six-four chords are only voiced when asked for
+        let prefs = VoicingPreferences {
+            allow_second_inversion: true,
+            ..*prefs
+        };
+        // Each layer holds the voicings of one harmony, the cost of the cheapest way of reaching each and where it came from
+        let mut layers: Vec<Vec<(SATB, u32, usize)>> = Vec::with_capacity(harmonies.len());
+        for (position, (root, quality, inversion)) in harmonies.into_iter().enumerate() {
+            let bass = (root + quality.intervals()[inversion]) % 12;
+            let pitch_classes: Vec<u8> = quality.pitch_classes(root).iter().collect();
+            let voicings: Vec<SATB> = enumerate_voicings(root, &pitch_classes, None, &prefs)
+                .into_iter()
+                .filter(|v| v.voice(Voice::Bass).pitch_class() == bass)
+                .collect();
+            if voicings.is_empty() {
+                return Err(ParseError::NoVoicing { position });
+            }
+            let layer: Vec<(SATB, u32, usize)> = match layers.last() {
+                None => voicings
+                    .into_iter()
+                    .map(|v| {
+                        let cost = standalone_cost(&v);
+                        (v, cost, 0)
+                    })
+                    .collect(),
+                Some(previous) => voicings
+                    .into_iter()
+                    .filter_map(|v| {
+                        let (back, cost) = previous
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(i, (p, cost, _))| {
+                                Some((i, cost + connection_cost(p, &v, &prefs)?))
+                            })
+                            .min_by_key(|(_, cost)| *cost)?;
+                        Some((v, cost, back))
+                    })
+                    .collect(),
+            };
+            if layer.is_empty() {
+                return Err(ParseError::NoProgression { position });
+            }
+            layers.push(layer);
+        }
+
+        // Trace the cheapest sequence back from the final harmony
+        let mut index = layers[layers.len() - 1]
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, (_, cost, _))| *cost)
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        let mut chords = Vec::with_capacity(layers.len());
+        for layer in layers.iter().rev() {
+            chords.push(layer[index].0.clone());
+            index = layer[index].2;
+        }
+        Ok(chords.into_iter().rev().collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_symbol() {
+        let c_major = Key::new(0, KeyMode::Major);
+        assert_eq!(
+            parse_symbol(&c_major, "V65"),
+            Some((7, ChordQuality::Dominant7, 1))
+        );
+        assert_eq!(
+            parse_symbol(&c_major, "ii°6"),
+            Some((2, ChordQuality::Diminished, 1))
+        );
+        assert_eq!(
+            parse_symbol(&c_major, "IM7"),
+            Some((0, ChordQuality::Major7, 0))
+        );
+        assert_eq!(
+            parse_symbol(&c_major, "♭VI"),
+            Some((8, ChordQuality::Major, 0))
+        );
+        assert_eq!(
+            parse_symbol(&c_major, "V7/V"),
+            Some((2, ChordQuality::Dominant7, 0))
+        );
+        assert_eq!(
+            parse_symbol(&c_major, "viiø42"),
+            Some((11, ChordQuality::HalfDiminished7, 3))
+        );
+        let a_minor = Key::new(9, KeyMode::Minor);
+        assert_eq!(
+            parse_symbol(&a_minor, "vii°7"),
+            Some((8, ChordQuality::Diminished7, 0))
+        );
+        assert_eq!(
+            parse_symbol(&a_minor, "VII"),
+            Some((7, ChordQuality::Major, 0))
+        );
+        for symbol in ["Vi", "ø7", "V9", "IVM", "iiø", "X"] {
+            assert_eq!(parse_symbol(&c_major, symbol), None, "{}", symbol);
+        }
+    }
+
+    #[test]
+    fn test_from_roman() {
+        let prefs = VoicingPreferences::default();
+        let c_major = Key::new(0, KeyMode::Major);
+        let progression = Progression::from_roman(&c_major, "I IV V I", &prefs).unwrap();
+        assert_eq!(progression.chords().len(), 4);
+        assert!(progression.violations().is_empty());
+        let roots: Vec<u8> = progression.chords().iter().map(|c| c.root()).collect();
+        assert_eq!(roots, vec![0, 5, 7, 0]);
+
+        // The first inversion dominant seventh has the leading tone in the bass
+        let progression = Progression::from_roman(&c_major, "I V65 I", &prefs).unwrap();
+        assert_eq!(progression.chords()[1].voice(Voice::Bass).pitch_class(), 11);
+
+        let a_minor = Key::new(9, KeyMode::Minor);
+        let progression = Progression::from_roman(&a_minor, "i iv V i", &prefs).unwrap();
+        assert!(progression.chords()[2].contains_pitch_class(8));
+
+        assert_eq!(
+            Progression::from_roman(&c_major, "I IV X I", &prefs),
+            Err(ParseError::UnknownToken {
+                position: 2,
+                token: "X".to_string()
+            })
+        );
+        assert_eq!(
+            Progression::from_roman(&c_major, " ", &prefs),
+            Err(ParseError::Empty)
+        );
+    }
+}
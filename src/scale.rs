@@ -0,0 +1,770 @@
+//! Types for representing scales, given by a tonic and an interval pattern, and sets of pitch classes.
+use crate::{math, Pitch, A_440_FREQUENCY};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Display;
+use core::hash::{Hash, Hasher};
+
+/// The semitones above the tonic of each degree of the major scale.
+const MAJOR_STEPS: [u8; 7] = [0, 2, 4, 5, 7, 9, 11];
+/// The semitones above the tonic of each degree of the natural minor scale.
+const NATURAL_MINOR_STEPS: [u8; 7] = [0, 2, 3, 5, 7, 8, 10];
+/// The semitones above the tonic of each degree of the harmonic minor scale.
+const HARMONIC_MINOR_STEPS: [u8; 7] = [0, 2, 3, 5, 7, 8, 11];
+/// The semitones above the tonic of each degree of the ascending melodic minor scale.
+const MELODIC_MINOR_STEPS: [u8; 7] = [0, 2, 3, 5, 7, 9, 11];
+
+/// Enumerates the church modes, the rotations of the major scale, in order of the major scale degree each starts on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Mode {
+    Ionian,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Aeolian,
+    Locrian,
+}
+
+impl Mode {
+    /// All modes, in order of the major scale degree each starts on.
+    pub const ALL: [Mode; 7] = [
+        Mode::Ionian,
+        Mode::Dorian,
+        Mode::Phrygian,
+        Mode::Lydian,
+        Mode::Mixolydian,
+        Mode::Aeolian,
+        Mode::Locrian,
+    ];
+}
+
+impl Display for Mode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            Mode::Ionian => "Ionian",
+            Mode::Dorian => "Dorian",
+            Mode::Phrygian => "Phrygian",
+            Mode::Lydian => "Lydian",
+            Mode::Mixolydian => "Mixolydian",
+            Mode::Aeolian => "Aeolian",
+            Mode::Locrian => "Locrian",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Enumerates the two orderings of the octatonic scale, by the step it starts with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Octatonic {
+    /// Alternating half and whole steps, starting with a half step
+    HalfWhole,
+    /// Alternating whole and half steps, starting with a whole step
+    WholeHalf,
+}
+
+/// Enumerates the directions a scale can be traversed in, for scales such as melodic minor that differ going up and down.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Ascending,
+    Descending,
+    /// Either direction, accepting the pitch classes of both forms of the scale
+    Any,
+}
+
+/// A set of pitch classes, stored as a bit for each of the twelve pitch classes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct PitchClassSet {
+    /// Bit `pc` is set if the pitch class `pc` belongs to the set
+    bits: u16,
+}
+
+impl PitchClassSet {
+    /// Associated method for creating a new, empty `PitchClassSet`.
+    pub fn new() -> Self {
+        PitchClassSet { bits: 0 }
+    }
+
+    /// Method for adding the pitch class `pc`, taken modulo 12, to the set.
+    pub fn insert(&mut self, pc: u8) {
+        self.bits |= 1 << (pc % 12);
+    }
+
+    /// Method for removing the pitch class `pc`, taken modulo 12, from the set.
+    pub fn remove(&mut self, pc: u8) {
+        self.bits &= !(1 << (pc % 12));
+    }
+
+    /// Method that returns true if the pitch class `pc`, taken modulo 12, belongs to the set.
+    pub fn contains(&self, pc: u8) -> bool {
+        self.bits & (1 << (pc % 12)) != 0
+    }
+
+    /// Method that returns the number of pitch classes in the set.
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    /// Method that returns true if the set has no pitch classes.
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Method that returns the set with every pitch class transposed up by `semitones`, taken modulo 12.
+    pub fn transpose(&self, semitones: u8) -> PitchClassSet {
+        self.iter().map(|pc| pc + semitones % 12).collect()
+    }
+
+    /// Method that returns an iterator over the pitch classes of the set, from lowest to highest.
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..12).filter(|pc| self.contains(*pc))
+    }
+}
+
+impl FromIterator<u8> for PitchClassSet {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        let mut set = PitchClassSet::new();
+        for pc in iter {
+            set.insert(pc);
+        }
+        set
+    }
+}
+
+impl Display for PitchClassSet {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let pcs: Vec<String> = self.iter().map(|pc| pc.to_string()).collect();
+        write!(f, "{{{}}}", pcs.join(", "))
+    }
+}
+
+/// Enumerates the reasons an interval pattern cannot form a scale.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ScaleError {
+    /// The intervals do not add up to an octave, twelve semitones.
+    NotAnOctave { sum: u32 },
+    /// The interval at the index is zero, repeating the degree before it.
+    ZeroInterval { index: usize },
+    /// The cents offsets do not start with 0 for the tonic.
+    MissingTonic,
+    /// The cents offset at the index does not lie above the one before it.
+    CentsOutOfOrder { index: usize },
+    /// The cents offset at the index lies outside of the octave, below 0 or at or above 1200 cents.
+    CentsOutOfRange { index: usize },
+}
+
+impl Display for ScaleError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ScaleError::NotAnOctave { sum } => {
+                write!(f, "the intervals span {} semitones rather than 12", sum)
+            }
+            ScaleError::ZeroInterval { index } => write!(f, "the interval at {} is zero", index),
+            ScaleError::MissingTonic => write!(f, "the cents offsets do not start at 0"),
+            ScaleError::CentsOutOfOrder { index } => {
+                write!(f, "the cents offset at {} does not ascend", index)
+            }
+            ScaleError::CentsOutOfRange { index } => {
+                write!(f, "the cents offset at {} is outside of the octave", index)
+            }
+        }
+    }
+}
+
+impl core::error::Error for ScaleError {}
+
+/// The exact tuning of a scale defined in cents, outside of the twelve tone equal temperament grid.
+#[derive(Debug, Clone)]
+struct Tuning {
+    /// The frequency of the tonic
+    frequency: f64,
+    /// The octave of the tonic
+    octave: u8,
+    /// The cents above the tonic of each degree, in ascending order starting with 0 for the tonic
+    cents: Vec<f64>,
+}
+
+impl PartialEq for Tuning {
+    fn eq(&self, other: &Self) -> bool {
+        self.frequency.to_bits() == other.frequency.to_bits()
+            && self.octave == other.octave
+            && self.cents.len() == other.cents.len()
+            && self
+                .cents
+                .iter()
+                .zip(&other.cents)
+                .all(|(a, b)| a.to_bits() == b.to_bits())
+    }
+}
+
+impl Eq for Tuning {}
+
+impl Hash for Tuning {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.frequency.to_bits().hash(state);
+        self.octave.hash(state);
+        for cents in &self.cents {
+            cents.to_bits().hash(state);
+        }
+    }
+}
+
+/// A struct that represents a scale, given by the pitch class of its tonic and the semitones above the tonic of each degree.
+/// Scales defined in cents by `from_cents` also carry their exact tuning, used by `degree_pitch`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Scale {
+    /// The pitch class of the tonic
+    tonic: u8,
+    /// The semitones above the tonic of each degree, in ascending order starting with 0 for the tonic
+    steps: Vec<u8>,
+    /// The degrees of the scale when descending, if they differ from the ascending degrees
+    descending: Option<Vec<u8>>,
+    /// The exact tuning of a scale defined in cents
+    tuning: Option<Tuning>,
+}
+
+impl Scale {
+    /// Associated method for creating a new `Scale` on `tonic`, taken modulo 12, whose degrees lie `steps` semitones above the
+    /// tonic. Returns `None` unless `steps` starts with 0 and strictly ascends within the octave.
+    pub fn new(tonic: u8, steps: &[u8]) -> Option<Self> {
+        let ascending = steps.windows(2).all(|w| w[0] < w[1]);
+        if steps.first() != Some(&0) || !ascending || steps.last().is_some_and(|s| *s >= 12) {
+            return None;
+        }
+        Some(Scale {
+            tonic: tonic % 12,
+            steps: steps.to_vec(),
+            descending: None,
+            tuning: None,
+        })
+    }
+
+    /// Associated method for creating a custom scale on `tonic`, taken modulo 12, from `intervals`, the semitones from each degree
+    /// to the next, ending with the interval from the last degree back up to the tonic, e.g. `[2, 2, 1, 2, 2, 2, 1]` for the
+    /// major scale. Returns `ScaleError::ZeroInterval` if an interval is zero and `ScaleError::NotAnOctave` unless the intervals
+    /// add up to twelve semitones.
+    pub fn from_intervals(tonic: u8, intervals: &[u8]) -> Result<Self, ScaleError> {
+        if let Some(index) = intervals.iter().position(|i| *i == 0) {
+            return Err(ScaleError::ZeroInterval { index });
+        }
+        let sum: u32 = intervals.iter().map(|i| *i as u32).sum();
+        if sum != 12 {
+            return Err(ScaleError::NotAnOctave { sum });
+        }
+        let steps: Vec<u8> = intervals
+            .iter()
+            .scan(0, |step, interval| {
+                let degree = *step;
+                *step += interval;
+                Some(degree)
+            })
+            .collect();
+        Ok(Scale::from_steps(tonic, &steps))
+    }
+
+    /// Associated method for creating a scale outside of twelve tone equal temperament on `tonic`, whose degrees lie `cents`
+    /// above it, e.g. `[0.0, 204.0, 386.0, 498.0, 702.0, 884.0, 1088.0]` for a just major scale. The exact frequencies of the
+    /// degrees are given by `degree_pitch`, so they can be rendered as tuned. Every other method, including the steps, pitch
+    /// classes and degrees of the scale and any analysis built on them, only applies to twelve tone equal temperament and
+    /// uses the nearest semitone to each degree, so distinct degrees may share a pitch class. Transforming the scale, e.g.
+    /// with `rotate`, gives an equally tempered scale. Returns `ScaleError::MissingTonic` unless `cents` starts with 0,
+    /// `ScaleError::CentsOutOfRange` for an offset of 1200 cents or more and `ScaleError::CentsOutOfOrder` unless the offsets
+    /// strictly ascend.
+    pub fn from_cents(tonic: Pitch, cents: &[f64]) -> Result<Self, ScaleError> {
+        if cents.first() != Some(&0.0) {
+            return Err(ScaleError::MissingTonic);
+        }
+        if let Some(index) = cents.iter().position(|c| !(0.0..1200.0).contains(c)) {
+            return Err(ScaleError::CentsOutOfRange { index });
+        }
+        if let Some(index) = (1..cents.len()).find(|i| cents[*i] <= cents[i - 1]) {
+            return Err(ScaleError::CentsOutOfOrder { index });
+        }
+        let steps: Vec<u8> = cents
+            .iter()
+            .map(|c| (math::round(c / 100.0) as u8).min(11))
+            .collect();
+        Ok(Scale {
+            tuning: Some(Tuning {
+                frequency: tonic.frequency(),
+                octave: tonic.octave(),
+                cents: cents.to_vec(),
+            }),
+            ..Scale::from_steps(tonic.pitch_class(), &steps)
+        })
+    }
+
+    /// Associated method for creating the major scale on `tonic`.
+    pub fn major(tonic: u8) -> Self {
+        Scale::from_steps(tonic, &MAJOR_STEPS)
+    }
+
+    /// Associated method for creating the natural minor scale on `tonic`.
+    pub fn natural_minor(tonic: u8) -> Self {
+        Scale::from_steps(tonic, &NATURAL_MINOR_STEPS)
+    }
+
+    /// Associated method for creating the harmonic minor scale on `tonic`, the natural minor scale with a raised seventh degree.
+    pub fn harmonic_minor(tonic: u8) -> Self {
+        Scale::from_steps(tonic, &HARMONIC_MINOR_STEPS)
+    }
+
+    /// Associated method for creating the melodic minor scale on `tonic`, the natural minor scale with raised sixth and seventh
+    /// degrees when ascending. The scale descends as the natural minor scale, see `pitches_descending`, while its degrees and
+    /// pitch classes are those of the ascending form.
+    pub fn melodic_minor(tonic: u8) -> Self {
+        Scale {
+            descending: Some(NATURAL_MINOR_STEPS.to_vec()),
+            ..Scale::from_steps(tonic, &MELODIC_MINOR_STEPS)
+        }
+    }
+
+    /// Associated method for creating the church mode `mode` on `tonic`, e.g. `Mode::Dorian` on 2 for D Dorian, which shares
+    /// the pitch classes of C major.
+    pub fn mode(tonic: u8, mode: Mode) -> Self {
+        let rotated = Scale::major(0).rotate(mode as usize);
+        Scale::from_steps(tonic, &rotated.steps)
+    }
+
+    /// Method that returns the scale starting on degree `n + 1` of this scale, with the same pitch classes and the tonic moved
+    /// up by `n` degrees, wrapping past the last degree. The rotated scale ascends and descends alike. Rotating the major scale by `n` gives its mode `Mode::ALL[n]`, so
+    /// rotating C major by 1 gives D Dorian.
+    pub fn rotate(&self, n: usize) -> Scale {
+        let len = self.steps.len();
+        let offset = self.steps[n % len];
+        let steps: Vec<u8> = (0..len)
+            .map(|i| (self.steps[(n + i) % len] + 12 - offset) % 12)
+            .collect();
+        Scale::from_steps(self.tonic + offset, &steps)
+    }
+
+    /// Associated method for creating the major pentatonic scale on `tonic`, the major scale without its fourth and seventh degrees.
+    pub fn major_pentatonic(tonic: u8) -> Self {
+        Scale::from_steps(tonic, &[0, 2, 4, 7, 9])
+    }
+
+    /// Associated method for creating the minor pentatonic scale on `tonic`, the natural minor scale without its second and
+    /// sixth degrees.
+    pub fn minor_pentatonic(tonic: u8) -> Self {
+        Scale::from_steps(tonic, &[0, 3, 5, 7, 10])
+    }
+
+    /// Associated method for creating the six note blues scale on `tonic`, the minor pentatonic scale with the flattened fifth
+    /// added. Only the minor, "blue", third is included, so the blues scale on C contains Eb but not E.
+    pub fn blues(tonic: u8) -> Self {
+        Scale::from_steps(tonic, &[0, 3, 5, 6, 7, 10])
+    }
+
+    /// Associated method for creating the whole tone scale on `tonic`, six whole steps.
+    pub fn whole_tone(tonic: u8) -> Self {
+        Scale::from_steps(tonic, &[0, 2, 4, 6, 8, 10])
+    }
+
+    /// Associated method for creating the eight note octatonic scale on `tonic`, alternating half and whole steps in the order
+    /// given by `octatonic`.
+    pub fn octatonic(tonic: u8, octatonic: Octatonic) -> Self {
+        match octatonic {
+            Octatonic::HalfWhole => Scale::from_steps(tonic, &[0, 1, 3, 4, 6, 7, 9, 10]),
+            Octatonic::WholeHalf => Scale::from_steps(tonic, &[0, 2, 3, 5, 6, 8, 9, 11]),
+        }
+    }
+
+    /// Helper method for creating a scale from a pattern known to be valid.
+    fn from_steps(tonic: u8, steps: &[u8]) -> Self {
+        Scale {
+            tonic: tonic % 12,
+            steps: steps.to_vec(),
+            descending: None,
+            tuning: None,
+        }
+    }
+
+    /// Method for accessing the pitch class of the tonic.
+    pub fn tonic(&self) -> u8 {
+        self.tonic
+    }
+
+    /// Method for accessing the semitones above the tonic of each degree of the scale.
+    pub fn steps(&self) -> &[u8] {
+        &self.steps
+    }
+
+    /// Method that returns the number of degrees of the scale.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Method that returns true if the scale has no degrees, which is never the case for a constructed scale.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Method that returns the pitch class of the 1-based scale `degree`, wrapping past the last degree, so the eighth degree
+    /// of a seven note scale is its tonic. A degree of 0 is treated as 1.
+    pub fn degree(&self, degree: u8) -> u8 {
+        let index = (degree.max(1) as usize - 1) % self.steps.len();
+        (self.tonic + self.steps[index]) % 12
+    }
+
+    /// Method that returns the pitch of the 1-based scale `degree` above the tonic in `octave`, with degrees past the last
+    /// continuing into the octaves above, so the eighth degree of a seven note scale is the tonic an octave higher. A degree of
+    /// 0 is treated as 1. For scales defined by `from_cents` the pitch has the exact frequency of the degree, along with the
+    /// nearest equally tempered pitch class and octave, otherwise it is equally tempered. Returns `None` if the pitch would lie
+    /// above octave 255.
+    pub fn degree_pitch(&self, degree: u8, octave: u8) -> Option<Pitch> {
+        let index = degree.max(1) as usize - 1;
+        let (wraps, index) = (index / self.steps.len(), index % self.steps.len());
+        match &self.tuning {
+            Some(tuning) => {
+                let octaves = octave as f64 - tuning.octave as f64 + wraps as f64;
+                let frequency =
+                    tuning.frequency * math::exp2(octaves + tuning.cents[index] / 1200.0);
+                // Position in semitones from C0 of the nearest equally tempered pitch, A4 lying at 57
+                let position =
+                    57 + math::round(12.0 * math::log2(frequency / A_440_FREQUENCY)) as i64;
+                let octave = u8::try_from(position.div_euclid(12)).ok()?;
+                Some(Pitch::new(frequency, position.rem_euclid(12) as u8, octave))
+            }
+            None => {
+                let position =
+                    12 * (octave as usize + wraps) + (self.tonic + self.steps[index]) as usize;
+                let octave = u8::try_from(position / 12).ok()?;
+                Some(Pitch::from_pitch_class((position % 12) as u8, octave))
+            }
+        }
+    }
+
+    /// Method that returns the pitch classes of the triad on the 1-based scale `degree`, stacked in generic thirds by taking every
+    /// other degree of the scale, ordered root, third, fifth. The intervals depend on the scale, so the triads of a pentatonic or
+    /// custom scale need not be one of the named chord qualities. Degrees wrap past the last as for `degree`.
+    pub fn triad(&self, degree: u8) -> [u8; 3] {
+        // Reduced to the first octave of degrees so adding the fifth cannot overflow
+        let degree = ((degree.max(1) as usize - 1) % self.steps.len() + 1) as u8;
+        [
+            self.degree(degree),
+            self.degree(degree + 2),
+            self.degree(degree + 4),
+        ]
+    }
+
+    /// Method that returns the pitch classes of the scale.
+    pub fn pitch_classes(&self) -> PitchClassSet {
+        self.steps.iter().map(|s| self.tonic + s).collect()
+    }
+
+    /// Method that returns true if the pitch class `pc`, taken modulo 12, belongs to the scale.
+    pub fn contains(&self, pc: u8) -> bool {
+        self.pitch_classes().contains(pc)
+    }
+
+    /// Method that returns true if the pitch class `pc`, taken modulo 12, belongs to the form of the scale used when moving in
+    /// `direction`, or to either form for `Direction::Any`. Only the melodic minor scale differs by direction, so F# and G#
+    /// belong to A melodic minor ascending and F and G descending.
+    pub fn contains_in(&self, pc: u8, direction: Direction) -> bool {
+        let descending = self.descending.as_deref().unwrap_or(&self.steps);
+        let in_steps = |steps: &[u8]| steps.iter().any(|s| (self.tonic + s) % 12 == pc % 12);
+        match direction {
+            Direction::Ascending => in_steps(&self.steps),
+            Direction::Descending => in_steps(descending),
+            Direction::Any => in_steps(&self.steps) || in_steps(descending),
+        }
+    }
+
+    /// Method that returns the pitches of the scale over `num_octaves` octaves from its tonic in `start_octave`, ascending as
+    /// `pitches` for `Direction::Ascending` and `Direction::Any`, and descending as `pitches_descending` for
+    /// `Direction::Descending`.
+    pub fn pitches_in(
+        &self,
+        direction: Direction,
+        start_octave: u8,
+        num_octaves: u8,
+    ) -> impl Iterator<Item = Pitch> {
+        let pitches: Vec<Pitch> = match direction {
+            Direction::Ascending | Direction::Any => {
+                self.pitches(start_octave, num_octaves).collect()
+            }
+            Direction::Descending => self.pitches_descending(start_octave, num_octaves).collect(),
+        };
+        pitches.into_iter()
+    }
+
+    /// Method that returns an iterator over the pitches of the scale ascending from its tonic in `start_octave` through
+    /// `num_octaves` octaves, ending on the tonic `num_octaves` octaves higher. The octave number is bumped wherever the scale
+    /// passes pitch class 11, so B major from octave 3 runs B3, C#4, ..., A#4, B4. Pitches above octave 255 are omitted.
+    pub fn pitches(&self, start_octave: u8, num_octaves: u8) -> impl Iterator<Item = Pitch> {
+        scale_pitches(self.tonic, &self.steps, start_octave, num_octaves)
+    }
+
+    /// Method that returns an iterator over the pitches of the scale descending from its tonic `num_octaves` octaves above
+    /// `start_octave` to its tonic in `start_octave`, the pitches of `pitches` in reverse except for the melodic minor scale,
+    /// which descends as the natural minor scale.
+    pub fn pitches_descending(
+        &self,
+        start_octave: u8,
+        num_octaves: u8,
+    ) -> impl Iterator<Item = Pitch> {
+        let steps = self.descending.as_deref().unwrap_or(&self.steps);
+        let pitches: Vec<Pitch> =
+            scale_pitches(self.tonic, steps, start_octave, num_octaves).collect();
+        pitches.into_iter().rev()
+    }
+}
+
+/// Helper function that returns the pitches of the scale on `tonic` with degrees `steps` semitones above it, ascending from the
+/// tonic in `start_octave` to the tonic `num_octaves` octaves higher.
+fn scale_pitches(
+    tonic: u8,
+    steps: &[u8],
+    start_octave: u8,
+    num_octaves: u8,
+) -> impl Iterator<Item = Pitch> {
+    let base = 12 * start_octave as u32 + tonic as u32;
+    let positions: Vec<u32> = (0..num_octaves as u32)
+        .flat_map(|octave| steps.iter().map(move |s| base + 12 * octave + *s as u32))
+        .chain([base + 12 * num_octaves as u32])
+        .collect();
+    positions
+        .into_iter()
+        .take_while(|p| p / 12 <= u8::MAX as u32)
+        .map(|p| Pitch::from_pitch_class((p % 12) as u8, (p / 12) as u8))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_scale_degrees() {
+        let c_major = Scale::major(0);
+        let degrees: Vec<u8> = (1..=7).map(|d| c_major.degree(d)).collect();
+        assert_eq!(degrees, [0, 2, 4, 5, 7, 9, 11]);
+        assert_eq!(c_major.degree(8), 0);
+        assert_eq!(c_major.pitch_classes().len(), 7);
+
+        let a_harmonic = Scale::harmonic_minor(9);
+        assert!(a_harmonic.contains(8));
+        assert!(!a_harmonic.contains(7));
+        assert!(Scale::natural_minor(9).contains(7));
+        assert_eq!(Scale::melodic_minor(9).degree(6), 6);
+        assert_eq!(
+            Scale::natural_minor(9).pitch_classes(),
+            c_major.pitch_classes()
+        );
+
+        assert!(Scale::new(0, &[0, 2, 4, 7, 9]).is_some());
+        assert_eq!(Scale::new(0, &[2, 4]), None);
+        assert_eq!(Scale::new(0, &[0, 4, 2]), None);
+    }
+
+    #[test]
+    fn test_modes() {
+        let d_dorian = Scale::mode(2, Mode::Dorian);
+        assert_eq!(d_dorian.tonic(), 2);
+        assert_eq!(d_dorian.pitch_classes(), Scale::major(0).pitch_classes());
+        assert_eq!(d_dorian, Scale::major(0).rotate(1));
+
+        let e_phrygian = Scale::mode(4, Mode::Phrygian);
+        assert_eq!(e_phrygian.degree(2), 5);
+        assert_eq!(Scale::mode(9, Mode::Aeolian), Scale::natural_minor(9));
+        assert_eq!(Scale::mode(0, Mode::Ionian), Scale::major(0));
+        // Rotating through every degree returns to the original scale
+        let g_major = Scale::major(7);
+        assert_eq!(g_major.rotate(3).rotate(4), g_major);
+        for (n, mode) in Mode::ALL.iter().enumerate() {
+            let rotated = g_major.rotate(n);
+            assert_eq!(rotated, Scale::mode(g_major.degree(n as u8 + 1), *mode));
+        }
+    }
+
+    #[test]
+    fn test_scale_pitches() {
+        let c_major: Vec<Pitch> = Scale::major(0).pitches(4, 1).collect();
+        assert_eq!(c_major.len(), 8);
+        assert_eq!(c_major[0], Pitch::from((0, 4)));
+        assert_eq!(c_major[7], Pitch::from((0, 5)));
+
+        let b_major: Vec<(u8, u8)> = Scale::major(11)
+            .pitches(3, 1)
+            .map(|p| (p.pitch_class(), p.octave()))
+            .collect();
+        assert_eq!(
+            b_major,
+            [
+                (11, 3),
+                (1, 4),
+                (3, 4),
+                (4, 4),
+                (6, 4),
+                (8, 4),
+                (10, 4),
+                (11, 4)
+            ]
+        );
+        assert_eq!(Scale::major(0).pitches(4, 2).count(), 15);
+
+        // The melodic minor scale raises the sixth and seventh degrees only on the way up
+        let a_melodic = Scale::melodic_minor(9);
+        let up: Vec<u8> = a_melodic.pitches(4, 1).map(|p| p.pitch_class()).collect();
+        let down: Vec<u8> = a_melodic
+            .pitches_descending(4, 1)
+            .map(|p| p.pitch_class())
+            .collect();
+        assert_eq!(up, [9, 11, 0, 2, 4, 6, 8, 9]);
+        assert_eq!(down, [9, 7, 5, 4, 2, 0, 11, 9]);
+        let c_down: Vec<Pitch> = Scale::major(0).pitches_descending(4, 1).collect();
+        assert_eq!(c_down, c_major.into_iter().rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_cents_scales() {
+        let ratios = [
+            1.0,
+            9.0 / 8.0,
+            5.0 / 4.0,
+            4.0 / 3.0,
+            1.5,
+            5.0 / 3.0,
+            15.0 / 8.0,
+        ];
+        let cents: Vec<f64> = ratios.iter().map(|r: &f64| 1200.0 * r.log2()).collect();
+        let c4 = Pitch::from((0, 4));
+        let just = Scale::from_cents(c4, &cents).unwrap();
+        let fifth = just.degree_pitch(5, 4).unwrap();
+        assert!((fifth.frequency() / c4.frequency() - 1.5).abs() < 1e-6);
+        assert_eq!((fifth.pitch_class(), fifth.octave()), (7, 4));
+        let third = just.degree_pitch(3, 4).unwrap();
+        assert!((third.frequency() / c4.frequency() - 1.25).abs() < 1e-6);
+        assert!(third.cents_offset() < -13.0);
+        let octave = just.degree_pitch(8, 4).unwrap();
+        assert!((octave.frequency() / c4.frequency() - 2.0).abs() < 1e-6);
+        assert_eq!(just.steps(), Scale::major(0).steps());
+
+        assert_eq!(
+            Scale::from_cents(c4, &[0.0, 386.0, 204.0]),
+            Err(ScaleError::CentsOutOfOrder { index: 2 })
+        );
+        assert_eq!(
+            Scale::from_cents(c4, &[0.0, 702.0, 1250.0]),
+            Err(ScaleError::CentsOutOfRange { index: 2 })
+        );
+        assert_eq!(
+            Scale::from_cents(c4, &[204.0, 386.0]),
+            Err(ScaleError::MissingTonic)
+        );
+
+        // Equally tempered scales give tempered pitches
+        assert_eq!(
+            Scale::major(7).degree_pitch(4, 3),
+            Some(Pitch::from((0, 4)))
+        );
+        assert_eq!(
+            Scale::major(0).degree_pitch(9, 4),
+            Some(Pitch::from((2, 5)))
+        );
+    }
+
+    #[test]
+    fn test_melodic_minor_directions() {
+        let a_melodic = Scale::melodic_minor(9);
+        let up: Vec<u8> = a_melodic
+            .pitches_in(Direction::Ascending, 4, 1)
+            .map(|p| p.pitch_class())
+            .collect();
+        let down: Vec<u8> = a_melodic
+            .pitches_in(Direction::Descending, 4, 1)
+            .map(|p| p.pitch_class())
+            .collect();
+        assert_eq!(up, [9, 11, 0, 2, 4, 6, 8, 9]);
+        assert_eq!(down, [9, 7, 5, 4, 2, 0, 11, 9]);
+
+        for raised in [6, 8] {
+            assert!(a_melodic.contains_in(raised, Direction::Ascending));
+            assert!(!a_melodic.contains_in(raised, Direction::Descending));
+            assert!(a_melodic.contains_in(raised, Direction::Any));
+        }
+        for natural in [5, 7] {
+            assert!(!a_melodic.contains_in(natural, Direction::Ascending));
+            assert!(a_melodic.contains_in(natural, Direction::Descending));
+            assert!(a_melodic.contains_in(natural, Direction::Any));
+        }
+        assert!(Scale::major(0).contains_in(11, Direction::Descending));
+        assert!(!Scale::major(0).contains_in(10, Direction::Any));
+    }
+
+    #[test]
+    fn test_symmetric_and_pentatonic_scales() {
+        // The whole tone scale has only two distinct transpositions
+        let whole_tone = Scale::whole_tone(0).pitch_classes();
+        let mut transpositions: Vec<PitchClassSet> =
+            (0..12).map(|t| whole_tone.transpose(t)).collect();
+        transpositions.sort_by_key(|set| set.iter().collect::<Vec<u8>>());
+        transpositions.dedup();
+        assert_eq!(transpositions.len(), 2);
+
+        let blues = Scale::blues(0);
+        assert!(blues.contains(3) && blues.contains(6));
+        assert!(!blues.contains(4));
+        assert_eq!(blues.degree(7), 0);
+
+        let octatonic = Scale::octatonic(0, Octatonic::HalfWhole);
+        assert_eq!(octatonic.pitch_classes().len(), 8);
+        assert_eq!(octatonic.degree(9), 0);
+        assert_eq!(
+            octatonic.rotate(1),
+            Scale::octatonic(1, Octatonic::WholeHalf)
+        );
+        assert_eq!(octatonic.pitches(4, 1).count(), 9);
+
+        let pentatonic = Scale::major_pentatonic(7);
+        let pitches: Vec<(u8, u8)> = pentatonic
+            .pitches(3, 1)
+            .map(|p| (p.pitch_class(), p.octave()))
+            .collect();
+        assert_eq!(pitches, [(7, 3), (9, 3), (11, 3), (2, 4), (4, 4), (7, 4)]);
+        assert_eq!(
+            Scale::minor_pentatonic(4).pitch_classes(),
+            pentatonic.pitch_classes()
+        );
+    }
+
+    #[test]
+    fn test_custom_scales() {
+        assert_eq!(
+            Scale::from_intervals(0, &[2, 2, 1, 2, 2, 2, 1]),
+            Ok(Scale::major(0))
+        );
+        let custom = Scale::from_intervals(0, &[1, 3, 1, 2, 1, 3, 1]).unwrap();
+        assert_eq!(custom.steps(), [0, 1, 4, 5, 7, 8, 11]);
+        assert_eq!(custom.triad(1), [0, 4, 7]);
+        assert_eq!(custom.triad(2), [1, 5, 8]);
+        assert_eq!(custom.triad(7), [11, 1, 5]);
+        for degree in 1..=7 {
+            let triad = custom.triad(degree);
+            assert_eq!(
+                triad,
+                [
+                    custom.degree(degree),
+                    custom.degree(degree + 2),
+                    custom.degree(degree + 4)
+                ]
+            );
+            assert!(triad.iter().all(|pc| custom.contains(*pc)));
+        }
+        assert_eq!(custom.degree(9), 1);
+        // Degrees wrap past the last, without overflowing
+        assert_eq!(custom.triad(8), custom.triad(1));
+        assert_eq!(custom.triad(255), custom.triad(3));
+        assert_eq!(Scale::major_pentatonic(0).triad(252), [2, 7, 0]);
+        assert_eq!(custom.pitches(4, 1).count(), 8);
+
+        assert_eq!(
+            Scale::from_intervals(0, &[2, 2, 2, 2, 2]),
+            Err(ScaleError::NotAnOctave { sum: 10 })
+        );
+        assert_eq!(
+            Scale::from_intervals(0, &[2, 0, 10]),
+            Err(ScaleError::ZeroInterval { index: 1 })
+        );
+        assert!(Scale::from_intervals(0, &[]).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+//! Strategies and `Arbitrary` implementations for property testing with `proptest`, generating only values the crate
+//! considers valid, so properties of the validators and the voice leading code can be checked against them.
+use crate::voicing::{enumerate_quality_voicings, VoicingPreferences};
+use crate::{ChordQuality, Pitch, PitchClassSet, SATB};
+use proptest::prelude::*;
+use proptest::sample::select;
+
+/// The octaves generated pitches lie in, from the lowest octave to the top of the piano.
+const OCTAVES: std::ops::RangeInclusive<u8> = 0..=8;
+
+/// A function that returns a strategy generating equally tempered pitches in octaves 0 to 8, each with the frequency of
+/// its pitch class and octave.
+pub fn pitch() -> impl Strategy<Value = Pitch> {
+    (0u8..12, OCTAVES).prop_map(|(pc, octave)| Pitch::from_pitch_class(pc, octave))
+}
+
+/// A function that returns a strategy generating any set of pitch classes, including the empty set.
+pub fn pitch_class_set() -> impl Strategy<Value = PitchClassSet> {
+    (0u16..1 << 12).prop_map(|bits| (0..12).filter(|pc| bits & (1 << pc) != 0).collect())
+}
+
+/// A function that returns a strategy generating any of `ChordQuality::ALL`.
+pub fn chord_quality() -> impl Strategy<Value = ChordQuality> {
+    select(ChordQuality::ALL.to_vec())
+}
+
+/// A function that returns a strategy generating valid harmonies of one of `qualities` on any root, each picked from the
+/// voicings enumerated by `enumerate_quality_voicings` under the default preferences, so every harmony passes validation.
+/// Roots with no voicing of the quality are skipped. Panics if `qualities` is empty.
+pub fn satb_with_qualities(qualities: &[ChordQuality]) -> impl Strategy<Value = SATB> {
+    (0u8..12, select(qualities.to_vec()))
+        .prop_filter_map("the harmony has no voicing", |(root, quality)| {
+            let voicings =
+                enumerate_quality_voicings(root, quality, None, &VoicingPreferences::default());
+            (!voicings.is_empty()).then_some(voicings)
+        })
+        .prop_flat_map(select)
+}
+
+impl Arbitrary for Pitch {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Pitch>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        pitch().boxed()
+    }
+}
+
+impl Arbitrary for PitchClassSet {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<PitchClassSet>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        pitch_class_set().boxed()
+    }
+}
+
+impl Arbitrary for ChordQuality {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<ChordQuality>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        chord_quality().boxed()
+    }
+}
+
+impl Arbitrary for SATB {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<SATB>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        satb_with_qualities(&ChordQuality::ALL).boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::voicing::{enumerate_voicings, voice_leading_distance};
+
+    /// The qualities of the triads
+    const TRIADS: [ChordQuality; 4] = [
+        ChordQuality::Major,
+        ChordQuality::Minor,
+        ChordQuality::Diminished,
+        ChordQuality::Augmented,
+    ];
+
+    proptest! {
+        #[test]
+        fn test_transposition_round_trip(
+            pitch in any::<Pitch>(),
+            set in any::<PitchClassSet>(),
+            semitones in -48i32..48,
+        ) {
+            if let Some(transposed) = pitch.transpose(semitones) {
+                prop_assert_eq!(transposed.transpose(-semitones), Some(pitch));
+                let tempered = Pitch::compute_frequency(transposed.pitch_class(), transposed.octave());
+                prop_assert_eq!(transposed.frequency(), tempered);
+            }
+            let up = semitones.rem_euclid(12) as u8;
+            prop_assert_eq!(set.transpose(up).transpose(12 - up), set);
+            prop_assert_eq!(set.transpose(up).len(), set.len());
+        }
+
+        #[test]
+        fn test_enumerated_voicings_validate(
+            root in 0u8..12,
+            // The triads and seventh chords
+            quality in select(ChordQuality::ALL[..11].to_vec()),
+            second_inversion: bool,
+        ) {
+            let prefs = VoicingPreferences {
+                allow_second_inversion: second_inversion,
+                ..VoicingPreferences::default()
+            };
+            let pitch_classes: Vec<u8> = quality.pitch_classes(root).iter().collect();
+            for voicing in enumerate_voicings(root, &pitch_classes, None, &prefs) {
+                let [soprano, alto, tenor, bass] = voicing.voices();
+                let validated =
+                    SATB::try_new_with_config(root, soprano, alto, tenor, bass, &prefs.validation);
+                prop_assert!(validated.is_ok());
+            }
+        }
+
+        #[test]
+        fn test_voice_leading_distance_metric(
+            a in satb_with_qualities(&TRIADS),
+            b in satb_with_qualities(&TRIADS),
+            c in satb_with_qualities(&TRIADS),
+        ) {
+            prop_assert_eq!(voice_leading_distance(&a, &b), voice_leading_distance(&b, &a));
+            prop_assert_eq!(voice_leading_distance(&a, &a), 0);
+            prop_assert!(
+                voice_leading_distance(&a, &c)
+                    <= voice_leading_distance(&a, &b) + voice_leading_distance(&b, &c)
+            );
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! A compact plain text format for entering progressions, one harmony per line, in the manner of a music tracker.
+//!
+//! Each line holds the four pitches of a harmony from the bass up to the soprano, written as note names with the octave of
+//! their letter, e.g. `C3 G3 E4 C5`, optionally followed by a colon and the duration of the harmony in seconds, e.g.
+//! `C3 G3 E4 C5 : 2`. Without a duration the harmony lasts `DEFAULT_CHORD_DURATION` seconds. A line holding `r` in place of
+//! the pitches is a rest, which must be given a duration. The root of each harmony is detected from its pitch classes, see
+//! `ChordQuality::detect`, unless given after the pitches as a note name following `@`, e.g. `B2 D4 F4 G#4 @B` for the
+//! diminished seventh on B, whose pitch classes name four roots. Bar lines, `|`, may be placed anywhere between the tokens and are ignored, as are blank lines.
+use crate::chord::{note_name, parse_note, parse_pitch};
+use crate::progression::DEFAULT_CHORD_DURATION;
+use crate::{ChordQuality, Pitch, PitchClassSet, Progression, SatbError, Spelling, SATB};
+use std::fmt::Display;
+
+/// Enumerates the kinds of error found in a line of the text format.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TextErrorKind {
+    /// The token is not a note name with an octave.
+    InvalidPitch(String),
+    /// The token following `@` is not a note name.
+    InvalidRoot(String),
+    /// The token is not a positive, finite number of seconds, or more than one token follows the colon.
+    InvalidDuration(String),
+    /// The line holds the given number of pitches rather than four, or a rest without a duration.
+    WrongVoiceCount(usize),
+    /// No root can be detected from the pitch classes of the harmony.
+    NoRoot,
+    /// The harmony fails validation.
+    InvalidHarmony(SatbError),
+}
+
+/// The error returned when a progression cannot be parsed from the text format, pointing to the offending token by its
+/// 1-based line and column, counted in characters.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextError {
+    /// The 1-based line of the error
+    pub line: usize,
+    /// The 1-based column of the error
+    pub column: usize,
+    /// What is wrong at the line and column
+    pub kind: TextErrorKind,
+}
+
+impl Display for TextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            TextErrorKind::InvalidPitch(token) => write!(f, "\"{}\" is not a pitch", token),
+            TextErrorKind::InvalidRoot(token) => write!(f, "\"{}\" is not a root", token),
+            TextErrorKind::InvalidDuration(token) => write!(f, "\"{}\" is not a duration", token),
+            TextErrorKind::WrongVoiceCount(count) => {
+                write!(f, "expected four pitches, found {}", count)
+            }
+            TextErrorKind::NoRoot => write!(f, "the harmony has no detectable root"),
+            TextErrorKind::InvalidHarmony(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for TextError {}
+
+/// Helper function that splits `s` into its whitespace separated tokens other than bar lines, each along with the 1-based
+/// column, counted in characters, it starts at when `s` starts at column `start`.
+fn tokens(s: &str, start: usize) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut token_start = None;
+    for (column, (i, c)) in s.char_indices().enumerate() {
+        match (c.is_whitespace(), token_start) {
+            (false, None) => token_start = Some((column, i)),
+            (true, Some((column, j))) => {
+                tokens.push((start + column, &s[j..i]));
+                token_start = None;
+            }
+            _ => (),
+        }
+    }
+    if let Some((column, j)) = token_start {
+        tokens.push((start + column, &s[j..]));
+    }
+    tokens.retain(|(_, token)| *token != "|");
+    tokens
+}
+
+impl Progression {
+    /// Associated method for parsing a progression from the text format described in the `text` module, e.g.
+    /// "C3 G3 E4 C5 : 2\nC3 F3 D4 A4 : 2 |\nD3 F4 G4 B4 : 2". Each harmony is validated as by `SATB::try_new`. Returns a
+    /// `TextError` pointing to the first token that cannot be parsed, or the first pitch of the first invalid harmony.
+    pub fn from_text(s: &str) -> Result<Progression, TextError> {
+        let mut progression = Progression::new();
+        for (index, line) in s.lines().enumerate() {
+            let error = |column: usize, kind: TextErrorKind| TextError {
+                line: index + 1,
+                column,
+                kind,
+            };
+            let (notes, duration) = match line.split_once(':') {
+                Some((notes, duration)) => {
+                    let start = notes.chars().count() + 2;
+                    match tokens(duration, start)[..] {
+                        [(column, token)] => match token.parse::<f64>() {
+                            Ok(d) if d.is_finite() && d > 0.0 => (notes, Some(d)),
+                            _ => {
+                                let kind = TextErrorKind::InvalidDuration(token.to_string());
+                                return Err(error(column, kind));
+                            }
+                        },
+                        [] => {
+                            let kind = TextErrorKind::InvalidDuration(String::new());
+                            return Err(error(start, kind));
+                        }
+                        [_, (column, token), ..] => {
+                            let kind = TextErrorKind::InvalidDuration(token.to_string());
+                            return Err(error(column, kind));
+                        }
+                    }
+                }
+                None => (line, None),
+            };
+            let mut tokens = tokens(notes, 1);
+            let first = tokens.first().map_or(1, |(column, _)| *column);
+            if tokens.is_empty() && duration.is_none() {
+                continue;
+            }
+            if let [(_, "r" | "R")] = tokens[..] {
+                match duration {
+                    Some(duration) => progression.push_rest_with_duration(duration),
+                    None => return Err(error(first, TextErrorKind::WrongVoiceCount(0))),
+                }
+                continue;
+            }
+
+            let root = match tokens.last() {
+                Some((column, token)) if token.starts_with('@') => {
+                    let root = match parse_note(&token[1..]) {
+                        Some((pc, "")) => pc,
+                        _ => {
+                            let kind = TextErrorKind::InvalidRoot(token.to_string());
+                            return Err(error(*column, kind));
+                        }
+                    };
+                    tokens.pop();
+                    Some(root)
+                }
+                _ => None,
+            };
+            let pitches = tokens
+                .iter()
+                .map(|(column, token)| {
+                    parse_pitch(token).ok_or_else(|| {
+                        error(*column, TextErrorKind::InvalidPitch(token.to_string()))
+                    })
+                })
+                .collect::<Result<Vec<Pitch>, _>>()?;
+            let [bass, tenor, alto, soprano] = pitches[..] else {
+                return Err(error(first, TextErrorKind::WrongVoiceCount(pitches.len())));
+            };
+            let root = match root {
+                Some(root) => root,
+                None => {
+                    let classes: PitchClassSet = pitches.iter().map(|p| p.pitch_class()).collect();
+                    match ChordQuality::detect(&classes).first() {
+                        Some((root, _)) => *root,
+                        None => return Err(error(first, TextErrorKind::NoRoot)),
+                    }
+                }
+            };
+            let chord = SATB::try_new(root, soprano, alto, tenor, bass)
+                .map_err(|e| error(first, TextErrorKind::InvalidHarmony(e)))?;
+            progression.push_with_duration(chord, duration.unwrap_or(DEFAULT_CHORD_DURATION));
+        }
+        Ok(progression)
+    }
+
+    /// Method that writes the progression in the text format described in the `text` module, which `from_text` parses back
+    /// into the same progression. Pitches are named with sharps, each harmony and rest is written with its duration, and the
+    /// root of a harmony is written only where it differs from the root detected from its pitch classes.
+    pub fn to_text(&self) -> String {
+        let mut lines = Vec::new();
+        for (i, chord) in self.chords().iter().enumerate() {
+            if self.rests()[i] > 0.0 {
+                lines.push(format!("r : {}", self.rests()[i]));
+            }
+            let mut line: Vec<String> = chord
+                .voices()
+                .iter()
+                .rev()
+                .map(|p| {
+                    format!(
+                        "{}{}",
+                        note_name(p.pitch_class(), Spelling::Sharps),
+                        p.octave()
+                    )
+                })
+                .collect();
+            let classes: PitchClassSet = chord.voices().iter().map(|p| p.pitch_class()).collect();
+            if ChordQuality::detect(&classes)
+                .first()
+                .map(|(root, _)| *root)
+                != Some(chord.root())
+            {
+                line.push(format!("@{}", note_name(chord.root(), Spelling::Sharps)));
+            }
+            lines.push(format!("{} : {}", line.join(" "), self.durations()[i]));
+        }
+        if self.trailing_rest() > 0.0 {
+            lines.push(format!("r : {}", self.trailing_rest()));
+        }
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_major_i() {
+        // The three harmonies of the major_I example
+        let text = "C3 G3 E4 C5 : 5 |\nC3 F3 D4 A4 : 5 |\nD3 F4 G4 B4 : 5 |\n";
+        let parsed = Progression::from_text(text).unwrap();
+
+        let mut progression = Progression::new();
+        let major_i = SATB::new(
+            0,
+            (0, 5).into(),
+            (4, 4).into(),
+            (7, 3).into(),
+            (0, 3).into(),
+        );
+        let minor_ii_4_2 = SATB::new(
+            2,
+            (9, 4).into(),
+            (2, 4).into(),
+            (5, 3).into(),
+            (0, 3).into(),
+        );
+        let major_5_6 = SATB::new(
+            7,
+            (11, 4).into(),
+            (7, 4).into(),
+            (5, 4).into(),
+            (2, 3).into(),
+        );
+        progression.push_with_duration(major_i, 5.0);
+        progression.push_with_duration(minor_ii_4_2, 5.0);
+        progression.push_with_duration(major_5_6, 5.0);
+        assert_eq!(parsed, progression);
+
+        assert_eq!(
+            progression.to_text(),
+            "C3 G3 E4 C5 : 5\nC3 F3 D4 A4 : 5\nD3 F4 G4 B4 : 5\n"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut progression = Progression::new();
+        progression.push_rest_with_duration(0.5);
+        // A fully diminished seventh is symmetric, so its root is written out
+        progression.push(SATB::new(
+            11,
+            (8, 4).into(),
+            (5, 4).into(),
+            (2, 4).into(),
+            (11, 2).into(),
+        ));
+        progression.push(SATB::new(
+            0,
+            (7, 4).into(),
+            (4, 4).into(),
+            (0, 4).into(),
+            (0, 3).into(),
+        ));
+        progression.push_rest_with_duration(2.0);
+        let text = progression.to_text();
+        assert!(text.starts_with("r : 0.5\nB2 D4 F4 G#4 @B : 1\n"));
+        assert_eq!(Progression::from_text(&text).unwrap(), progression);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = Progression::from_text("C3 G3 E4 C5\n\nC3 G3 X4 C5 : 2").unwrap_err();
+        assert_eq!(
+            (error.line, error.column, error.kind),
+            (3, 7, TextErrorKind::InvalidPitch("X4".to_string()))
+        );
+        let error = Progression::from_text("  C3 G3 E4 C5 :  two").unwrap_err();
+        assert_eq!(
+            (error.line, error.column, error.kind),
+            (1, 18, TextErrorKind::InvalidDuration("two".to_string()))
+        );
+        let error = Progression::from_text("C3 G3 E4").unwrap_err();
+        assert_eq!(error.kind, TextErrorKind::WrongVoiceCount(3));
+        // A root position triad doubling its third
+        let error = Progression::from_text("| C3 E3 G4 E5 |").unwrap_err();
+        assert_eq!(error.column, 3);
+        assert!(matches!(error.kind, TextErrorKind::InvalidHarmony(_)));
+    }
+}
@@ -0,0 +1,161 @@
+//! The reference frequency equal temperament is tuned to, and tables of the frequencies of every pitch under a tuning, for
+//! embedding in firmware or documentation.
+use crate::{const_powi, Pitch, A_440_FREQUENCY, A_440_HALFSTEPS_FROM_0, SEMITONE_FREQUENCY_RATIO};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+/// The lowest octave of the table written by `export_frequency_table` and `frequency_table_csv`.
+pub const TABLE_LOW_OCTAVE: u8 = 0;
+/// The highest octave of the table written by `export_frequency_table` and `frequency_table_csv`.
+pub const TABLE_HIGH_OCTAVE: u8 = 8;
+
+/// The names of the pitch classes heading the columns of `frequency_table_csv`.
+const COLUMNS: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// A struct that represents the tuning of twelve tone equal temperament, given by the frequency of A4.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tuning {
+    /// The frequency of A4 in Hz
+    pub reference: f64,
+}
+
+impl Tuning {
+    /// The standard tuning, with A4 at `A_440_FREQUENCY`, used by `Pitch::compute_frequency`.
+    pub const STANDARD: Tuning = Tuning {
+        reference: A_440_FREQUENCY,
+    };
+
+    /// Associated method for creating a new `Tuning` with A4 at `reference` Hz, e.g. 415.0 for baroque pitch.
+    pub const fn new(reference: f64) -> Self {
+        Tuning { reference }
+    }
+
+    /// Method that computes the frequency of the pitch with `pitch_class` in `octave` under the tuning, in the same way
+    /// `Pitch::compute_frequency` does, so the two agree exactly under `Tuning::STANDARD`.
+    pub const fn frequency(&self, pitch_class: u8, octave: u8) -> f64 {
+        let num_semitones = Pitch::compute_half_steps_from_zero(pitch_class, octave);
+        self.reference
+            * const_powi(
+                SEMITONE_FREQUENCY_RATIO,
+                (num_semitones as i32) - (A_440_HALFSTEPS_FROM_0 as i32),
+            )
+    }
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Tuning::STANDARD
+    }
+}
+
+/// A function that computes the table of the frequencies of each pitch class, indexed from C, in each of the `N` octaves from
+/// `low_octave` upwards under `tuning`. Usable in constant expressions, see `generate_frequency_table!`.
+pub const fn frequency_table<const N: usize>(tuning: &Tuning, low_octave: u8) -> [[f64; 12]; N] {
+    let mut table = [[0.0; 12]; N];
+    let mut row = 0;
+    while row < N {
+        let mut pc = 0;
+        while pc < 12 {
+            table[row][pc] = tuning.frequency(pc as u8, low_octave + row as u8);
+            pc += 1;
+        }
+        row += 1;
+    }
+    table
+}
+
+/// Expands to a table of type `[[f64; 12]; N]` holding the frequency of each pitch class, indexed from C, in each octave
+/// from `low_octave` to `high_octave` inclusive, so `N` is `high_octave - low_octave + 1`, with A4 tuned to `tuning` Hz.
+/// The table is computed at compile time, so it can initialize a `static` to embed in firmware, and its values equal those
+/// of `Tuning::frequency`, and of `Pitch::compute_frequency` when tuned to 440 Hz.
+///
+/// ```
+/// use twelve_et::{generate_frequency_table, Pitch};
+///
+/// static FREQUENCIES: [[f64; 12]; 9] =
+///     generate_frequency_table!(tuning = 440.0, low_octave = 0, high_octave = 8);
+/// assert_eq!(FREQUENCIES[4][9], 440.0);
+/// assert_eq!(FREQUENCIES[3][0], Pitch::compute_frequency(0, 3));
+/// ```
+#[macro_export]
+macro_rules! generate_frequency_table {
+    (tuning = $tuning:expr, low_octave = $low:expr, high_octave = $high:expr $(,)?) => {{
+        const TABLE: [[f64; 12]; ($high - $low + 1) as usize] =
+            $crate::tuning::frequency_table(&$crate::Tuning::new($tuning), $low);
+        TABLE
+    }};
+}
+
+/// A function that returns the frequency of each pitch class, indexed from C, in each octave from `TABLE_LOW_OCTAVE` to
+/// `TABLE_HIGH_OCTAVE` under `tuning`, one row per octave from the lowest, as computed by `Tuning::frequency`.
+pub fn export_frequency_table(tuning: &Tuning) -> Vec<Vec<f64>> {
+    (TABLE_LOW_OCTAVE..=TABLE_HIGH_OCTAVE)
+        .map(|octave| (0..12).map(|pc| tuning.frequency(pc, octave)).collect())
+        .collect()
+}
+
+/// A function that writes the table of `export_frequency_table` as CSV, a header row naming the pitch classes with sharps
+/// followed by a row for each octave from the lowest, each with the twelve frequencies of the octave in Hz, written with as
+/// many digits as it takes to read them back exactly.
+pub fn frequency_table_csv(tuning: &Tuning) -> String {
+    let mut csv = COLUMNS.join(",");
+    csv.push('\n');
+    for row in export_frequency_table(tuning) {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|frequency| {
+                let mut cell = String::new();
+                let _ = write!(cell, "{}", frequency);
+                cell
+            })
+            .collect();
+        csv += &cells.join(",");
+        csv.push('\n');
+    }
+    csv
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_frequency_table_macro() {
+        static TABLE: [[f64; 12]; 9] =
+            generate_frequency_table!(tuning = 440.0, low_octave = 0, high_octave = 8);
+        let runtime = export_frequency_table(&Tuning::STANDARD);
+        for (octave, pc) in [(0, 0), (1, 11), (4, 9), (4, 0), (8, 11)] {
+            assert_eq!(
+                TABLE[octave][pc],
+                Pitch::compute_frequency(pc as u8, octave as u8)
+            );
+            assert_eq!(TABLE[octave][pc], runtime[octave][pc]);
+        }
+        assert_eq!(TABLE[4][9], 440.0);
+
+        let baroque = generate_frequency_table!(tuning = 415.0, low_octave = 3, high_octave = 5);
+        assert_eq!(baroque.len(), 3);
+        assert_eq!(baroque[1][9], 415.0);
+        let tuning = Tuning::new(415.0);
+        assert_eq!(baroque[0][2], tuning.frequency(2, 3));
+        assert_eq!(baroque[2][7], export_frequency_table(&tuning)[5][7]);
+    }
+
+    #[test]
+    fn test_frequency_table_csv() {
+        let csv = frequency_table_csv(&Tuning::default());
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 10);
+        assert_eq!(rows[0], "C,C#,D,D#,E,F,F#,G,G#,A,A#,B");
+        for row in &rows {
+            assert_eq!(row.split(',').count(), 12);
+        }
+        let a4: f64 = rows[5].split(',').nth(9).unwrap().parse().unwrap();
+        assert_eq!(a4, 440.0);
+        let c3: f64 = rows[4].split(',').next().unwrap().parse().unwrap();
+        assert_eq!(c3, Pitch::compute_frequency(0, 3));
+    }
+}
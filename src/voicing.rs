@@ -0,0 +1,232 @@
+//! Automatic voicing of harmonies given by their pitch classes, and the voice leading engine that connects one voicing to the next.
+use crate::progression::{check_voicing_pair, motion};
+use crate::{ChordMember, ChordQuality, Pitch, ValidationConfig, Voice, SATB};
+
+/// Configures how harmonies are voiced and connected by the voicing functions.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VoicingPreferences {
+    /// The policy each candidate voicing is validated with
+    pub validation: ValidationConfig,
+    /// When true, second inversion triads are considered as candidate voicings
+    pub allow_second_inversion: bool,
+    /// The largest leap in semitones the alto and tenor may make between consecutive harmonies
+    pub max_inner_leap: u32,
+}
+
+impl Default for VoicingPreferences {
+    fn default() -> Self {
+        VoicingPreferences {
+            validation: ValidationConfig::default(),
+            allow_second_inversion: false,
+            max_inner_leap: 7,
+        }
+    }
+}
+
+/// Helper function that computes the position of `pitch` in semitones above C0.
+fn position(pitch: &Pitch) -> i32 {
+    12 * pitch.octave() as i32 + pitch.pitch_class() as i32
+}
+
+/// Helper function that returns every pitch within the range of `voice` whose pitch class is one of `pitch_classes`, from lowest to highest.
+fn candidate_pitches(voice: Voice, pitch_classes: &[u8]) -> Vec<Pitch> {
+    let (low, high) = voice.range();
+    (position(&low)..=position(&high))
+        .filter(|p| pitch_classes.contains(&((p % 12) as u8)))
+        .map(|p| Pitch::from_pitch_class((p % 12) as u8, (p / 12) as u8))
+        .collect()
+}
+
+/// A function that enumerates every valid voicing of the harmony with root `root` made up of `pitch_classes`, ordered by
+/// soprano, then alto, tenor and bass from lowest to highest. Every member other than the fifth is sounded, so a seventh chord
+/// is never voiced as its triad. When `soprano` is given, only voicings with that soprano are returned.
+/// Second inversion triads are only included if `prefs.allow_second_inversion` is set.
+pub fn enumerate_voicings(
+    root: u8,
+    pitch_classes: &[u8],
+    soprano: Option<&Pitch>,
+    prefs: &VoicingPreferences,
+) -> Vec<SATB> {
+    let has_seventh = pitch_classes
+        .iter()
+        .any(|pc| crate::chord_member(root, *pc) == ChordMember::Seventh);
+    enumerate_with(pitch_classes, soprano, |s, a, t, b| {
+        let voicing = SATB::try_new_with_config(root, s, a, t, b, &prefs.validation).ok()?;
+        let complete = pitch_classes.iter().all(|pc| {
+            crate::chord_member(root, *pc) == ChordMember::Fifth
+                || voicing.contains_pitch_class(*pc)
+        });
+        if !complete {
+            return None;
+        }
+        let six_four = voicing.role_of(Voice::Bass) == ChordMember::Fifth && !has_seventh;
+        (!six_four || prefs.allow_second_inversion).then_some(voicing)
+    })
+}
+
+/// A function that enumerates every valid voicing of the harmony of `quality` on `root`, as validated by
+/// `SATB::try_with_quality`, ordered as for `enumerate_voicings`, so suspended and added tone harmonies can be voiced. When
+/// `soprano` is given, only voicings with that soprano are returned.
+pub fn enumerate_quality_voicings(
+    root: u8,
+    quality: ChordQuality,
+    soprano: Option<&Pitch>,
+    prefs: &VoicingPreferences,
+) -> Vec<SATB> {
+    let pitch_classes: Vec<u8> = quality.pitch_classes(root).iter().collect();
+    enumerate_with(&pitch_classes, soprano, |s, a, t, b| {
+        SATB::try_with_quality(root, quality, s, a, t, b, &prefs.validation).ok()
+    })
+}
+
+/// Helper function that returns the voicings made by `voicing` from each choice of pitches of `pitch_classes` within the range
+/// of each voice, without crossed voices or upper voices more than an octave apart, ordered by soprano, then alto, tenor and
+/// bass from lowest to highest. When `soprano` is given, only that soprano is tried.
+fn enumerate_with(
+    pitch_classes: &[u8],
+    soprano: Option<&Pitch>,
+    voicing: impl Fn(Pitch, Pitch, Pitch, Pitch) -> Option<SATB>,
+) -> Vec<SATB> {
+    let sopranos = match soprano {
+        Some(s) if Voice::Soprano.contains(s) && pitch_classes.contains(&s.pitch_class()) => {
+            vec![*s]
+        }
+        Some(_) => return Vec::new(),
+        None => candidate_pitches(Voice::Soprano, pitch_classes),
+    };
+    let altos = candidate_pitches(Voice::Alto, pitch_classes);
+    let tenors = candidate_pitches(Voice::Tenor, pitch_classes);
+    let basses = candidate_pitches(Voice::Bass, pitch_classes);
+
+    let mut voicings = Vec::new();
+    for s in &sopranos {
+        for a in altos.iter().filter(|a| (0..=12).contains(&motion(a, s))) {
+            for t in tenors.iter().filter(|t| (0..=12).contains(&motion(t, a))) {
+                for b in basses.iter().filter(|b| motion(b, t) >= 0) {
+                    voicings.extend(voicing(*s, *a, *t, *b));
+                }
+            }
+        }
+    }
+    voicings
+}
+
+/// A function that computes the voice leading distance between two voicings, the total number of semitones moved by all four voices.
+pub fn voice_leading_distance(from: &SATB, to: &SATB) -> u32 {
+    from.voices()
+        .iter()
+        .zip(to.voices().iter())
+        .map(|(f, t)| motion(f, t).unsigned_abs())
+        .sum()
+}
+
+/// Helper function that computes how far `voicing` strays from the middle of each voice's range, in semitones,
+/// with a penalty for voicings not in root position.
+pub(crate) fn standalone_cost(voicing: &SATB) -> u32 {
+    let spread: u32 = Voice::ALL
+        .iter()
+        .map(|v| {
+            let (low, high) = v.range();
+            let middle = (position(&low) + position(&high)) / 2;
+            (position(voicing.voice(*v)) - middle).unsigned_abs()
+        })
+        .sum();
+    spread + inversion_cost(voicing)
+}
+
+/// Helper function that computes the penalty given to `voicing` for the chord member in its bass.
+pub(crate) fn inversion_cost(voicing: &SATB) -> u32 {
+    match voicing.role_of(Voice::Bass) {
+        ChordMember::Root => 0,
+        ChordMember::Third => 4,
+        _ => 8,
+    }
+}
+
+/// Helper function that computes the cost of moving from `from` to `to`, or `None` if the connection is not allowed,
+/// i.e. it has parallel fifths or octaves or an inner voice leaps further than `prefs.max_inner_leap`.
+pub(crate) fn connection_cost(from: &SATB, to: &SATB, prefs: &VoicingPreferences) -> Option<u32> {
+    if !check_voicing_pair(&from.voices(), &to.voices()).is_empty() {
+        return None;
+    }
+    let inner_leap = [Voice::Alto, Voice::Tenor]
+        .iter()
+        .map(|v| motion(from.voice(*v), to.voice(*v)).unsigned_abs())
+        .max()
+        .unwrap_or(0);
+    if inner_leap > prefs.max_inner_leap {
+        return None;
+    }
+    Some(voice_leading_distance(from, to) + inversion_cost(to))
+}
+
+/// A function that voices the harmony with root `root` made up of `pitch_classes` on its own, preferring root position and
+/// voices near the middle of their ranges. When `soprano` is given it is kept as the soprano of the voicing.
+/// Returns `None` if the harmony has no valid voicing.
+pub fn auto_voice(
+    root: u8,
+    pitch_classes: &[u8],
+    soprano: Option<&Pitch>,
+    prefs: &VoicingPreferences,
+) -> Option<SATB> {
+    enumerate_voicings(root, pitch_classes, soprano, prefs)
+        .into_iter()
+        .min_by_key(standalone_cost)
+}
+
+/// A function that voices the harmony with root `root` made up of `pitch_classes` so that it follows `from` with the least
+/// voice leading distance, without parallel fifths or octaves. When `soprano` is given it is kept as the soprano of the voicing.
+/// Returns `None` if no voicing can follow `from`.
+pub fn lead_to(
+    from: &SATB,
+    root: u8,
+    pitch_classes: &[u8],
+    soprano: Option<&Pitch>,
+    prefs: &VoicingPreferences,
+) -> Option<SATB> {
+    enumerate_voicings(root, pitch_classes, soprano, prefs)
+        .into_iter()
+        .filter_map(|v| connection_cost(from, &v, prefs).map(|cost| (cost, v)))
+        .min_by_key(|(cost, _)| *cost)
+        .map(|(_, v)| v)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_enumerate_voicings_with_soprano() {
+        let soprano = Pitch::from((4, 5));
+        let voicings = enumerate_voicings(
+            0,
+            &[0, 4, 7],
+            Some(&soprano),
+            &VoicingPreferences::default(),
+        );
+        assert!(!voicings.is_empty());
+        for voicing in &voicings {
+            assert_eq!(voicing.soprano, soprano);
+            assert_ne!(voicing.role_of(Voice::Bass), ChordMember::Fifth);
+        }
+        // A soprano outside of the harmony has no voicings
+        let voicings = enumerate_voicings(
+            0,
+            &[0, 4, 7],
+            Some(&Pitch::from((2, 5))),
+            &VoicingPreferences::default(),
+        );
+        assert!(voicings.is_empty());
+    }
+
+    #[test]
+    fn test_auto_voice_and_lead_to() {
+        let prefs = VoicingPreferences::default();
+        let tonic = auto_voice(0, &[0, 4, 7], None, &prefs).unwrap();
+        assert_eq!(tonic.role_of(Voice::Bass), ChordMember::Root);
+
+        let dominant = lead_to(&tonic, 7, &[7, 11, 2], None, &prefs).unwrap();
+        assert!(check_voicing_pair(&tonic.voices(), &dominant.voices()).is_empty());
+        assert!(voice_leading_distance(&tonic, &dominant) <= 12);
+    }
+}
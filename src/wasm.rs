@@ -0,0 +1,462 @@
+//! Bindings for JavaScript through `wasm-bindgen`, so a browser app can build, validate, analyze and render harmonies
+//! without a server. The wrapper types `JsPitch`, `JsSatb` and `JsProgression` hold the types of the crate, and every error
+//! is converted to a `JsTheoryError`, an object naming the rule that failed.
+use crate::audio::sample_count;
+use crate::chord::{note_name, parse_note};
+use crate::convert::{parse_note_name, ConvertError};
+use crate::{
+    Key, KeyMode, ParseError, Pitch, Progression, RenderOptions, RenderState, SatbError, Spelling,
+    Violation, VoicingPreferences, SATB,
+};
+use wasm_bindgen::prelude::*;
+
+/// An error passed to JavaScript, naming the rule or check that failed along with a message describing it.
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsTheoryError {
+    /// The name of the rule, in snake case, e.g. "voice_crossing" or "parallel_fifths"
+    rule: String,
+    /// The description of the error
+    message: String,
+    /// The index of the harmony or symbol the error was found at, if any
+    index: Option<u32>,
+}
+
+#[wasm_bindgen]
+impl JsTheoryError {
+    /// Method for accessing the name of the rule that failed, e.g. "voice_crossing".
+    #[wasm_bindgen(getter)]
+    pub fn rule(&self) -> String {
+        self.rule.clone()
+    }
+
+    /// Method for accessing the message describing the error.
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    /// Method for accessing the index of the harmony or symbol the error was found at, if any.
+    #[wasm_bindgen(getter)]
+    pub fn index(&self) -> Option<u32> {
+        self.index
+    }
+}
+
+impl JsTheoryError {
+    /// Associated method for creating a new `JsTheoryError` breaking `rule`, described by `message`.
+    fn new(rule: &str, message: impl ToString, index: Option<usize>) -> Self {
+        JsTheoryError {
+            rule: rule.to_string(),
+            message: message.to_string(),
+            index: index.map(|i| i as u32),
+        }
+    }
+}
+
+impl From<SatbError> for JsTheoryError {
+    fn from(error: SatbError) -> Self {
+        let rule = match error {
+            SatbError::OutOfRange(_) => "out_of_range",
+            SatbError::Crossing { .. } => "voice_crossing",
+            SatbError::Spacing { .. } => "spacing",
+            SatbError::InvalidHarmony => "incomplete_harmony",
+        };
+        JsTheoryError::new(rule, error, None)
+    }
+}
+
+impl From<Violation> for JsTheoryError {
+    fn from(violation: Violation) -> Self {
+        JsTheoryError::new(violation.kind.rule(), violation, Some(violation.index))
+    }
+}
+
+impl From<ParseError> for JsTheoryError {
+    fn from(error: ParseError) -> Self {
+        let (rule, index) = match &error {
+            ParseError::Empty => ("empty_progression", None),
+            ParseError::UnknownToken { position, .. } => ("unknown_numeral", Some(*position)),
+            ParseError::NoVoicing { position } => ("no_voicing", Some(*position)),
+            ParseError::NoProgression { position } => ("no_progression", Some(*position)),
+        };
+        JsTheoryError::new(rule, error, index)
+    }
+}
+
+impl From<ConvertError> for JsTheoryError {
+    fn from(error: ConvertError) -> Self {
+        let rule = match error {
+            ConvertError::InvalidNote(_) => "invalid_note",
+            ConvertError::OutOfRange(_) => "out_of_range",
+            ConvertError::InvalidFrequency(_) => "invalid_frequency",
+        };
+        JsTheoryError::new(rule, error, None)
+    }
+}
+
+/// Helper function that returns the key on the tonic named `tonic`, e.g. "F#", in the minor mode if `minor` is set.
+fn key(tonic: &str, minor: bool) -> Result<Key, JsTheoryError> {
+    let mode = if minor {
+        KeyMode::Minor
+    } else {
+        KeyMode::Major
+    };
+    match parse_note(tonic) {
+        Some((pc, "")) => Ok(Key::new(pc, mode)),
+        _ => Err(JsTheoryError::new(
+            "invalid_key",
+            format!("\"{}\" is not the name of a tonic", tonic),
+            None,
+        )),
+    }
+}
+
+/// A pitch, wrapping a `Pitch`.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct JsPitch {
+    pitch: Pitch,
+}
+
+#[wasm_bindgen]
+impl JsPitch {
+    /// Associated method for creating the equally tempered pitch with `pitch_class`, from 0 for C to 11 for B, in `octave`.
+    /// Returns an error if `pitch_class` is 12 or more.
+    #[wasm_bindgen(constructor)]
+    pub fn new(pitch_class: u8, octave: u8) -> Result<JsPitch, JsTheoryError> {
+        if pitch_class >= 12 {
+            return Err(JsTheoryError::new(
+                "invalid_pitch_class",
+                format!("{} is not a pitch class from 0 to 11", pitch_class),
+                None,
+            ));
+        }
+        Ok(JsPitch {
+            pitch: Pitch::from_pitch_class(pitch_class, octave),
+        })
+    }
+
+    /// Associated method for creating the equally tempered pitch named `name` in scientific or Helmholtz notation, e.g.
+    /// "C4" or "c'", see the `convert` module.
+    #[wasm_bindgen(js_name = fromName)]
+    pub fn from_name(name: &str) -> Result<JsPitch, JsTheoryError> {
+        let (pitch_class, octave) = parse_note_name(name)?;
+        JsPitch::new(pitch_class, octave)
+    }
+
+    /// Method for accessing the frequency of the pitch in Hz.
+    #[wasm_bindgen(getter)]
+    pub fn frequency(&self) -> f64 {
+        self.pitch.frequency()
+    }
+
+    /// Method for accessing the pitch class of the pitch.
+    #[wasm_bindgen(getter, js_name = pitchClass)]
+    pub fn pitch_class(&self) -> u8 {
+        self.pitch.pitch_class()
+    }
+
+    /// Method for accessing the octave of the pitch.
+    #[wasm_bindgen(getter)]
+    pub fn octave(&self) -> u8 {
+        self.pitch.octave()
+    }
+
+    /// Method that returns the MIDI note number of the pitch, or `undefined` above the MIDI note range.
+    #[wasm_bindgen(getter, js_name = midiNote)]
+    pub fn midi_note(&self) -> Option<u8> {
+        self.pitch.midi_note()
+    }
+
+    /// Method that names the pitch in scientific pitch notation with sharps, e.g. "F#3".
+    #[wasm_bindgen(js_name = toString)]
+    pub fn name(&self) -> String {
+        format!(
+            "{}{}",
+            note_name(self.pitch.pitch_class(), Spelling::Sharps),
+            self.pitch.octave()
+        )
+    }
+}
+
+/// A four part harmony, wrapping an `SATB`.
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsSatb {
+    satb: SATB,
+}
+
+#[wasm_bindgen]
+impl JsSatb {
+    /// Associated method for creating the harmony with root `root` from the pitch of each voice, validated as by
+    /// `SATB::try_new`. Returns an error naming the first rule the harmony breaks.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        root: u8,
+        soprano: &JsPitch,
+        alto: &JsPitch,
+        tenor: &JsPitch,
+        bass: &JsPitch,
+    ) -> Result<JsSatb, JsTheoryError> {
+        let satb = SATB::try_new(root, soprano.pitch, alto.pitch, tenor.pitch, bass.pitch)?;
+        Ok(JsSatb { satb })
+    }
+
+    /// Method for accessing the root of the harmony.
+    #[wasm_bindgen(getter)]
+    pub fn root(&self) -> u8 {
+        self.satb.root()
+    }
+
+    /// Method for accessing the soprano of the harmony.
+    #[wasm_bindgen(getter)]
+    pub fn soprano(&self) -> JsPitch {
+        JsPitch {
+            pitch: self.satb.soprano,
+        }
+    }
+
+    /// Method for accessing the alto of the harmony.
+    #[wasm_bindgen(getter)]
+    pub fn alto(&self) -> JsPitch {
+        JsPitch {
+            pitch: self.satb.alto,
+        }
+    }
+
+    /// Method for accessing the tenor of the harmony.
+    #[wasm_bindgen(getter)]
+    pub fn tenor(&self) -> JsPitch {
+        JsPitch {
+            pitch: self.satb.tenor,
+        }
+    }
+
+    /// Method for accessing the bass of the harmony.
+    #[wasm_bindgen(getter)]
+    pub fn bass(&self) -> JsPitch {
+        JsPitch {
+            pitch: self.satb.bass,
+        }
+    }
+
+    /// Method that labels the harmony with its roman numeral in the key on `tonic`, minor if `minor` is set, e.g. "V65", see
+    /// `Key::analyze`. Returns `undefined` for harmonies the key cannot label, or an error if `tonic` is not a note name.
+    #[wasm_bindgen(js_name = romanNumeral)]
+    pub fn roman_numeral(&self, tonic: &str, minor: bool) -> Result<Option<String>, JsTheoryError> {
+        Ok(key(tonic, minor)?
+            .analyze(&self.satb)
+            .map(|numeral| numeral.to_string()))
+    }
+
+    /// Method that renders `duration` seconds of the harmony's sound wave at `sample_rate`, as by `SATB::render_into`,
+    /// passed to JavaScript as a `Float32Array`.
+    pub fn render(&self, duration: f64, sample_rate: u32) -> Vec<f32> {
+        let mut samples = vec![0.0; sample_count(duration, sample_rate)];
+        self.satb
+            .render_into(&mut samples, sample_rate, &mut RenderState::default());
+        samples
+    }
+}
+
+/// A progression of four part harmonies, wrapping a `Progression`.
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct JsProgression {
+    progression: Progression,
+}
+
+#[wasm_bindgen]
+impl JsProgression {
+    /// Associated method for creating a new, empty progression.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JsProgression {
+        JsProgression::default()
+    }
+
+    /// Associated method for realizing the roman numerals `numerals` in the key on `tonic`, minor if `minor` is set, e.g.
+    /// "I IV V I", see `Progression::from_roman`. Returns an error naming why the numerals cannot be realized.
+    #[wasm_bindgen(js_name = fromRoman)]
+    pub fn from_roman(
+        tonic: &str,
+        minor: bool,
+        numerals: &str,
+    ) -> Result<JsProgression, JsTheoryError> {
+        let progression = Progression::from_roman(
+            &key(tonic, minor)?,
+            numerals,
+            &VoicingPreferences::default(),
+        )?;
+        Ok(JsProgression { progression })
+    }
+
+    /// Method that appends `satb` to the progression, lasting `duration` seconds.
+    pub fn push(&mut self, satb: &JsSatb, duration: f64) {
+        self.progression
+            .push_with_duration(satb.satb.clone(), duration);
+    }
+
+    /// Method that appends a rest lasting `duration` seconds to the progression.
+    #[wasm_bindgen(js_name = pushRest)]
+    pub fn push_rest(&mut self, duration: f64) {
+        self.progression.push_rest_with_duration(duration);
+    }
+
+    /// Method that returns the number of harmonies in the progression.
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> u32 {
+        self.progression.chords().len() as u32
+    }
+
+    /// Method that returns the voice leading violations of the progression, see `Progression::violations`, each naming the
+    /// rule broken and the index of the harmony it was found at.
+    pub fn violations(&self) -> Vec<JsTheoryError> {
+        self.progression
+            .violations()
+            .into_iter()
+            .map(JsTheoryError::from)
+            .collect()
+    }
+
+    /// Method that labels each harmony of the progression with its roman numeral in the key on `tonic`, minor if `minor` is
+    /// set, with an empty string for harmonies the key cannot label. Returns an error if `tonic` is not a note name.
+    #[wasm_bindgen(js_name = romanNumerals)]
+    pub fn roman_numerals(&self, tonic: &str, minor: bool) -> Result<Vec<String>, JsTheoryError> {
+        Ok(key(tonic, minor)?
+            .analyze_progression(&self.progression)
+            .into_iter()
+            .map(|numeral| numeral.map_or_else(String::new, |n| n.to_string()))
+            .collect())
+    }
+
+    /// Method that renders the progression's sound wave at `sample_rate` with the default `RenderOptions`, passed to
+    /// JavaScript as a `Float32Array`.
+    pub fn render(&self, sample_rate: u32) -> Vec<f32> {
+        self.progression
+            .render(sample_rate, &RenderOptions::default())
+            .samples
+            .iter()
+            .map(|sample| *sample as f32)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    /// Helper function that returns the C major harmony E5, G4, C4, C3.
+    fn c_major() -> JsSatb {
+        let pitch = |name| JsPitch::from_name(name).unwrap();
+        JsSatb::new(0, &pitch("E5"), &pitch("G4"), &pitch("C4"), &pitch("C3")).unwrap()
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn test_construction() {
+        let pitch = JsPitch::from_name("a'").unwrap();
+        assert_eq!(pitch.frequency(), 440.0);
+        assert_eq!((pitch.pitch_class(), pitch.octave()), (9, 4));
+        assert_eq!(pitch.midi_note(), Some(69));
+        assert_eq!(JsPitch::new(6, 3).unwrap().name(), "F#3");
+        assert_eq!(
+            JsPitch::new(12, 4).unwrap_err().rule(),
+            "invalid_pitch_class"
+        );
+        assert_eq!(JsPitch::from_name("H4").unwrap_err().rule(), "invalid_note");
+
+        let satb = c_major();
+        assert_eq!(satb.root(), 0);
+        assert_eq!(satb.soprano().name(), "E5");
+        assert_eq!(satb.roman_numeral("G", false), Ok(Some("IV".to_string())));
+        assert_eq!(
+            satb.roman_numeral("X", false).unwrap_err().rule(),
+            "invalid_key"
+        );
+
+        let progression = JsProgression::from_roman("C", false, "I IV V I").unwrap();
+        assert_eq!(progression.length(), 4);
+        assert_eq!(
+            progression.roman_numerals("C", false).unwrap(),
+            ["I", "IV", "V", "I"]
+        );
+        assert!(progression.violations().is_empty());
+        let error = JsProgression::from_roman("C", false, "I Q V").unwrap_err();
+        assert_eq!(
+            (error.rule(), error.index()),
+            ("unknown_numeral".to_string(), Some(1))
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn test_validation_errors() {
+        let pitch = |name| JsPitch::from_name(name).unwrap();
+        // The tenor C4 above the alto G3
+        let error =
+            JsSatb::new(0, &pitch("E4"), &pitch("G3"), &pitch("C4"), &pitch("C3")).unwrap_err();
+        assert_eq!(error.rule(), "voice_crossing");
+        assert_eq!(error.message(), "the tenor is crossed above the alto");
+        assert_eq!(error.index(), None);
+        let error =
+            JsSatb::new(2, &pitch("E5"), &pitch("G4"), &pitch("C4"), &pitch("C3")).unwrap_err();
+        assert_eq!(error.rule(), "incomplete_harmony");
+        // The soprano C6 above its range, and the alto C4 more than an octave below the soprano E5
+        let error =
+            JsSatb::new(0, &pitch("C6"), &pitch("G4"), &pitch("E4"), &pitch("C3")).unwrap_err();
+        assert_eq!(error.rule(), "out_of_range");
+        let error =
+            JsSatb::new(0, &pitch("E5"), &pitch("C4"), &pitch("G3"), &pitch("C3")).unwrap_err();
+        assert_eq!(error.rule(), "spacing");
+        assert_eq!(
+            JsPitch::from_name("C200000000").unwrap_err().rule(),
+            "out_of_range"
+        );
+        assert_eq!(
+            JsProgression::from_roman("C", false, "")
+                .unwrap_err()
+                .rule(),
+            "empty_progression"
+        );
+
+        // Parallel octaves between the tenor and bass from C major to D minor
+        let mut progression = JsProgression::new();
+        progression.push(&c_major(), 1.0);
+        progression.push(
+            &JsSatb::new(2, &pitch("F5"), &pitch("A4"), &pitch("D4"), &pitch("D3")).unwrap(),
+            1.0,
+        );
+        let violations = progression.violations();
+        assert!(!violations.is_empty());
+        assert!(violations.iter().all(|v| v.index() == Some(0)));
+        assert!(violations.iter().any(|v| v.rule() == "parallel_octaves"
+            && v.message()
+                .starts_with("parallel octaves between tenor and bass")));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn test_render() {
+        let satb = c_major();
+        let samples = satb.render(0.5, 8000);
+        assert_eq!(samples.len(), 4000);
+        assert!(samples.iter().all(|s| s.abs() <= 1.0));
+        assert!(satb.render(0.0, 8000).is_empty());
+
+        let mut progression = JsProgression::new();
+        progression.push(&satb, 1.0);
+        progression.push_rest(0.5);
+        progression.push(&satb, 1.0);
+        assert_eq!(progression.render(8000).len(), 20000);
+
+        // Harmonies realized from roman numerals last a second each
+        let progression = JsProgression::from_roman("G", false, "I IV V7 I").unwrap();
+        let samples = progression.render(4000);
+        assert_eq!(samples.len(), 4 * 4000);
+        assert!(samples.iter().all(|s| s.is_finite() && s.abs() <= 1.0));
+        assert!(JsProgression::new().render(8000).is_empty());
+    }
+}
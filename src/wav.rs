@@ -0,0 +1,108 @@
+//! Export of sound waves to WAV files, available with the `wav` feature.
+use crate::audio::Sample;
+use std::fmt::Display;
+use std::path::Path;
+
+/// Enumerates the sample formats a WAV file can be written with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum WavFormat {
+    /// 32 bit floating point samples, written unscaled
+    #[default]
+    Float32,
+    /// 16 bit integer samples, scaled to the full integer range and clamped as by `Sample for i16`
+    Int16,
+}
+
+/// The error returned when a WAV file cannot be written.
+#[derive(Debug)]
+pub struct WavError(hound::Error);
+
+impl Display for WavError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to write wav file: {}", self.0)
+    }
+}
+
+impl std::error::Error for WavError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl From<hound::Error> for WavError {
+    fn from(e: hound::Error) -> Self {
+        WavError(e)
+    }
+}
+
+/// A function that writes `samples`, a mono sound wave sampled at `sample_rate`, to a WAV file at `path` in the given `format`.
+pub fn write_wav(
+    path: impl AsRef<Path>,
+    samples: &[f64],
+    sample_rate: u32,
+    format: WavFormat,
+) -> Result<(), WavError> {
+    let (bits_per_sample, sample_format) = match format {
+        WavFormat::Float32 => (32, hound::SampleFormat::Float),
+        WavFormat::Int16 => (16, hound::SampleFormat::Int),
+    };
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample,
+        sample_format,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    for sample in samples {
+        match format {
+            WavFormat::Float32 => writer.write_sample(f32::from_f64(*sample))?,
+            WavFormat::Int16 => writer.write_sample(i16::from_f64(*sample))?,
+        }
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Harmony, SATB};
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("twelve_et_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    #[test]
+    fn test_write_wav_round_trip() {
+        let samples = [0.0, 0.5, -0.5, 1.0, -1.0];
+        let path = temp_path("round_trip_int.wav");
+        write_wav(&path, &samples, 8000, WavFormat::Int16).unwrap();
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().sample_rate, 8000);
+        let read: Vec<i16> = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(read, vec![0, 16384, -16384, 32767, -32768]);
+
+        let path = temp_path("round_trip_float.wav");
+        write_wav(&path, &samples, 8000, WavFormat::Float32).unwrap();
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        let read: Vec<f32> = reader.samples::<f32>().map(|s| s.unwrap()).collect();
+        assert_eq!(read, vec![0.0, 0.5, -0.5, 1.0, -1.0]);
+    }
+
+    #[test]
+    fn test_render_to_wav() {
+        let chord = SATB::new(
+            0,
+            (0, 5).into(),
+            (7, 4).into(),
+            (4, 4).into(),
+            (0, 3).into(),
+        );
+        let path = temp_path("render.wav");
+        chord.render_to_wav(&path, 1, 8000).unwrap();
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.len(), 8000);
+    }
+}